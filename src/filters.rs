@@ -4,18 +4,38 @@ use pyo3::prelude::*;
 
 use crate::types::Argument;
 
+/// A filter resolved by name at parse time. Rendering dispatches on the
+/// variant alone, so the name is never looked up again.
 #[derive(Clone, Debug, PartialEq)]
 pub enum FilterType {
     Add(AddFilter),
     AddSlashes(AddSlashesFilter),
     Capfirst(CapfirstFilter),
+    Date(DateFilter),
     Default(DefaultFilter),
+    Dictsort(DictsortFilter),
+    Dictsortreversed(DictsortreversedFilter),
+    Divisibleby(DivisiblebyFilter),
     Escape(EscapeFilter),
     External(ExternalFilter),
+    First(FirstFilter),
+    Floatformat(FloatformatFilter),
+    Join(JoinFilter),
+    Last(LastFilter),
+    Length(LengthFilter),
     Lower(LowerFilter),
     Safe(SafeFilter),
+    Safeseq(SafeseqFilter),
     Slugify(SlugifyFilter),
+    Stringformat(StringformatFilter),
+    Time(TimeFilter),
+    Truncatechars(TruncatecharsFilter),
+    TruncatecharsHtml(TruncatecharsHtmlFilter),
+    Truncatewords(TruncatewordsFilter),
+    TruncatewordsHtml(TruncatewordsHtmlFilter),
     Upper(UpperFilter),
+    Wordcount(WordcountFilter),
+    Yesno(YesnoFilter),
 }
 
 #[derive(Clone, Debug, PartialEq)]
@@ -46,6 +66,50 @@ impl DefaultFilter {
     }
 }
 
+#[derive(Clone, Debug, PartialEq)]
+pub struct DateFilter {
+    pub argument: Option<Argument>,
+}
+
+impl DateFilter {
+    pub fn new(argument: Option<Argument>) -> Self {
+        Self { argument }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct DictsortFilter {
+    pub argument: Argument,
+}
+
+impl DictsortFilter {
+    pub fn new(argument: Argument) -> Self {
+        Self { argument }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct DictsortreversedFilter {
+    pub argument: Argument,
+}
+
+impl DictsortreversedFilter {
+    pub fn new(argument: Argument) -> Self {
+        Self { argument }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct DivisiblebyFilter {
+    pub argument: Argument,
+}
+
+impl DivisiblebyFilter {
+    pub fn new(argument: Argument) -> Self {
+        Self { argument }
+    }
+}
+
 #[derive(Clone, Debug, PartialEq)]
 pub struct EscapeFilter;
 
@@ -74,14 +138,128 @@ impl PartialEq for ExternalFilter {
     }
 }
 
+#[derive(Clone, Debug, PartialEq)]
+pub struct FirstFilter;
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct FloatformatFilter {
+    pub argument: Option<Argument>,
+}
+
+impl FloatformatFilter {
+    pub fn new(argument: Option<Argument>) -> Self {
+        Self { argument }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct JoinFilter {
+    pub argument: Argument,
+}
+
+impl JoinFilter {
+    pub fn new(argument: Argument) -> Self {
+        Self { argument }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct LastFilter;
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct LengthFilter;
+
 #[derive(Clone, Debug, PartialEq)]
 pub struct LowerFilter;
 
 #[derive(Clone, Debug, PartialEq)]
 pub struct SafeFilter;
 
+#[derive(Clone, Debug, PartialEq)]
+pub struct SafeseqFilter;
+
 #[derive(Clone, Debug, PartialEq)]
 pub struct SlugifyFilter;
 
+#[derive(Clone, Debug, PartialEq)]
+pub struct StringformatFilter {
+    pub argument: Argument,
+}
+
+impl StringformatFilter {
+    pub fn new(argument: Argument) -> Self {
+        Self { argument }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct TimeFilter {
+    pub argument: Option<Argument>,
+}
+
+impl TimeFilter {
+    pub fn new(argument: Option<Argument>) -> Self {
+        Self { argument }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct TruncatecharsFilter {
+    pub argument: Argument,
+}
+
+impl TruncatecharsFilter {
+    pub fn new(argument: Argument) -> Self {
+        Self { argument }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct TruncatecharsHtmlFilter {
+    pub argument: Argument,
+}
+
+impl TruncatecharsHtmlFilter {
+    pub fn new(argument: Argument) -> Self {
+        Self { argument }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct TruncatewordsFilter {
+    pub argument: Argument,
+}
+
+impl TruncatewordsFilter {
+    pub fn new(argument: Argument) -> Self {
+        Self { argument }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct TruncatewordsHtmlFilter {
+    pub argument: Argument,
+}
+
+impl TruncatewordsHtmlFilter {
+    pub fn new(argument: Argument) -> Self {
+        Self { argument }
+    }
+}
+
 #[derive(Clone, Debug, PartialEq)]
 pub struct UpperFilter;
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct WordcountFilter;
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct YesnoFilter {
+    pub argument: Option<Argument>,
+}
+
+impl YesnoFilter {
+    pub fn new(argument: Option<Argument>) -> Self {
+        Self { argument }
+    }
+}
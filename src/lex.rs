@@ -1,10 +1,11 @@
 pub mod autoescape;
 pub mod common;
 pub mod core;
+pub mod forloop;
 pub mod ifcondition;
 pub mod load;
+pub mod simple_tag;
 pub mod tag;
-pub mod url;
 pub mod variable;
 
 pub const START_TAG_LEN: usize = 2;
@@ -24,6 +24,11 @@ pub enum LexerError {
         #[label("here")]
         at: SourceSpan,
     },
+    #[error("Variables and attributes may not begin with underscores")]
+    LeadingUnderscore {
+        #[label("here")]
+        at: SourceSpan,
+    },
     #[error("Could not parse the remainder")]
     InvalidRemainder {
         #[label("here")]
@@ -97,6 +102,8 @@ pub fn lex_text<'t>(
     }
 }
 
+/// Lex a translated string like `_('foo')`. As in Django, the closing paren
+/// must directly follow the string, so `_('foo' )` is incomplete.
 pub fn lex_translated<'t>(
     byte: usize,
     rest: &'t str,
@@ -132,16 +139,20 @@ pub fn lex_translated<'t>(
 }
 
 pub fn lex_numeric(byte: usize, rest: &str) -> ((usize, usize), usize, &str) {
+    // Unlike Django, accept a sign straight after the exponent marker:
+    // https://code.djangoproject.com/ticket/35816
+    let mut previous = None;
     let end = rest
-        .find(|c: char| !(c.is_ascii_digit() || c == '-' || c == '.' || c == 'e'))
+        .find(|c: char| {
+            let sign_allowed = matches!(previous, None | Some('e' | 'E'));
+            previous = Some(c);
+            !(c.is_ascii_digit()
+                || c == '.'
+                || c == 'e'
+                || c == 'E'
+                || (sign_allowed && (c == '-' || c == '+')))
+        })
         .unwrap_or(rest.len());
-    let content = &rest[..end];
-    // Match django bug
-    let end = match content[1..].find('-') {
-        Some(n) => n + 1,
-        None => end,
-    };
-    // End match django bug
     let at = (byte, end);
     (at, byte + end, &rest[end..])
 }
@@ -156,15 +167,11 @@ pub fn trim_variable(variable: &str) -> &str {
 pub fn check_variable_attrs(variable: &str, start: usize) -> Result<(), LexerError> {
     let mut offset = 0;
     for var in variable.split('.') {
+        let at = (start + offset, var.len());
         match var.chars().next() {
-            Some(c) if c != '_' => {
-                offset += var.len() + 1;
-                continue;
-            }
-            _ => {
-                let at = (start + offset, var.len());
-                return Err(LexerError::InvalidVariableName { at: at.into() });
-            }
+            Some('_') => return Err(LexerError::LeadingUnderscore { at: at.into() }),
+            Some(_) => offset += var.len() + 1,
+            None => return Err(LexerError::InvalidVariableName { at: at.into() }),
         }
     }
     Ok(())
@@ -210,6 +217,29 @@ mod tests {
         assert_eq!(rest, "");
     }
 
+    #[test]
+    fn test_lex_translated_escaped_quote() {
+        let template = "_('it\\'s') rest";
+        let mut chars = template.chars();
+        chars.nth(1);
+        let (at, byte, rest) = lex_translated(0, template, &mut chars).unwrap();
+        assert_eq!(at, (0, 10));
+        assert_eq!(byte, 10);
+        assert_eq!(rest, " rest");
+    }
+
+    #[test]
+    fn test_lex_translated_space_before_paren() {
+        let template = "_('foo' )";
+        let mut chars = template.chars();
+        chars.nth(1);
+        let error = lex_translated(0, template, &mut chars).unwrap_err();
+        assert_eq!(
+            error,
+            LexerError::IncompleteTranslatedString { at: (0, 7).into() }
+        );
+    }
+
     #[test]
     fn test_lex_argument_non_ascii() {
         let template = "ZJ5G4YXZJUH6|default:\"#`´କ¯\"";
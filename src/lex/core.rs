@@ -52,25 +52,36 @@ impl Token {
 }
 
 impl<'t> Token {
-    pub fn content(&self, template: TemplateString<'t>) -> &'t str {
-        let (start, len) = self.at;
-        let start = start + START_TAG_LEN;
-        let len = len - START_TAG_LEN - END_TAG_LEN;
-        let at = match self.token_type {
+    /// The span of the token without any `{{ }}`, `{% %}` or `{# #}` delimiters.
+    pub fn content_at(&self) -> (usize, usize) {
+        match self.token_type {
             TokenType::Text => self.at,
-            TokenType::Variable => (start, len),
-            TokenType::Tag => (start, len),
-            TokenType::Comment => (start, len),
-        };
-        template.content(at)
+            TokenType::Variable | TokenType::Tag | TokenType::Comment => {
+                let (start, len) = self.at;
+                (start + START_TAG_LEN, len - START_TAG_LEN - END_TAG_LEN)
+            }
+        }
+    }
+
+    pub fn content(&self, template: impl Into<TemplateString<'t>>) -> &'t str {
+        template.into().content(self.content_at())
     }
 }
 
+/// Find the start of the first `{{`, `{%` or `{#` delimiter in a single pass.
+pub fn find_delimiter(text: &str) -> Option<usize> {
+    let bytes = text.as_bytes();
+    text.match_indices('{')
+        .map(|(i, _)| i)
+        .find(|i| matches!(bytes.get(i + 1), Some(b'{' | b'%' | b'#')))
+}
+
 pub struct Lexer<'t> {
     template: TemplateString<'t>,
     rest: &'t str,
     byte: usize,
     verbatim: Option<&'t str>,
+    trim_blocks: bool,
 }
 
 impl<'t> Lexer<'t> {
@@ -80,18 +91,33 @@ impl<'t> Lexer<'t> {
             rest: template.0,
             byte: 0,
             verbatim: None,
+            trim_blocks: false,
+        }
+    }
+
+    /// Create a lexer which drops the first newline (`\n` or `\r\n`) directly
+    /// after a `{% %}` block tag. This is not Django's default behaviour.
+    pub fn new_with_trim_blocks(template: TemplateString<'t>) -> Self {
+        Self {
+            trim_blocks: true,
+            ..Self::new(template)
         }
     }
 
+    fn trim_newline(&mut self) {
+        let newline = if self.rest.starts_with("\r\n") {
+            2
+        } else if self.rest.starts_with('\n') {
+            1
+        } else {
+            return;
+        };
+        self.byte += newline;
+        self.rest = &self.rest[newline..];
+    }
+
     fn lex_text(&mut self) -> Token {
-        let next_tag = self.rest.find("{%");
-        let next_variable = self.rest.find("{{");
-        let next_comment = self.rest.find("{#");
-        let next = [next_tag, next_variable, next_comment]
-            .iter()
-            .filter_map(|n| *n)
-            .min();
-        let len = match next {
+        let len = match find_delimiter(self.rest) {
             None => {
                 let len = self.rest.len();
                 self.rest = "";
@@ -168,11 +194,23 @@ impl<'t> Lexer<'t> {
                     rest = &rest[start_tag..];
                     let close_tag = rest.find("%}");
                     match close_tag {
+                        // An unclosed tag can't end the verbatim block, so the
+                        // remainder of the template is all text.
                         None => return self.lex_text_to_end(),
+                        // Like outside verbatim, a tag can't span lines, so
+                        // look for the next tag after this `{%`.
+                        Some(end_tag) if rest[..end_tag].contains('\n') => {
+                            rest = &rest[START_TAG_LEN..];
+                            index += start_tag + START_TAG_LEN;
+                        }
                         Some(end_tag) => {
-                            let inner = &rest[2..end_tag].trim();
-                            // Check we have the right endverbatim tag
-                            if inner.len() < 3 || &inner[3..] != verbatim {
+                            let inner = rest[2..end_tag].trim();
+                            // Check we have the right endverbatim tag, ignoring
+                            // differences in whitespace between the words
+                            let matches = inner.strip_prefix("end").is_some_and(|inner| {
+                                inner.split_whitespace().eq(verbatim.split_whitespace())
+                            });
+                            if !matches {
                                 rest = &rest[end_tag + 2..];
                                 index += start_tag + end_tag + 2;
                                 continue;
@@ -221,6 +259,9 @@ impl Iterator for Lexer<'_> {
                         if verbatim == "verbatim" || verbatim.starts_with("verbatim ") {
                             self.verbatim = Some(verbatim)
                         }
+                        if self.trim_blocks {
+                            self.trim_newline();
+                        }
                     }
                     tag
                 }
@@ -240,6 +281,65 @@ mod tests {
         tokens.iter().map(|t| t.content(template)).collect()
     }
 
+    #[test]
+    fn test_lex_many_small_tags() {
+        let chunk = "a{{ b }}{c{% d %}{# e #}{";
+        let template = chunk.repeat(100);
+        let lexer = Lexer::new(template.as_str().into());
+        let tokens: Vec<_> = lexer.collect();
+        let mut expected = Vec::new();
+        for i in 0..100 {
+            let start = i * chunk.len();
+            if i == 0 {
+                expected.push(Token::text((start, 1)));
+            } else {
+                expected.push(Token::text((start - 1, 2)));
+            }
+            expected.push(Token::variable((start + 1, 7)));
+            expected.push(Token::text((start + 8, 2)));
+            expected.push(Token::tag((start + 10, 7)));
+            expected.push(Token::comment((start + 17, 7)));
+        }
+        expected.push(Token::text((template.len() - 1, 1)));
+        assert_eq!(tokens, expected);
+    }
+
+    #[test]
+    fn test_find_delimiter() {
+        assert_eq!(find_delimiter("{ { % #}"), None);
+        assert_eq!(find_delimiter("a{{"), Some(1));
+        assert_eq!(find_delimiter("{ {%"), Some(2));
+        assert_eq!(find_delimiter("{{#"), Some(0));
+        assert_eq!(find_delimiter("é{#"), Some(2));
+        assert_eq!(find_delimiter("{"), None);
+    }
+
+    #[test]
+    fn test_token_content() {
+        let template = "a{{ b }}{% c %}{# d #}";
+        let tokens: Vec<_> = Lexer::new(template.into()).collect();
+        let spans: Vec<_> = tokens.iter().map(Token::content_at).collect();
+        assert_eq!(spans, vec![(0, 1), (3, 3), (10, 3), (17, 3)]);
+        assert_eq!(contents(template, tokens), vec!["a", " b ", " c ", " d "]);
+    }
+
+    #[test]
+    fn test_token_content_empty_tags() {
+        let template = "{{}}{%%}{##}";
+        let tokens: Vec<_> = Lexer::new(template.into()).collect();
+        assert_eq!(
+            tokens,
+            vec![
+                Token::variable((0, 4)),
+                Token::tag((4, 4)),
+                Token::comment((8, 4)),
+            ]
+        );
+        for token in tokens {
+            assert_eq!(token.content(template), "");
+        }
+    }
+
     #[test]
     fn test_lex_empty() {
         let template = "";
@@ -349,6 +449,101 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_lex_multiline_variable() {
+        let template = "{{ foo\n|upper }}{{ bar }}";
+        let lexer = Lexer::new(template.into());
+        let tokens: Vec<_> = lexer.collect();
+        assert_eq!(
+            tokens,
+            vec![
+                Token::text((0, 7)),
+                Token::text((7, 9)),
+                Token::variable((16, 9)),
+            ]
+        );
+        assert_eq!(
+            contents(template, tokens),
+            vec!["{{ foo\n", "|upper }}", " bar "]
+        );
+    }
+
+    #[test]
+    fn test_lex_multiline_tag() {
+        let template = "{% if a\n and b %}{% endif %}";
+        let lexer = Lexer::new(template.into());
+        let tokens: Vec<_> = lexer.collect();
+        assert_eq!(
+            tokens,
+            vec![
+                Token::text((0, 8)),
+                Token::text((8, 9)),
+                Token::tag((17, 11)),
+            ]
+        );
+        assert_eq!(
+            contents(template, tokens),
+            vec!["{% if a\n", " and b %}", " endif "]
+        );
+    }
+
+    #[test]
+    fn test_verbatim_multiline_endverbatim() {
+        let template = "{% verbatim %}{% endverbatim\n%}{% x\n{% endverbatim %}";
+        let lexer = Lexer::new(template.into());
+        let tokens: Vec<_> = lexer.collect();
+        assert_eq!(
+            tokens,
+            vec![
+                Token::tag((0, 14)),
+                Token::text((14, 22)),
+                Token::tag((36, 17)),
+            ]
+        );
+        assert_eq!(
+            contents(template, tokens),
+            vec![" verbatim ", "{% endverbatim\n%}{% x\n", " endverbatim "]
+        );
+    }
+
+    #[test]
+    fn test_lex_trim_blocks() {
+        let template = "{% if a %}\nyes\n{% endif %}\r\n\nafter";
+        let lexer = Lexer::new(template.into());
+        let tokens: Vec<_> = lexer.collect();
+        assert_eq!(
+            contents(template, tokens),
+            vec![" if a ", "\nyes\n", " endif ", "\r\n\nafter"]
+        );
+
+        let lexer = Lexer::new_with_trim_blocks(template.into());
+        let tokens: Vec<_> = lexer.collect();
+        assert_eq!(
+            tokens,
+            vec![
+                Token::tag((0, 10)),
+                Token::text((11, 4)),
+                Token::tag((15, 11)),
+                Token::text((28, 6)),
+            ]
+        );
+        assert_eq!(
+            contents(template, tokens),
+            vec![" if a ", "yes\n", " endif ", "\nafter"]
+        );
+    }
+
+    #[test]
+    fn test_lex_trim_blocks_only_tags() {
+        let template = "{{ a }}\nabc{# b #}\nabc{% c %}\nabcd";
+        let lexer = Lexer::new_with_trim_blocks(template.into());
+        let tokens: Vec<_> = lexer.collect();
+        assert_eq!(
+            contents(template, tokens),
+            vec![" a ", "\nabc", " b ", "\nabc", " c ", "abcd"]
+        );
+    }
+
     #[test]
     fn test_verbatim_with_variable() {
         let template = "{% verbatim %}{{bare   }}{% endverbatim %}";
@@ -431,6 +626,32 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_verbatim_special_whitespace() {
+        let template = "{% verbatim special %}{% endverbatim %}{% endverbatim   special %}";
+        let lexer = Lexer::new(template.into());
+        let tokens: Vec<_> = lexer.collect();
+        assert_eq!(
+            contents(template, tokens),
+            vec![
+                " verbatim special ",
+                "{% endverbatim %}",
+                " endverbatim   special ",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_verbatim_special_opening_whitespace() {
+        let template = "{% verbatim   special %}text{% endverbatim special %}";
+        let lexer = Lexer::new(template.into());
+        let tokens: Vec<_> = lexer.collect();
+        assert_eq!(
+            contents(template, tokens),
+            vec![" verbatim   special ", "text", " endverbatim special "]
+        );
+    }
+
     #[test]
     fn test_verbatim_adjacent() {
         let template = "{% verbatim %}{% endverbatim %}{% verbatim %}{% endverbatim %}";
@@ -484,6 +705,29 @@ mod tests {
         assert_eq!(contents(template, tokens), vec![" verbatim ", "Don't {% "]);
     }
 
+    #[test]
+    fn test_verbatim_unclosed_tag() {
+        let template = "{% verbatim %}text {% not closed";
+        let lexer = Lexer::new(template.into());
+        let tokens: Vec<_> = lexer.collect();
+        assert_eq!(tokens, vec![Token::tag((0, 14)), Token::text((14, 18))]);
+        assert_eq!(
+            contents(template, tokens),
+            vec![" verbatim ", "text {% not closed"]
+        );
+    }
+
+    #[test]
+    fn test_verbatim_multibyte_tag() {
+        let template = "{% verbatim %}{% é %}{% endverbatim %}";
+        let lexer = Lexer::new(template.into());
+        let tokens: Vec<_> = lexer.collect();
+        assert_eq!(
+            contents(template, tokens),
+            vec![" verbatim ", "{% é %}", " endverbatim "]
+        );
+    }
+
     #[test]
     fn test_verbatim_no_tag() {
         let template = "{% verbatim %}Don't end verbatim";
@@ -0,0 +1,196 @@
+use miette::{Diagnostic, SourceSpan};
+use thiserror::Error;
+
+use crate::lex::tag::TagParts;
+use crate::types::TemplateString;
+
+#[derive(Debug, PartialEq)]
+pub struct ForTokens {
+    pub variables: Vec<(usize, usize)>,
+    pub iterable: (usize, usize),
+    pub reversed: bool,
+}
+
+#[derive(Error, Debug, Diagnostic, PartialEq, Eq)]
+pub enum ForLexerError {
+    #[error("'for' statements should use the format 'for x in y'")]
+    InvalidFormat {
+        #[label("expected 'in'")]
+        at: SourceSpan,
+    },
+    #[error("Invalid variable name in 'for' tag")]
+    InvalidVariableName {
+        #[label("here")]
+        at: SourceSpan,
+    },
+    #[error("'for' statements should have at least four words: 'for x in y'")]
+    MissingArguments {
+        #[label("here")]
+        at: SourceSpan,
+    },
+}
+
+/// Split the tag content on whitespace, keeping quoted strings together.
+fn split_bits(content: &str, start: usize) -> Vec<(usize, usize)> {
+    let mut bits = Vec::new();
+    let mut bit_start = None;
+    let mut in_text = None;
+    for (index, c) in content.char_indices() {
+        match (c, in_text) {
+            ('"' | '\'', None) => in_text = Some(c),
+            (c, Some(quote)) if c == quote => in_text = None,
+            (c, None) if c.is_whitespace() => {
+                if let Some(bit_start) = bit_start.take() {
+                    bits.push((start + bit_start, index - bit_start));
+                }
+                continue;
+            }
+            _ => {}
+        }
+        bit_start.get_or_insert(index);
+    }
+    if let Some(bit_start) = bit_start {
+        bits.push((start + bit_start, content.len() - bit_start));
+    }
+    bits
+}
+
+fn lex_variables(
+    template: TemplateString<'_>,
+    at: (usize, usize),
+) -> Result<Vec<(usize, usize)>, ForLexerError> {
+    let mut variables = Vec::new();
+    let mut start = at.0;
+    for variable in template.content(at).split(',') {
+        let trimmed = variable.trim_start();
+        let offset = variable.len() - trimmed.len();
+        let trimmed = trimmed.trim_end();
+        let variable_at = (start + offset, trimmed.len());
+        if trimmed.is_empty() || trimmed.contains(char::is_whitespace) {
+            let at = match trimmed.is_empty() {
+                true => at,
+                false => variable_at,
+            };
+            return Err(ForLexerError::InvalidVariableName { at: at.into() });
+        }
+        variables.push(variable_at);
+        start += variable.len() + 1;
+    }
+    Ok(variables)
+}
+
+pub fn lex_for(template: TemplateString<'_>, parts: TagParts) -> Result<ForTokens, ForLexerError> {
+    let bits = split_bits(template.content(parts.at), parts.at.0);
+    if bits.len() < 3 {
+        return Err(ForLexerError::MissingArguments {
+            at: parts.at.into(),
+        });
+    }
+
+    let reversed = template.content(bits[bits.len() - 1]) == "reversed";
+    let in_index = match reversed {
+        true => bits.len() - 3,
+        false => bits.len() - 2,
+    };
+    if template.content(bits[in_index]) != "in" {
+        return Err(ForLexerError::InvalidFormat {
+            at: bits[in_index].into(),
+        });
+    }
+    if in_index == 0 {
+        return Err(ForLexerError::InvalidVariableName {
+            at: bits[in_index].into(),
+        });
+    }
+
+    let start = bits[0].0;
+    let last = bits[in_index - 1];
+    let variables = lex_variables(template, (start, last.0 + last.1 - start))?;
+    Ok(ForTokens {
+        variables,
+        iterable: bits[in_index + 1],
+        reversed,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lex_for() {
+        let template = "{% for item in items %}";
+        let parts = TagParts { at: (7, 13) };
+        let tokens = lex_for(template.into(), parts).unwrap();
+        let expected = ForTokens {
+            variables: vec![(7, 4)],
+            iterable: (15, 5),
+            reversed: false,
+        };
+        assert_eq!(tokens, expected);
+    }
+
+    #[test]
+    fn test_lex_for_unpack() {
+        let template = "{% for k , v in d.items reversed %}";
+        let parts = TagParts { at: (7, 25) };
+        let tokens = lex_for(template.into(), parts).unwrap();
+        let expected = ForTokens {
+            variables: vec![(7, 1), (11, 1)],
+            iterable: (16, 7),
+            reversed: true,
+        };
+        assert_eq!(tokens, expected);
+    }
+
+    #[test]
+    fn test_lex_for_quoted_iterable() {
+        let template = "{% for x in items|join:', ' %}";
+        let parts = TagParts { at: (7, 20) };
+        let tokens = lex_for(template.into(), parts).unwrap();
+        let expected = ForTokens {
+            variables: vec![(7, 1)],
+            iterable: (12, 15),
+            reversed: false,
+        };
+        assert_eq!(tokens, expected);
+    }
+
+    #[test]
+    fn test_lex_for_missing_arguments() {
+        let template = "{% for x in %}";
+        let parts = TagParts { at: (7, 4) };
+        let error = lex_for(template.into(), parts).unwrap_err();
+        assert_eq!(error, ForLexerError::MissingArguments { at: (7, 4).into() });
+    }
+
+    #[test]
+    fn test_lex_for_missing_in() {
+        let template = "{% for x from y %}";
+        let parts = TagParts { at: (7, 8) };
+        let error = lex_for(template.into(), parts).unwrap_err();
+        assert_eq!(error, ForLexerError::InvalidFormat { at: (9, 4).into() });
+    }
+
+    #[test]
+    fn test_lex_for_invalid_variable() {
+        let template = "{% for x y in z %}";
+        let parts = TagParts { at: (7, 8) };
+        let error = lex_for(template.into(), parts).unwrap_err();
+        assert_eq!(
+            error,
+            ForLexerError::InvalidVariableName { at: (7, 3).into() }
+        );
+    }
+
+    #[test]
+    fn test_lex_for_empty_variable() {
+        let template = "{% for x, in z %}";
+        let parts = TagParts { at: (7, 7) };
+        let error = lex_for(template.into(), parts).unwrap_err();
+        assert_eq!(
+            error,
+            ForLexerError::InvalidVariableName { at: (7, 2).into() }
+        );
+    }
+}
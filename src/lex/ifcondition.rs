@@ -79,7 +79,7 @@ impl<'t> IfConditionLexer<'t> {
             }
             '"' => self.lex_text(&mut chars, '"')?,
             '\'' => self.lex_text(&mut chars, '\'')?,
-            '0'..='9' | '-' => self.lex_numeric(),
+            '0'..='9' | '-' | '+' => self.lex_numeric(),
             _ => self.lex_variable(),
         };
         self.lex_remainder()?;
@@ -311,6 +311,20 @@ mod tests {
         assert_eq!(tokens, vec![Ok(numeric)]);
     }
 
+    #[test]
+    fn test_lex_numeric_signed() {
+        let template = "{% if +1E-3 %}";
+        let parts = TagParts { at: (6, 5) };
+        let lexer = IfConditionLexer::new(template.into(), parts);
+        let tokens: Vec<_> = lexer.collect();
+
+        let numeric = IfConditionToken {
+            at: (6, 5),
+            token_type: IfConditionTokenType::Atom(IfConditionAtom::Numeric),
+        };
+        assert_eq!(tokens, vec![Ok(numeric)]);
+    }
+
     #[test]
     fn test_lex_text() {
         let template = "{% if 'foo' %}";
@@ -339,6 +353,28 @@ mod tests {
         assert_eq!(tokens, vec![Ok(text)]);
     }
 
+    #[test]
+    fn test_lex_text_escaped_quote() {
+        let template = r"{% if status == 'it\'s ok' %}";
+        let parts = TagParts { at: (6, 20) };
+        let lexer = IfConditionLexer::new(template.into(), parts);
+        let tokens: Vec<_> = lexer.collect();
+
+        let status = IfConditionToken {
+            at: (6, 6),
+            token_type: IfConditionTokenType::Atom(IfConditionAtom::Variable),
+        };
+        let equal = IfConditionToken {
+            at: (13, 2),
+            token_type: IfConditionTokenType::Operator(IfConditionOperator::Equal),
+        };
+        let text = IfConditionToken {
+            at: (16, 10),
+            token_type: IfConditionTokenType::Atom(IfConditionAtom::Text),
+        };
+        assert_eq!(tokens, vec![Ok(status), Ok(equal), Ok(text)]);
+    }
+
     #[test]
     fn test_lex_translated() {
         let template = "{% if _('foo') %}";
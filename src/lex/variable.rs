@@ -63,26 +63,8 @@ impl<'t> FilterToken {
     }
 }
 
-#[derive(Debug, PartialEq, Eq)]
-pub struct VariableToken {
-    pub at: (usize, usize),
-}
-
-#[cfg(test)]
-impl<'t> VariableToken {
-    pub fn content(&self, template: &'t str) -> &'t str {
-        let (start, len) = self.at;
-        &template[start..start + len]
-    }
-}
-
 #[derive(Error, Debug, Diagnostic, PartialEq, Eq)]
 pub enum VariableLexerError {
-    #[error("Variables and attributes may not begin with underscores")]
-    LeadingUnderscore {
-        #[label("here")]
-        at: SourceSpan,
-    },
     #[error(transparent)]
     #[diagnostic(transparent)]
     LexerError(#[from] LexerError),
@@ -98,16 +80,44 @@ pub enum VariableLexerError {
     },
 }
 
+/// Lex the start of a variable tag, which is either a variable name or a
+/// literal like `{{ 42 }}` or `{{ "foo" }}`, followed by any filters.
 pub fn lex_variable(
     variable: &str,
     start: usize,
-) -> Result<Option<(VariableToken, FilterLexer)>, VariableLexerError> {
+) -> Result<Option<(Argument, FilterLexer<'_>)>, VariableLexerError> {
     let rest = variable.trim_start();
     if rest.trim().is_empty() {
         return Ok(None);
     }
 
     let start = start + variable.len() - rest.len();
+    if rest.starts_with(|c: char| c.is_ascii_digit() || c == '-' || c == '+') {
+        let (at, byte, rest) = lex_numeric(start, rest);
+        let argument = Argument {
+            argument_type: ArgumentType::Numeric,
+            at,
+        };
+        return Ok(Some((argument, FilterLexer::new(rest, byte))));
+    }
+
+    let mut chars = rest.chars();
+    let literal = match chars.next() {
+        Some(quote @ ('\'' | '"')) => Some((
+            ArgumentType::Text,
+            lex_text(start, rest, &mut chars, quote)?,
+        )),
+        Some('_') if chars.next() == Some('(') => Some((
+            ArgumentType::TranslatedText,
+            lex_translated(start, rest, &mut chars)?,
+        )),
+        _ => None,
+    };
+    if let Some((argument_type, (at, byte, rest))) = literal {
+        let argument = Argument { argument_type, at };
+        return Ok(Some((argument, FilterLexer::new(rest, byte))));
+    }
+
     let content = trim_variable(rest);
     if content.is_empty() {
         let at = (start, rest.trim().len());
@@ -117,9 +127,12 @@ pub fn lex_variable(
     check_variable_attrs(content, start)?;
 
     let end = content.len();
-    let at = (start, end);
+    let argument = Argument {
+        argument_type: ArgumentType::Variable,
+        at: (start, end),
+    };
     Ok(Some((
-        VariableToken { at },
+        argument,
         FilterLexer::new(&rest[end..], start + end),
     )))
 }
@@ -128,27 +141,42 @@ pub fn lex_variable(
 pub struct FilterLexer<'t> {
     rest: &'t str,
     byte: usize,
+    lenient: bool,
+}
+
+/// Lex a chain of filters with no variable in front of them, like the
+/// argument of `{% filter lower|title %}`.
+pub fn lex_filters(filters: &str, start: usize) -> FilterLexer<'_> {
+    FilterLexer::from_filters(filters, start)
 }
 
 impl<'t> FilterLexer<'t> {
     fn new(variable: &'t str, start: usize) -> Self {
-        let offset = match variable.find('|') {
-            Some(n) => n + 1,
-            None => {
-                return Self {
-                    rest: "",
-                    byte: start + variable.len(),
-                };
-            }
-        };
-        let variable = &variable[offset..];
-        let rest = variable.trim_start();
+        match variable.find('|') {
+            Some(n) => Self::from_filters(&variable[n + 1..], start + n + 1),
+            None => Self {
+                rest: "",
+                byte: start + variable.len(),
+                lenient: false,
+            },
+        }
+    }
+
+    fn from_filters(filters: &'t str, start: usize) -> Self {
+        let rest = filters.trim_start();
         Self {
             rest: rest.trim_end(),
-            byte: start + offset + variable.len() - rest.len(),
+            byte: start + filters.len() - rest.len(),
+            lenient: false,
         }
     }
 
+    /// Allow whitespace around the `:` between a filter and its argument,
+    /// as in `{{ foo | default : "x" }}`. Django itself rejects this.
+    pub fn lenient(self, lenient: bool) -> Self {
+        Self { lenient, ..self }
+    }
+
     fn lex_text(
         &mut self,
         chars: &mut std::str::Chars,
@@ -235,12 +263,25 @@ impl<'t> FilterLexer<'t> {
                 self.rest = &self.rest[end..];
                 let (remainder, _start_next) = self.remainder_to_filter_or_argument();
                 match remainder {
+                    _ if self.lenient && remainder.trim().is_empty() => {
+                        let argument = self.lex_argument()?;
+                        Ok(FilterToken { at, argument })
+                    }
                     "" => {
                         let argument = self.lex_argument()?;
                         Ok(FilterToken { at, argument })
                     }
                     _ => {
-                        let at = (self.byte, remainder.trim().len());
+                        // Point at the unexpected characters, or at the
+                        // whitespace itself when there is nothing else.
+                        let trimmed = remainder.trim_start();
+                        let at = match trimmed.is_empty() {
+                            true => (self.byte, remainder.len()),
+                            false => (
+                                self.byte + remainder.len() - trimmed.len(),
+                                trimmed.trim_end().len(),
+                            ),
+                        };
                         self.rest = "";
                         Err(LexerError::InvalidRemainder { at: at.into() }.into())
                     }
@@ -261,6 +302,10 @@ impl<'t> FilterLexer<'t> {
             (Some(f), Some(a)) if f < a => return Ok(None),
             (_, Some(a)) => a + 1,
         };
+        let next = match self.lenient {
+            true => self.rest.len() - self.rest[next..].trim_start().len(),
+            false => next,
+        };
         self.rest = &self.rest[next..];
         self.byte += next;
 
@@ -277,12 +322,12 @@ impl<'t> FilterLexer<'t> {
                     let at = (self.byte, end);
                     self.byte += self.rest.len();
                     self.rest = "";
-                    return Err(VariableLexerError::LeadingUnderscore { at: at.into() });
+                    return Err(LexerError::LeadingUnderscore { at: at.into() }.into());
                 }
             }
             '\'' => self.lex_text(&mut chars, '\'')?,
             '"' => self.lex_text(&mut chars, '"')?,
-            '0'..='9' | '-' => self.lex_numeric(),
+            '0'..='9' | '-' | '+' => self.lex_numeric(),
             _ => self.lex_variable_argument()?,
         }))
     }
@@ -370,31 +415,109 @@ mod tests {
         let template = "{{ foo.bar }}";
         let variable = trim_variable(template);
         let (token, lexer) = lex_variable(variable, START_TAG_LEN).unwrap().unwrap();
-        assert_eq!(token, VariableToken { at: (3, 7) });
+        assert_eq!(
+            token,
+            Argument {
+                argument_type: ArgumentType::Variable,
+                at: (3, 7)
+            }
+        );
         assert_eq!(token.content(template), "foo.bar");
         let tokens: Vec<_> = lexer.collect();
         assert_eq!(tokens, vec![]);
     }
 
     #[test]
-    fn test_lex_variable_index() {
-        let template = "{{ 1 }}";
+    fn test_lex_variable_numeric() {
+        for (template, content) in [
+            ("{{ 1 }}", "1"),
+            ("{{ 3.14 }}", "3.14"),
+            ("{{ -1 }}", "-1"),
+            ("{{ +1e-5 }}", "+1e-5"),
+        ] {
+            let variable = trim_variable(template);
+            let (token, lexer) = lex_variable(variable, START_TAG_LEN).unwrap().unwrap();
+            assert_eq!(token.argument_type, ArgumentType::Numeric);
+            assert_eq!(token.content(template), content);
+            let tokens: Vec<_> = lexer.collect();
+            assert_eq!(tokens, vec![]);
+        }
+    }
+
+    #[test]
+    fn test_lex_variable_numeric_filter() {
+        let template = "{{ -1|add:2 }}";
         let variable = trim_variable(template);
         let (token, lexer) = lex_variable(variable, START_TAG_LEN).unwrap().unwrap();
-        assert_eq!(token, VariableToken { at: (3, 1) });
-        assert_eq!(token.content(template), "1");
+        assert_eq!(
+            token,
+            Argument {
+                argument_type: ArgumentType::Numeric,
+                at: (3, 2)
+            }
+        );
         let tokens: Vec<_> = lexer.collect();
-        assert_eq!(tokens, vec![]);
+        assert_eq!(contents(template, tokens), vec![("add", Some("2"))]);
     }
 
     #[test]
-    fn test_lex_variable_negative_index() {
-        let template = "{{ -1 }}";
+    fn test_lex_variable_text() {
+        for (template, content) in [
+            ("{{ 'foo' }}", "foo"),
+            ("{{ \"foo bar\" }}", "foo bar"),
+            ("{{ 'it\\'s' }}", "it\\'s"),
+        ] {
+            let variable = trim_variable(template);
+            let (token, lexer) = lex_variable(variable, START_TAG_LEN).unwrap().unwrap();
+            assert_eq!(token.argument_type, ArgumentType::Text);
+            assert_eq!(token.content(template), content);
+            let tokens: Vec<_> = lexer.collect();
+            assert_eq!(tokens, vec![]);
+        }
+    }
+
+    #[test]
+    fn test_lex_variable_text_filter() {
+        let template = "{{ 'foo|bar'|upper }}";
+        let variable = trim_variable(template);
+        let (token, lexer) = lex_variable(variable, START_TAG_LEN).unwrap().unwrap();
+        assert_eq!(
+            token,
+            Argument {
+                argument_type: ArgumentType::Text,
+                at: (3, 9)
+            }
+        );
+        assert_eq!(token.content(template), "foo|bar");
+        let tokens: Vec<_> = lexer.collect();
+        assert_eq!(contents(template, tokens), vec![("upper", None)]);
+    }
+
+    #[test]
+    fn test_lex_variable_translated() {
+        let template = "{{ _('foo')|lower }}";
+        let variable = trim_variable(template);
+        let (token, lexer) = lex_variable(variable, START_TAG_LEN).unwrap().unwrap();
+        assert_eq!(
+            token,
+            Argument {
+                argument_type: ArgumentType::TranslatedText,
+                at: (3, 8)
+            }
+        );
+        assert_eq!(token.content(template), "foo");
+        let tokens: Vec<_> = lexer.collect();
+        assert_eq!(contents(template, tokens), vec![("lower", None)]);
+    }
+
+    #[test]
+    fn test_lex_variable_text_incomplete() {
+        let template = "{{ 'foo }}";
         let variable = trim_variable(template);
         let err = lex_variable(variable, START_TAG_LEN).unwrap_err();
         assert_eq!(
             err,
-            VariableLexerError::InvalidVariableName { at: (3, 2).into() }
+            LexerError::IncompleteString { at: (3, 5).into() }.into()
         );
     }
 
@@ -404,7 +527,7 @@ mod tests {
         let err = lex_variable(variable, START_TAG_LEN).unwrap_err();
         assert_eq!(
             err,
-            LexerError::InvalidVariableName { at: (3, 4).into() }.into()
+            LexerError::LeadingUnderscore { at: (3, 4).into() }.into()
         );
     }
 
@@ -414,7 +537,7 @@ mod tests {
         let err = lex_variable(variable, START_TAG_LEN).unwrap_err();
         assert_eq!(
             err,
-            LexerError::InvalidVariableName { at: (7, 4).into() }.into()
+            LexerError::LeadingUnderscore { at: (7, 4).into() }.into()
         );
     }
 
@@ -423,7 +546,13 @@ mod tests {
         let template = "{{ foo.1 }}";
         let variable = trim_variable(template);
         let (token, lexer) = lex_variable(variable, START_TAG_LEN).unwrap().unwrap();
-        assert_eq!(token, VariableToken { at: (3, 5) });
+        assert_eq!(
+            token,
+            Argument {
+                argument_type: ArgumentType::Variable,
+                at: (3, 5)
+            }
+        );
         assert_eq!(token.content(template), "foo.1");
         let tokens: Vec<_> = lexer.collect();
         assert_eq!(tokens, vec![]);
@@ -495,6 +624,108 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_lex_filter_whitespace_lenient() {
+        let lex = |template| {
+            let variable = trim_variable(template);
+            let (_token, lexer) = lex_variable(variable, START_TAG_LEN).unwrap().unwrap();
+            lexer.lenient(true).collect::<Vec<_>>()
+        };
+        let template = "{{ foo | upper }}";
+        let tokens = lex(template);
+        assert_eq!(contents(template, tokens), vec![("upper", None)]);
+        let template = "{{ foo|upper }}";
+        assert_eq!(contents(template, lex(template)), vec![("upper", None)]);
+
+        let template = "{{ foo | default : \"x\" | lower }}";
+        let tokens = lex(template);
+        assert_eq!(
+            contents(template, tokens),
+            vec![("default", Some("x")), ("lower", None)]
+        );
+        let template = "{{ foo|default:\"x\"|lower }}";
+        assert_eq!(
+            contents(template, lex(template)),
+            vec![("default", Some("x")), ("lower", None)]
+        );
+    }
+
+    #[test]
+    fn test_lex_filter_whitespace_strict() {
+        let template = "{{ foo | default : \"x\" }}";
+        let variable = trim_variable(template);
+        let (_token, lexer) = lex_variable(variable, START_TAG_LEN).unwrap().unwrap();
+        let tokens: Vec<_> = lexer.collect();
+        assert_eq!(
+            tokens,
+            vec![Err(
+                LexerError::InvalidRemainder { at: (16, 1).into() }.into()
+            )]
+        );
+    }
+
+    #[test]
+    fn test_lex_text_argument_with_colons() {
+        let template = "{{ t|date:\"H:i:s\"|default:'a|b:c' }}";
+        let variable = trim_variable(template);
+        let (_token, lexer) = lex_variable(variable, START_TAG_LEN).unwrap().unwrap();
+        let tokens: Vec<_> = lexer.collect();
+        assert_eq!(
+            tokens[0],
+            Ok(FilterToken {
+                at: (5, 4),
+                argument: Some(Argument {
+                    argument_type: ArgumentType::Text,
+                    at: (10, 7),
+                }),
+            })
+        );
+        assert_eq!(
+            contents(template, tokens),
+            vec![("date", Some("H:i:s")), ("default", Some("a|b:c"))]
+        );
+    }
+
+    #[test]
+    fn test_lex_argument_remainder() {
+        let template = "{{ foo|default:'spam'xyz }}";
+        let variable = trim_variable(template);
+        let (_token, lexer) = lex_variable(variable, START_TAG_LEN).unwrap().unwrap();
+        let tokens: Vec<_> = lexer.collect();
+        let error = LexerError::InvalidRemainder { at: (21, 3).into() };
+        assert_eq!(tokens, vec![Err(error.into())]);
+    }
+
+    #[test]
+    fn test_lex_argument_remainder_before_filter() {
+        let template = "{{ foo|default:'spam'xyz|title }}";
+        let variable = trim_variable(template);
+        let (_token, lexer) = lex_variable(variable, START_TAG_LEN).unwrap().unwrap();
+        let tokens: Vec<_> = lexer.collect();
+        let error = LexerError::InvalidRemainder { at: (21, 3).into() };
+        assert_eq!(tokens, vec![Err(error.into())]);
+    }
+
+    #[test]
+    fn test_lex_argument_remainder_after_space() {
+        let template = "{{ foo|default:'spam'  xyz |title }}";
+        let variable = trim_variable(template);
+        let (_token, lexer) = lex_variable(variable, START_TAG_LEN).unwrap().unwrap();
+        let tokens: Vec<_> = lexer.collect();
+        let error = LexerError::InvalidRemainder { at: (23, 3).into() };
+        assert_eq!(tokens, vec![Err(error.into())]);
+    }
+
+    #[test]
+    fn test_lex_filter_remainder_after_space() {
+        let template = "{{ foo|title  xyz|lower }}";
+        let variable = trim_variable(template);
+        let (_token, lexer) = lex_variable(variable, START_TAG_LEN).unwrap().unwrap();
+        let tokens: Vec<_> = lexer.collect();
+        let error = LexerError::InvalidRemainder { at: (14, 3).into() };
+        assert_eq!(tokens, vec![Err(error.into())]);
+    }
+
     #[test]
     fn test_lex_filter_invalid_start() {
         let template = "{{ foo.bar|'foo' }}";
@@ -666,28 +897,75 @@ mod tests {
 
     #[test]
     fn test_lex_numeric_argument_scientific_negative_exponent() {
-        // Django mishandles this case, so we do too:
-        // https://code.djangoproject.com/ticket/35816
         let template = "{{ foo.bar|default:5.2e-3 }}";
         let variable = trim_variable(template);
         let (_token, lexer) = lex_variable(variable, START_TAG_LEN).unwrap().unwrap();
         let tokens: Vec<_> = lexer.collect();
         assert_eq!(
             tokens,
-            vec![
-                Err(LexerError::InvalidRemainder { at: (23, 2).into() }.into()),
-                /* When fixed we can do:
-                Ok(FilterToken {
-                    argument: Some(Argument {
-                        argument_type: ArgumentType::Numeric,
-                        at: (19, 6),
-                    }),
-                    at: (11, 7),
-                })
-                */
-            ]
+            vec![Ok(FilterToken {
+                argument: Some(Argument {
+                    argument_type: ArgumentType::Numeric,
+                    at: (19, 6),
+                }),
+                at: (11, 7),
+            })]
+        );
+        assert_eq!(
+            contents(template, tokens),
+            vec![("default", Some("5.2e-3"))]
+        );
+    }
+
+    #[test]
+    fn test_lex_numeric_argument_scientific_positive_exponent() {
+        let template = "{{ foo.bar|default:2E+4 }}";
+        let variable = trim_variable(template);
+        let (_token, lexer) = lex_variable(variable, START_TAG_LEN).unwrap().unwrap();
+        let tokens: Vec<_> = lexer.collect();
+        assert_eq!(
+            tokens,
+            vec![Ok(FilterToken {
+                argument: Some(Argument {
+                    argument_type: ArgumentType::Numeric,
+                    at: (19, 4),
+                }),
+                at: (11, 7),
+            })]
+        );
+        assert_eq!(contents(template, tokens), vec![("default", Some("2E+4"))]);
+    }
+
+    #[test]
+    fn test_lex_numeric_argument_signs_and_exponents() {
+        for (template, argument) in [
+            ("{{ foo|add:+3 }}", "+3"),
+            ("{{ foo|add:1E5 }}", "1E5"),
+            ("{{ foo|add:1e-5 }}", "1e-5"),
+            ("{{ foo|add:1e- }}", "1e-"),
+        ] {
+            let variable = trim_variable(template);
+            let (_token, lexer) = lex_variable(variable, START_TAG_LEN).unwrap().unwrap();
+            let tokens: Vec<_> = lexer.collect();
+            let token = tokens[0].as_ref().unwrap();
+            let lexed = token.argument.as_ref().unwrap();
+            assert_eq!(lexed.argument_type, ArgumentType::Numeric);
+            assert_eq!(lexed.content(template), argument);
+        }
+    }
+
+    #[test]
+    fn test_lex_numeric_argument_inner_minus() {
+        let template = "{{ foo.bar|default:5-3 }}";
+        let variable = trim_variable(template);
+        let (_token, lexer) = lex_variable(variable, START_TAG_LEN).unwrap().unwrap();
+        let tokens: Vec<_> = lexer.collect();
+        assert_eq!(
+            tokens,
+            vec![Err(
+                LexerError::InvalidRemainder { at: (20, 2).into() }.into()
+            )]
         );
-        //assert_eq!(contents(template, tokens), vec![("default", Some("5.2e-3"))]);
     }
 
     #[test]
@@ -773,9 +1051,9 @@ mod tests {
         let tokens: Vec<_> = lexer.collect();
         assert_eq!(
             tokens,
-            vec![Err(VariableLexerError::LeadingUnderscore {
-                at: (19, 5).into()
-            })]
+            vec![Err(
+                LexerError::LeadingUnderscore { at: (19, 5).into() }.into()
+            )]
         );
     }
 
@@ -788,7 +1066,7 @@ mod tests {
         assert_eq!(
             tokens,
             vec![Err(
-                LexerError::InvalidVariableName { at: (24, 5).into() }.into()
+                LexerError::LeadingUnderscore { at: (24, 5).into() }.into()
             )]
         );
     }
@@ -801,9 +1079,9 @@ mod tests {
         let tokens: Vec<_> = lexer.collect();
         assert_eq!(
             tokens,
-            vec![Err(VariableLexerError::LeadingUnderscore {
-                at: (19, 1).into()
-            })]
+            vec![Err(
+                LexerError::LeadingUnderscore { at: (19, 1).into() }.into()
+            )]
         );
     }
 
@@ -827,6 +1105,28 @@ mod tests {
         assert_eq!(tokens, vec![Err(error.into())]);
     }
 
+    #[test]
+    fn test_lex_translated_text_argument_escaped_quote() {
+        let template = "{{ foo.bar|default:_('it\\'s') }}";
+        let variable = trim_variable(template);
+        let (_token, lexer) = lex_variable(variable, START_TAG_LEN).unwrap().unwrap();
+        let tokens: Vec<_> = lexer.collect();
+        assert_eq!(
+            contents(template, tokens),
+            vec![("default", Some("it\\'s"))]
+        );
+    }
+
+    #[test]
+    fn test_lex_translated_text_argument_space_before_paren() {
+        let template = "{{ foo.bar|default:_('foo' ) }}";
+        let variable = trim_variable(template);
+        let (_token, lexer) = lex_variable(variable, START_TAG_LEN).unwrap().unwrap();
+        let tokens: Vec<_> = lexer.collect();
+        let error = LexerError::IncompleteTranslatedString { at: (19, 7).into() };
+        assert_eq!(tokens, vec![Err(error.into())]);
+    }
+
     #[test]
     fn test_lex_translated_text_argument_incomplete_string() {
         let template = "{{ foo.bar|default:_('foo }}";
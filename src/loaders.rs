@@ -1,5 +1,6 @@
 use std::collections::HashMap;
 use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, MutexGuard};
 
 use cached::proc_macro::cached;
 use encoding_rs::Encoding;
@@ -82,7 +83,7 @@ impl FileSystemLoader {
         &self,
         py: Python<'_>,
         template_name: &str,
-        engine: &EngineData,
+        engine: &Arc<EngineData>,
     ) -> Result<PyResult<Template>, LoaderError> {
         let mut tried = Vec::new();
         for template_dir in &self.dirs {
@@ -126,7 +127,7 @@ impl AppDirsLoader {
         &self,
         py: Python<'_>,
         template_name: &str,
-        engine: &EngineData,
+        engine: &Arc<EngineData>,
     ) -> Result<PyResult<Template>, LoaderError> {
         let dirs = match get_app_template_dirs(py, "templates") {
             Ok(dirs) => dirs,
@@ -137,8 +138,10 @@ impl AppDirsLoader {
     }
 }
 
+/// Cached templates are stored without their engine, since the engine owns
+/// this loader. The engine is attached again when a template is returned.
 pub struct CachedLoader {
-    cache: HashMap<String, Result<Template, LoaderError>>,
+    cache: Mutex<HashMap<String, Result<Template, LoaderError>>>,
     pub loaders: Vec<Loader>,
 }
 
@@ -146,26 +149,43 @@ impl CachedLoader {
     pub fn new(loaders: Vec<Loader>) -> Self {
         Self {
             loaders,
-            cache: HashMap::new(),
+            cache: Mutex::new(HashMap::new()),
         }
     }
 
+    /// Forget all cached templates, so they are loaded again on next use.
+    pub fn reset(&self) {
+        self.lock_cache().clear();
+        for loader in &self.loaders {
+            loader.reset();
+        }
+    }
+
+    // The lock is never held while loading a template, since that can
+    // release the GIL and let another thread in.
+    fn lock_cache(&self) -> MutexGuard<'_, HashMap<String, Result<Template, LoaderError>>> {
+        self.cache
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
     fn get_template(
-        &mut self,
+        &self,
         py: Python<'_>,
         template_name: &str,
-        engine: &EngineData,
+        engine: &Arc<EngineData>,
     ) -> Result<PyResult<Template>, LoaderError> {
-        match self.cache.get(template_name) {
-            Some(Ok(template)) => Ok(Ok((*template).clone())),
-            Some(Err(e)) => Err(e.clone()),
+        let cached = self.lock_cache().get(template_name).cloned();
+        match cached {
+            Some(Ok(template)) => Ok(Ok(template.with_engine(engine))),
+            Some(Err(e)) => Err(e),
             None => {
                 let mut tried = Vec::new();
-                for loader in &mut self.loaders {
+                for loader in &self.loaders {
                     match loader.get_template(py, template_name, engine) {
                         Ok(Ok(template)) => {
-                            self.cache
-                                .insert(template_name.to_string(), Ok(template.clone()));
+                            self.lock_cache()
+                                .insert(template_name.to_string(), Ok(template.without_engine()));
                             return Ok(Ok(template));
                         }
                         Ok(Err(e)) => return Ok(Err(e)),
@@ -173,7 +193,7 @@ impl CachedLoader {
                     }
                 }
                 let error = LoaderError { tried };
-                self.cache
+                self.lock_cache()
                     .insert(template_name.to_string(), Err(error.clone()));
                 Err(error)
             }
@@ -194,7 +214,7 @@ impl LocMemLoader {
         &self,
         py: Python<'_>,
         template_name: &str,
-        engine: &EngineData,
+        engine: &Arc<EngineData>,
     ) -> Result<PyResult<Template>, LoaderError> {
         if let Some(contents) = self.templates.get(template_name) {
             Ok(Template::new(
@@ -214,40 +234,31 @@ impl LocMemLoader {
     }
 }
 
-pub struct ExternalLoader {}
-
-impl ExternalLoader {
-    fn get_template(
-        &self,
-        _py: Python<'_>,
-        _template_name: &str,
-        _engine: &EngineData,
-    ) -> Result<PyResult<Template>, LoaderError> {
-        todo!()
-    }
-}
-
 pub enum Loader {
     FileSystem(FileSystemLoader),
     AppDirs(AppDirsLoader),
     Cached(CachedLoader),
     LocMem(LocMemLoader),
-    External(ExternalLoader),
 }
 
 impl Loader {
     pub fn get_template(
-        &mut self,
+        &self,
         py: Python<'_>,
         template_name: &str,
-        engine: &EngineData,
+        engine: &Arc<EngineData>,
     ) -> Result<PyResult<Template>, LoaderError> {
         match self {
             Self::FileSystem(loader) => loader.get_template(py, template_name, engine),
             Self::AppDirs(loader) => loader.get_template(py, template_name, engine),
             Self::Cached(loader) => loader.get_template(py, template_name, engine),
             Self::LocMem(loader) => loader.get_template(py, template_name, engine),
-            Self::External(loader) => loader.get_template(py, template_name, engine),
+        }
+    }
+
+    pub fn reset(&self) {
+        if let Self::Cached(loader) = self {
+            loader.reset()
         }
     }
 }
@@ -298,6 +309,34 @@ mod tests {
         })
     }
 
+    #[test]
+    fn test_filesystem_loader_temp_dir() {
+        pyo3::prepare_freethreaded_python();
+
+        let dir = std::env::temp_dir().join(format!("rusty-templates-{}", std::process::id()));
+        std::fs::create_dir_all(dir.join("nested")).unwrap();
+        std::fs::write(dir.join("nested/hello.html"), "Hello {{ user }}!").unwrap();
+
+        Python::with_gil(|py| {
+            let engine = EngineData::empty();
+            let loader = FileSystemLoader::new(vec![dir.clone()], encoding_rs::UTF_8);
+            let template = loader
+                .get_template(py, "nested/hello.html", &engine)
+                .unwrap()
+                .unwrap();
+
+            assert_eq!(template.template, "Hello {{ user }}!");
+            assert_eq!(template.filename.unwrap(), dir.join("nested/hello.html"));
+
+            let error = loader
+                .get_template(py, "../hello.html", &engine)
+                .unwrap_err();
+            assert_eq!(error, LoaderError { tried: Vec::new() });
+        });
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
     #[test]
     fn test_filesystem_loader_missing_template() {
         pyo3::prepare_freethreaded_python();
@@ -373,7 +412,7 @@ mod tests {
                 FileSystemLoader::new(vec![PathBuf::from("tests/templates")], encoding_rs::UTF_8);
 
             // Wrap the FileSystemLoader in a CachedLoader
-            let mut cached_loader = CachedLoader::new(vec![Loader::FileSystem(filesystem_loader)]);
+            let cached_loader = CachedLoader::new(vec![Loader::FileSystem(filesystem_loader)]);
 
             // Load a template via the CachedLoader
             let template = cached_loader
@@ -391,8 +430,8 @@ mod tests {
             assert_eq!(template.filename.unwrap(), expected_path);
 
             // Verify the cache state after first load
-            assert_eq!(cached_loader.cache.len(), 1);
-            verify_cache(&cached_loader.cache, "basic.txt", &expected_path);
+            assert_eq!(cached_loader.lock_cache().len(), 1);
+            verify_cache(&cached_loader.lock_cache(), "basic.txt", &expected_path);
 
             // Load the same template again via the CachedLoader
             let template = cached_loader
@@ -404,8 +443,70 @@ mod tests {
             assert_eq!(template.filename.unwrap(), expected_path);
 
             // Verify the cache state remains consistent
-            assert_eq!(cached_loader.cache.len(), 1);
-            verify_cache(&cached_loader.cache, "basic.txt", &expected_path);
+            assert_eq!(cached_loader.lock_cache().len(), 1);
+            verify_cache(&cached_loader.lock_cache(), "basic.txt", &expected_path);
+        });
+    }
+
+    #[test]
+    fn test_cached_loader_loads_once() {
+        pyo3::prepare_freethreaded_python();
+
+        let dir =
+            std::env::temp_dir().join(format!("rusty-templates-cached-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("cached.html");
+        std::fs::write(&path, "first").unwrap();
+
+        Python::with_gil(|py| {
+            let engine = EngineData::empty();
+            let filesystem_loader = FileSystemLoader::new(vec![dir.clone()], encoding_rs::UTF_8);
+            let cached_loader = CachedLoader::new(vec![Loader::FileSystem(filesystem_loader)]);
+            let load = || {
+                cached_loader
+                    .get_template(py, "cached.html", &engine)
+                    .unwrap()
+                    .unwrap()
+                    .template
+            };
+
+            assert_eq!(load(), "first");
+
+            // The file is not read again while the template is cached
+            std::fs::write(&path, "second").unwrap();
+            assert_eq!(load(), "first");
+            std::fs::remove_file(&path).unwrap();
+            assert_eq!(load(), "first");
+
+            cached_loader.reset();
+            assert!(cached_loader.lock_cache().is_empty());
+            std::fs::write(&path, "third").unwrap();
+            assert_eq!(load(), "third");
+        });
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_cached_loader_does_not_keep_engine_alive() {
+        pyo3::prepare_freethreaded_python();
+
+        Python::with_gil(|py| {
+            let templates = HashMap::from([("index.html".to_string(), "Hello".to_string())]);
+            let locmem_loader = Loader::LocMem(LocMemLoader::new(templates));
+            let cached_loader = Loader::Cached(CachedLoader::new(vec![locmem_loader]));
+            let engine = EngineData::with_loaders(vec![cached_loader]);
+
+            let template = engine.get_template(py, "index.html").unwrap();
+            assert_eq!(template, template.without_engine().with_engine(&engine));
+            let cached = engine.get_template(py, "index.html").unwrap();
+            assert_eq!(cached, template);
+
+            drop(template);
+            drop(cached);
+            let weak = Arc::downgrade(&engine);
+            drop(engine);
+            assert!(weak.upgrade().is_none());
         });
     }
 
@@ -418,7 +519,7 @@ mod tests {
             let filesystem_loader =
                 FileSystemLoader::new(vec![PathBuf::from("tests/templates")], encoding_rs::UTF_8);
 
-            let mut cached_loader = CachedLoader::new(vec![Loader::FileSystem(filesystem_loader)]);
+            let cached_loader = CachedLoader::new(vec![Loader::FileSystem(filesystem_loader)]);
             let error = cached_loader
                 .get_template(py, "missing.txt", &engine)
                 .unwrap_err();
@@ -436,9 +537,13 @@ mod tests {
             };
             assert_eq!(error, expected_err);
 
-            let cache = &cached_loader.cache;
             assert_eq!(
-                cache.get("missing.txt").unwrap().as_ref().unwrap_err(),
+                cached_loader
+                    .lock_cache()
+                    .get("missing.txt")
+                    .unwrap()
+                    .as_ref()
+                    .unwrap_err(),
                 &expected_err
             );
 
@@ -458,7 +563,7 @@ mod tests {
             let filesystem_loader =
                 FileSystemLoader::new(vec![PathBuf::from("tests/templates")], encoding_rs::UTF_8);
 
-            let mut cached_loader = CachedLoader::new(vec![Loader::FileSystem(filesystem_loader)]);
+            let cached_loader = CachedLoader::new(vec![Loader::FileSystem(filesystem_loader)]);
             let error = cached_loader
                 .get_template(py, "invalid.txt", &engine)
                 .unwrap()
@@ -1,5 +1,6 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::iter::Peekable;
+use std::sync::Arc;
 
 use either::Either;
 use miette::{Diagnostic, SourceSpan};
@@ -11,26 +12,48 @@ use thiserror::Error;
 use crate::filters::AddFilter;
 use crate::filters::AddSlashesFilter;
 use crate::filters::CapfirstFilter;
+use crate::filters::DateFilter;
 use crate::filters::DefaultFilter;
+use crate::filters::DictsortFilter;
+use crate::filters::DictsortreversedFilter;
+use crate::filters::DivisiblebyFilter;
 use crate::filters::EscapeFilter;
 use crate::filters::ExternalFilter;
 use crate::filters::FilterType;
+use crate::filters::FirstFilter;
+use crate::filters::FloatformatFilter;
+use crate::filters::JoinFilter;
+use crate::filters::LastFilter;
+use crate::filters::LengthFilter;
 use crate::filters::LowerFilter;
 use crate::filters::SafeFilter;
+use crate::filters::SafeseqFilter;
 use crate::filters::SlugifyFilter;
+use crate::filters::StringformatFilter;
+use crate::filters::TimeFilter;
+use crate::filters::TruncatecharsFilter;
+use crate::filters::TruncatecharsHtmlFilter;
+use crate::filters::TruncatewordsFilter;
+use crate::filters::TruncatewordsHtmlFilter;
 use crate::filters::UpperFilter;
+use crate::filters::WordcountFilter;
+use crate::filters::YesnoFilter;
 use crate::lex::START_TAG_LEN;
 use crate::lex::autoescape::{AutoescapeEnabled, AutoescapeError, lex_autoescape_argument};
 use crate::lex::common::LexerError;
-use crate::lex::core::{Lexer, TokenType};
+use crate::lex::core::{Lexer, Token, TokenType, find_delimiter};
+use crate::lex::forloop::{ForLexerError, lex_for};
 use crate::lex::ifcondition::{
     IfConditionAtom, IfConditionLexer, IfConditionOperator, IfConditionTokenType,
 };
 use crate::lex::load::{LoadLexer, LoadToken};
+use crate::lex::simple_tag::{
+    SimpleTagLexer, SimpleTagLexerError, SimpleTagToken, SimpleTagTokenType,
+};
 use crate::lex::tag::{TagLexerError, TagParts, lex_tag};
-use crate::lex::url::{UrlLexer, UrlLexerError, UrlToken, UrlTokenType};
 use crate::lex::variable::{
-    Argument as ArgumentToken, ArgumentType as ArgumentTokenType, VariableLexerError, lex_variable,
+    Argument as ArgumentToken, ArgumentType as ArgumentTokenType, VariableLexerError, lex_filters,
+    lex_variable,
 };
 use crate::types::Argument;
 use crate::types::ArgumentType;
@@ -44,15 +67,24 @@ impl ArgumentToken {
         Ok(Argument {
             at: self.at,
             argument_type: match self.argument_type {
-                ArgumentTokenType::Variable => ArgumentType::Variable(Variable::new(self.at)),
+                ArgumentTokenType::Variable => {
+                    ArgumentType::Variable(Variable::new(self.at, template))
+                }
                 ArgumentTokenType::Text => ArgumentType::Text(Text::new(self.content_at())),
-                ArgumentTokenType::Numeric => match template.content(self.at).parse::<BigInt>() {
-                    Ok(n) => ArgumentType::Int(n),
-                    Err(_) => match template.content(self.at).parse::<f64>() {
-                        Ok(f) => ArgumentType::Float(f),
-                        Err(_) => return Err(ParseError::InvalidNumber { at: self.at.into() }),
-                    },
-                },
+                // Like Python's `int`, leading zeros are dropped rather than read as octal.
+                ArgumentTokenType::Numeric => {
+                    let content = template.content(self.at);
+                    if let Ok(n) = content.parse::<i64>() {
+                        ArgumentType::Int(n)
+                    } else if let Ok(n) = content.parse::<BigInt>() {
+                        ArgumentType::BigInt(n)
+                    } else {
+                        match content.parse::<f64>() {
+                            Ok(f) => ArgumentType::Float(f),
+                            Err(_) => return Err(ParseError::InvalidNumber { at: self.at.into() }),
+                        }
+                    }
+                }
                 ArgumentTokenType::TranslatedText => {
                     ArgumentType::TranslatedText(TranslatedText::new(self.content_at()))
                 }
@@ -71,10 +103,20 @@ pub enum TagElement {
     Filter(Box<Filter>),
 }
 
-fn unexpected_argument(filter: &'static str, right: Argument) -> ParseError {
-    ParseError::UnexpectedArgument {
-        filter,
-        at: right.at.into(),
+fn expect_no_argument(filter: &'static str, right: Option<Argument>) -> Result<(), ParseError> {
+    match right {
+        Some(right) => Err(ParseError::UnexpectedArgument {
+            filter,
+            at: right.at.into(),
+        }),
+        None => Ok(()),
+    }
+}
+
+fn expect_argument(at: (usize, usize), right: Option<Argument>) -> Result<Argument, ParseError> {
+    match right {
+        Some(right) => Ok(right),
+        None => Err(ParseError::MissingArgument { at: at.into() }),
     }
 }
 
@@ -92,60 +134,130 @@ impl Filter {
         left: TagElement,
         right: Option<Argument>,
     ) -> Result<Self, ParseError> {
-        let filter = match parser.template.content(at) {
-            "add" => match right {
-                Some(right) => FilterType::Add(AddFilter::new(right)),
-                None => return Err(ParseError::MissingArgument { at: at.into() }),
-            },
-            "addslashes" => match right {
-                Some(right) => return Err(unexpected_argument("addslashes", right)),
-                None => FilterType::AddSlashes(AddSlashesFilter),
-            },
-            "capfirst" => match right {
-                Some(right) => return Err(unexpected_argument("capfirst", right)),
-                None => FilterType::Capfirst(CapfirstFilter),
-            },
-            "default" => match right {
-                Some(right) => FilterType::Default(DefaultFilter::new(right)),
-                None => return Err(ParseError::MissingArgument { at: at.into() }),
-            },
-            "escape" => match right {
-                Some(right) => return Err(unexpected_argument("escape", right)),
-                None => FilterType::Escape(EscapeFilter),
-            },
-            "lower" => match right {
-                Some(right) => return Err(unexpected_argument("lower", right)),
-                None => FilterType::Lower(LowerFilter),
-            },
-            "safe" => match right {
-                Some(right) => return Err(unexpected_argument("safe", right)),
-                None => FilterType::Safe(SafeFilter),
-            },
-            "slugify" => match right {
-                Some(right) => return Err(unexpected_argument("slugify", right)),
-                None => FilterType::Slugify(SlugifyFilter),
-            },
-            "upper" => match right {
-                Some(right) => return Err(unexpected_argument("upper", right)),
-                None => FilterType::Upper(UpperFilter),
-            },
-            external => {
-                let external = match parser.external_filters.get(external) {
-                    Some(external) => external.clone().unbind(),
-                    None => {
-                        return Err(ParseError::InvalidFilter {
-                            at: at.into(),
-                            filter: external.to_string(),
-                        });
-                    }
-                };
-                FilterType::External(ExternalFilter::new(external, right))
-            }
-        };
+        let filter = parse_filter_type(parser, at, right)?;
         Ok(Self { at, left, filter })
     }
 }
 
+fn parse_filter_type(
+    parser: &Parser,
+    at: (usize, usize),
+    right: Option<Argument>,
+) -> Result<FilterType, ParseError> {
+    Ok(match parser.template.content(at) {
+        "add" => FilterType::Add(AddFilter::new(expect_argument(at, right)?)),
+        "addslashes" => {
+            expect_no_argument("addslashes", right)?;
+            FilterType::AddSlashes(AddSlashesFilter)
+        }
+        "capfirst" => {
+            expect_no_argument("capfirst", right)?;
+            FilterType::Capfirst(CapfirstFilter)
+        }
+        "date" => FilterType::Date(DateFilter::new(right)),
+        "default" => FilterType::Default(DefaultFilter::new(expect_argument(at, right)?)),
+        "dictsort" => FilterType::Dictsort(DictsortFilter::new(expect_argument(at, right)?)),
+        "dictsortreversed" => {
+            FilterType::Dictsortreversed(DictsortreversedFilter::new(expect_argument(at, right)?))
+        }
+        "divisibleby" => {
+            FilterType::Divisibleby(DivisiblebyFilter::new(expect_argument(at, right)?))
+        }
+        "escape" => {
+            expect_no_argument("escape", right)?;
+            FilterType::Escape(EscapeFilter)
+        }
+        "first" => {
+            expect_no_argument("first", right)?;
+            FilterType::First(FirstFilter)
+        }
+        "floatformat" => FilterType::Floatformat(FloatformatFilter::new(right)),
+        "join" => FilterType::Join(JoinFilter::new(expect_argument(at, right)?)),
+        "last" => {
+            expect_no_argument("last", right)?;
+            FilterType::Last(LastFilter)
+        }
+        "length" => {
+            expect_no_argument("length", right)?;
+            FilterType::Length(LengthFilter)
+        }
+        "lower" => {
+            expect_no_argument("lower", right)?;
+            FilterType::Lower(LowerFilter)
+        }
+        "safe" => {
+            expect_no_argument("safe", right)?;
+            FilterType::Safe(SafeFilter)
+        }
+        "safeseq" => {
+            expect_no_argument("safeseq", right)?;
+            FilterType::Safeseq(SafeseqFilter)
+        }
+        "slugify" => {
+            expect_no_argument("slugify", right)?;
+            FilterType::Slugify(SlugifyFilter)
+        }
+        "stringformat" => {
+            FilterType::Stringformat(StringformatFilter::new(expect_argument(at, right)?))
+        }
+        "time" => FilterType::Time(TimeFilter::new(right)),
+        "truncatechars" => {
+            FilterType::Truncatechars(TruncatecharsFilter::new(expect_argument(at, right)?))
+        }
+        "truncatechars_html" => {
+            FilterType::TruncatecharsHtml(TruncatecharsHtmlFilter::new(expect_argument(at, right)?))
+        }
+        "truncatewords" => {
+            FilterType::Truncatewords(TruncatewordsFilter::new(expect_argument(at, right)?))
+        }
+        "truncatewords_html" => {
+            FilterType::TruncatewordsHtml(TruncatewordsHtmlFilter::new(expect_argument(at, right)?))
+        }
+        "upper" => {
+            expect_no_argument("upper", right)?;
+            FilterType::Upper(UpperFilter)
+        }
+        "wordcount" => {
+            expect_no_argument("wordcount", right)?;
+            FilterType::Wordcount(WordcountFilter)
+        }
+        "yesno" => FilterType::Yesno(YesnoFilter::new(right)),
+        external => {
+            let external = match parser.external_filters.get(external) {
+                Some(external) => external.clone().unbind(),
+                None => {
+                    return Err(ParseError::InvalidFilter {
+                        at: at.into(),
+                        filter: external.to_string(),
+                    });
+                }
+            };
+            FilterType::External(ExternalFilter::new(external, right))
+        }
+    })
+}
+
+/// Collapse whitespace spanning lines to a single space, like Django's
+/// `trim_whitespace` for `{% blocktranslate trimmed %}`.
+fn trim_whitespace(message: &str) -> String {
+    let mut trimmed = String::with_capacity(message.len());
+    let mut whitespace = String::new();
+    for c in message.trim().chars() {
+        if c.is_whitespace() {
+            whitespace.push(c);
+            continue;
+        }
+        if whitespace.contains('\n') {
+            trimmed.push(' ');
+        } else {
+            trimmed.push_str(&whitespace);
+        }
+        whitespace.clear();
+        trimmed.push(c);
+    }
+    trimmed
+}
+
 fn parse_numeric(content: &str, at: (usize, usize)) -> Result<TagElement, ParseError> {
     match content.parse::<BigInt>() {
         Ok(n) => Ok(TagElement::Int(n)),
@@ -156,20 +268,128 @@ fn parse_numeric(content: &str, at: (usize, usize)) -> Result<TagElement, ParseE
     }
 }
 
-impl UrlToken {
+impl SimpleTagToken {
     fn parse(&self, parser: &Parser) -> Result<TagElement, ParseError> {
         let content_at = self.content_at();
         let (start, _len) = content_at;
         let content = parser.template.content(content_at);
         match self.token_type {
-            UrlTokenType::Numeric => parse_numeric(content, self.at),
-            UrlTokenType::Text => Ok(TagElement::Text(Text::new(content_at))),
-            UrlTokenType::TranslatedText => Ok(TagElement::TranslatedText(Text::new(content_at))),
-            UrlTokenType::Variable => parser.parse_variable(content, content_at, start),
+            SimpleTagTokenType::Numeric => parse_numeric(content, self.at),
+            SimpleTagTokenType::Text => Ok(TagElement::Text(Text::new(content_at))),
+            SimpleTagTokenType::TranslatedText => {
+                Ok(TagElement::TranslatedText(Text::new(content_at)))
+            }
+            SimpleTagTokenType::Variable => parser.parse_variable(content, content_at, start),
         }
     }
 }
 
+#[derive(Clone, Debug, PartialEq)]
+pub struct Block {
+    pub name: String,
+    pub nodes: Arc<Vec<TokenTree>>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Extends {
+    pub parent: TagElement,
+    pub parent_at: (usize, usize),
+    pub blocks: Vec<Block>,
+}
+
+/// A `{% cycle %}` tag. A named cycle referenced again later is a clone
+/// sharing the same `values`, whose address identifies the cycle's state.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Cycle {
+    pub values: Arc<Vec<TagElement>>,
+    pub variable: Option<String>,
+    pub silent: bool,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Firstof {
+    pub args: Vec<TagElement>,
+    pub variable: Option<String>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct For {
+    pub variables: Vec<String>,
+    pub iterable: TagElement,
+    pub reversed: bool,
+    pub body: Vec<TokenTree>,
+    pub empty: Option<Vec<TokenTree>>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Include {
+    pub template_name: TagElement,
+    pub kwargs: Vec<(String, TagElement)>,
+    pub only: bool,
+}
+
+/// A `{% blocktranslate %}` tag. The messages hold `%(name)s` placeholders
+/// for the variables, as they are passed to `gettext` and `ngettext`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Blocktranslate {
+    pub tag: &'static str,
+    pub singular: String,
+    pub plural: Option<String>,
+    pub variables: Vec<String>,
+    pub kwargs: Vec<(String, TagElement)>,
+    pub count: Option<(String, TagElement)>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct FilterBlock {
+    pub filters: Vec<FilterType>,
+    pub nodes: Vec<TokenTree>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Translate {
+    pub message: TagElement,
+    pub noop: bool,
+    pub message_context: Option<TagElement>,
+    pub variable: Option<String>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Regroup {
+    pub target: TagElement,
+    /// The grouping key, looked up on each item through `variable`.
+    pub key: TagElement,
+    pub variable: String,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Widthratio {
+    pub value: TagElement,
+    pub max_value: TagElement,
+    pub max_width: TagElement,
+    pub variable: Option<String>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum LoremMethod {
+    Words,
+    Paragraphs,
+    Blocks,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Lorem {
+    pub count: TagElement,
+    pub method: LoremMethod,
+    pub common: bool,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Now {
+    pub format: String,
+    pub variable: Option<String>,
+}
+
 #[derive(Clone, Debug, PartialEq)]
 pub struct Url {
     pub view_name: TagElement,
@@ -324,13 +544,81 @@ pub enum Tag {
         enabled: AutoescapeEnabled,
         nodes: Vec<TokenTree>,
     },
+    Block(Block),
+    Blocktranslate(Blocktranslate),
+    CsrfToken,
+    Cycle(Cycle),
+    /// `{% debug %}`, with the names of the filters loaded so far.
+    Debug {
+        filters: Vec<String>,
+    },
+    Extends(Extends),
+    FilterBlock(FilterBlock),
+    Firstof(Firstof),
+    For(For),
     If {
         condition: IfCondition,
         truthy: Vec<TokenTree>,
         falsey: Option<Vec<TokenTree>>,
     },
+    Include(Include),
     Load,
+    Lorem(Lorem),
+    Now(Now),
+    Regroup(Regroup),
+    Spaceless {
+        nodes: Vec<TokenTree>,
+    },
+    Templatetag(&'static str),
+    Translate(Translate),
     Url(Url),
+    Widthratio(Widthratio),
+}
+
+/// Collect all `{% block %}` tags in `nodes`, including blocks nested inside
+/// other tags.
+pub fn collect_blocks<'a>(nodes: &'a [TokenTree], blocks: &mut Vec<&'a Block>) {
+    for node in nodes {
+        let TokenTree::Tag(tag) = node else {
+            continue;
+        };
+        match tag {
+            Tag::Autoescape { nodes, .. }
+            | Tag::FilterBlock(FilterBlock { nodes, .. })
+            | Tag::Spaceless { nodes } => collect_blocks(nodes, blocks),
+            Tag::Block(block) => {
+                blocks.push(block);
+                collect_blocks(&block.nodes, blocks);
+            }
+            Tag::For(for_tag) => {
+                collect_blocks(&for_tag.body, blocks);
+                if let Some(empty) = &for_tag.empty {
+                    collect_blocks(empty, blocks);
+                }
+            }
+            Tag::If { truthy, falsey, .. } => {
+                collect_blocks(truthy, blocks);
+                if let Some(falsey) = falsey {
+                    collect_blocks(falsey, blocks);
+                }
+            }
+            Tag::Blocktranslate(_)
+            | Tag::CsrfToken
+            | Tag::Cycle(_)
+            | Tag::Debug { .. }
+            | Tag::Extends(_)
+            | Tag::Firstof(_)
+            | Tag::Include(_)
+            | Tag::Load
+            | Tag::Lorem(_)
+            | Tag::Now(_)
+            | Tag::Regroup(_)
+            | Tag::Templatetag(_)
+            | Tag::Translate(_)
+            | Tag::Url(_)
+            | Tag::Widthratio(_) => {}
+        }
+    }
 }
 
 #[derive(PartialEq, Eq)]
@@ -338,7 +626,12 @@ enum EndTagType {
     Autoescape,
     Elif,
     Else,
+    Empty,
+    EndBlock,
+    EndFilter,
+    EndFor,
     EndIf,
+    EndSpaceless,
     Verbatim,
 }
 
@@ -348,7 +641,12 @@ impl EndTagType {
             EndTagType::Autoescape => "endautoescape",
             EndTagType::Elif => "elif",
             EndTagType::Else => "else",
+            EndTagType::Empty => "empty",
+            EndTagType::EndBlock => "endblock",
+            EndTagType::EndFilter => "endfilter",
+            EndTagType::EndFor => "endfor",
             EndTagType::EndIf => "endif",
+            EndTagType::EndSpaceless => "endspaceless",
             EndTagType::Verbatim => "endverbatim",
         }
     }
@@ -369,6 +667,11 @@ impl EndTag {
 
 #[derive(Clone, Debug, PartialEq)]
 pub enum TokenTree {
+    /// A `{# #}` comment, only kept when parsing with
+    /// `ParserOptions::keep_comments`.
+    Comment(Text),
+    Int(BigInt),
+    Float(f64),
     Text(Text),
     TranslatedText(Text),
     Tag(Tag),
@@ -383,8 +686,8 @@ impl From<TagElement> for TokenTree {
             TagElement::TranslatedText(text) => Self::TranslatedText(text),
             TagElement::Variable(variable) => Self::Variable(variable),
             TagElement::Filter(filter) => Self::Filter(filter),
-            TagElement::Int(_) => todo!(),
-            TagElement::Float(_) => todo!(),
+            TagElement::Int(n) => Self::Int(n),
+            TagElement::Float(f) => Self::Float(f),
         }
     }
 }
@@ -414,13 +717,123 @@ pub enum ParseError {
     BlockError(#[from] TagLexerError),
     #[error(transparent)]
     #[diagnostic(transparent)]
+    ForLexerError(#[from] ForLexerError),
+    #[error(transparent)]
+    #[diagnostic(transparent)]
     LexerError(#[from] LexerError),
     #[error(transparent)]
     #[diagnostic(transparent)]
-    UrlLexerError(#[from] UrlLexerError),
+    SimpleTagLexerError(#[from] SimpleTagLexerError),
     #[error(transparent)]
     #[diagnostic(transparent)]
     VariableError(#[from] VariableLexerError),
+    #[error("'block' tag takes only one argument")]
+    BlockArguments {
+        #[label("here")]
+        at: SourceSpan,
+    },
+    #[error("'count' in '{tag}' tag expected exactly one keyword argument")]
+    BlocktranslateCountArguments {
+        tag: &'static str,
+        #[label("here")]
+        at: SourceSpan,
+    },
+    #[error("'{tag}' doesn't allow other block tags inside it")]
+    BlocktranslateNestedTag {
+        tag: &'static str,
+        #[label("here")]
+        at: SourceSpan,
+    },
+    #[error("'{tag}' doesn't allow other block tags (seen '{seen}') inside it")]
+    BlocktranslateUnexpectedTag {
+        tag: &'static str,
+        seen: String,
+        #[label("here")]
+        at: SourceSpan,
+    },
+    #[error("Unknown argument for '{tag}' tag: '{argument}'")]
+    BlocktranslateUnknownArgument {
+        tag: &'static str,
+        argument: String,
+        #[label("here")]
+        at: SourceSpan,
+    },
+    #[error("'with' in '{tag}' tag needs at least one keyword argument")]
+    BlocktranslateWithoutKeywords {
+        tag: &'static str,
+        #[label("here")]
+        at: SourceSpan,
+    },
+    #[error("'cycle' tag requires at least two arguments")]
+    CycleArguments {
+        #[label("here")]
+        at: SourceSpan,
+    },
+    #[error("Only 'silent' flag is allowed after cycle's name, not '{flag}'.")]
+    CycleSilentFlag {
+        flag: String,
+        #[label("here")]
+        at: SourceSpan,
+    },
+    #[error("'block' tag with name '{name}' appears more than once")]
+    DuplicateBlock {
+        name: String,
+        #[label("here")]
+        at: SourceSpan,
+    },
+    #[error("'extends' takes one argument")]
+    ExtendsArguments {
+        #[label("here")]
+        at: SourceSpan,
+    },
+    #[error("'extends' must be the first tag in the template")]
+    ExtendsNotFirst {
+        #[label("here")]
+        at: SourceSpan,
+    },
+    #[error("'extends' cannot appear more than once in the same template")]
+    ExtendsRepeated {
+        #[label("here")]
+        at: SourceSpan,
+    },
+    #[error("'filter' tag requires at least one filter")]
+    FilterTagNoArguments {
+        #[label("here")]
+        at: SourceSpan,
+    },
+    #[error("\"filter {filter}\" is not permitted.  Use the \"autoescape\" tag instead.")]
+    FilterTagNotPermitted {
+        filter: String,
+        #[label("here")]
+        at: SourceSpan,
+    },
+    #[error("'firstof' statement requires at least one argument")]
+    FirstofNoArguments {
+        #[label("here")]
+        at: SourceSpan,
+    },
+    #[error("'include' tag takes at least one argument: the name of the template to be included")]
+    IncludeNoArguments {
+        #[label("here")]
+        at: SourceSpan,
+    },
+    #[error("The '{option}' option was specified more than once")]
+    OptionRepeated {
+        option: String,
+        #[label("here")]
+        at: SourceSpan,
+    },
+    #[error("Unknown argument for 'include' tag: '{argument}'")]
+    IncludeUnknownArgument {
+        argument: String,
+        #[label("here")]
+        at: SourceSpan,
+    },
+    #[error("'with' in 'include' tag needs at least one keyword argument")]
+    IncludeWithoutKeywords {
+        #[label("here")]
+        at: SourceSpan,
+    },
     #[error("Invalid filter: '{filter}'")]
     InvalidFilter {
         filter: String,
@@ -438,18 +851,20 @@ pub enum ParseError {
         #[label("here")]
         at: SourceSpan,
     },
+    #[error("Unexpected tag {found}, expected {expected}")]
+    MismatchedEndTag {
+        found: &'static str,
+        expected: String,
+        #[label("unexpected tag")]
+        at: SourceSpan,
+        #[label("start tag")]
+        start_at: SourceSpan,
+    },
     #[error("Missing boolean expression")]
     MissingBooleanExpression {
         #[label("here")]
         at: SourceSpan,
     },
-    #[error("Unclosed '{start}' tag. Looking for one of: {expected}")]
-    MissingEndTag {
-        start: &'static str,
-        expected: String,
-        #[label("started here")]
-        at: SourceSpan,
-    },
     #[error("'{tag}' is not a valid tag or filter in tag library '{library}'")]
     MissingFilterTag {
         tag: String,
@@ -472,11 +887,55 @@ pub enum ParseError {
         #[label("here")]
         at: SourceSpan,
     },
+    #[error("Named cycle '{name}' does not exist")]
+    NamedCycleMissing {
+        name: String,
+        #[label("here")]
+        at: SourceSpan,
+    },
+    #[error("No named cycles in template. '{name}' is not defined")]
+    NoNamedCycles {
+        name: String,
+        #[label("here")]
+        at: SourceSpan,
+    },
+    #[error("Incorrect format for 'lorem' tag")]
+    LoremArguments {
+        #[label("here")]
+        at: SourceSpan,
+    },
+    #[error("'now' statement takes one argument")]
+    NowArguments {
+        #[label("here")]
+        at: SourceSpan,
+    },
     #[error("'url' view name must be a string or variable, not a number")]
     NumericUrlName {
         #[label("here")]
         at: SourceSpan,
     },
+    #[error("'regroup' tag takes five arguments")]
+    RegroupArguments {
+        #[label("here")]
+        at: SourceSpan,
+    },
+    #[error("next-to-last argument to 'regroup' tag must be 'as'")]
+    RegroupExpectedAs {
+        #[label("here")]
+        at: SourceSpan,
+    },
+    #[error("second argument to 'regroup' tag must be 'by'")]
+    RegroupExpectedBy {
+        #[label("here")]
+        at: SourceSpan,
+    },
+    #[error("Unclosed '{tag}' tag. Looking for one of: {expected}")]
+    UnclosedTag {
+        tag: &'static str,
+        expected: String,
+        #[label("started here")]
+        at: SourceSpan,
+    },
     #[error("{filter} filter does not take an argument")]
     UnexpectedArgument {
         filter: &'static str,
@@ -500,14 +959,66 @@ pub enum ParseError {
         #[label("here")]
         at: SourceSpan,
     },
+    #[error("'templatetag' statement takes one argument")]
+    TemplatetagArguments {
+        #[label("here")]
+        at: SourceSpan,
+    },
+    #[error(
+        "Invalid templatetag argument: '{argument}'. Must be one of: ['openblock', 'closeblock', 'openvariable', 'closevariable', 'openbrace', 'closebrace', 'opencomment', 'closecomment']"
+    )]
+    TemplatetagInvalidArgument {
+        argument: String,
+        #[label("here")]
+        at: SourceSpan,
+    },
+    #[error("'{tag}' takes at least one argument")]
+    TranslateArguments {
+        tag: &'static str,
+        #[label("here")]
+        at: SourceSpan,
+    },
+    #[error("Invalid argument '{argument}' provided to the '{tag}' tag for the context option")]
+    TranslateInvalidContext {
+        tag: &'static str,
+        argument: String,
+        #[label("here")]
+        at: SourceSpan,
+    },
+    #[error("No argument provided to the '{tag}' tag for the {option} option")]
+    TranslateMissingOption {
+        tag: &'static str,
+        option: &'static str,
+        #[label("here")]
+        at: SourceSpan,
+    },
+    #[error(
+        "Unknown argument for '{tag}' tag: '{argument}'. The only options available are 'noop', 'context' \"xxx\", and 'as VAR'"
+    )]
+    TranslateUnknownArgument {
+        tag: &'static str,
+        argument: String,
+        #[label("here")]
+        at: SourceSpan,
+    },
     #[error("'url' takes at least one argument, a URL pattern name")]
     UrlTagNoArguments {
         #[label("here")]
         at: SourceSpan,
     },
-    #[error("Unexpected tag {unexpected}, expected {expected}")]
-    WrongEndTag {
-        unexpected: &'static str,
+    #[error("widthratio takes at least three arguments")]
+    WidthratioArguments {
+        #[label("here")]
+        at: SourceSpan,
+    },
+    #[error("Invalid syntax in widthratio tag. Expecting 'as' keyword")]
+    WidthratioExpectedAs {
+        #[label("here")]
+        at: SourceSpan,
+    },
+    #[error("Unexpected tag 'endblock {unexpected}', expected 'endblock' or 'endblock {expected}'")]
+    WrongEndBlock {
+        unexpected: String,
         expected: String,
         #[label("unexpected tag")]
         at: SourceSpan,
@@ -565,6 +1076,18 @@ impl LoadToken {
     }
 }
 
+/// Options changing how a template is parsed, for tooling such as linters.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct ParserOptions {
+    /// Keep `{# #}` comments as `TokenTree::Comment` nodes instead of
+    /// dropping them.
+    pub keep_comments: bool,
+    /// Drop the first newline after a `{% %}` block tag.
+    pub trim_blocks: bool,
+    /// Allow whitespace around the `:` separating a filter from its argument.
+    pub lenient_filters: bool,
+}
+
 pub struct Parser<'t, 'l, 'py> {
     py: Python<'py>,
     template: TemplateString<'t>,
@@ -572,6 +1095,9 @@ pub struct Parser<'t, 'l, 'py> {
     libraries: &'l HashMap<String, Py<PyAny>>,
     external_tags: HashMap<String, Bound<'py, PyAny>>,
     external_filters: HashMap<String, Bound<'py, PyAny>>,
+    block_names: HashSet<String>,
+    named_cycles: HashMap<String, Cycle>,
+    options: ParserOptions,
 }
 
 impl<'t, 'l, 'py> Parser<'t, 'l, 'py> {
@@ -579,14 +1105,30 @@ impl<'t, 'l, 'py> Parser<'t, 'l, 'py> {
         py: Python<'py>,
         template: TemplateString<'t>,
         libraries: &'l HashMap<String, Py<PyAny>>,
+    ) -> Self {
+        Self::new_with_options(py, template, libraries, ParserOptions::default())
+    }
+
+    pub fn new_with_options(
+        py: Python<'py>,
+        template: TemplateString<'t>,
+        libraries: &'l HashMap<String, Py<PyAny>>,
+        options: ParserOptions,
     ) -> Self {
         Self {
             py,
             template,
-            lexer: Lexer::new(template),
+            lexer: if options.trim_blocks {
+                Lexer::new_with_trim_blocks(template)
+            } else {
+                Lexer::new(template)
+            },
             libraries,
             external_tags: HashMap::new(),
             external_filters: HashMap::new(),
+            block_names: HashSet::new(),
+            named_cycles: HashMap::new(),
+            options,
         }
     }
 
@@ -604,14 +1146,25 @@ impl<'t, 'l, 'py> Parser<'t, 'l, 'py> {
             libraries,
             external_tags: HashMap::new(),
             external_filters,
+            block_names: HashSet::new(),
+            named_cycles: HashMap::new(),
+            options: ParserOptions::default(),
         }
     }
 
     pub fn parse(&mut self) -> Result<Vec<TokenTree>, PyParseError> {
+        let content = self.template.0;
+        // Plain text templates don't need the lexer
+        if !content.is_empty() && find_delimiter(content).is_none() {
+            return Ok(vec![TokenTree::Text(Text::new((0, content.len())))]);
+        }
         let mut nodes = Vec::new();
         while let Some(token) = self.lexer.next() {
             let node = match token.token_type {
                 TokenType::Text => TokenTree::Text(Text::new(token.at)),
+                TokenType::Comment if self.options.keep_comments => {
+                    TokenTree::Comment(Text::new(token.content_at()))
+                }
                 TokenType::Comment => continue,
                 TokenType::Variable => self
                     .parse_variable(
@@ -621,10 +1174,21 @@ impl<'t, 'l, 'py> Parser<'t, 'l, 'py> {
                     )?
                     .into(),
                 TokenType::Tag => match self.parse_tag(token.content(self.template), token.at)? {
-                    Either::Left(token_tree) => token_tree,
-                    Either::Right(end_tag) => {
-                        return Err(ParseError::UnexpectedEndTag {
-                            at: end_tag.at.into(),
+                    None => continue,
+                    Some(Either::Left(TokenTree::Tag(Tag::Extends(_))))
+                        if nodes.iter().any(|node| {
+                            !matches!(node, TokenTree::Text(_) | TokenTree::Comment(_))
+                        }) =>
+                    {
+                        return Err(ParseError::ExtendsNotFirst {
+                            at: token.at.into(),
+                        }
+                        .into());
+                    }
+                    Some(Either::Left(token_tree)) => token_tree,
+                    Some(Either::Right(end_tag)) => {
+                        return Err(ParseError::UnexpectedEndTag {
+                            at: end_tag.at.into(),
                             unexpected: end_tag.as_str(),
                         }
                         .into());
@@ -646,6 +1210,9 @@ impl<'t, 'l, 'py> Parser<'t, 'l, 'py> {
         while let Some(token) = self.lexer.next() {
             let node = match token.token_type {
                 TokenType::Text => TokenTree::Text(Text::new(token.at)),
+                TokenType::Comment if self.options.keep_comments => {
+                    TokenTree::Comment(Text::new(token.content_at()))
+                }
                 TokenType::Comment => continue,
                 TokenType::Variable => self
                     .parse_variable(
@@ -655,18 +1222,19 @@ impl<'t, 'l, 'py> Parser<'t, 'l, 'py> {
                     )?
                     .into(),
                 TokenType::Tag => match self.parse_tag(token.content(self.template), token.at)? {
-                    Either::Left(token_tree) => token_tree,
-                    Either::Right(end_tag) => {
+                    None => continue,
+                    Some(Either::Left(token_tree)) => token_tree,
+                    Some(Either::Right(end_tag)) => {
                         if until.contains(&end_tag.end) {
                             return Ok((nodes, end_tag));
                         } else {
-                            return Err(ParseError::WrongEndTag {
+                            return Err(ParseError::MismatchedEndTag {
                                 expected: until
                                     .iter()
                                     .map(|u| u.as_str())
                                     .collect::<Vec<_>>()
                                     .join(", "),
-                                unexpected: end_tag.as_str(),
+                                found: end_tag.as_str(),
                                 at: end_tag.at.into(),
                                 start_at: start_at.into(),
                             }
@@ -677,8 +1245,8 @@ impl<'t, 'l, 'py> Parser<'t, 'l, 'py> {
             };
             nodes.push(node)
         }
-        Err(ParseError::MissingEndTag {
-            start,
+        Err(ParseError::UnclosedTag {
+            tag: start,
             expected: until
                 .iter()
                 .map(|u| u.as_str())
@@ -695,12 +1263,19 @@ impl<'t, 'l, 'py> Parser<'t, 'l, 'py> {
         at: (usize, usize),
         start: usize,
     ) -> Result<TagElement, ParseError> {
-        let (variable_token, filter_lexer) = match lex_variable(variable, start)? {
+        let (head, filter_lexer) = match lex_variable(variable, start)? {
             None => return Err(ParseError::EmptyVariable { at: at.into() }),
             Some(t) => t,
         };
-        let mut var = TagElement::Variable(Variable::new(variable_token.at));
-        for filter_token in filter_lexer {
+        let mut var = match head.parse(self.template)?.argument_type {
+            ArgumentType::Variable(variable) => TagElement::Variable(variable),
+            ArgumentType::Int(n) => TagElement::Int(n.into()),
+            ArgumentType::BigInt(n) => TagElement::Int(n),
+            ArgumentType::Float(f) => TagElement::Float(f),
+            ArgumentType::Text(text) => TagElement::Text(text),
+            ArgumentType::TranslatedText(text) => TagElement::TranslatedText(Text::new(text.at)),
+        };
+        for filter_token in filter_lexer.lenient(self.options.lenient_filters) {
             let filter_token = filter_token?;
             let argument = match filter_token.argument {
                 None => None,
@@ -712,11 +1287,13 @@ impl<'t, 'l, 'py> Parser<'t, 'l, 'py> {
         Ok(var)
     }
 
+    /// Parse a tag into a node or an end tag, or `None` for a tag that
+    /// produces no node, like `{% comment %}`.
     fn parse_tag(
         &mut self,
         tag: &'t str,
         at: (usize, usize),
-    ) -> Result<Either<TokenTree, EndTag>, PyParseError> {
+    ) -> Result<Option<Either<TokenTree, EndTag>>, PyParseError> {
         let maybe_tag = match lex_tag(tag, at.0 + START_TAG_LEN) {
             Ok(maybe_tag) => maybe_tag,
             Err(e) => {
@@ -728,10 +1305,61 @@ impl<'t, 'l, 'py> Parser<'t, 'l, 'py> {
             None => return Err(ParseError::EmptyTag { at: at.into() }.into()),
             Some(t) => t,
         };
-        Ok(match self.template.content(tag.at) {
+        Ok(Some(match self.template.content(tag.at) {
+            "comment" => {
+                self.skip_comment(at)?;
+                return Ok(None);
+            }
             "url" => Either::Left(self.parse_url(at, parts)?),
             "load" => Either::Left(self.parse_load(at, parts)?),
             "autoescape" => Either::Left(self.parse_autoescape(at, parts)?),
+            "block" => Either::Left(self.parse_block(at, parts)?),
+            "blocktranslate" => {
+                Either::Left(self.parse_blocktranslate(at, parts, "blocktranslate")?)
+            }
+            "blocktrans" => Either::Left(self.parse_blocktranslate(at, parts, "blocktrans")?),
+            // Like Django, any arguments to `{% csrf_token %}` are ignored.
+            "csrf_token" => Either::Left(TokenTree::Tag(Tag::CsrfToken)),
+            "cycle" => Either::Left(self.parse_cycle(at, parts)?),
+            "debug" => Either::Left(self.parse_debug()),
+            "endblock" => Either::Right(EndTag {
+                end: EndTagType::EndBlock,
+                at,
+                parts,
+            }),
+            "extends" => Either::Left(self.parse_extends(at, parts)?),
+            "filter" => Either::Left(self.parse_filter_tag(at, parts)?),
+            "endfilter" => Either::Right(EndTag {
+                end: EndTagType::EndFilter,
+                at,
+                parts,
+            }),
+            "firstof" => Either::Left(self.parse_firstof(at, parts)?),
+            "for" => Either::Left(self.parse_for(at, parts)?),
+            "include" => Either::Left(self.parse_include(at, parts)?),
+            "lorem" => Either::Left(self.parse_lorem(at, parts)?),
+            "now" => Either::Left(self.parse_now(at, parts)?),
+            "regroup" => Either::Left(self.parse_regroup(at, parts)?),
+            "templatetag" => Either::Left(self.parse_templatetag(at, parts)?),
+            "translate" => Either::Left(self.parse_translate(at, parts, "translate")?),
+            "trans" => Either::Left(self.parse_translate(at, parts, "trans")?),
+            "widthratio" => Either::Left(self.parse_widthratio(at, parts)?),
+            "spaceless" => Either::Left(self.parse_spaceless(at)?),
+            "endspaceless" => Either::Right(EndTag {
+                end: EndTagType::EndSpaceless,
+                at,
+                parts,
+            }),
+            "empty" => Either::Right(EndTag {
+                end: EndTagType::Empty,
+                at,
+                parts,
+            }),
+            "endfor" => Either::Right(EndTag {
+                end: EndTagType::EndFor,
+                at,
+                parts,
+            }),
             "endautoescape" => Either::Right(EndTag {
                 end: EndTagType::Autoescape,
                 at,
@@ -759,6 +1387,23 @@ impl<'t, 'l, 'py> Parser<'t, 'l, 'py> {
                 parts,
             }),
             _ => todo!(),
+        }))
+    }
+
+    /// Discard everything up to the matching `{% endcomment %}`, without
+    /// parsing it.
+    fn skip_comment(&mut self, at: (usize, usize)) -> Result<(), ParseError> {
+        for token in self.lexer.by_ref() {
+            if token.token_type == TokenType::Tag
+                && token.content(self.template).trim() == "endcomment"
+            {
+                return Ok(());
+            }
+        }
+        Err(ParseError::UnclosedTag {
+            tag: "comment",
+            expected: "endcomment".to_string(),
+            at: at.into(),
         })
     }
 
@@ -769,30 +1414,30 @@ impl<'t, 'l, 'py> Parser<'t, 'l, 'py> {
     ) -> Result<TokenTree, PyParseError> {
         let tokens: Vec<_> = LoadLexer::new(self.template, parts).collect();
         let mut rev = tokens.iter().rev();
-        if let (Some(last), Some(prev)) = (rev.next(), rev.next()) {
-            if self.template.content(prev.at) == "from" {
-                let library = last.load_library(self.py, self.libraries, self.template)?;
-                let filters = self.get_filters(library)?;
-                let tags = self.get_tags(library)?;
-                for token in rev {
-                    let content = self.template.content(token.at);
-                    if let Some(filter) = filters.get(content) {
-                        self.external_filters
-                            .insert(content.to_string(), filter.clone());
-                    } else if let Some(tag) = tags.get(content) {
-                        self.external_tags.insert(content.to_string(), tag.clone());
-                    } else {
-                        return Err(ParseError::MissingFilterTag {
-                            library: self.template.content(last.at).to_string(),
-                            library_at: last.at.into(),
-                            tag: content.to_string(),
-                            tag_at: token.at.into(),
-                        }
-                        .into());
+        if let (Some(last), Some(prev)) = (rev.next(), rev.next())
+            && self.template.content(prev.at) == "from"
+        {
+            let library = last.load_library(self.py, self.libraries, self.template)?;
+            let filters = self.get_filters(library)?;
+            let tags = self.get_tags(library)?;
+            for token in rev {
+                let content = self.template.content(token.at);
+                if let Some(filter) = filters.get(content) {
+                    self.external_filters
+                        .insert(content.to_string(), filter.clone());
+                } else if let Some(tag) = tags.get(content) {
+                    self.external_tags.insert(content.to_string(), tag.clone());
+                } else {
+                    return Err(ParseError::MissingFilterTag {
+                        library: self.template.content(last.at).to_string(),
+                        library_at: last.at.into(),
+                        tag: content.to_string(),
+                        tag_at: token.at.into(),
                     }
+                    .into());
                 }
-                return Ok(TokenTree::Tag(Tag::Load));
             }
+            return Ok(TokenTree::Tag(Tag::Load));
         }
         for token in tokens {
             let library = token.load_library(self.py, self.libraries, self.template)?;
@@ -818,8 +1463,561 @@ impl<'t, 'l, 'py> Parser<'t, 'l, 'py> {
         library.getattr(intern!(self.py, "filters"))?.extract()
     }
 
+    fn parse_cycle(
+        &mut self,
+        at: (usize, usize),
+        parts: TagParts,
+    ) -> Result<TokenTree, ParseError> {
+        let mut tokens = Vec::new();
+        for token in SimpleTagLexer::new(self.template, parts) {
+            let token = token?;
+            if let Some(kwarg) = token.kwarg {
+                let start = kwarg.0 + kwarg.1;
+                let at = (start, token.at.0 + token.at.1 - start);
+                return Err(LexerError::InvalidRemainder { at: at.into() }.into());
+            }
+            tokens.push(token);
+        }
+        let content = |token: &SimpleTagToken| self.template.content(token.at);
+        let (values, variable, silent) = match tokens.as_slice() {
+            [] => return Err(ParseError::CycleArguments { at: at.into() }),
+            [name] => {
+                let name_at = name.at;
+                let name = content(name);
+                if self.named_cycles.is_empty() {
+                    return Err(ParseError::NoNamedCycles {
+                        name: name.to_string(),
+                        at: name_at.into(),
+                    });
+                }
+                return match self.named_cycles.get(name) {
+                    Some(cycle) => Ok(TokenTree::Tag(Tag::Cycle(cycle.clone()))),
+                    None => Err(ParseError::NamedCycleMissing {
+                        name: name.to_string(),
+                        at: name_at.into(),
+                    }),
+                };
+            }
+            [values @ .., as_token, name, flag]
+                if tokens.len() > 3 && content(as_token) == "as" =>
+            {
+                if content(flag) != "silent" {
+                    return Err(ParseError::CycleSilentFlag {
+                        flag: content(flag).to_string(),
+                        at: flag.at.into(),
+                    });
+                }
+                (values, Some(content(name).to_string()), true)
+            }
+            [values @ .., as_token, name] if tokens.len() > 3 && content(as_token) == "as" => {
+                (values, Some(content(name).to_string()), false)
+            }
+            values => (values, None, false),
+        };
+        let values = values
+            .iter()
+            .map(|token| token.parse(self))
+            .collect::<Result<Vec<_>, _>>()?;
+        let cycle = Cycle {
+            values: Arc::new(values),
+            variable,
+            silent,
+        };
+        if let Some(name) = &cycle.variable {
+            self.named_cycles.insert(name.clone(), cycle.clone());
+        }
+        Ok(TokenTree::Tag(Tag::Cycle(cycle)))
+    }
+
+    fn parse_filter_tag(
+        &mut self,
+        at: (usize, usize),
+        parts: TagParts,
+    ) -> Result<TokenTree, PyParseError> {
+        let content = self.template.content(parts.at);
+        if content.trim().is_empty() {
+            return Err(ParseError::FilterTagNoArguments { at: at.into() }.into());
+        }
+        let mut filters = Vec::new();
+        for filter_token in lex_filters(content, parts.at.0) {
+            let filter_token = filter_token.map_err(ParseError::from)?;
+            let name = self.template.content(filter_token.at);
+            // Like Django, the block's escaping is controlled by `{% autoescape %}`
+            if name == "escape" || name == "safe" {
+                return Err(ParseError::FilterTagNotPermitted {
+                    filter: name.to_string(),
+                    at: filter_token.at.into(),
+                }
+                .into());
+            }
+            let argument = match filter_token.argument {
+                None => None,
+                Some(ref a) => Some(a.parse(self.template)?),
+            };
+            filters.push(parse_filter_type(self, filter_token.at, argument)?);
+        }
+        let (nodes, _) = self.parse_until(vec![EndTagType::EndFilter], "filter", at)?;
+        Ok(TokenTree::Tag(Tag::FilterBlock(FilterBlock {
+            filters,
+            nodes,
+        })))
+    }
+
+    fn parse_firstof(
+        &mut self,
+        at: (usize, usize),
+        parts: TagParts,
+    ) -> Result<TokenTree, ParseError> {
+        let mut tokens = vec![];
+        for token in SimpleTagLexer::new(self.template, parts) {
+            let token = token?;
+            if let Some(kwarg) = token.kwarg {
+                let start = kwarg.0 + kwarg.1;
+                let at = (start, token.at.0 + token.at.1 - start);
+                return Err(LexerError::InvalidRemainder { at: at.into() }.into());
+            }
+            tokens.push(token);
+        }
+        if tokens.is_empty() {
+            return Err(ParseError::FirstofNoArguments { at: at.into() });
+        }
+        let variable = match tokens.as_slice() {
+            [.., as_token, variable] if self.template.content(as_token.at) == "as" => {
+                Some(self.template.content(variable.at).to_string())
+            }
+            _ => None,
+        };
+        if variable.is_some() {
+            tokens.truncate(tokens.len() - 2);
+        }
+        let args = tokens
+            .iter()
+            .map(|token| token.parse(self))
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(TokenTree::Tag(Tag::Firstof(Firstof { args, variable })))
+    }
+
+    fn parse_include(
+        &mut self,
+        at: (usize, usize),
+        parts: TagParts,
+    ) -> Result<TokenTree, ParseError> {
+        let mut lexer = SimpleTagLexer::new(self.template, parts).peekable();
+        let template_name = match lexer.next() {
+            Some(token) => token?.parse(self)?,
+            None => return Err(ParseError::IncludeNoArguments { at: at.into() }),
+        };
+        let mut kwargs = None;
+        let mut only = false;
+        while let Some(token) = lexer.next() {
+            let token = token?;
+            let option = self.template.content(token.at);
+            let repeated = match (token.kwarg, option) {
+                (None, "with") => kwargs.is_some(),
+                (None, "only") => only,
+                _ => {
+                    return Err(ParseError::IncludeUnknownArgument {
+                        argument: option.to_string(),
+                        at: token.at.into(),
+                    });
+                }
+            };
+            if repeated {
+                return Err(ParseError::OptionRepeated {
+                    option: option.to_string(),
+                    at: token.at.into(),
+                });
+            }
+            if option == "only" {
+                only = true;
+                continue;
+            }
+            let mut with = Vec::new();
+            while let Some(Ok(SimpleTagToken {
+                kwarg: Some(kwarg), ..
+            })) = lexer.peek()
+            {
+                let kwarg = self.template.content(*kwarg).to_string();
+                let token = lexer.next().expect("peeked")?;
+                with.push((kwarg, token.parse(self)?));
+            }
+            if with.is_empty() {
+                return Err(ParseError::IncludeWithoutKeywords {
+                    at: token.at.into(),
+                });
+            }
+            kwargs = Some(with);
+        }
+        Ok(TokenTree::Tag(Tag::Include(Include {
+            template_name,
+            kwargs: kwargs.unwrap_or_default(),
+            only,
+        })))
+    }
+
+    fn parse_blocktranslate(
+        &mut self,
+        at: (usize, usize),
+        parts: TagParts,
+        tag: &'static str,
+    ) -> Result<TokenTree, ParseError> {
+        let mut lexer = SimpleTagLexer::new(self.template, parts).peekable();
+        let mut kwargs = None;
+        let mut count = None;
+        let mut trimmed = false;
+        while let Some(token) = lexer.next() {
+            let token = token?;
+            let option = self.template.content(token.at);
+            let repeated = match (token.kwarg, option) {
+                (None, "with") => kwargs.is_some(),
+                (None, "count") => count.is_some(),
+                (None, "trimmed") => trimmed,
+                _ => {
+                    return Err(ParseError::BlocktranslateUnknownArgument {
+                        tag,
+                        argument: option.to_string(),
+                        at: token.at.into(),
+                    });
+                }
+            };
+            if repeated {
+                return Err(ParseError::OptionRepeated {
+                    option: option.to_string(),
+                    at: token.at.into(),
+                });
+            }
+            if option == "trimmed" {
+                trimmed = true;
+                continue;
+            }
+            let mut values = Vec::new();
+            while let Some(Ok(SimpleTagToken {
+                kwarg: Some(kwarg), ..
+            })) = lexer.peek()
+            {
+                let kwarg = self.template.content(*kwarg).to_string();
+                let token = lexer.next().expect("peeked")?;
+                values.push((kwarg, token.parse(self)?));
+            }
+            if option == "with" {
+                if values.is_empty() {
+                    return Err(ParseError::BlocktranslateWithoutKeywords {
+                        tag,
+                        at: token.at.into(),
+                    });
+                }
+                kwargs = Some(values);
+            } else {
+                if values.len() != 1 {
+                    return Err(ParseError::BlocktranslateCountArguments {
+                        tag,
+                        at: token.at.into(),
+                    });
+                }
+                count = values.pop();
+            }
+        }
+
+        let mut variables = Vec::new();
+        let (mut singular, mut end) = self.parse_translate_message(at, tag, &mut variables)?;
+        let mut plural = None;
+        if count.is_some() {
+            if end.content(self.template).trim() != "plural" {
+                return Err(ParseError::BlocktranslateNestedTag {
+                    tag,
+                    at: end.at.into(),
+                });
+            }
+            let (message, plural_end) = self.parse_translate_message(at, tag, &mut variables)?;
+            plural = Some(message);
+            end = plural_end;
+        }
+        let seen = end.content(self.template).trim();
+        if seen.strip_prefix("end") != Some(tag) {
+            return Err(ParseError::BlocktranslateUnexpectedTag {
+                tag,
+                seen: seen.to_string(),
+                at: end.at.into(),
+            });
+        }
+        if trimmed {
+            singular = trim_whitespace(&singular);
+            plural = plural.as_deref().map(trim_whitespace);
+        }
+        Ok(TokenTree::Tag(Tag::Blocktranslate(Blocktranslate {
+            tag,
+            singular,
+            plural,
+            variables,
+            kwargs: kwargs.unwrap_or_default(),
+            count,
+        })))
+    }
+
+    /// Build a translatable message from the text and variables up to the
+    /// next tag, escaping `%` for Python's string formatting like Django.
+    fn parse_translate_message(
+        &mut self,
+        at: (usize, usize),
+        tag: &'static str,
+        variables: &mut Vec<String>,
+    ) -> Result<(String, Token), ParseError> {
+        let mut message = String::new();
+        for token in self.lexer.by_ref() {
+            match token.token_type {
+                TokenType::Text => {
+                    let text = token.content(self.template);
+                    message.push_str(&text.replace('%', "%%"));
+                }
+                TokenType::Variable => {
+                    let variable = token.content(self.template).trim();
+                    message.push_str(&format!("%({variable})s"));
+                    variables.push(variable.to_string());
+                }
+                TokenType::Tag | TokenType::Comment => return Ok((message, token)),
+            }
+        }
+        Err(ParseError::UnclosedTag {
+            tag,
+            expected: format!("end{tag}"),
+            at: at.into(),
+        })
+    }
+
+    fn parse_translate(
+        &mut self,
+        at: (usize, usize),
+        parts: TagParts,
+        tag: &'static str,
+    ) -> Result<TokenTree, ParseError> {
+        let mut lexer = SimpleTagLexer::new(self.template, parts);
+        let message = match lexer.next() {
+            Some(token) => token?.parse(self)?,
+            None => return Err(ParseError::TranslateArguments { tag, at: at.into() }),
+        };
+        let mut noop = false;
+        let mut message_context = None;
+        let mut variable = None;
+        let mut seen = HashSet::new();
+        while let Some(token) = lexer.next() {
+            let token = token?;
+            let option = self.template.content(token.at);
+            if !seen.insert(option) {
+                return Err(ParseError::OptionRepeated {
+                    option: option.to_string(),
+                    at: token.at.into(),
+                });
+            }
+            match (token.kwarg, option) {
+                (None, "noop") => noop = true,
+                (None, "context") => {
+                    let value = match lexer.next() {
+                        Some(value) => value?,
+                        None => {
+                            return Err(ParseError::TranslateMissingOption {
+                                tag,
+                                option: "context",
+                                at: token.at.into(),
+                            });
+                        }
+                    };
+                    let argument = self.template.content(value.at);
+                    if argument == "as" || argument == "noop" {
+                        return Err(ParseError::TranslateInvalidContext {
+                            tag,
+                            argument: argument.to_string(),
+                            at: value.at.into(),
+                        });
+                    }
+                    message_context = Some(value.parse(self)?);
+                }
+                (None, "as") => match lexer.next() {
+                    Some(value) => {
+                        variable = Some(self.template.content(value?.at).to_string());
+                    }
+                    None => {
+                        return Err(ParseError::TranslateMissingOption {
+                            tag,
+                            option: "as",
+                            at: token.at.into(),
+                        });
+                    }
+                },
+                _ => {
+                    return Err(ParseError::TranslateUnknownArgument {
+                        tag,
+                        argument: option.to_string(),
+                        at: token.at.into(),
+                    });
+                }
+            }
+        }
+        Ok(TokenTree::Tag(Tag::Translate(Translate {
+            message,
+            noop,
+            message_context,
+            variable,
+        })))
+    }
+
+    fn parse_lorem(
+        &mut self,
+        at: (usize, usize),
+        parts: TagParts,
+    ) -> Result<TokenTree, ParseError> {
+        let mut tokens =
+            SimpleTagLexer::new(self.template, parts).collect::<Result<Vec<_>, _>>()?;
+        let common = match tokens.last() {
+            Some(token) if self.template.content(token.at) == "random" => {
+                tokens.pop();
+                false
+            }
+            _ => true,
+        };
+        let method = match tokens.last().map(|token| self.template.content(token.at)) {
+            Some("w") => Some(LoremMethod::Words),
+            Some("p") => Some(LoremMethod::Paragraphs),
+            Some("b") => Some(LoremMethod::Blocks),
+            _ => None,
+        };
+        let method = match method {
+            Some(method) => {
+                tokens.pop();
+                method
+            }
+            None => LoremMethod::Blocks,
+        };
+        let count = match tokens.pop() {
+            Some(token) => {
+                if let Some(kwarg) = token.kwarg {
+                    let start = kwarg.0 + kwarg.1;
+                    let at = (start, token.at.0 + token.at.1 - start);
+                    return Err(LexerError::InvalidRemainder { at: at.into() }.into());
+                }
+                token.parse(self)?
+            }
+            None => TagElement::Int(1.into()),
+        };
+        if !tokens.is_empty() {
+            return Err(ParseError::LoremArguments { at: at.into() });
+        }
+        Ok(TokenTree::Tag(Tag::Lorem(Lorem {
+            count,
+            method,
+            common,
+        })))
+    }
+
+    fn parse_now(&mut self, at: (usize, usize), parts: TagParts) -> Result<TokenTree, ParseError> {
+        let tokens = SimpleTagLexer::new(self.template, parts).collect::<Result<Vec<_>, _>>()?;
+        let (format, variable) = match tokens.as_slice() {
+            [format] => (format, None),
+            [format, as_token, variable] if self.template.content(as_token.at) == "as" => {
+                (format, Some(self.template.content(variable.at).to_string()))
+            }
+            _ => return Err(ParseError::NowArguments { at: at.into() }),
+        };
+        // Like Django, strip the surrounding quotes without checking for them
+        let mut format = self.template.content(format.at).chars();
+        format.next();
+        format.next_back();
+        let format = format.as_str().to_string();
+        Ok(TokenTree::Tag(Tag::Now(Now { format, variable })))
+    }
+
+    fn parse_regroup(
+        &mut self,
+        at: (usize, usize),
+        parts: TagParts,
+    ) -> Result<TokenTree, ParseError> {
+        let tokens = SimpleTagLexer::new(self.template, parts).collect::<Result<Vec<_>, _>>()?;
+        let [target, by_token, key, as_token, variable] = tokens.as_slice() else {
+            return Err(ParseError::RegroupArguments { at: at.into() });
+        };
+        if self.template.content(by_token.at) != "by" {
+            return Err(ParseError::RegroupExpectedBy {
+                at: by_token.at.into(),
+            });
+        }
+        if self.template.content(as_token.at) != "as" {
+            return Err(ParseError::RegroupExpectedAs {
+                at: as_token.at.into(),
+            });
+        }
+        // Like Django, the key is looked up as `variable.key`, so each item
+        // is bound to `variable` while grouping.
+        let mut key = key.parse(self)?;
+        let mut element = &mut key;
+        while let TagElement::Filter(filter) = element {
+            element = &mut filter.left;
+        }
+        if let TagElement::Variable(key) = element {
+            *key = key.clone().with_parent(variable.at);
+        }
+        Ok(TokenTree::Tag(Tag::Regroup(Regroup {
+            target: target.parse(self)?,
+            key,
+            variable: self.template.content(variable.at).to_string(),
+        })))
+    }
+
+    fn parse_templatetag(
+        &mut self,
+        at: (usize, usize),
+        parts: TagParts,
+    ) -> Result<TokenTree, ParseError> {
+        let mut arguments = self.template.content(parts.at).split_whitespace();
+        let (Some(argument), None) = (arguments.next(), arguments.next()) else {
+            return Err(ParseError::TemplatetagArguments { at: at.into() });
+        };
+        let literal = match argument {
+            "openblock" => "{%",
+            "closeblock" => "%}",
+            "openvariable" => "{{",
+            "closevariable" => "}}",
+            "openbrace" => "{",
+            "closebrace" => "}",
+            "opencomment" => "{#",
+            "closecomment" => "#}",
+            _ => {
+                return Err(ParseError::TemplatetagInvalidArgument {
+                    argument: argument.to_string(),
+                    at: parts.at.into(),
+                });
+            }
+        };
+        Ok(TokenTree::Tag(Tag::Templatetag(literal)))
+    }
+
+    fn parse_widthratio(
+        &mut self,
+        at: (usize, usize),
+        parts: TagParts,
+    ) -> Result<TokenTree, ParseError> {
+        let tokens = SimpleTagLexer::new(self.template, parts).collect::<Result<Vec<_>, _>>()?;
+        let (value, max_value, max_width, variable) = match tokens.as_slice() {
+            [value, max_value, max_width] => (value, max_value, max_width, None),
+            [value, max_value, max_width, as_token, variable] => {
+                if self.template.content(as_token.at) != "as" {
+                    return Err(ParseError::WidthratioExpectedAs {
+                        at: as_token.at.into(),
+                    });
+                }
+                let variable = self.template.content(variable.at).to_string();
+                (value, max_value, max_width, Some(variable))
+            }
+            _ => return Err(ParseError::WidthratioArguments { at: at.into() }),
+        };
+        Ok(TokenTree::Tag(Tag::Widthratio(Widthratio {
+            value: value.parse(self)?,
+            max_value: max_value.parse(self)?,
+            max_width: max_width.parse(self)?,
+            variable,
+        })))
+    }
+
     fn parse_url(&mut self, at: (usize, usize), parts: TagParts) -> Result<TokenTree, ParseError> {
-        let mut lexer = UrlLexer::new(self.template, parts);
+        let mut lexer = SimpleTagLexer::new(self.template, parts);
         let view_name = match lexer.next() {
             Some(view_token) => view_token?.parse(self)?,
             None => return Err(ParseError::UrlTagNoArguments { at: at.into() }),
@@ -832,14 +2030,14 @@ impl<'t, 'l, 'py> Parser<'t, 'l, 'py> {
         let mut rev = tokens.iter().rev();
         let variable = match (rev.next(), rev.next()) {
             (
-                Some(UrlToken {
+                Some(SimpleTagToken {
                     at: last,
-                    token_type: UrlTokenType::Variable,
+                    token_type: SimpleTagTokenType::Variable,
                     ..
                 }),
-                Some(UrlToken {
+                Some(SimpleTagToken {
                     at: prev,
-                    token_type: UrlTokenType::Variable,
+                    token_type: SimpleTagTokenType::Variable,
                     ..
                 }),
             ) => {
@@ -892,6 +2090,122 @@ impl<'t, 'l, 'py> Parser<'t, 'l, 'py> {
         }))
     }
 
+    /// Like Django, any arguments to `{% debug %}` are ignored.
+    fn parse_debug(&self) -> TokenTree {
+        let mut filters: Vec<_> = self.external_filters.keys().cloned().collect();
+        filters.sort();
+        TokenTree::Tag(Tag::Debug { filters })
+    }
+
+    fn parse_spaceless(&mut self, at: (usize, usize)) -> Result<TokenTree, PyParseError> {
+        let (nodes, _) = self.parse_until(vec![EndTagType::EndSpaceless], "spaceless", at)?;
+        Ok(TokenTree::Tag(Tag::Spaceless { nodes }))
+    }
+
+    fn parse_block(
+        &mut self,
+        at: (usize, usize),
+        parts: TagParts,
+    ) -> Result<TokenTree, PyParseError> {
+        let mut names = LoadLexer::new(self.template, parts);
+        let name = match (names.next(), names.next()) {
+            (Some(name), None) => self.template.content(name.at),
+            _ => return Err(ParseError::BlockArguments { at: at.into() }.into()),
+        };
+        if !self.block_names.insert(name.to_string()) {
+            return Err(ParseError::DuplicateBlock {
+                name: name.to_string(),
+                at: at.into(),
+            }
+            .into());
+        }
+        let (nodes, end_tag) = self.parse_until(vec![EndTagType::EndBlock], "block", at)?;
+        let end_name = self.template.content(end_tag.parts.at);
+        if !end_name.is_empty() && end_name != name {
+            return Err(ParseError::WrongEndBlock {
+                unexpected: end_name.to_string(),
+                expected: name.to_string(),
+                at: end_tag.at.into(),
+                start_at: at.into(),
+            }
+            .into());
+        }
+        Ok(TokenTree::Tag(Tag::Block(Block {
+            name: name.to_string(),
+            nodes: Arc::new(nodes),
+        })))
+    }
+
+    fn parse_extends(
+        &mut self,
+        at: (usize, usize),
+        parts: TagParts,
+    ) -> Result<TokenTree, PyParseError> {
+        let mut lexer = SimpleTagLexer::new(self.template, parts);
+        let (parent, parent_at) = match (lexer.next(), lexer.next()) {
+            (Some(token), None) => {
+                let token = token.map_err(ParseError::from)?;
+                if token.kwarg.is_some() {
+                    return Err(ParseError::ExtendsArguments { at: at.into() }.into());
+                }
+                (token.parse(self)?, token.at)
+            }
+            _ => return Err(ParseError::ExtendsArguments { at: at.into() }.into()),
+        };
+        // Everything after `{% extends %}` belongs to it, but only the blocks
+        // are used when rendering.
+        let nodes = self.parse()?;
+        if nodes
+            .iter()
+            .any(|node| matches!(node, TokenTree::Tag(Tag::Extends(_))))
+        {
+            return Err(ParseError::ExtendsRepeated { at: at.into() }.into());
+        }
+        let mut blocks = Vec::new();
+        collect_blocks(&nodes, &mut blocks);
+        let blocks = blocks.into_iter().cloned().collect();
+        Ok(TokenTree::Tag(Tag::Extends(Extends {
+            parent,
+            parent_at,
+            blocks,
+        })))
+    }
+
+    fn parse_for(
+        &mut self,
+        at: (usize, usize),
+        parts: TagParts,
+    ) -> Result<TokenTree, PyParseError> {
+        let tokens = lex_for(self.template, parts).map_err(ParseError::from)?;
+        let variables = tokens
+            .variables
+            .into_iter()
+            .map(|variable| self.template.content(variable).to_string())
+            .collect();
+        let iterable = self.parse_variable(
+            self.template.content(tokens.iterable),
+            tokens.iterable,
+            tokens.iterable.0,
+        )?;
+        let (body, end_tag) =
+            self.parse_until(vec![EndTagType::Empty, EndTagType::EndFor], "for", at)?;
+        let empty = match end_tag.end {
+            EndTagType::Empty => {
+                let (nodes, _) = self.parse_until(vec![EndTagType::EndFor], "empty", at)?;
+                Some(nodes)
+            }
+            EndTagType::EndFor => None,
+            _ => unreachable!(),
+        };
+        Ok(TokenTree::Tag(Tag::For(For {
+            variables,
+            iterable,
+            reversed: tokens.reversed,
+            body,
+            empty,
+        })))
+    }
+
     fn parse_if(
         &mut self,
         at: (usize, usize),
@@ -1011,37 +2325,202 @@ mod tests {
     }
 
     #[test]
-    fn test_empty_variable() {
+    fn test_parse_plain_text() {
         pyo3::prepare_freethreaded_python();
 
         Python::with_gil(|py| {
             let libraries = HashMap::new();
-            let template = "{{ }}";
-            let mut parser = Parser::new(py, template.into(), &libraries);
-            let error = parser.parse().unwrap_err().unwrap_parse_error();
-            assert_eq!(error, ParseError::EmptyVariable { at: (0, 5).into() });
+            let template = "lorem { ipsum } %}#}".repeat(500);
+            assert_eq!(template.len(), 10_000);
+            let mut parser = Parser::new(py, template.as_str().into(), &libraries);
+            let nodes = parser.parse().unwrap();
+            assert_eq!(nodes, vec![TokenTree::Text(Text::new((0, 10_000)))]);
         })
     }
 
     #[test]
-    fn test_variable() {
+    fn test_keep_comments() {
         pyo3::prepare_freethreaded_python();
 
         Python::with_gil(|py| {
             let libraries = HashMap::new();
-            let template = TemplateString("{{ foo }}");
-            let mut parser = Parser::new(py, template, &libraries);
+            let template = "{# A comment #}{% if x %}{#nested#}{% endif %}";
+            let options = ParserOptions {
+                keep_comments: true,
+                ..Default::default()
+            };
+            let mut parser = Parser::new_with_options(py, template.into(), &libraries, options);
             let nodes = parser.parse().unwrap();
-            let variable = Variable { at: (3, 3) };
-            assert_eq!(nodes, vec![TokenTree::Variable(variable)]);
+
+            let comment = Text::new((2, 11));
+            assert_eq!(TemplateString(template).content(comment.at), " A comment ");
+            let if_tag = Tag::If {
+                condition: IfCondition::Variable(TagElement::Variable(Variable::new(
+                    (21, 1),
+                    template,
+                ))),
+                truthy: vec![TokenTree::Comment(Text::new((27, 6)))],
+                falsey: None,
+            };
             assert_eq!(
-                variable.parts(template).collect::<Vec<_>>(),
-                vec![("foo", (3, 3))]
+                nodes,
+                vec![TokenTree::Comment(comment), TokenTree::Tag(if_tag)]
             );
-        })
-    }
 
-    #[test]
+            let mut parser = Parser::new(py, template.into(), &libraries);
+            let nodes = parser.parse().unwrap();
+            let if_tag = Tag::If {
+                condition: IfCondition::Variable(TagElement::Variable(Variable::new(
+                    (21, 1),
+                    template,
+                ))),
+                truthy: vec![],
+                falsey: None,
+            };
+            assert_eq!(nodes, vec![TokenTree::Tag(if_tag)]);
+        })
+    }
+
+    #[test]
+    fn test_lenient_filters() {
+        pyo3::prepare_freethreaded_python();
+
+        Python::with_gil(|py| {
+            let libraries = HashMap::new();
+            let template = "{{ foo | default : 'x' }}";
+            let mut parser = Parser::new(py, template.into(), &libraries);
+            let error = parser.parse().unwrap_err().unwrap_parse_error();
+            assert!(matches!(error, ParseError::VariableError(_)));
+
+            let options = ParserOptions {
+                lenient_filters: true,
+                ..Default::default()
+            };
+            let mut parser = Parser::new_with_options(py, template.into(), &libraries, options);
+            let nodes = parser.parse().unwrap();
+            let default = Filter {
+                at: (9, 7),
+                left: TagElement::Variable(Variable::new((3, 3), template)),
+                filter: FilterType::Default(DefaultFilter::new(Argument {
+                    at: (19, 3),
+                    argument_type: ArgumentType::Text(Text { at: (20, 1) }),
+                })),
+            };
+            assert_eq!(nodes, vec![TokenTree::Filter(Box::new(default))]);
+        })
+    }
+
+    #[test]
+    fn test_empty_variable() {
+        pyo3::prepare_freethreaded_python();
+
+        Python::with_gil(|py| {
+            let libraries = HashMap::new();
+            let template = "{{ }}";
+            let mut parser = Parser::new(py, template.into(), &libraries);
+            let error = parser.parse().unwrap_err().unwrap_parse_error();
+            assert_eq!(error, ParseError::EmptyVariable { at: (0, 5).into() });
+        })
+    }
+
+    #[test]
+    fn test_variable() {
+        pyo3::prepare_freethreaded_python();
+
+        Python::with_gil(|py| {
+            let libraries = HashMap::new();
+            let template = TemplateString("{{ foo }}");
+            let mut parser = Parser::new(py, template, &libraries);
+            let nodes = parser.parse().unwrap();
+            let variable = Variable::new((3, 3), template);
+            assert_eq!(nodes, vec![TokenTree::Variable(variable.clone())]);
+            assert_eq!(
+                variable.parts(template).collect::<Vec<_>>(),
+                vec![("foo", (3, 3))]
+            );
+        })
+    }
+
+    #[test]
+    fn test_numeric_variable() {
+        pyo3::prepare_freethreaded_python();
+
+        Python::with_gil(|py| {
+            let libraries = HashMap::new();
+            let template = TemplateString("{{ 42 }}{{ 3.5 }}{{ -5 }}{{ 1e3 }}");
+            let mut parser = Parser::new(py, template, &libraries);
+            let nodes = parser.parse().unwrap();
+            assert_eq!(
+                nodes,
+                vec![
+                    TokenTree::Int(42.into()),
+                    TokenTree::Float(3.5),
+                    TokenTree::Int((-5).into()),
+                    TokenTree::Float(1000.0),
+                ]
+            );
+        })
+    }
+
+    #[test]
+    #[allow(clippy::approx_constant)]
+    fn test_float_or_index_lookup() {
+        pyo3::prepare_freethreaded_python();
+
+        Python::with_gil(|py| {
+            let libraries = HashMap::new();
+            let template = TemplateString("{{ 3.14 }}{{ a.0 }}{{ a.0.b }}");
+            let mut parser = Parser::new(py, template, &libraries);
+            let nodes = parser.parse().unwrap();
+            let index = Variable::new((13, 3), template);
+            let nested = Variable::new((22, 5), template);
+            assert_eq!(
+                nodes,
+                vec![
+                    TokenTree::Float(3.14),
+                    TokenTree::Variable(index.clone()),
+                    TokenTree::Variable(nested.clone()),
+                ]
+            );
+            let parts: Vec<_> = index.parts(template).collect();
+            assert_eq!(parts, vec![("a", (13, 1)), ("0", (15, 1))]);
+            let parts: Vec<_> = nested.parts(template).collect();
+            assert_eq!(parts, vec![("a", (22, 1)), ("0", (24, 1)), ("b", (26, 1))]);
+        })
+    }
+
+    #[test]
+    fn test_numeric_variable_filter() {
+        pyo3::prepare_freethreaded_python();
+
+        Python::with_gil(|py| {
+            let libraries = HashMap::new();
+            let template = TemplateString("{{ -5|upper }}");
+            let mut parser = Parser::new(py, template, &libraries);
+            let nodes = parser.parse().unwrap();
+            let upper = TokenTree::Filter(Box::new(Filter {
+                at: (6, 5),
+                left: TagElement::Int((-5).into()),
+                filter: FilterType::Upper(UpperFilter),
+            }));
+            assert_eq!(nodes, vec![upper]);
+        })
+    }
+
+    #[test]
+    fn test_numeric_variable_invalid() {
+        pyo3::prepare_freethreaded_python();
+
+        Python::with_gil(|py| {
+            let libraries = HashMap::new();
+            let template = TemplateString("{{ 1.2.3 }}");
+            let mut parser = Parser::new(py, template, &libraries);
+            let error = parser.parse().unwrap_err().unwrap_parse_error();
+            assert_eq!(error, ParseError::InvalidNumber { at: (3, 5).into() });
+        })
+    }
+
+    #[test]
     fn test_variable_attribute() {
         pyo3::prepare_freethreaded_python();
 
@@ -1050,8 +2529,8 @@ mod tests {
             let template = TemplateString("{{ foo.bar.baz }}");
             let mut parser = Parser::new(py, template, &libraries);
             let nodes = parser.parse().unwrap();
-            let variable = Variable { at: (3, 11) };
-            assert_eq!(nodes, vec![TokenTree::Variable(variable)]);
+            let variable = Variable::new((3, 11), template);
+            assert_eq!(nodes, vec![TokenTree::Variable(variable.clone())]);
             assert_eq!(
                 variable.parts(template).collect::<Vec<_>>(),
                 vec![("foo", (3, 3)), ("bar", (7, 3)), ("baz", (11, 3))]
@@ -1072,12 +2551,12 @@ mod tests {
 
             assert_eq!(nodes.len(), 1);
 
-            let foo = Variable { at: (3, 3) };
+            let foo = Variable::new((3, 3), template);
             let external = get_external_filter(&nodes[0]);
             assert!(external.is_none(py));
             let bar = TokenTree::Filter(Box::new(Filter {
                 at: (7, 3),
-                left: TagElement::Variable(foo),
+                left: TagElement::Variable(foo.clone()),
                 filter: FilterType::External(ExternalFilter {
                     filter: external,
                     argument: None,
@@ -1110,6 +2589,26 @@ mod tests {
         })
     }
 
+    #[test]
+    fn test_external_filter_parsed_once() {
+        pyo3::prepare_freethreaded_python();
+
+        Python::with_gil(|py| {
+            let libraries = HashMap::new();
+            let bar = py.eval(c"lambda value: value", None, None).unwrap();
+            let filters = HashMap::from([("bar".to_string(), bar.clone())]);
+            let template = TemplateString("{{ foo|bar }}{{ baz|bar }}");
+            let mut parser = Parser::new_with_filters(py, template, &libraries, filters);
+            let nodes = parser.parse().unwrap();
+
+            assert_eq!(nodes.len(), 2);
+            for node in &nodes {
+                let external = get_external_filter(node);
+                assert!(external.bind(py).is(&bar));
+            }
+        })
+    }
+
     #[test]
     fn test_filter_multiple() {
         pyo3::prepare_freethreaded_python();
@@ -1125,7 +2624,7 @@ mod tests {
             let nodes = parser.parse().unwrap();
             assert_eq!(nodes.len(), 1);
 
-            let foo = TagElement::Variable(Variable { at: (3, 3) });
+            let foo = TagElement::Variable(Variable::new((3, 3), template));
             let external = get_external_filter_tag_element(&nodes[0]);
             assert!(external.is_none(py));
             let bar = TagElement::Filter(Box::new(Filter {
@@ -1162,8 +2661,8 @@ mod tests {
             let nodes = parser.parse().unwrap();
             assert_eq!(nodes.len(), 1);
 
-            let foo = TagElement::Variable(Variable { at: (3, 3) });
-            let baz = Variable { at: (11, 3) };
+            let foo = TagElement::Variable(Variable::new((3, 3), template));
+            let baz = Variable::new((11, 3), template);
             let external = get_external_filter(&nodes[0]);
             assert!(external.is_none(py));
             let bar = TokenTree::Filter(Box::new(Filter {
@@ -1173,7 +2672,7 @@ mod tests {
                     filter: external,
                     argument: Some(Argument {
                         at: (11, 3),
-                        argument_type: ArgumentType::Variable(baz),
+                        argument_type: ArgumentType::Variable(baz.clone()),
                     }),
                 }),
             }));
@@ -1196,7 +2695,7 @@ mod tests {
             let mut parser = Parser::new_with_filters(py, template, &libraries, filters);
             let nodes = parser.parse().unwrap();
 
-            let foo = TagElement::Variable(Variable { at: (3, 3) });
+            let foo = TagElement::Variable(Variable::new((3, 3), template));
             let baz = Text::new((12, 3));
             let external = get_external_filter(&nodes[0]);
             assert!(external.is_none(py));
@@ -1227,7 +2726,7 @@ mod tests {
             let mut parser = Parser::new_with_filters(py, template, &libraries, filters);
             let nodes = parser.parse().unwrap();
 
-            let foo = TagElement::Variable(Variable { at: (3, 3) });
+            let foo = TagElement::Variable(Variable::new((3, 3), template));
             let baz = TranslatedText::new((14, 3));
             let external = get_external_filter(&nodes[0]);
             assert!(external.is_none(py));
@@ -1258,7 +2757,7 @@ mod tests {
             let mut parser = Parser::new_with_filters(py, template.into(), &libraries, filters);
             let nodes = parser.parse().unwrap();
 
-            let foo = TagElement::Variable(Variable { at: (3, 3) });
+            let foo = TagElement::Variable(Variable::new((3, 3), template));
             let num = Argument {
                 at: (11, 5),
                 argument_type: ArgumentType::Float(5.2e3),
@@ -1277,6 +2776,74 @@ mod tests {
         })
     }
 
+    #[test]
+    fn test_filter_argument_float_signed_exponent() {
+        pyo3::prepare_freethreaded_python();
+
+        Python::with_gil(|py| {
+            let libraries = HashMap::new();
+            let template = "{{ foo|default:1.5e-3 }}{{ foo|default:2E+4 }}";
+            let mut parser = Parser::new(py, template.into(), &libraries);
+            let nodes = parser.parse().unwrap();
+
+            let small = Argument {
+                at: (15, 6),
+                argument_type: ArgumentType::Float(1.5e-3),
+            };
+            let large = Argument {
+                at: (39, 4),
+                argument_type: ArgumentType::Float(2e4),
+            };
+            assert_eq!(
+                nodes,
+                vec![
+                    TokenTree::Filter(Box::new(Filter {
+                        at: (7, 7),
+                        left: TagElement::Variable(Variable::new((3, 3), template)),
+                        filter: FilterType::Default(DefaultFilter::new(small)),
+                    })),
+                    TokenTree::Filter(Box::new(Filter {
+                        at: (31, 7),
+                        left: TagElement::Variable(Variable::new((27, 3), template)),
+                        filter: FilterType::Default(DefaultFilter::new(large)),
+                    })),
+                ]
+            );
+        })
+    }
+
+    #[test]
+    fn test_filter_floatformat_optional_argument() {
+        pyo3::prepare_freethreaded_python();
+
+        Python::with_gil(|py| {
+            let libraries = HashMap::new();
+            let template = "{{ foo|floatformat }}{{ foo|floatformat:2 }}";
+            let mut parser = Parser::new(py, template.into(), &libraries);
+            let nodes = parser.parse().unwrap();
+
+            let two = Argument {
+                at: (40, 1),
+                argument_type: ArgumentType::Int(2.into()),
+            };
+            assert_eq!(
+                nodes,
+                vec![
+                    TokenTree::Filter(Box::new(Filter {
+                        at: (7, 11),
+                        left: TagElement::Variable(Variable::new((3, 3), template)),
+                        filter: FilterType::Floatformat(FloatformatFilter::new(None)),
+                    })),
+                    TokenTree::Filter(Box::new(Filter {
+                        at: (28, 11),
+                        left: TagElement::Variable(Variable::new((24, 3), template)),
+                        filter: FilterType::Floatformat(FloatformatFilter::new(Some(two))),
+                    })),
+                ]
+            );
+        })
+    }
+
     #[test]
     fn test_filter_argument_int() {
         pyo3::prepare_freethreaded_python();
@@ -1288,7 +2855,7 @@ mod tests {
             let mut parser = Parser::new_with_filters(py, template.into(), &libraries, filters);
             let nodes = parser.parse().unwrap();
 
-            let foo = TagElement::Variable(Variable { at: (3, 3) });
+            let foo = TagElement::Variable(Variable::new((3, 3), template));
             let num = Argument {
                 at: (11, 2),
                 argument_type: ArgumentType::Int(99.into()),
@@ -1307,6 +2874,29 @@ mod tests {
         })
     }
 
+    #[test]
+    fn test_filter_argument_leading_zeros() {
+        pyo3::prepare_freethreaded_python();
+
+        Python::with_gil(|py| {
+            let libraries = HashMap::new();
+            let template = "{{ foo|default:007 }}";
+            let mut parser = Parser::new(py, template.into(), &libraries);
+            let nodes = parser.parse().unwrap();
+
+            let foo = TagElement::Variable(Variable::new((3, 3), template));
+            let default = TokenTree::Filter(Box::new(Filter {
+                at: (7, 7),
+                left: foo,
+                filter: FilterType::Default(DefaultFilter::new(Argument {
+                    at: (15, 3),
+                    argument_type: ArgumentType::Int(7.into()),
+                })),
+            }));
+            assert_eq!(nodes, vec![default]);
+        })
+    }
+
     #[test]
     fn test_filter_argument_bigint() {
         pyo3::prepare_freethreaded_python();
@@ -1314,14 +2904,16 @@ mod tests {
         Python::with_gil(|py| {
             let libraries = HashMap::new();
             let filters = HashMap::from([("bar".to_string(), py.None().bind(py).clone())]);
-            let template = "{{ foo|bar:99999999999999999 }}";
+            let template = "{{ foo|bar:99999999999999999999 }}";
             let mut parser = Parser::new_with_filters(py, template.into(), &libraries, filters);
             let nodes = parser.parse().unwrap();
 
-            let foo = TagElement::Variable(Variable { at: (3, 3) });
+            let foo = TagElement::Variable(Variable::new((3, 3), template));
             let num = Argument {
-                at: (11, 17),
-                argument_type: ArgumentType::Int("99999999999999999".parse::<BigInt>().unwrap()),
+                at: (11, 20),
+                argument_type: ArgumentType::BigInt(
+                    "99999999999999999999".parse::<BigInt>().unwrap(),
+                ),
             };
             let external = get_external_filter(&nodes[0]);
             assert!(external.is_none(py));
@@ -1337,6 +2929,45 @@ mod tests {
         })
     }
 
+    #[test]
+    fn test_filter_argument_int_size() {
+        pyo3::prepare_freethreaded_python();
+
+        Python::with_gil(|py| {
+            let libraries = HashMap::new();
+            let argument_type = |number: &str| {
+                let filters = HashMap::from([("bar".to_string(), py.None().bind(py).clone())]);
+                let template = format!("{{{{ foo|bar:{number} }}}}");
+                let mut parser =
+                    Parser::new_with_filters(py, template.as_str().into(), &libraries, filters);
+                let nodes = parser.parse().unwrap();
+                match &nodes[0] {
+                    TokenTree::Filter(filter) => match &filter.filter {
+                        FilterType::External(filter) => {
+                            filter.argument.as_ref().unwrap().argument_type.clone()
+                        }
+                        _ => panic!(),
+                    },
+                    _ => panic!(),
+                }
+            };
+
+            assert_eq!(argument_type("5"), ArgumentType::Int(5));
+            assert_eq!(
+                argument_type("9223372036854775807"),
+                ArgumentType::Int(i64::MAX)
+            );
+            assert_eq!(
+                argument_type("-9223372036854775808"),
+                ArgumentType::Int(i64::MIN)
+            );
+            assert_eq!(
+                argument_type("9223372036854775808"),
+                ArgumentType::BigInt(BigInt::from(i64::MAX) + 1)
+            );
+        })
+    }
+
     #[test]
     fn test_filter_argument_invalid_number() {
         pyo3::prepare_freethreaded_python();
@@ -1350,6 +2981,41 @@ mod tests {
         })
     }
 
+    #[test]
+    fn test_filter_argument_signs_and_exponents() {
+        pyo3::prepare_freethreaded_python();
+
+        Python::with_gil(|py| {
+            let libraries = HashMap::new();
+            let argument_type = |number: &str| {
+                let template = format!("{{{{ foo|add:{number} }}}}");
+                let mut parser = Parser::new(py, template.as_str().into(), &libraries);
+                match parser.parse() {
+                    Ok(nodes) => match &nodes[0] {
+                        TokenTree::Filter(filter) => match &filter.filter {
+                            FilterType::Add(filter) => Ok(filter.argument.argument_type.clone()),
+                            _ => panic!(),
+                        },
+                        _ => panic!(),
+                    },
+                    Err(error) => Err(error.unwrap_parse_error()),
+                }
+            };
+
+            assert_eq!(argument_type("1e-5"), Ok(ArgumentType::Float(1e-5)));
+            assert_eq!(argument_type("+3"), Ok(ArgumentType::Int(3)));
+            assert_eq!(argument_type("1E5"), Ok(ArgumentType::Float(1e5)));
+            assert_eq!(
+                argument_type("1e-"),
+                Err(ParseError::InvalidNumber { at: (11, 3).into() })
+            );
+            assert_eq!(
+                argument_type("1e"),
+                Err(ParseError::InvalidNumber { at: (11, 2).into() })
+            );
+        })
+    }
+
     #[test]
     fn test_filter_parse_addslashes() {
         pyo3::prepare_freethreaded_python();
@@ -1384,14 +3050,14 @@ mod tests {
             let mut parser = Parser::new(py, template, &libraries);
             let nodes = parser.parse().unwrap();
 
-            let foo = TagElement::Variable(Variable { at: (3, 3) });
-            let baz = Variable { at: (15, 3) };
+            let foo = TagElement::Variable(Variable::new((3, 3), template));
+            let baz = Variable::new((15, 3), template);
             let bar = TokenTree::Filter(Box::new(Filter {
                 at: (7, 7),
                 left: foo,
                 filter: FilterType::Default(DefaultFilter::new(Argument {
                     at: (15, 3),
-                    argument_type: ArgumentType::Variable(baz),
+                    argument_type: ArgumentType::Variable(baz.clone()),
                 })),
             }));
             assert_eq!(nodes, vec![bar]);
@@ -1434,6 +3100,78 @@ mod tests {
         })
     }
 
+    #[test]
+    fn test_filter_safeseq_unexpected_argument() {
+        pyo3::prepare_freethreaded_python();
+
+        Python::with_gil(|py| {
+            let libraries = HashMap::new();
+            let template = "{{ foo|safeseq:baz }}";
+            let mut parser = Parser::new(py, template.into(), &libraries);
+            let error = parser.parse().unwrap_err().unwrap_parse_error();
+            assert_eq!(
+                error,
+                ParseError::UnexpectedArgument {
+                    filter: "safeseq",
+                    at: (15, 3).into()
+                }
+            );
+        })
+    }
+
+    #[test]
+    fn test_expect_no_argument() {
+        let template = TemplateString("{{ foo|first:bar }}");
+        let argument = Argument {
+            at: (13, 3),
+            argument_type: ArgumentType::Variable(Variable::new((13, 3), template)),
+        };
+        assert_eq!(expect_no_argument("first", None), Ok(()));
+        assert_eq!(
+            expect_no_argument("first", Some(argument)),
+            Err(ParseError::UnexpectedArgument {
+                filter: "first",
+                at: (13, 3).into()
+            })
+        );
+    }
+
+    #[test]
+    fn test_expect_argument() {
+        let template = TemplateString("{{ foo|default:bar }}");
+        let argument = Argument {
+            at: (15, 3),
+            argument_type: ArgumentType::Variable(Variable::new((15, 3), template)),
+        };
+        assert_eq!(
+            expect_argument((7, 7), Some(argument.clone())),
+            Ok(argument)
+        );
+        assert_eq!(
+            expect_argument((7, 7), None),
+            Err(ParseError::MissingArgument { at: (7, 7).into() })
+        );
+    }
+
+    #[test]
+    fn test_filter_last_unexpected_argument() {
+        pyo3::prepare_freethreaded_python();
+
+        Python::with_gil(|py| {
+            let libraries = HashMap::new();
+            let template = "{{ foo|last:'bar'|first }}";
+            let mut parser = Parser::new(py, template.into(), &libraries);
+            let error = parser.parse().unwrap_err().unwrap_parse_error();
+            assert_eq!(
+                error,
+                ParseError::UnexpectedArgument {
+                    filter: "last",
+                    at: (12, 5).into()
+                }
+            );
+        })
+    }
+
     #[test]
     fn test_variable_lexer_error() {
         pyo3::prepare_freethreaded_python();
@@ -1446,7 +3184,7 @@ mod tests {
             assert_eq!(
                 error,
                 ParseError::VariableError(
-                    LexerError::InvalidVariableName { at: (3, 4).into() }.into()
+                    LexerError::LeadingUnderscore { at: (3, 4).into() }.into()
                 )
             );
         })
@@ -1481,6 +3219,1212 @@ mod tests {
         })
     }
 
+    #[test]
+    fn test_parse_autoescape() {
+        pyo3::prepare_freethreaded_python();
+
+        Python::with_gil(|py| {
+            let libraries = HashMap::new();
+            let template = "{% autoescape off %}{{ html }}{% endautoescape %}";
+            let mut parser = Parser::new(py, template.into(), &libraries);
+            let nodes = parser.parse().unwrap();
+
+            let autoescape = TokenTree::Tag(Tag::Autoescape {
+                enabled: AutoescapeEnabled::Off,
+                nodes: vec![TokenTree::Variable(Variable::new((23, 4), template))],
+            });
+
+            assert_eq!(nodes, vec![autoescape]);
+        })
+    }
+
+    #[test]
+    fn test_parse_spaceless() {
+        pyo3::prepare_freethreaded_python();
+
+        Python::with_gil(|py| {
+            let libraries = HashMap::new();
+            let template = "{% spaceless %}<p> {{ html }} </p>{% endspaceless %}";
+            let mut parser = Parser::new(py, template.into(), &libraries);
+            let nodes = parser.parse().unwrap();
+
+            let spaceless = TokenTree::Tag(Tag::Spaceless {
+                nodes: vec![
+                    TokenTree::Text(Text::new((15, 4))),
+                    TokenTree::Variable(Variable::new((22, 4), template)),
+                    TokenTree::Text(Text::new((29, 5))),
+                ],
+            });
+
+            assert_eq!(nodes, vec![spaceless]);
+        })
+    }
+
+    #[test]
+    fn test_parse_filter_tag() {
+        pyo3::prepare_freethreaded_python();
+
+        Python::with_gil(|py| {
+            let libraries = HashMap::new();
+            let template = "{% filter lower|capfirst %}Hi{% endfilter %}";
+            let mut parser = Parser::new(py, template.into(), &libraries);
+            let nodes = parser.parse().unwrap();
+
+            let filter_block = TokenTree::Tag(Tag::FilterBlock(FilterBlock {
+                filters: vec![
+                    FilterType::Lower(LowerFilter),
+                    FilterType::Capfirst(CapfirstFilter),
+                ],
+                nodes: vec![TokenTree::Text(Text::new((27, 2)))],
+            }));
+
+            assert_eq!(nodes, vec![filter_block]);
+        })
+    }
+
+    #[test]
+    fn test_parse_filter_tag_not_permitted() {
+        pyo3::prepare_freethreaded_python();
+
+        Python::with_gil(|py| {
+            let libraries = HashMap::new();
+            let template = "{% filter lower|escape %}Hi{% endfilter %}";
+            let mut parser = Parser::new(py, template.into(), &libraries);
+            let error = parser.parse().unwrap_err().unwrap_parse_error();
+
+            assert_eq!(
+                error,
+                ParseError::FilterTagNotPermitted {
+                    filter: "escape".to_string(),
+                    at: (16, 6).into(),
+                }
+            );
+
+            let template = "{% filter %}Hi{% endfilter %}";
+            let mut parser = Parser::new(py, template.into(), &libraries);
+            let error = parser.parse().unwrap_err().unwrap_parse_error();
+
+            assert_eq!(
+                error,
+                ParseError::FilterTagNoArguments { at: (0, 12).into() }
+            );
+        })
+    }
+
+    #[test]
+    fn test_parse_comment_tag() {
+        pyo3::prepare_freethreaded_python();
+
+        Python::with_gil(|py| {
+            let libraries = HashMap::new();
+            let template = "a{% comment %}{% for x in y %}{{ x|invalid }}{% endcomment %}b{% comment 'note' %}{% endcomment %}";
+            let mut parser = Parser::new(py, template.into(), &libraries);
+            let nodes = parser.parse().unwrap();
+
+            assert_eq!(
+                nodes,
+                vec![
+                    TokenTree::Text(Text::new((0, 1))),
+                    TokenTree::Text(Text::new((61, 1))),
+                ]
+            );
+        })
+    }
+
+    #[test]
+    fn test_parse_comment_tag_unclosed() {
+        pyo3::prepare_freethreaded_python();
+
+        Python::with_gil(|py| {
+            let libraries = HashMap::new();
+            let template = "{% comment %}{% endfor %}";
+            let mut parser = Parser::new(py, template.into(), &libraries);
+            let error = parser.parse().unwrap_err().unwrap_parse_error();
+
+            assert_eq!(
+                error,
+                ParseError::UnclosedTag {
+                    tag: "comment",
+                    expected: "endcomment".to_string(),
+                    at: (0, 13).into(),
+                }
+            );
+        })
+    }
+
+    #[test]
+    fn test_parse_if_else() {
+        pyo3::prepare_freethreaded_python();
+
+        Python::with_gil(|py| {
+            let libraries = HashMap::new();
+            let template = "{% if foo %}yes{% else %}no{% endif %}";
+            let mut parser = Parser::new(py, template.into(), &libraries);
+            let nodes = parser.parse().unwrap();
+
+            let if_tag = TokenTree::Tag(Tag::If {
+                condition: IfCondition::Variable(TagElement::Variable(Variable::new(
+                    (6, 3),
+                    template,
+                ))),
+                truthy: vec![TokenTree::Text(Text::new((12, 3)))],
+                falsey: Some(vec![TokenTree::Text(Text::new((25, 2)))]),
+            });
+
+            assert_eq!(nodes, vec![if_tag]);
+        })
+    }
+
+    #[test]
+    fn test_parse_nested_if() {
+        pyo3::prepare_freethreaded_python();
+
+        Python::with_gil(|py| {
+            let libraries = HashMap::new();
+            let template = "{% if a %}{% if b %}x{% endif %}{% endif %}";
+            let mut parser = Parser::new(py, template.into(), &libraries);
+            let nodes = parser.parse().unwrap();
+
+            let inner = TokenTree::Tag(Tag::If {
+                condition: IfCondition::Variable(TagElement::Variable(Variable::new(
+                    (16, 1),
+                    template,
+                ))),
+                truthy: vec![TokenTree::Text(Text::new((20, 1)))],
+                falsey: None,
+            });
+            let outer = TokenTree::Tag(Tag::If {
+                condition: IfCondition::Variable(TagElement::Variable(Variable::new(
+                    (6, 1),
+                    template,
+                ))),
+                truthy: vec![inner],
+                falsey: None,
+            });
+
+            assert_eq!(nodes, vec![outer]);
+        })
+    }
+
+    #[test]
+    fn test_parse_unclosed_if() {
+        pyo3::prepare_freethreaded_python();
+
+        Python::with_gil(|py| {
+            let libraries = HashMap::new();
+            let template = "{% if foo %}{% if bar %}{% endif %}";
+            let mut parser = Parser::new(py, template.into(), &libraries);
+            let error = parser.parse().unwrap_err().unwrap_parse_error();
+            assert_eq!(
+                error,
+                ParseError::UnclosedTag {
+                    tag: "if",
+                    expected: "elif, else, endif".to_string(),
+                    at: (0, 12).into(),
+                }
+            );
+        })
+    }
+
+    #[test]
+    fn test_parse_if_precedence() {
+        pyo3::prepare_freethreaded_python();
+
+        Python::with_gil(|py| {
+            let libraries = HashMap::new();
+            let template = "{% if a or b and not c %}{% endif %}";
+            let mut parser = Parser::new(py, template.into(), &libraries);
+            let nodes = parser.parse().unwrap();
+
+            let variable =
+                |at| IfCondition::Variable(TagElement::Variable(Variable::new(at, template)));
+            let condition = IfCondition::Or(Box::new((
+                variable((6, 1)),
+                IfCondition::And(Box::new((
+                    variable((11, 1)),
+                    IfCondition::Not(Box::new(variable((21, 1)))),
+                ))),
+            )));
+            let if_tag = TokenTree::Tag(Tag::If {
+                condition,
+                truthy: vec![],
+                falsey: None,
+            });
+
+            assert_eq!(nodes, vec![if_tag]);
+        })
+    }
+
+    #[test]
+    fn test_parse_if_comparison_precedence() {
+        pyo3::prepare_freethreaded_python();
+
+        Python::with_gil(|py| {
+            let libraries = HashMap::new();
+            let template = "{% if not x == 1 and item in items %}{% endif %}";
+            let mut parser = Parser::new(py, template.into(), &libraries);
+            let nodes = parser.parse().unwrap();
+
+            let variable =
+                |at| IfCondition::Variable(TagElement::Variable(Variable::new(at, template)));
+            let condition = IfCondition::And(Box::new((
+                IfCondition::Not(Box::new(IfCondition::Equal(Box::new((
+                    variable((10, 1)),
+                    IfCondition::Variable(TagElement::Int(1.into())),
+                ))))),
+                IfCondition::In(Box::new((variable((21, 4)), variable((29, 5))))),
+            )));
+            let if_tag = TokenTree::Tag(Tag::If {
+                condition,
+                truthy: vec![],
+                falsey: None,
+            });
+
+            assert_eq!(nodes, vec![if_tag]);
+        })
+    }
+
+    #[test]
+    fn test_parse_firstof() {
+        pyo3::prepare_freethreaded_python();
+
+        Python::with_gil(|py| {
+            let libraries = HashMap::new();
+            let template = "{% firstof a 'b' %}";
+            let mut parser = Parser::new(py, template.into(), &libraries);
+            let nodes = parser.parse().unwrap();
+
+            let firstof = TokenTree::Tag(Tag::Firstof(Firstof {
+                args: vec![
+                    TagElement::Variable(Variable::new((11, 1), template)),
+                    TagElement::Text(Text::new((14, 1))),
+                ],
+                variable: None,
+            }));
+
+            assert_eq!(nodes, vec![firstof]);
+        })
+    }
+
+    #[test]
+    fn test_parse_firstof_as() {
+        pyo3::prepare_freethreaded_python();
+
+        Python::with_gil(|py| {
+            let libraries = HashMap::new();
+            let template = "{% firstof a 'b' as c %}";
+            let mut parser = Parser::new(py, template.into(), &libraries);
+            let nodes = parser.parse().unwrap();
+
+            let firstof = TokenTree::Tag(Tag::Firstof(Firstof {
+                args: vec![
+                    TagElement::Variable(Variable::new((11, 1), template)),
+                    TagElement::Text(Text::new((14, 1))),
+                ],
+                variable: Some("c".to_string()),
+            }));
+
+            assert_eq!(nodes, vec![firstof]);
+        })
+    }
+
+    #[test]
+    fn test_parse_firstof_no_arguments() {
+        pyo3::prepare_freethreaded_python();
+
+        Python::with_gil(|py| {
+            let libraries = HashMap::new();
+            let template = "{% firstof %}";
+            let mut parser = Parser::new(py, template.into(), &libraries);
+            let error = parser.parse().unwrap_err().unwrap_parse_error();
+            assert_eq!(error, ParseError::FirstofNoArguments { at: (0, 13).into() });
+        })
+    }
+
+    #[test]
+    fn test_parse_firstof_keyword_argument() {
+        pyo3::prepare_freethreaded_python();
+
+        Python::with_gil(|py| {
+            let libraries = HashMap::new();
+            let template = "{% firstof a=b %}";
+            let mut parser = Parser::new(py, template.into(), &libraries);
+            let error = parser.parse().unwrap_err().unwrap_parse_error();
+            assert_eq!(
+                error,
+                LexerError::InvalidRemainder { at: (12, 2).into() }.into()
+            );
+        })
+    }
+
+    #[test]
+    fn test_parse_for() {
+        pyo3::prepare_freethreaded_python();
+
+        Python::with_gil(|py| {
+            let libraries = HashMap::new();
+            let template = "{% for k, v in d.items %}{{ k }}{% empty %}none{% endfor %}";
+            let mut parser = Parser::new(py, template.into(), &libraries);
+            let nodes = parser.parse().unwrap();
+
+            let for_tag = TokenTree::Tag(Tag::For(For {
+                variables: vec!["k".to_string(), "v".to_string()],
+                iterable: TagElement::Variable(Variable::new((15, 7), template)),
+                reversed: false,
+                body: vec![TokenTree::Variable(Variable::new((28, 1), template))],
+                empty: Some(vec![TokenTree::Text(Text::new((43, 4)))]),
+            }));
+
+            assert_eq!(nodes, vec![for_tag]);
+        })
+    }
+
+    #[test]
+    fn test_parse_unclosed_for() {
+        pyo3::prepare_freethreaded_python();
+
+        Python::with_gil(|py| {
+            let libraries = HashMap::new();
+            let template = "{% for x in y %}{{ x }}";
+            let mut parser = Parser::new(py, template.into(), &libraries);
+            let error = parser.parse().unwrap_err().unwrap_parse_error();
+            assert_eq!(
+                error,
+                ParseError::UnclosedTag {
+                    tag: "for",
+                    expected: "empty, endfor".to_string(),
+                    at: (0, 16).into(),
+                }
+            );
+        })
+    }
+
+    #[test]
+    fn test_parse_for_mismatched_end_tag() {
+        pyo3::prepare_freethreaded_python();
+
+        Python::with_gil(|py| {
+            let libraries = HashMap::new();
+            let template = "{% for x in y %}{% endif %}";
+            let mut parser = Parser::new(py, template.into(), &libraries);
+            let error = parser.parse().unwrap_err().unwrap_parse_error();
+            assert_eq!(
+                error,
+                ParseError::MismatchedEndTag {
+                    found: "endif",
+                    expected: "empty, endfor".to_string(),
+                    at: (16, 11).into(),
+                    start_at: (0, 16).into(),
+                }
+            );
+        })
+    }
+
+    #[test]
+    fn test_parse_nested_for_mismatched_end_tag() {
+        pyo3::prepare_freethreaded_python();
+
+        Python::with_gil(|py| {
+            let libraries = HashMap::new();
+            let template = "{% if a %}{% for x in y %}{% endif %}{% endif %}";
+            let mut parser = Parser::new(py, template.into(), &libraries);
+            let error = parser.parse().unwrap_err().unwrap_parse_error();
+            assert_eq!(
+                error,
+                ParseError::MismatchedEndTag {
+                    found: "endif",
+                    expected: "empty, endfor".to_string(),
+                    at: (26, 11).into(),
+                    start_at: (10, 16).into(),
+                }
+            );
+        })
+    }
+
+    #[test]
+    fn test_parse_block() {
+        pyo3::prepare_freethreaded_python();
+
+        Python::with_gil(|py| {
+            let libraries = HashMap::new();
+            let template = "{% block a %}x{% endblock a %}";
+            let mut parser = Parser::new(py, template.into(), &libraries);
+            let nodes = parser.parse().unwrap();
+
+            let block = Block {
+                name: "a".to_string(),
+                nodes: Arc::new(vec![TokenTree::Text(Text::new((13, 1)))]),
+            };
+            assert_eq!(nodes, vec![TokenTree::Tag(Tag::Block(block))]);
+        })
+    }
+
+    #[test]
+    fn test_parse_block_arguments() {
+        pyo3::prepare_freethreaded_python();
+
+        Python::with_gil(|py| {
+            let libraries = HashMap::new();
+            let template = "{% block %}{% endblock %}";
+            let mut parser = Parser::new(py, template.into(), &libraries);
+            let error = parser.parse().unwrap_err().unwrap_parse_error();
+            assert_eq!(error, ParseError::BlockArguments { at: (0, 11).into() });
+        })
+    }
+
+    #[test]
+    fn test_parse_block_duplicate() {
+        pyo3::prepare_freethreaded_python();
+
+        Python::with_gil(|py| {
+            let libraries = HashMap::new();
+            let template = "{% block a %}{% endblock %}{% block a %}{% endblock %}";
+            let mut parser = Parser::new(py, template.into(), &libraries);
+            let error = parser.parse().unwrap_err().unwrap_parse_error();
+            assert_eq!(
+                error,
+                ParseError::DuplicateBlock {
+                    name: "a".to_string(),
+                    at: (27, 13).into(),
+                }
+            );
+        })
+    }
+
+    #[test]
+    fn test_parse_block_wrong_end_name() {
+        pyo3::prepare_freethreaded_python();
+
+        Python::with_gil(|py| {
+            let libraries = HashMap::new();
+            let template = "{% block a %}{% endblock b %}";
+            let mut parser = Parser::new(py, template.into(), &libraries);
+            let error = parser.parse().unwrap_err().unwrap_parse_error();
+            assert_eq!(
+                error,
+                ParseError::WrongEndBlock {
+                    unexpected: "b".to_string(),
+                    expected: "a".to_string(),
+                    at: (13, 16).into(),
+                    start_at: (0, 13).into(),
+                }
+            );
+        })
+    }
+
+    #[test]
+    fn test_parse_extends() {
+        pyo3::prepare_freethreaded_python();
+
+        Python::with_gil(|py| {
+            let libraries = HashMap::new();
+            let template =
+                "{% extends 'base.html' %}ignored{% if x %}{% block a %}{% endblock %}{% endif %}";
+            let mut parser = Parser::new(py, template.into(), &libraries);
+            let nodes = parser.parse().unwrap();
+
+            let extends = Extends {
+                parent: TagElement::Text(Text::new((12, 9))),
+                parent_at: (11, 11),
+                blocks: vec![Block {
+                    name: "a".to_string(),
+                    nodes: Arc::new(vec![]),
+                }],
+            };
+            assert_eq!(nodes, vec![TokenTree::Tag(Tag::Extends(extends))]);
+        })
+    }
+
+    #[test]
+    fn test_parse_extends_arguments() {
+        pyo3::prepare_freethreaded_python();
+
+        Python::with_gil(|py| {
+            let libraries = HashMap::new();
+            let template = "{% extends %}";
+            let mut parser = Parser::new(py, template.into(), &libraries);
+            let error = parser.parse().unwrap_err().unwrap_parse_error();
+            assert_eq!(error, ParseError::ExtendsArguments { at: (0, 13).into() });
+        })
+    }
+
+    #[test]
+    fn test_parse_extends_not_first() {
+        pyo3::prepare_freethreaded_python();
+
+        Python::with_gil(|py| {
+            let libraries = HashMap::new();
+            let template = "{{ x }}{% extends 'a' %}";
+            let mut parser = Parser::new(py, template.into(), &libraries);
+            let error = parser.parse().unwrap_err().unwrap_parse_error();
+            assert_eq!(error, ParseError::ExtendsNotFirst { at: (7, 17).into() });
+        })
+    }
+
+    #[test]
+    fn test_parse_extends_repeated() {
+        pyo3::prepare_freethreaded_python();
+
+        Python::with_gil(|py| {
+            let libraries = HashMap::new();
+            let template = "{% extends 'a' %}{% extends 'b' %}";
+            let mut parser = Parser::new(py, template.into(), &libraries);
+            let error = parser.parse().unwrap_err().unwrap_parse_error();
+            assert_eq!(error, ParseError::ExtendsRepeated { at: (0, 17).into() });
+        })
+    }
+
+    #[test]
+    fn test_parse_include() {
+        pyo3::prepare_freethreaded_python();
+
+        Python::with_gil(|py| {
+            let libraries = HashMap::new();
+            let template = "{% include 'a.html' with x=y only %}";
+            let mut parser = Parser::new(py, template.into(), &libraries);
+            let nodes = parser.parse().unwrap();
+
+            let include = Include {
+                template_name: TagElement::Text(Text::new((12, 6))),
+                kwargs: vec![(
+                    "x".to_string(),
+                    TagElement::Variable(Variable::new((27, 1), template)),
+                )],
+                only: true,
+            };
+            assert_eq!(nodes, vec![TokenTree::Tag(Tag::Include(include))]);
+        })
+    }
+
+    #[test]
+    fn test_parse_include_errors() {
+        pyo3::prepare_freethreaded_python();
+
+        Python::with_gil(|py| {
+            let libraries = HashMap::new();
+            let parse_error = |template: &str| {
+                let mut parser = Parser::new(py, template.into(), &libraries);
+                parser.parse().unwrap_err().unwrap_parse_error()
+            };
+
+            assert_eq!(
+                parse_error("{% include %}"),
+                ParseError::IncludeNoArguments { at: (0, 13).into() }
+            );
+            assert_eq!(
+                parse_error("{% include 'a' only only %}"),
+                ParseError::OptionRepeated {
+                    option: "only".to_string(),
+                    at: (20, 4).into(),
+                }
+            );
+            assert_eq!(
+                parse_error("{% include 'a' foo %}"),
+                ParseError::IncludeUnknownArgument {
+                    argument: "foo".to_string(),
+                    at: (15, 3).into(),
+                }
+            );
+            assert_eq!(
+                parse_error("{% include 'a' with only %}"),
+                ParseError::IncludeWithoutKeywords { at: (15, 4).into() }
+            );
+        })
+    }
+
+    #[test]
+    fn test_parse_blocktranslate() {
+        pyo3::prepare_freethreaded_python();
+
+        Python::with_gil(|py| {
+            let template = "{% blocktrans trimmed count n=items|length %}\n  {{ n }} at 100%\n{% plural %}{{ n }} at 100%{% endblocktrans %}";
+            let libraries = HashMap::new();
+            let mut parser = Parser::new(py, template.into(), &libraries);
+            let nodes = parser.parse().unwrap();
+
+            let items = Variable::new((30, 5), template);
+            let length = Filter::new(&parser, (36, 6), TagElement::Variable(items), None).unwrap();
+            let blocktranslate = Blocktranslate {
+                tag: "blocktrans",
+                singular: "%(n)s at 100%%".to_string(),
+                plural: Some("%(n)s at 100%%".to_string()),
+                variables: vec!["n".to_string(), "n".to_string()],
+                kwargs: Vec::new(),
+                count: Some(("n".to_string(), TagElement::Filter(Box::new(length)))),
+            };
+            assert_eq!(
+                nodes,
+                vec![TokenTree::Tag(Tag::Blocktranslate(blocktranslate))]
+            );
+        })
+    }
+
+    #[test]
+    fn test_parse_blocktranslate_errors() {
+        pyo3::prepare_freethreaded_python();
+
+        Python::with_gil(|py| {
+            let libraries = HashMap::new();
+            let parse_error = |template: &str| {
+                let mut parser = Parser::new(py, template.into(), &libraries);
+                parser.parse().unwrap_err().unwrap_parse_error()
+            };
+
+            assert_eq!(
+                parse_error("{% blocktranslate foo %}{% endblocktranslate %}"),
+                ParseError::BlocktranslateUnknownArgument {
+                    tag: "blocktranslate",
+                    argument: "foo".to_string(),
+                    at: (18, 3).into(),
+                }
+            );
+            assert_eq!(
+                parse_error("{% blocktranslate with %}{% endblocktranslate %}"),
+                ParseError::BlocktranslateWithoutKeywords {
+                    tag: "blocktranslate",
+                    at: (18, 4).into(),
+                }
+            );
+            assert_eq!(
+                parse_error(
+                    "{% blocktranslate count a=1 b=2 %}{% plural %}{% endblocktranslate %}"
+                ),
+                ParseError::BlocktranslateCountArguments {
+                    tag: "blocktranslate",
+                    at: (18, 5).into(),
+                }
+            );
+            assert_eq!(
+                parse_error("{% blocktranslate trimmed trimmed %}{% endblocktranslate %}"),
+                ParseError::OptionRepeated {
+                    option: "trimmed".to_string(),
+                    at: (26, 7).into(),
+                }
+            );
+            assert_eq!(
+                parse_error("{% blocktranslate %}{% if x %}{% endblocktranslate %}"),
+                ParseError::BlocktranslateUnexpectedTag {
+                    tag: "blocktranslate",
+                    seen: "if x".to_string(),
+                    at: (20, 10).into(),
+                }
+            );
+            assert_eq!(
+                parse_error("{% blocktranslate count n=1 %}x{% endblocktranslate %}"),
+                ParseError::BlocktranslateNestedTag {
+                    tag: "blocktranslate",
+                    at: (31, 23).into(),
+                }
+            );
+            assert_eq!(
+                parse_error("{% blocktranslate %}Hello"),
+                ParseError::UnclosedTag {
+                    tag: "blocktranslate",
+                    expected: "endblocktranslate".to_string(),
+                    at: (0, 20).into(),
+                }
+            );
+        })
+    }
+
+    #[test]
+    fn test_parse_translate_errors() {
+        pyo3::prepare_freethreaded_python();
+
+        Python::with_gil(|py| {
+            let libraries = HashMap::new();
+            let parse_error = |template: &str| {
+                let mut parser = Parser::new(py, template.into(), &libraries);
+                parser.parse().unwrap_err().unwrap_parse_error()
+            };
+
+            assert_eq!(
+                parse_error("{% translate %}"),
+                ParseError::TranslateArguments {
+                    tag: "translate",
+                    at: (0, 15).into(),
+                }
+            );
+            assert_eq!(
+                parse_error("{% trans 'a' noop noop %}"),
+                ParseError::OptionRepeated {
+                    option: "noop".to_string(),
+                    at: (18, 4).into(),
+                }
+            );
+            assert_eq!(
+                parse_error("{% translate 'a' context %}"),
+                ParseError::TranslateMissingOption {
+                    tag: "translate",
+                    option: "context",
+                    at: (17, 7).into(),
+                }
+            );
+            assert_eq!(
+                parse_error("{% translate 'a' context noop %}"),
+                ParseError::TranslateInvalidContext {
+                    tag: "translate",
+                    argument: "noop".to_string(),
+                    at: (25, 4).into(),
+                }
+            );
+            assert_eq!(
+                parse_error("{% translate 'a' as %}"),
+                ParseError::TranslateMissingOption {
+                    tag: "translate",
+                    option: "as",
+                    at: (17, 2).into(),
+                }
+            );
+            assert_eq!(
+                parse_error("{% translate 'a' foo %}"),
+                ParseError::TranslateUnknownArgument {
+                    tag: "translate",
+                    argument: "foo".to_string(),
+                    at: (17, 3).into(),
+                }
+            );
+        })
+    }
+
+    fn tag_library(py: Python<'_>) -> HashMap<String, Py<PyAny>> {
+        let locals = PyDict::new(py);
+        py.run(
+            c"
+from types import SimpleNamespace
+
+mytags = SimpleNamespace(filters={'bar': str.upper, 'baz': str.lower}, tags={})
+",
+            None,
+            Some(&locals),
+        )
+        .unwrap();
+        let library = locals.get_item("mytags").unwrap().unwrap();
+        HashMap::from([("mytags".to_string(), library.unbind())])
+    }
+
+    #[test]
+    fn test_parse_load() {
+        pyo3::prepare_freethreaded_python();
+
+        Python::with_gil(|py| {
+            let libraries = tag_library(py);
+            let template = TemplateString("{% load mytags %}{{ foo|bar }}{{ foo|baz }}");
+            let mut parser = Parser::new(py, template, &libraries);
+            let nodes = parser.parse().unwrap();
+
+            assert_eq!(nodes.len(), 3);
+            assert_eq!(nodes[0], TokenTree::Tag(Tag::Load));
+            let filters = libraries["mytags"].bind(py).getattr("filters").unwrap();
+            let bar = get_external_filter(&nodes[1]);
+            assert!(bar.bind(py).is(&filters.get_item("bar").unwrap()));
+            let baz = get_external_filter(&nodes[2]);
+            assert!(baz.bind(py).is(&filters.get_item("baz").unwrap()));
+        })
+    }
+
+    #[test]
+    fn test_parse_load_from() {
+        pyo3::prepare_freethreaded_python();
+
+        Python::with_gil(|py| {
+            let libraries = tag_library(py);
+            let template = TemplateString("{% load bar from mytags %}{{ foo|bar }}");
+            let mut parser = Parser::new(py, template, &libraries);
+            let nodes = parser.parse().unwrap();
+
+            assert_eq!(nodes.len(), 2);
+            assert_eq!(nodes[0], TokenTree::Tag(Tag::Load));
+            let filters = libraries["mytags"].bind(py).getattr("filters").unwrap();
+            let bar = get_external_filter(&nodes[1]);
+            assert!(bar.bind(py).is(&filters.get_item("bar").unwrap()));
+
+            let template = TemplateString("{% load bar from mytags %}{{ foo|baz }}");
+            let mut parser = Parser::new(py, template, &libraries);
+            let error = parser.parse().unwrap_err().unwrap_parse_error();
+            assert_eq!(
+                error,
+                ParseError::InvalidFilter {
+                    filter: "baz".to_string(),
+                    at: (33, 3).into(),
+                }
+            );
+        })
+    }
+
+    #[test]
+    fn test_parse_load_errors() {
+        pyo3::prepare_freethreaded_python();
+
+        Python::with_gil(|py| {
+            let libraries = tag_library(py);
+            let parse_error = |template: &str| {
+                let mut parser = Parser::new(py, template.into(), &libraries);
+                parser.parse().unwrap_err().unwrap_parse_error()
+            };
+
+            assert_eq!(
+                parse_error("{% load missing %}"),
+                ParseError::MissingTagLibrary {
+                    library: "missing".to_string(),
+                    at: (8, 7).into(),
+                    help: "Must be one of:\nmytags".to_string(),
+                }
+            );
+            assert_eq!(
+                parse_error("{% load missing from mytags %}"),
+                ParseError::MissingFilterTag {
+                    tag: "missing".to_string(),
+                    library: "mytags".to_string(),
+                    tag_at: (8, 7).into(),
+                    library_at: (21, 6).into(),
+                }
+            );
+            assert_eq!(
+                parse_error("{% load bar from missing %}"),
+                ParseError::MissingTagLibrary {
+                    library: "missing".to_string(),
+                    at: (17, 7).into(),
+                    help: "Must be one of:\nmytags".to_string(),
+                }
+            );
+        })
+    }
+
+    #[test]
+    fn test_parse_lorem() {
+        pyo3::prepare_freethreaded_python();
+
+        Python::with_gil(|py| {
+            let libraries = HashMap::new();
+            let template = "{% lorem %}{% lorem 3 w %}{% lorem count p random %}{% lorem random %}";
+            let mut parser = Parser::new(py, template.into(), &libraries);
+            let nodes = parser.parse().unwrap();
+
+            assert_eq!(
+                nodes,
+                vec![
+                    TokenTree::Tag(Tag::Lorem(Lorem {
+                        count: TagElement::Int(1.into()),
+                        method: LoremMethod::Blocks,
+                        common: true,
+                    })),
+                    TokenTree::Tag(Tag::Lorem(Lorem {
+                        count: TagElement::Int(3.into()),
+                        method: LoremMethod::Words,
+                        common: true,
+                    })),
+                    TokenTree::Tag(Tag::Lorem(Lorem {
+                        count: TagElement::Variable(Variable::new((35, 5), template)),
+                        method: LoremMethod::Paragraphs,
+                        common: false,
+                    })),
+                    TokenTree::Tag(Tag::Lorem(Lorem {
+                        count: TagElement::Int(1.into()),
+                        method: LoremMethod::Blocks,
+                        common: false,
+                    })),
+                ]
+            );
+        })
+    }
+
+    #[test]
+    fn test_parse_lorem_arguments() {
+        pyo3::prepare_freethreaded_python();
+
+        Python::with_gil(|py| {
+            let libraries = HashMap::new();
+            let template = "{% lorem 1 2 w %}";
+            let mut parser = Parser::new(py, template.into(), &libraries);
+            let error = parser.parse().unwrap_err().unwrap_parse_error();
+            let at = (0, template.len());
+            assert_eq!(error, ParseError::LoremArguments { at: at.into() });
+        })
+    }
+
+    #[test]
+    fn test_parse_now() {
+        pyo3::prepare_freethreaded_python();
+
+        Python::with_gil(|py| {
+            let libraries = HashMap::new();
+            let template = "{% now 'Y-m-d H:i' %}{% now \"jS F\" as today %}";
+            let mut parser = Parser::new(py, template.into(), &libraries);
+            let nodes = parser.parse().unwrap();
+
+            assert_eq!(
+                nodes,
+                vec![
+                    TokenTree::Tag(Tag::Now(Now {
+                        format: "Y-m-d H:i".to_string(),
+                        variable: None,
+                    })),
+                    TokenTree::Tag(Tag::Now(Now {
+                        format: "jS F".to_string(),
+                        variable: Some("today".to_string()),
+                    })),
+                ]
+            );
+        })
+    }
+
+    #[test]
+    fn test_parse_now_arguments() {
+        pyo3::prepare_freethreaded_python();
+
+        Python::with_gil(|py| {
+            let libraries = HashMap::new();
+            for template in ["{% now %}", "{% now 'Y' 'm' %}", "{% now 'Y' to today %}"] {
+                let mut parser = Parser::new(py, template.into(), &libraries);
+                let error = parser.parse().unwrap_err().unwrap_parse_error();
+                let at = (0, template.len());
+                assert_eq!(error, ParseError::NowArguments { at: at.into() });
+            }
+        })
+    }
+
+    #[test]
+    fn test_parse_templatetag() {
+        pyo3::prepare_freethreaded_python();
+
+        Python::with_gil(|py| {
+            let libraries = HashMap::new();
+            let cases = [
+                ("openblock", "{%"),
+                ("closeblock", "%}"),
+                ("openvariable", "{{"),
+                ("closevariable", "}}"),
+                ("openbrace", "{"),
+                ("closebrace", "}"),
+                ("opencomment", "{#"),
+                ("closecomment", "#}"),
+            ];
+            for (argument, literal) in cases {
+                let template = format!("{{% templatetag {argument} %}}");
+                let mut parser = Parser::new(py, template.as_str().into(), &libraries);
+                let nodes = parser.parse().unwrap();
+
+                assert_eq!(nodes, vec![TokenTree::Tag(Tag::Templatetag(literal))]);
+            }
+        })
+    }
+
+    #[test]
+    fn test_parse_templatetag_errors() {
+        pyo3::prepare_freethreaded_python();
+
+        Python::with_gil(|py| {
+            let libraries = HashMap::new();
+            for template in [
+                "{% templatetag %}",
+                "{% templatetag openblock closeblock %}",
+            ] {
+                let mut parser = Parser::new(py, template.into(), &libraries);
+                let error = parser.parse().unwrap_err().unwrap_parse_error();
+                let at = (0, template.len());
+                assert_eq!(error, ParseError::TemplatetagArguments { at: at.into() });
+            }
+
+            let template = "{% templatetag openbracket %}";
+            let mut parser = Parser::new(py, template.into(), &libraries);
+            let error = parser.parse().unwrap_err().unwrap_parse_error();
+            assert_eq!(
+                error,
+                ParseError::TemplatetagInvalidArgument {
+                    argument: "openbracket".to_string(),
+                    at: (15, 11).into(),
+                }
+            );
+        })
+    }
+
+    #[test]
+    fn test_parse_widthratio() {
+        pyo3::prepare_freethreaded_python();
+
+        Python::with_gil(|py| {
+            let libraries = HashMap::new();
+            let template = "{% widthratio value 200 100 %}{% widthratio a b 10 as width %}";
+            let mut parser = Parser::new(py, template.into(), &libraries);
+            let nodes = parser.parse().unwrap();
+
+            assert_eq!(
+                nodes,
+                vec![
+                    TokenTree::Tag(Tag::Widthratio(Widthratio {
+                        value: TagElement::Variable(Variable::new((14, 5), template)),
+                        max_value: TagElement::Int(200.into()),
+                        max_width: TagElement::Int(100.into()),
+                        variable: None,
+                    })),
+                    TokenTree::Tag(Tag::Widthratio(Widthratio {
+                        value: TagElement::Variable(Variable::new((44, 1), template)),
+                        max_value: TagElement::Variable(Variable::new((46, 1), template)),
+                        max_width: TagElement::Int(10.into()),
+                        variable: Some("width".to_string()),
+                    })),
+                ]
+            );
+        })
+    }
+
+    #[test]
+    fn test_parse_regroup() {
+        pyo3::prepare_freethreaded_python();
+
+        Python::with_gil(|py| {
+            let libraries = HashMap::new();
+            let template = "{% regroup people by gender as grouped %}";
+            let mut parser = Parser::new(py, template.into(), &libraries);
+            let nodes = parser.parse().unwrap();
+
+            let key = Variable::new((21, 6), template).with_parent((31, 7));
+            assert_eq!(
+                nodes,
+                vec![TokenTree::Tag(Tag::Regroup(Regroup {
+                    target: TagElement::Variable(Variable::new((11, 6), template)),
+                    key: TagElement::Variable(key),
+                    variable: "grouped".to_string(),
+                }))]
+            );
+        })
+    }
+
+    #[test]
+    fn test_parse_regroup_arguments() {
+        pyo3::prepare_freethreaded_python();
+
+        Python::with_gil(|py| {
+            let libraries = HashMap::new();
+            let template = "{% regroup people by gender %}";
+            let mut parser = Parser::new(py, template.into(), &libraries);
+            let error = parser.parse().unwrap_err().unwrap_parse_error();
+            let at = (0, template.len());
+            assert_eq!(error, ParseError::RegroupArguments { at: at.into() });
+
+            let template = "{% regroup people with gender as grouped %}";
+            let mut parser = Parser::new(py, template.into(), &libraries);
+            let error = parser.parse().unwrap_err().unwrap_parse_error();
+            assert_eq!(error, ParseError::RegroupExpectedBy { at: (18, 4).into() });
+
+            let template = "{% regroup people by gender to grouped %}";
+            let mut parser = Parser::new(py, template.into(), &libraries);
+            let error = parser.parse().unwrap_err().unwrap_parse_error();
+            assert_eq!(error, ParseError::RegroupExpectedAs { at: (28, 2).into() });
+        })
+    }
+
+    #[test]
+    fn test_parse_widthratio_arguments() {
+        pyo3::prepare_freethreaded_python();
+
+        Python::with_gil(|py| {
+            let libraries = HashMap::new();
+            for template in ["{% widthratio a b %}", "{% widthratio a b c as %}"] {
+                let mut parser = Parser::new(py, template.into(), &libraries);
+                let error = parser.parse().unwrap_err().unwrap_parse_error();
+                let at = (0, template.len());
+                assert_eq!(error, ParseError::WidthratioArguments { at: at.into() });
+            }
+
+            let template = "{% widthratio a b c to width %}";
+            let mut parser = Parser::new(py, template.into(), &libraries);
+            let error = parser.parse().unwrap_err().unwrap_parse_error();
+            assert_eq!(
+                error,
+                ParseError::WidthratioExpectedAs { at: (20, 2).into() }
+            );
+        })
+    }
+
+    #[test]
+    fn test_parse_cycle() {
+        pyo3::prepare_freethreaded_python();
+
+        Python::with_gil(|py| {
+            let libraries = HashMap::new();
+            let template = "{% cycle 'a' b %}";
+            let mut parser = Parser::new(py, template.into(), &libraries);
+            let nodes = parser.parse().unwrap();
+
+            let cycle = TokenTree::Tag(Tag::Cycle(Cycle {
+                values: Arc::new(vec![
+                    TagElement::Text(Text::new((10, 1))),
+                    TagElement::Variable(Variable::new((13, 1), template)),
+                ]),
+                variable: None,
+                silent: false,
+            }));
+            assert_eq!(nodes, vec![cycle]);
+        })
+    }
+
+    #[test]
+    fn test_parse_cycle_named() {
+        pyo3::prepare_freethreaded_python();
+
+        Python::with_gil(|py| {
+            let libraries = HashMap::new();
+            let template = "{% cycle 'a' 'b' as row silent %}{% cycle row %}";
+            let mut parser = Parser::new(py, template.into(), &libraries);
+            let nodes = parser.parse().unwrap();
+
+            let [
+                TokenTree::Tag(Tag::Cycle(first)),
+                TokenTree::Tag(Tag::Cycle(second)),
+            ] = nodes.as_slice()
+            else {
+                panic!("Expected two cycle tags, got {nodes:?}");
+            };
+            assert_eq!(first.variable, Some("row".to_string()));
+            assert!(first.silent);
+            assert_eq!(first.values.len(), 2);
+            assert!(Arc::ptr_eq(&first.values, &second.values));
+        })
+    }
+
+    #[test]
+    fn test_parse_cycle_errors() {
+        pyo3::prepare_freethreaded_python();
+
+        Python::with_gil(|py| {
+            let libraries = HashMap::new();
+            let cases = [
+                (
+                    "{% cycle %}",
+                    ParseError::CycleArguments { at: (0, 11).into() },
+                ),
+                (
+                    "{% cycle row %}",
+                    ParseError::NoNamedCycles {
+                        name: "row".to_string(),
+                        at: (9, 3).into(),
+                    },
+                ),
+                (
+                    "{% cycle 'a' 'b' as row %}{% cycle col %}",
+                    ParseError::NamedCycleMissing {
+                        name: "col".to_string(),
+                        at: (35, 3).into(),
+                    },
+                ),
+                (
+                    "{% cycle 'a' 'b' as row loud %}",
+                    ParseError::CycleSilentFlag {
+                        flag: "loud".to_string(),
+                        at: (24, 4).into(),
+                    },
+                ),
+            ];
+            for (template, expected) in cases {
+                let mut parser = Parser::new(py, template.into(), &libraries);
+                let error = parser.parse().unwrap_err().unwrap_parse_error();
+                assert_eq!(error, expected);
+            }
+        })
+    }
+
     #[test]
     fn test_parse_url_tag() {
         pyo3::prepare_freethreaded_python();
@@ -1534,7 +4478,7 @@ mod tests {
             let nodes = parser.parse().unwrap();
 
             let url = TokenTree::Tag(Tag::Url(Url {
-                view_name: TagElement::Variable(Variable { at: (7, 14) }),
+                view_name: TagElement::Variable(Variable::new((7, 14), template)),
                 args: vec![],
                 kwargs: vec![],
                 variable: None,
@@ -1554,7 +4498,7 @@ mod tests {
             let mut parser = Parser::new(py, template.into(), &libraries);
             let nodes = parser.parse().unwrap();
 
-            let some_view_name = TagElement::Variable(Variable { at: (7, 14) });
+            let some_view_name = TagElement::Variable(Variable::new((7, 14), template));
             let home = Text { at: (31, 4) };
             let default = Box::new(Filter {
                 at: (22, 7),
@@ -1620,12 +4564,12 @@ mod tests {
             let nodes = parser.parse().unwrap();
 
             let url = TokenTree::Tag(Tag::Url(Url {
-                view_name: TagElement::Variable(Variable { at: (7, 14) }),
+                view_name: TagElement::Variable(Variable::new((7, 14), template)),
                 args: vec![
                     TagElement::Text(Text { at: (23, 3) }),
                     TagElement::Filter(Box::new(Filter {
                         at: (32, 7),
-                        left: TagElement::Variable(Variable { at: (28, 3) }),
+                        left: TagElement::Variable(Variable::new((28, 3), template)),
                         filter: FilterType::Default(DefaultFilter::new(Argument {
                             at: (40, 6),
                             argument_type: ArgumentType::Text(Text { at: (41, 4) }),
@@ -1654,7 +4598,7 @@ mod tests {
             let nodes = parser.parse().unwrap();
 
             let url = TokenTree::Tag(Tag::Url(Url {
-                view_name: TagElement::Variable(Variable { at: (7, 14) }),
+                view_name: TagElement::Variable(Variable::new((7, 14), template)),
                 args: vec![],
                 kwargs: vec![
                     ("foo".to_string(), TagElement::Text(Text { at: (27, 3) })),
@@ -1678,7 +4622,7 @@ mod tests {
             let nodes = parser.parse().unwrap();
 
             let url = TokenTree::Tag(Tag::Url(Url {
-                view_name: TagElement::Variable(Variable { at: (7, 14) }),
+                view_name: TagElement::Variable(Variable::new((7, 14), template)),
                 args: vec![TagElement::Text(Text { at: (23, 3) })],
                 kwargs: vec![],
                 variable: Some("some_url".to_string()),
@@ -1699,7 +4643,7 @@ mod tests {
             let nodes = parser.parse().unwrap();
 
             let url = TokenTree::Tag(Tag::Url(Url {
-                view_name: TagElement::Variable(Variable { at: (7, 14) }),
+                view_name: TagElement::Variable(Variable::new((7, 14), template)),
                 args: vec![],
                 kwargs: vec![("foo".to_string(), TagElement::Text(Text { at: (27, 3) }))],
                 variable: Some("some_url".to_string()),
@@ -1720,11 +4664,11 @@ mod tests {
             let nodes = parser.parse().unwrap();
 
             let url = TokenTree::Tag(Tag::Url(Url {
-                view_name: TagElement::Variable(Variable { at: (7, 14) }),
+                view_name: TagElement::Variable(Variable::new((7, 14), template)),
                 args: vec![
                     TagElement::Text(Text { at: (23, 3) }),
-                    TagElement::Variable(Variable { at: (28, 3) }),
-                    TagElement::Variable(Variable { at: (32, 4) }),
+                    TagElement::Variable(Variable::new((28, 3), template)),
+                    TagElement::Variable(Variable::new((32, 4), template)),
                 ],
                 kwargs: vec![],
                 variable: None,
@@ -1,18 +1,61 @@
 use std::borrow::Cow;
 use std::collections::BTreeMap;
 
+use pyo3::exceptions::{PyImportError, PyTypeError, PyValueError};
+use pyo3::intern;
 use pyo3::prelude::*;
 
+use super::filters::ResolveFilter;
+use super::tags::render_block_super;
 use super::types::{Content, ContentString, Context};
 use super::{Evaluate, Render, RenderResult, Resolve, ResolveFailures, ResolveResult};
 use crate::error::RenderError;
-use crate::parse::{TagElement, TokenTree};
+use crate::parse::{Filter, TagElement, TokenTree};
 use crate::types::Argument;
 use crate::types::ArgumentType;
 use crate::types::TemplateString;
 use crate::types::Text;
 use crate::types::TranslatedText;
 use crate::types::Variable;
+use crate::utils::{PyResultMethods, getattr_flag};
+
+/// Call a resolved value if it is callable, as Django does during variable
+/// lookup. Returns `None` when the callable requires arguments or is marked
+/// with `alters_data`, and the callable itself when it is marked with
+/// `do_not_call_in_templates`.
+fn call_if_callable(variable: Bound<'_, PyAny>) -> PyResult<Option<Bound<'_, PyAny>>> {
+    if !variable.is_callable() {
+        return Ok(Some(variable));
+    }
+    let py = variable.py();
+    if getattr_flag(&variable, intern!(py, "do_not_call_in_templates"))? {
+        return Ok(Some(variable));
+    }
+    if getattr_flag(&variable, intern!(py, "alters_data"))? {
+        return Ok(None);
+    }
+    let error = match variable.call0().ok_or_isinstance_of::<PyTypeError>(py)? {
+        Ok(result) => return Ok(Some(result)),
+        Err(error) => error,
+    };
+    let inspect = py.import(intern!(py, "inspect"))?;
+    let signature = match inspect
+        .call_method1(intern!(py, "signature"), (&variable,))
+        .ok_or_isinstance_of::<PyValueError>(py)?
+    {
+        Ok(signature) => signature,
+        Err(_) => return Ok(None),
+    };
+    match signature
+        .call_method0(intern!(py, "bind"))
+        .ok_or_isinstance_of::<PyTypeError>(py)?
+    {
+        // Arguments were required, so the callable can't be used in a template
+        Err(_) => Ok(None),
+        // The TypeError came from inside the callable
+        Ok(_) => Err(error),
+    }
+}
 
 impl Resolve for Variable {
     fn resolve<'t, 'py>(
@@ -24,11 +67,34 @@ impl Resolve for Variable {
     ) -> ResolveResult<'t, 'py> {
         let mut parts = self.parts(template);
         let (first, mut object_at) = parts.next().expect("Variable names cannot be empty");
-        let mut variable = match context.context.get(first) {
+        // Inside a `{% block %}`, `block` refers to the block being rendered.
+        if first == "block"
+            && let Some(name) = context.blocks.active()
+        {
+            return match parts.next() {
+                Some(("super", _)) => {
+                    let rendered = render_block_super(py, context)?;
+                    Ok(Some(Content::String(ContentString::HtmlSafe(Cow::Owned(
+                        rendered,
+                    )))))
+                }
+                Some(("name", _)) => Ok(Some(Content::String(ContentString::String(Cow::Owned(
+                    name.to_string(),
+                ))))),
+                _ => Ok(None),
+            };
+        }
+        let variable = match context.get(first) {
             Some(variable) => variable.bind(py).clone(),
             None => return Ok(None),
         };
+        let mut variable = match call_if_callable(variable)? {
+            Some(variable) => variable,
+            None => return Ok(None),
+        };
 
+        // Like Django, try a key lookup, then an attribute, then a list index.
+        // Names starting with an underscore are already rejected by the lexer.
         for (part, key_at) in parts {
             variable = match variable.get_item(part) {
                 Ok(variable) => variable,
@@ -54,17 +120,49 @@ impl Resolve for Variable {
                         };
                         match variable.get_item(int) {
                             Ok(variable) => variable,
-                            Err(_) => todo!(),
+                            // Like Django, an index out of range is rendered as empty
+                            Err(_) => return Ok(None),
                         }
                     }
                 },
             };
+            variable = match call_if_callable(variable)? {
+                Some(variable) => variable,
+                None => return Ok(None),
+            };
             object_at.1 += key_at.1 + 1;
         }
         Ok(Some(Content::Py(variable)))
     }
 }
 
+/// Remove the escaping from a quoted string literal, like Django's
+/// `unescape_string_literal`. `at` excludes the quotes.
+fn unescape_string_literal(template: TemplateString<'_>, at: (usize, usize)) -> Cow<'_, str> {
+    let content = template.content(at);
+    if !content.contains('\\') {
+        return Cow::Borrowed(content);
+    }
+    let quote = template.content((at.0 - 1, 1));
+    Cow::Owned(
+        content
+            .replace(&format!("\\{quote}"), quote)
+            .replace("\\\\", "\\"),
+    )
+}
+
+fn resolve_string_literal<'t, 'py>(
+    text: &Text,
+    template: TemplateString<'t>,
+    context: &Context,
+) -> ResolveResult<'t, 'py> {
+    let resolved = unescape_string_literal(template, text.at);
+    Ok(Some(Content::String(match context.autoescape {
+        false => ContentString::String(resolved),
+        true => ContentString::HtmlSafe(resolved),
+    })))
+}
+
 impl Resolve for Text {
     fn resolve<'t, 'py>(
         &self,
@@ -81,6 +179,16 @@ impl Resolve for Text {
     }
 }
 
+/// Django's `django.utils.translation` module, or `None` without Django's
+/// translation machinery, in which case messages are left as they are.
+pub fn django_translation(py: Python<'_>) -> PyResult<Option<Bound<'_, PyModule>>> {
+    match py.import(intern!(py, "django.utils.translation")) {
+        Ok(django_translation) => Ok(Some(django_translation)),
+        Err(error) if error.is_instance_of::<PyImportError>(py) => Ok(None),
+        Err(error) => Err(error),
+    }
+}
+
 impl Resolve for TranslatedText {
     fn resolve<'t, 'py>(
         &self,
@@ -89,13 +197,17 @@ impl Resolve for TranslatedText {
         context: &mut Context,
         _failures: ResolveFailures,
     ) -> ResolveResult<'t, 'py> {
-        let resolved = Cow::Borrowed(template.content(self.at));
-        let django_translation = py.import("django.utils.translation")?;
-        let get_text = django_translation.getattr("gettext")?;
-        let resolved = get_text.call1((resolved,))?.extract::<String>()?;
+        let message = unescape_string_literal(template, self.at);
+        let resolved = match django_translation(py)? {
+            Some(django_translation) => {
+                let get_text = django_translation.getattr(intern!(py, "gettext"))?;
+                Cow::Owned(get_text.call1((message,))?.extract::<String>()?)
+            }
+            None => message,
+        };
         Ok(Some(Content::String(match context.autoescape {
-            false => ContentString::String(Cow::Owned(resolved)),
-            true => ContentString::HtmlSafe(Cow::Owned(resolved)),
+            false => ContentString::String(resolved),
+            true => ContentString::HtmlSafe(resolved),
         })))
     }
 }
@@ -109,7 +221,7 @@ impl Resolve for Argument {
         failures: ResolveFailures,
     ) -> ResolveResult<'t, 'py> {
         Ok(Some(match &self.argument_type {
-            ArgumentType::Text(text) => return text.resolve(py, template, context, failures),
+            ArgumentType::Text(text) => return resolve_string_literal(text, template, context),
             ArgumentType::TranslatedText(text) => {
                 return text.resolve(py, template, context, failures);
             }
@@ -118,9 +230,9 @@ impl Resolve for Argument {
                     Some(content) => content,
                     None => {
                         let key = template.content(variable.at).to_string();
-                        let context: BTreeMap<&String, &Bound<'py, PyAny>> = context
-                            .context
-                            .iter()
+                        let context: BTreeMap<&str, &Bound<'py, PyAny>> = context
+                            .flatten()
+                            .into_iter()
                             .map(|(k, v)| (k, v.bind(py)))
                             .collect();
                         let object = format!("{:?}", context);
@@ -135,7 +247,8 @@ impl Resolve for Argument {
                 }
             }
             ArgumentType::Float(number) => Content::Float(*number),
-            ArgumentType::Int(number) => Content::Int(number.clone()),
+            ArgumentType::Int(number) => Content::Int((*number).into()),
+            ArgumentType::BigInt(number) => Content::Int(number.clone()),
         }))
     }
 }
@@ -149,8 +262,10 @@ impl Resolve for TagElement {
         failures: ResolveFailures,
     ) -> ResolveResult<'t, 'py> {
         match self {
-            Self::Text(text) => text.resolve(py, template, context, failures),
-            Self::TranslatedText(text) => text.resolve(py, template, context, failures),
+            Self::Text(text) => resolve_string_literal(text, template, context),
+            Self::TranslatedText(text) => {
+                TranslatedText::new(text.at).resolve(py, template, context, failures)
+            }
             Self::Variable(variable) => variable.resolve(py, template, context, failures),
             Self::Filter(filter) => filter.resolve(py, template, context, failures),
             Self::Int(int) => Ok(Some(Content::Int(int.clone()))),
@@ -178,6 +293,60 @@ impl Evaluate for TagElement {
     }
 }
 
+/// Render Django's `string_if_invalid`, escaped like any other string.
+pub fn render_invalid<'t>(invalid: Option<String>, context: &Context) -> Cow<'t, str> {
+    match invalid {
+        None => Cow::Borrowed(""),
+        Some(invalid) if context.autoescape => {
+            ContentString::HtmlUnsafe(Cow::Owned(invalid)).content()
+        }
+        Some(invalid) => Cow::Owned(invalid),
+    }
+}
+
+/// Apply the filters of `filter` to its already resolved leftmost element.
+fn apply_filters<'t, 'py>(
+    filter: &Filter,
+    head: Option<Content<'t, 'py>>,
+    py: Python<'py>,
+    template: TemplateString<'t>,
+    context: &mut Context,
+) -> ResolveResult<'t, 'py> {
+    let left = match &filter.left {
+        TagElement::Filter(left) => apply_filters(left, head, py, template, context)?,
+        _ => head,
+    };
+    filter.filter.resolve(left, py, template, context)
+}
+
+fn render_filter<'t>(
+    filter: &Filter,
+    py: Python<'_>,
+    template: TemplateString<'t>,
+    context: &mut Context,
+) -> RenderResult<'t> {
+    let mut head = &filter.left;
+    while let TagElement::Filter(filter) = head {
+        head = &filter.left;
+    }
+    let invalid = match head {
+        TagElement::Variable(variable) => context.string_if_invalid(template.content(variable.at)),
+        _ => None,
+    };
+    if invalid.is_none() {
+        return filter.render(py, template, context);
+    }
+    // Like Django, a missing variable is replaced without applying its filters
+    let content = match head.resolve(py, template, context, ResolveFailures::Raise)? {
+        None => return Ok(render_invalid(invalid, context)),
+        head => apply_filters(filter, head, py, template, context)?,
+    };
+    match content {
+        Some(content) => Ok(content.render(context)?),
+        None => Ok(Cow::Borrowed("")),
+    }
+}
+
 impl Render for TokenTree {
     fn render<'t>(
         &self,
@@ -186,11 +355,24 @@ impl Render for TokenTree {
         context: &mut Context,
     ) -> RenderResult<'t> {
         match self {
+            Self::Comment(_) => Ok(Cow::Borrowed("")),
+            Self::Int(n) => Ok(Content::Int(n.clone()).render(context)?),
+            Self::Float(f) => Ok(Content::Float(*f).render(context)?),
             Self::Text(text) => text.render(py, template, context),
-            Self::TranslatedText(_text) => todo!(),
+            Self::TranslatedText(text) => {
+                TranslatedText::new(text.at).render(py, template, context)
+            }
             Self::Tag(tag) => tag.render(py, template, context),
-            Self::Variable(variable) => variable.render(py, template, context),
-            Self::Filter(filter) => filter.render(py, template, context),
+            Self::Variable(variable) => {
+                match variable.resolve(py, template, context, ResolveFailures::Raise)? {
+                    Some(content) => Ok(content.render(context)?),
+                    None => {
+                        let invalid = context.string_if_invalid(template.content(variable.at));
+                        Ok(render_invalid(invalid, context))
+                    }
+                }
+            }
+            Self::Filter(filter) => render_filter(filter, py, template, context),
         }
     }
 }
@@ -203,6 +385,8 @@ mod tests {
 
     use pyo3::types::{PyDict, PyList, PyString};
 
+    use crate::utils::with_mock_translation;
+
     #[test]
     fn test_render_variable() {
         pyo3::prepare_freethreaded_python();
@@ -210,13 +394,9 @@ mod tests {
         Python::with_gil(|py| {
             let name = PyString::new(py, "Lily").into_any();
             let context = HashMap::from([("name".to_string(), name.unbind())]);
-            let mut context = Context {
-                context,
-                request: None,
-                autoescape: false,
-            };
+            let mut context = Context::new(context, None, false);
             let template = TemplateString("{{ name }}");
-            let variable = Variable::new((3, 4));
+            let variable = Variable::new((3, 4), template);
 
             let rendered = variable.render(py, template, &mut context).unwrap();
             assert_eq!(rendered, "Lily");
@@ -232,19 +412,58 @@ mod tests {
             let name = PyString::new(py, "Lily");
             data.set_item("name", name).unwrap();
             let context = HashMap::from([("data".to_string(), data.into_any().unbind())]);
-            let mut context = Context {
-                context,
-                request: None,
-                autoescape: false,
-            };
+            let mut context = Context::new(context, None, false);
             let template = TemplateString("{{ data.name }}");
-            let variable = Variable::new((3, 9));
+            let variable = Variable::new((3, 9), template);
 
             let rendered = variable.render(py, template, &mut context).unwrap();
             assert_eq!(rendered, "Lily");
         })
     }
 
+    #[test]
+    fn test_render_variable_repeatedly() {
+        pyo3::prepare_freethreaded_python();
+
+        Python::with_gil(|py| {
+            let items = PyList::new(py, [["a", "b"], ["c", "d"]]).unwrap();
+            let context = HashMap::from([("items".to_string(), items.into_any().unbind())]);
+            let mut context = Context::new(context, None, false);
+            let template = TemplateString("{{ items.1.0 }}");
+            // The parts are split once here and reused by every render below
+            let variable = Variable::new((3, 9), template);
+            assert_eq!(
+                variable.parts(template).collect::<Vec<_>>(),
+                vec![("items", (3, 5)), ("1", (9, 1)), ("0", (11, 1))]
+            );
+
+            for _ in 0..3 {
+                let rendered = variable.render(py, template, &mut context).unwrap();
+                assert_eq!(rendered, "c");
+            }
+        })
+    }
+
+    #[test]
+    fn test_render_flat_dotted_key() {
+        pyo3::prepare_freethreaded_python();
+
+        Python::with_gil(|py| {
+            let name = PyString::new(py, "Lily");
+            let context = HashMap::from([("data.name".to_string(), name.into_any().unbind())]);
+            let mut context = Context::new(context, None, false);
+            let template = TemplateString("{{ data.name }}");
+            let variable = Variable::new((3, 9), template);
+
+            let resolved = variable
+                .resolve(py, template, &mut context, ResolveFailures::Raise)
+                .unwrap();
+            assert!(resolved.is_none());
+            let rendered = variable.render(py, template, &mut context).unwrap();
+            assert_eq!(rendered, "");
+        })
+    }
+
     #[test]
     fn test_render_list_lookup() {
         pyo3::prepare_freethreaded_python();
@@ -253,19 +472,74 @@ mod tests {
             let name = PyString::new(py, "Lily");
             let names = PyList::new(py, [name]).unwrap();
             let context = HashMap::from([("names".to_string(), names.into_any().unbind())]);
-            let mut context = Context {
-                context,
-                request: None,
-                autoescape: false,
-            };
+            let mut context = Context::new(context, None, false);
             let template = TemplateString("{{ names.0 }}");
-            let variable = Variable::new((3, 7));
+            let variable = Variable::new((3, 7), template);
 
             let rendered = variable.render(py, template, &mut context).unwrap();
             assert_eq!(rendered, "Lily");
         })
     }
 
+    #[test]
+    fn test_render_text_not_escaped() {
+        pyo3::prepare_freethreaded_python();
+
+        Python::with_gil(|py| {
+            let name = PyString::new(py, "<b>Lily</b>").into_any();
+            let context = HashMap::from([("name".to_string(), name.unbind())]);
+            let mut context = Context::new(context, None, true);
+            let template = TemplateString("<div>{{ name }}</div>");
+            let nodes = vec![
+                TokenTree::Text(Text::new((0, 5))),
+                TokenTree::Variable(Variable::new((8, 4), template)),
+                TokenTree::Text(Text::new((15, 6))),
+            ];
+
+            let rendered = nodes.render(py, template, &mut context).unwrap();
+            assert_eq!(rendered, "<div>&lt;b&gt;Lily&lt;/b&gt;</div>");
+        })
+    }
+
+    #[test]
+    fn test_render_translated_text() {
+        pyo3::prepare_freethreaded_python();
+
+        Python::with_gil(|py| {
+            let mut context = Context::new(HashMap::new(), None, false);
+            let template = TemplateString("{{ _('Hello') }}{{ _('Goodbye') }}");
+            let nodes = vec![
+                TokenTree::TranslatedText(Text::new((6, 5))),
+                TokenTree::TranslatedText(Text::new((22, 7))),
+            ];
+
+            let mut rendered = None;
+            with_mock_translation(py, || {
+                rendered = Some(nodes.render(py, template, &mut context));
+            });
+            assert_eq!(rendered.unwrap().unwrap(), "BonjourGoodbye");
+        })
+    }
+
+    #[test]
+    fn test_render_list_index_out_of_range() {
+        pyo3::prepare_freethreaded_python();
+
+        Python::with_gil(|py| {
+            let items = PyList::new(py, ["a", "b"]).unwrap().into_any();
+            let context = HashMap::from([("items".to_string(), items.unbind())]);
+            let mut context = Context::new(context, None, false);
+            let template = TemplateString("{{ items.1 }}{{ items.5 }}");
+            let nodes = vec![
+                TokenTree::Variable(Variable::new((3, 7), template)),
+                TokenTree::Variable(Variable::new((16, 7), template)),
+            ];
+
+            let rendered = nodes.render(py, template, &mut context).unwrap();
+            assert_eq!(rendered, "b");
+        })
+    }
+
     #[test]
     fn test_render_attribute_lookup() {
         pyo3::prepare_freethreaded_python();
@@ -286,19 +560,179 @@ user = User('Lily')
             .unwrap();
 
             let context = locals.extract().unwrap();
-            let mut context = Context {
-                context,
-                request: None,
-                autoescape: false,
-            };
+            let mut context = Context::new(context, None, false);
             let template = TemplateString("{{ user.name }}");
-            let variable = Variable::new((3, 9));
+            let variable = Variable::new((3, 9), template);
 
             let rendered = variable.render(py, template, &mut context).unwrap();
             assert_eq!(rendered, "Lily");
         })
     }
 
+    #[test]
+    fn test_render_dict_key_shadows_method() {
+        pyo3::prepare_freethreaded_python();
+
+        Python::with_gil(|py| {
+            let locals = PyDict::new(py);
+            py.run(
+                c"
+shadowed = {'items': 'shadowed'}
+plain = {'a': 1}
+",
+                None,
+                Some(&locals),
+            )
+            .unwrap();
+
+            let context = locals.extract().unwrap();
+            let mut context = Context::new(context, None, false);
+            let template = TemplateString("{{ shadowed.items }}{{ plain.items }}");
+
+            let variable = Variable::new((3, 14), template);
+            let rendered = variable.render(py, template, &mut context).unwrap();
+            assert_eq!(rendered, "shadowed");
+
+            let variable = Variable::new((23, 11), template);
+            let rendered = variable.render(py, template, &mut context).unwrap();
+            assert_eq!(rendered, "dict_items([('a', 1)])");
+        })
+    }
+
+    #[test]
+    fn test_render_lookup_order() {
+        pyo3::prepare_freethreaded_python();
+
+        Python::with_gil(|py| {
+            let locals = PyDict::new(py);
+            py.run(
+                c"
+class Lookup:
+    name = 'attribute'
+
+    def __getitem__(self, key):
+        if key == 'name':
+            return 'key'
+        if isinstance(key, int):
+            return 'index'
+        raise KeyError(key)
+
+both = Lookup()
+indexed = Lookup()
+setattr(indexed, '0', 'attribute')
+",
+                None,
+                Some(&locals),
+            )
+            .unwrap();
+
+            let context = locals.extract().unwrap();
+            let mut context = Context::new(context, None, false);
+            let template = TemplateString("{{ both.name }}{{ both.0 }}{{ indexed.0 }}");
+
+            let variable = Variable::new((3, 9), template);
+            let rendered = variable.render(py, template, &mut context).unwrap();
+            assert_eq!(rendered, "key");
+
+            let variable = Variable::new((18, 6), template);
+            let rendered = variable.render(py, template, &mut context).unwrap();
+            assert_eq!(rendered, "index");
+
+            let variable = Variable::new((30, 9), template);
+            let rendered = variable.render(py, template, &mut context).unwrap();
+            assert_eq!(rendered, "attribute");
+        })
+    }
+
+    #[test]
+    fn test_render_top_level_callable() {
+        pyo3::prepare_freethreaded_python();
+
+        Python::with_gil(|py| {
+            let locals = PyDict::new(py);
+            py.run(
+                c"
+def get_greeting():
+    return 'Hello'
+
+def greet(name):
+    return f'Hello {name}'
+",
+                None,
+                Some(&locals),
+            )
+            .unwrap();
+
+            let context = locals.extract().unwrap();
+            let mut context = Context::new(context, None, false);
+            let template = TemplateString("{{ get_greeting }}{{ greet }}");
+
+            let variable = Variable::new((3, 12), template);
+            let rendered = variable.render(py, template, &mut context).unwrap();
+            assert_eq!(rendered, "Hello");
+
+            let variable = Variable::new((21, 5), template);
+            let rendered = variable.render(py, template, &mut context).unwrap();
+            assert_eq!(rendered, "");
+        })
+    }
+
+    #[test]
+    fn test_render_callable_flags() {
+        pyo3::prepare_freethreaded_python();
+
+        Python::with_gil(|py| {
+            let locals = PyDict::new(py);
+            py.run(
+                c"
+class User:
+    deleted = False
+
+    def get_full_name(self):
+        return 'Lily Potter'
+
+    def delete(self):
+        self.deleted = True
+        return 'Deleted'
+
+    delete.alters_data = True
+
+class Choices:
+    do_not_call_in_templates = True
+    label = 'Choice'
+
+    def __init__(self, value=None):
+        pass
+
+user = User()
+choices = Choices
+",
+                None,
+                Some(&locals),
+            )
+            .unwrap();
+
+            let user = locals.get_item("user").unwrap().unwrap();
+            let context = locals.extract().unwrap();
+            let mut context = Context::new(context, None, false);
+            let template =
+                TemplateString("{{ user.get_full_name }}{{ user.delete }}{{ choices.label }}");
+
+            let variable = Variable::new((3, 18), template);
+            let rendered = variable.render(py, template, &mut context).unwrap();
+            assert_eq!(rendered, "Lily Potter");
+
+            let variable = Variable::new((27, 11), template);
+            let rendered = variable.render(py, template, &mut context).unwrap();
+            assert_eq!(rendered, "");
+            assert!(!user.getattr("deleted").unwrap().is_truthy().unwrap());
+
+            let variable = Variable::new((44, 13), template);
+            let rendered = variable.render(py, template, &mut context).unwrap();
+            assert_eq!(rendered, "Choice");
+        })
+    }
+
     #[test]
     fn test_render_html_autoescape() {
         pyo3::prepare_freethreaded_python();
@@ -306,13 +740,9 @@ user = User('Lily')
         Python::with_gil(|py| {
             let html = PyString::new(py, "<p>Hello World!</p>").into_any().unbind();
             let context = HashMap::from([("html".to_string(), html)]);
-            let mut context = Context {
-                context,
-                request: None,
-                autoescape: true,
-            };
+            let mut context = Context::new(context, None, true);
             let template = TemplateString("{{ html }}");
-            let html = Variable::new((3, 4));
+            let html = Variable::new((3, 4), template);
 
             let rendered = html.render(py, template, &mut context).unwrap();
             assert_eq!(rendered, "&lt;p&gt;Hello World!&lt;/p&gt;");
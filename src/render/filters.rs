@@ -1,21 +1,34 @@
 use std::borrow::Cow;
 use std::sync::LazyLock;
 
-use html_escape::encode_quoted_attribute_to_string;
+use html_escape::{
+    decode_html_entities, encode_quoted_attribute, encode_quoted_attribute_to_string,
+};
+use num_bigint::{BigInt, Sign};
+use num_traits::{ToPrimitive, Zero};
+use pyo3::exceptions::{PyAttributeError, PyIndexError, PyTypeError, PyValueError};
+use pyo3::intern;
 use pyo3::prelude::*;
 use pyo3::sync::GILOnceCell;
-use pyo3::types::PyType;
+use pyo3::types::{PyDict, PyInt, PyList, PyString, PyTuple, PyType};
 
+use crate::error::PyRenderError;
 use crate::filters::{
-    AddFilter, AddSlashesFilter, CapfirstFilter, DefaultFilter, EscapeFilter, ExternalFilter,
-    FilterType, LowerFilter, SafeFilter, SlugifyFilter, UpperFilter,
+    AddFilter, AddSlashesFilter, CapfirstFilter, DateFilter, DefaultFilter, DictsortFilter,
+    DictsortreversedFilter, DivisiblebyFilter, EscapeFilter, ExternalFilter, FilterType,
+    FirstFilter, FloatformatFilter, JoinFilter, LastFilter, LengthFilter, LowerFilter, SafeFilter,
+    SafeseqFilter, SlugifyFilter, StringformatFilter, TimeFilter, TruncatecharsFilter,
+    TruncatecharsHtmlFilter, TruncatewordsFilter, TruncatewordsHtmlFilter, UpperFilter,
+    WordcountFilter, YesnoFilter,
 };
 use crate::parse::Filter;
-use crate::render::types::{Content, ContentString, Context};
-use crate::render::{Resolve, ResolveFailures, ResolveResult};
-use crate::types::TemplateString;
+use crate::render::types::{Content, ContentString, Context, bool_repr, float_repr};
+use crate::render::{Evaluate, Resolve, ResolveFailures, ResolveResult};
+use crate::types::{Argument, TemplateString};
+use crate::utils::{PyResultMethods, getattr_flag};
 use regex::Regex;
 use unicode_normalization::UnicodeNormalization;
+use unicode_normalization::char::canonical_combining_class;
 
 // Used for replacing all non-word and non-spaces with an empty string
 static NON_WORD_RE: LazyLock<Regex> =
@@ -62,19 +75,7 @@ impl Resolve for Filter {
         failures: ResolveFailures,
     ) -> ResolveResult<'t, 'py> {
         let left = self.left.resolve(py, template, context, failures)?;
-        let result = match &self.filter {
-            FilterType::Add(filter) => filter.resolve(left, py, template, context),
-            FilterType::AddSlashes(filter) => filter.resolve(left, py, template, context),
-            FilterType::Capfirst(filter) => filter.resolve(left, py, template, context),
-            FilterType::Default(filter) => filter.resolve(left, py, template, context),
-            FilterType::Escape(filter) => filter.resolve(left, py, template, context),
-            FilterType::External(filter) => filter.resolve(left, py, template, context),
-            FilterType::Lower(filter) => filter.resolve(left, py, template, context),
-            FilterType::Safe(filter) => filter.resolve(left, py, template, context),
-            FilterType::Slugify(filter) => filter.resolve(left, py, template, context),
-            FilterType::Upper(filter) => filter.resolve(left, py, template, context),
-        };
-        result
+        self.filter.resolve(left, py, template, context)
     }
 }
 
@@ -88,6 +89,47 @@ pub trait ResolveFilter {
     ) -> ResolveResult<'t, 'py>;
 }
 
+impl ResolveFilter for FilterType {
+    fn resolve<'t, 'py>(
+        &self,
+        variable: Option<Content<'t, 'py>>,
+        py: Python<'py>,
+        template: TemplateString<'t>,
+        context: &mut Context,
+    ) -> ResolveResult<'t, 'py> {
+        match self {
+            Self::Add(filter) => filter.resolve(variable, py, template, context),
+            Self::AddSlashes(filter) => filter.resolve(variable, py, template, context),
+            Self::Capfirst(filter) => filter.resolve(variable, py, template, context),
+            Self::Date(filter) => filter.resolve(variable, py, template, context),
+            Self::Default(filter) => filter.resolve(variable, py, template, context),
+            Self::Dictsort(filter) => filter.resolve(variable, py, template, context),
+            Self::Dictsortreversed(filter) => filter.resolve(variable, py, template, context),
+            Self::Divisibleby(filter) => filter.resolve(variable, py, template, context),
+            Self::Escape(filter) => filter.resolve(variable, py, template, context),
+            Self::External(filter) => filter.resolve(variable, py, template, context),
+            Self::First(filter) => filter.resolve(variable, py, template, context),
+            Self::Floatformat(filter) => filter.resolve(variable, py, template, context),
+            Self::Join(filter) => filter.resolve(variable, py, template, context),
+            Self::Last(filter) => filter.resolve(variable, py, template, context),
+            Self::Length(filter) => filter.resolve(variable, py, template, context),
+            Self::Lower(filter) => filter.resolve(variable, py, template, context),
+            Self::Safe(filter) => filter.resolve(variable, py, template, context),
+            Self::Safeseq(filter) => filter.resolve(variable, py, template, context),
+            Self::Slugify(filter) => filter.resolve(variable, py, template, context),
+            Self::Stringformat(filter) => filter.resolve(variable, py, template, context),
+            Self::Time(filter) => filter.resolve(variable, py, template, context),
+            Self::Truncatechars(filter) => filter.resolve(variable, py, template, context),
+            Self::TruncatecharsHtml(filter) => filter.resolve(variable, py, template, context),
+            Self::Truncatewords(filter) => filter.resolve(variable, py, template, context),
+            Self::TruncatewordsHtml(filter) => filter.resolve(variable, py, template, context),
+            Self::Upper(filter) => filter.resolve(variable, py, template, context),
+            Self::Wordcount(filter) => filter.resolve(variable, py, template, context),
+            Self::Yesno(filter) => filter.resolve(variable, py, template, context),
+        }
+    }
+}
+
 impl ResolveFilter for AddSlashesFilter {
     fn resolve<'t, 'py>(
         &self,
@@ -128,6 +170,8 @@ impl ResolveFilter for AddFilter {
         match (variable.to_bigint(), right.to_bigint()) {
             (Some(variable), Some(right)) => Ok(Some(Content::Int(variable + right))),
             _ => {
+                // Like Django, fall back to Python's `+` so any object
+                // defining `__add__` can be added.
                 let variable = variable.to_py(py)?;
                 let right = right.to_py(py)?;
                 match variable.add(right) {
@@ -182,11 +226,250 @@ impl ResolveFilter for DefaultFilter {
     }
 }
 
+/// How `dictsort` finds the sort key of each item, like Django's
+/// `_property_resolver`.
+enum SortKey<'py> {
+    /// A number is used directly as an index or key.
+    Item(Bound<'py, PyAny>),
+    /// Anything else is split into a path of keys or attributes.
+    Path(Vec<String>),
+}
+
+impl<'py> SortKey<'py> {
+    fn new(argument: Content<'_, 'py>, py: Python<'py>) -> PyResult<Self> {
+        let argument = argument.to_py(py)?;
+        if argument.is_instance_of::<PyString>() {
+            let path = argument.extract::<String>()?;
+            if path.parse::<f64>().is_err() {
+                if path.starts_with('_') || path.contains("._") {
+                    return Err(PyAttributeError::new_err(
+                        "Access to private variables is forbidden.",
+                    ));
+                }
+                return Ok(Self::Path(path.split('.').map(String::from).collect()));
+            }
+        }
+        Ok(Self::Item(argument))
+    }
+
+    fn key(&self, item: Bound<'py, PyAny>) -> PyResult<Bound<'py, PyAny>> {
+        match self {
+            Self::Item(key) => item.get_item(key),
+            Self::Path(path) => {
+                let mut value = item;
+                for part in path {
+                    value = match value.get_item(part) {
+                        Ok(inner) => inner,
+                        Err(_) => value.getattr(part.as_str())?,
+                    };
+                }
+                Ok(value)
+            }
+        }
+    }
+}
+
+/// Sort `value` by the key described by `argument`, in descending order if
+/// `reverse` is set. Like Python's `sorted`, items with equal keys keep their
+/// order either way.
+fn dictsort<'py>(
+    value: Bound<'py, PyAny>,
+    argument: Content<'_, 'py>,
+    reverse: bool,
+) -> PyResult<Bound<'py, PyAny>> {
+    let py = value.py();
+    let sort_key = SortKey::new(argument, py)?;
+    let mut keyed = Vec::new();
+    for item in value.try_iter()? {
+        let item = item?;
+        keyed.push((sort_key.key(item.clone())?, item));
+    }
+    let mut error = None;
+    keyed.sort_by(|(a, _), (b, _)| {
+        let (a, b) = match reverse {
+            true => (b, a),
+            false => (a, b),
+        };
+        let ordering = match a.lt(b) {
+            Ok(true) => Ok(std::cmp::Ordering::Less),
+            Ok(false) => b.lt(a).map(|greater| match greater {
+                true => std::cmp::Ordering::Greater,
+                false => std::cmp::Ordering::Equal,
+            }),
+            Err(e) => Err(e),
+        };
+        ordering.unwrap_or_else(|e| {
+            error.get_or_insert(e);
+            std::cmp::Ordering::Equal
+        })
+    });
+    if let Some(error) = error {
+        return Err(error);
+    }
+    let sorted = PyList::new(py, keyed.into_iter().map(|(_, item)| item))?;
+    Ok(sorted.into_any())
+}
+
+/// Format a date or time using Django's `date` or `time` filter, which
+/// handle named formats like `"SHORT_DATE_FORMAT"` and localization.
+fn format_date<'t, 'py>(
+    filter: &Bound<'py, PyString>,
+    argument: &Option<Argument>,
+    variable: Option<Content<'t, 'py>>,
+    py: Python<'py>,
+    template: TemplateString<'t>,
+    context: &mut Context,
+) -> ResolveResult<'t, 'py> {
+    let argument = match argument {
+        Some(argument) => argument
+            .resolve(py, template, context, ResolveFailures::Raise)?
+            .expect("missing argument in context should already have raised")
+            .to_py(py)?,
+        None => py.None().into_bound(py),
+    };
+    // Like Django, `None` and empty values format as an empty string
+    let value = match variable {
+        None => return Ok("".as_content()),
+        Some(content) => content.to_py(py)?,
+    };
+    if value.is_none() || value.eq(intern!(py, ""))? {
+        return Ok("".as_content());
+    }
+    // Django converts aware datetimes to the current timezone first
+    let value = py
+        .import(intern!(py, "django.utils.timezone"))?
+        .getattr(intern!(py, "template_localtime"))?
+        .call1((value,))?;
+    let filter = py
+        .import(intern!(py, "django.template.defaultfilters"))?
+        .getattr(filter)?;
+    Ok(Some(Content::Py(filter.call1((value, argument))?)))
+}
+
+impl ResolveFilter for DateFilter {
+    fn resolve<'t, 'py>(
+        &self,
+        variable: Option<Content<'t, 'py>>,
+        py: Python<'py>,
+        template: TemplateString<'t>,
+        context: &mut Context,
+    ) -> ResolveResult<'t, 'py> {
+        let filter = intern!(py, "date");
+        format_date(filter, &self.argument, variable, py, template, context)
+    }
+}
+
+impl ResolveFilter for TimeFilter {
+    fn resolve<'t, 'py>(
+        &self,
+        variable: Option<Content<'t, 'py>>,
+        py: Python<'py>,
+        template: TemplateString<'t>,
+        context: &mut Context,
+    ) -> ResolveResult<'t, 'py> {
+        let filter = intern!(py, "time");
+        format_date(filter, &self.argument, variable, py, template, context)
+    }
+}
+
+/// Shared by `dictsort` and `dictsortreversed`. Django returns an empty
+/// string when sorting fails with an `AttributeError` or a `TypeError`.
+fn resolve_dictsort<'t, 'py>(
+    argument: &Argument,
+    reverse: bool,
+    variable: Option<Content<'t, 'py>>,
+    py: Python<'py>,
+    template: TemplateString<'t>,
+    context: &mut Context,
+) -> ResolveResult<'t, 'py> {
+    // Django sorts `string_if_invalid` when the variable is missing
+    let value = match variable {
+        Some(content) => content.to_py(py)?,
+        None => PyString::new(py, "").into_any(),
+    };
+    let argument = argument
+        .resolve(py, template, context, ResolveFailures::Raise)?
+        .expect("missing argument in context should already have raised");
+    match dictsort(value, argument, reverse) {
+        Ok(sorted) => Ok(Some(Content::Py(sorted))),
+        Err(error)
+            if error.is_instance_of::<PyAttributeError>(py)
+                || error.is_instance_of::<PyTypeError>(py) =>
+        {
+            Ok("".as_content())
+        }
+        Err(error) => Err(error.into()),
+    }
+}
+
+impl ResolveFilter for DictsortFilter {
+    fn resolve<'t, 'py>(
+        &self,
+        variable: Option<Content<'t, 'py>>,
+        py: Python<'py>,
+        template: TemplateString<'t>,
+        context: &mut Context,
+    ) -> ResolveResult<'t, 'py> {
+        resolve_dictsort(&self.argument, false, variable, py, template, context)
+    }
+}
+
+impl ResolveFilter for DictsortreversedFilter {
+    fn resolve<'t, 'py>(
+        &self,
+        variable: Option<Content<'t, 'py>>,
+        py: Python<'py>,
+        template: TemplateString<'t>,
+        context: &mut Context,
+    ) -> ResolveResult<'t, 'py> {
+        resolve_dictsort(&self.argument, true, variable, py, template, context)
+    }
+}
+
+/// Convert to an integer like Python's `int`, raising its error on failure.
+fn to_int(content: Content<'_, '_>, py: Python<'_>) -> PyResult<BigInt> {
+    match content.to_bigint() {
+        Some(int) => Ok(int),
+        None => py
+            .get_type::<PyInt>()
+            .call1((content.to_py(py)?,))?
+            .extract::<BigInt>(),
+    }
+}
+
+impl ResolveFilter for DivisiblebyFilter {
+    fn resolve<'t, 'py>(
+        &self,
+        variable: Option<Content<'t, 'py>>,
+        py: Python<'py>,
+        template: TemplateString<'t>,
+        context: &mut Context,
+    ) -> ResolveResult<'t, 'py> {
+        // Django's `int(value)` fails for a missing value, like `int("")`
+        let variable =
+            variable.unwrap_or(Content::String(ContentString::String(Cow::Borrowed(""))));
+        let argument = self
+            .argument
+            .resolve(py, template, context, ResolveFailures::Raise)?
+            .expect("missing argument in context should already have raised");
+        let variable = to_int(variable, py)?;
+        let argument = to_int(argument, py)?;
+        if argument.is_zero() {
+            // Let Python raise its `ZeroDivisionError`
+            (&variable).into_pyobject(py)?.rem(&argument)?;
+        }
+        let divisible = (variable % argument).is_zero();
+        Ok(Some(Content::Bool(divisible)))
+    }
+}
+
 impl ResolveFilter for EscapeFilter {
+    /// Escape regardless of autoescaping, like Django's `conditional_escape`,
+    /// so content that's already safe isn't escaped twice.
     fn resolve<'t, 'py>(
         &self,
         variable: Option<Content<'t, 'py>>,
-        _py: Python<'py>,
+        py: Python<'py>,
         _template: TemplateString<'t>,
         _context: &mut Context,
     ) -> ResolveResult<'t, 'py> {
@@ -200,13 +483,20 @@ impl ResolveFilter for EscapeFilter {
                         Cow::Owned(encoded)
                     }
                     Content::Int(n) => Cow::Owned(n.to_string()),
-                    Content::Float(n) => Cow::Owned(n.to_string()),
-                    Content::Py(object) => {
-                        let content = object.str()?.extract::<String>()?;
-                        let mut encoded = String::new();
-                        encode_quoted_attribute_to_string(&content, &mut encoded);
-                        Cow::Owned(encoded)
-                    }
+                    Content::Float(n) => Cow::Owned(float_repr(n)),
+                    Content::Bool(b) => Cow::Borrowed(bool_repr(b)),
+                    Content::Py(object) => match object
+                        .getattr(intern!(py, "__html__"))
+                        .ok_or_isinstance_of::<PyAttributeError>(py)?
+                    {
+                        Ok(html) => Cow::Owned(html.call0()?.extract::<String>()?),
+                        Err(_) => {
+                            let content = object.str()?.extract::<String>()?;
+                            let mut encoded = String::new();
+                            encode_quoted_attribute_to_string(&content, &mut encoded);
+                            Cow::Owned(encoded)
+                        }
+                    },
                 },
                 None => Cow::Borrowed(""),
             },
@@ -227,11 +517,196 @@ impl ResolveFilter for ExternalFilter {
             None => None,
         };
         let filter = self.filter.bind(py);
-        let value = match arg {
-            Some(arg) => filter.call1((variable, arg))?,
-            None => filter.call1((variable,))?,
+        let value = match variable {
+            Some(variable) => variable.to_py(py)?,
+            None => py.None().into_bound(py),
+        };
+        let args = match arg {
+            Some(arg) => PyTuple::new(py, [value.clone(), arg.to_py(py)?])?,
+            None => PyTuple::new(py, [value.clone()])?,
+        };
+        // Like Django, pass `autoescape` to filters which ask for it
+        let kwargs = match getattr_flag(filter, intern!(py, "needs_autoescape"))? {
+            true => {
+                let kwargs = PyDict::new(py);
+                kwargs.set_item(intern!(py, "autoescape"), context.autoescape)?;
+                Some(kwargs)
+            }
+            false => None,
+        };
+        let filtered = filter.call(args, kwargs.as_ref())?;
+        #[allow(non_snake_case)]
+        let SafeData = SAFEDATA.import(py, "django.utils.safestring", "SafeData")?;
+        // An `is_safe` filter keeps safe input safe
+        if getattr_flag(filter, intern!(py, "is_safe"))? && value.is_instance(SafeData)? {
+            let filtered = filtered.str()?.extract::<String>()?;
+            return Ok(Some(Content::String(ContentString::HtmlSafe(Cow::Owned(
+                filtered,
+            )))));
+        }
+        Ok(Some(Content::Py(filtered)))
+    }
+}
+
+/// Shared implementation of the `first` and `last` filters. Django returns
+/// an empty string for an empty sequence.
+fn resolve_index<'t, 'py>(
+    variable: Option<Content<'t, 'py>>,
+    py: Python<'py>,
+    context: &Context,
+    index: isize,
+) -> ResolveResult<'t, 'py> {
+    let content = match variable {
+        Some(Content::String(content)) => {
+            let content = content.as_raw();
+            let c = match index {
+                0 => content.chars().next(),
+                _ => content.chars().next_back(),
+            };
+            match c {
+                Some(c) => {
+                    let c = Cow::Owned(c.to_string());
+                    Some(Content::String(match context.autoescape {
+                        true => ContentString::HtmlUnsafe(c),
+                        false => ContentString::String(c),
+                    }))
+                }
+                None => "".as_content(),
+            }
+        }
+        Some(content) => match content.to_py(py)?.get_item(index) {
+            Ok(item) => Some(Content::Py(item)),
+            Err(e) if e.is_instance_of::<PyIndexError>(py) => "".as_content(),
+            Err(e) => return Err(e.into()),
+        },
+        None => "".as_content(),
+    };
+    Ok(content)
+}
+
+impl ResolveFilter for FirstFilter {
+    fn resolve<'t, 'py>(
+        &self,
+        variable: Option<Content<'t, 'py>>,
+        py: Python<'py>,
+        _template: TemplateString<'t>,
+        context: &mut Context,
+    ) -> ResolveResult<'t, 'py> {
+        resolve_index(variable, py, context, 0)
+    }
+}
+
+impl ResolveFilter for FloatformatFilter {
+    fn resolve<'t, 'py>(
+        &self,
+        variable: Option<Content<'t, 'py>>,
+        py: Python<'py>,
+        template: TemplateString<'t>,
+        context: &mut Context,
+    ) -> ResolveResult<'t, 'py> {
+        let value = match variable {
+            Some(content) => content.to_py(py)?,
+            None => PyString::new(py, "").into_any(),
+        };
+        let argument = match &self.argument {
+            Some(argument) => argument
+                .resolve(py, template, context, ResolveFailures::Raise)?
+                .expect("missing argument in context should already have raised")
+                .to_py(py)?,
+            None => Content::Int((-1).into()).to_py(py)?,
+        };
+        // Django rounds with `Decimal` and formats the result for the active
+        // locale, so we defer to it to match exactly.
+        let floatformat = py
+            .import(intern!(py, "django.template.defaultfilters"))?
+            .getattr(intern!(py, "floatformat"))?;
+        Ok(Some(Content::Py(floatformat.call1((value, argument))?)))
+    }
+}
+
+impl ResolveFilter for JoinFilter {
+    fn resolve<'t, 'py>(
+        &self,
+        variable: Option<Content<'t, 'py>>,
+        py: Python<'py>,
+        template: TemplateString<'t>,
+        context: &mut Context,
+    ) -> ResolveResult<'t, 'py> {
+        let Some(variable) = variable else {
+            return Ok("".as_content());
+        };
+        let separator = self
+            .argument
+            .resolve(py, template, context, ResolveFailures::Raise)?
+            .expect("missing argument in context should already have raised")
+            .render(context)?;
+        let items = match variable.to_py(py)?.try_iter() {
+            Ok(items) => items,
+            Err(e) if e.is_instance_of::<PyTypeError>(py) => return Ok(Some(variable)),
+            Err(e) => return Err(e.into()),
+        };
+        let mut joined = Vec::new();
+        for item in items {
+            let item = item?;
+            // Without autoescaping Django uses `str.join`, which rejects
+            // anything that isn't a string.
+            if !context.autoescape && !item.is_instance_of::<PyString>() {
+                return Ok(Some(variable));
+            }
+            joined.push(Content::Py(item).render(context)?);
+        }
+        let joined = Cow::Owned(joined.join(separator.as_ref()));
+        Ok(Some(Content::String(ContentString::HtmlSafe(joined))))
+    }
+}
+
+impl ResolveFilter for LastFilter {
+    fn resolve<'t, 'py>(
+        &self,
+        variable: Option<Content<'t, 'py>>,
+        py: Python<'py>,
+        _template: TemplateString<'t>,
+        context: &mut Context,
+    ) -> ResolveResult<'t, 'py> {
+        resolve_index(variable, py, context, -1)
+    }
+}
+
+impl ResolveFilter for LengthFilter {
+    fn resolve<'t, 'py>(
+        &self,
+        variable: Option<Content<'t, 'py>>,
+        py: Python<'py>,
+        _template: TemplateString<'t>,
+        _context: &mut Context,
+    ) -> ResolveResult<'t, 'py> {
+        let length = match variable {
+            Some(content) => match content.to_py(py)?.len() {
+                Ok(length) => length,
+                Err(e) if e.is_instance_of::<PyTypeError>(py) => 0,
+                Err(e) if e.is_instance_of::<PyValueError>(py) => 0,
+                Err(e) => return Err(e.into()),
+            },
+            None => 0,
         };
-        Ok(Some(Content::Py(value)))
+        Ok(Some(Content::Int(length.into())))
+    }
+}
+
+/// Change the case of `content`, borrowing it unchanged when no character
+/// would be changed.
+fn convert_case<'t, I: Iterator<Item = char>>(
+    content: Cow<'t, str>,
+    convert_char: impl Fn(char) -> I,
+    convert: impl Fn(&str) -> String,
+) -> Cow<'t, str> {
+    let unchanged = content.chars().all(|c| {
+        let mut converted = convert_char(c);
+        converted.next() == Some(c) && converted.next().is_none()
+    });
+    match unchanged {
+        true => content,
+        false => Cow::Owned(convert(&content)),
     }
 }
 
@@ -244,11 +719,9 @@ impl ResolveFilter for LowerFilter {
         context: &mut Context,
     ) -> ResolveResult<'t, 'py> {
         let content = match variable {
-            Some(content) => Some(
-                content
-                    .resolve_string(context)?
-                    .map_content(|content| Cow::Owned(content.to_lowercase())),
-            ),
+            Some(content) => Some(content.resolve_string(context)?.map_content(|content| {
+                convert_case(content, char::to_lowercase, str::to_lowercase)
+            })),
             None => "".as_content(),
         };
         Ok(content)
@@ -269,6 +742,7 @@ impl ResolveFilter for SafeFilter {
                     Content::String(content) => content.into_raw(),
                     Content::Int(n) => Cow::Owned(n.to_string()),
                     Content::Float(n) => Cow::Owned(n.to_string()),
+                    Content::Bool(b) => Cow::Borrowed(bool_repr(b)),
                     Content::Py(object) => {
                         let content = object.str()?.extract::<String>()?;
                         Cow::Owned(content)
@@ -280,6 +754,29 @@ impl ResolveFilter for SafeFilter {
     }
 }
 
+impl ResolveFilter for SafeseqFilter {
+    /// Mark each item as safe, so a later `join` doesn't escape them.
+    fn resolve<'t, 'py>(
+        &self,
+        variable: Option<Content<'t, 'py>>,
+        py: Python<'py>,
+        _template: TemplateString<'t>,
+        _context: &mut Context,
+    ) -> ResolveResult<'t, 'py> {
+        let items = PyList::empty(py);
+        let Some(variable) = variable else {
+            return Ok(Some(Content::Py(items.into_any())));
+        };
+        let mark_safe = py
+            .import(intern!(py, "django.utils.safestring"))?
+            .getattr(intern!(py, "mark_safe"))?;
+        for item in variable.to_py(py)?.try_iter()? {
+            items.append(mark_safe.call1((item?,))?)?;
+        }
+        Ok(Some(Content::Py(items.into_any())))
+    }
+}
+
 fn slugify(content: Cow<str>) -> Cow<str> {
     let content = content
         .nfkd()
@@ -294,15 +791,39 @@ fn slugify(content: Cow<str>) -> Cow<str> {
     Cow::Owned(content.to_string())
 }
 
+/// Slugify using Django's `allow_unicode=True` mode, which keeps non-ASCII
+/// letters instead of dropping them.
+fn slugify_unicode<'t, 'py>(py: Python<'py>, content: Content<'t, 'py>) -> ResolveResult<'t, 'py> {
+    let content = content.to_py(py)?;
+    let kwargs = PyDict::new(py);
+    kwargs.set_item(intern!(py, "allow_unicode"), true)?;
+    let slug = py
+        .import(intern!(py, "django.utils.text"))?
+        .getattr(intern!(py, "slugify"))?
+        .call((content.str()?,), Some(&kwargs))?;
+    let slug = Cow::Owned(slug.extract::<String>()?);
+    #[allow(non_snake_case)]
+    let SafeData = SAFEDATA.import(py, "django.utils.safestring", "SafeData")?;
+    match content.is_instance(SafeData)? {
+        true => Ok(Some(Content::String(ContentString::HtmlSafe(slug)))),
+        false => Ok(Some(Content::String(ContentString::HtmlUnsafe(slug)))),
+    }
+}
+
 impl ResolveFilter for SlugifyFilter {
     fn resolve<'t, 'py>(
         &self,
         variable: Option<Content<'t, 'py>>,
         py: Python<'py>,
         _template: TemplateString<'t>,
-        _context: &mut Context,
+        context: &mut Context,
     ) -> ResolveResult<'t, 'py> {
+        let allow_unicode = context
+            .engine
+            .as_ref()
+            .is_some_and(|engine| engine.slugify_allow_unicode);
         let content = match variable {
+            Some(content) if allow_unicode => return slugify_unicode(py, content),
             Some(content) => match content {
                 Content::Py(content) => {
                     let slug = slugify(Cow::Owned(content.str()?.extract::<String>()?));
@@ -320,6 +841,9 @@ impl ResolveFilter for SlugifyFilter {
                 Content::Float(content) => Some(Content::String(ContentString::String(
                     Cow::Owned(content.to_string()),
                 ))),
+                Content::Bool(content) => Some(Content::String(ContentString::String(slugify(
+                    Cow::Borrowed(bool_repr(content)),
+                )))),
                 Content::String(content) => Some(content.map_content(slugify)),
             },
             None => "".as_content(),
@@ -328,75 +852,1192 @@ impl ResolveFilter for SlugifyFilter {
     }
 }
 
-impl ResolveFilter for UpperFilter {
-    fn resolve<'t, 'py>(
-        &self,
-        variable: Option<Content<'t, 'py>>,
-        _py: Python<'py>,
-        _template: TemplateString<'t>,
-        context: &mut Context,
-    ) -> ResolveResult<'t, 'py> {
-        let content = match variable {
-            Some(content) => {
-                let content = content.resolve_string(context)?;
-                Some(content.map_content(|content| Cow::Owned(content.to_uppercase())))
-            }
-            None => "".as_content(),
-        };
-        Ok(content)
+/// Resolve the length argument of the truncate filters, returning `None`
+/// when it cannot be converted to an integer.
+fn truncate_length<'t, 'py>(
+    argument: &Argument,
+    py: Python<'py>,
+    template: TemplateString<'t>,
+    context: &mut Context,
+) -> Result<Option<usize>, PyRenderError> {
+    let length = argument
+        .resolve(py, template, context, ResolveFailures::Raise)?
+        .expect("missing argument in context should already have raised");
+    Ok(length.to_bigint().map(|length| match length.sign() {
+        Sign::Minus => 0,
+        _ => length.to_usize().unwrap_or(usize::MAX),
+    }))
+}
+
+fn add_truncation_text(text: &str, truncate: &str) -> String {
+    if truncate.contains("%(truncated_text)s") {
+        truncate.replace("%(truncated_text)s", text)
+    } else if text.ends_with(truncate) {
+        text.to_string()
+    } else {
+        format!("{text}{truncate}")
     }
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use crate::filters::{AddSlashesFilter, DefaultFilter, LowerFilter, UpperFilter};
-    use crate::parse::TagElement;
-    use crate::render::Render;
-    use crate::template::django_rusty_templates::{EngineData, Template};
-    use crate::types::{Argument, ArgumentType, Text, Variable};
+fn is_combining(c: char) -> bool {
+    canonical_combining_class(c) != 0
+}
 
-    use pyo3::types::{PyDict, PyString};
-    static MARK_SAFE: GILOnceCell<Py<PyAny>> = GILOnceCell::new();
+/// The translated text marking where `truncatechars` cut the text.
+fn truncation_text(py: Python<'_>) -> PyResult<String> {
+    let translation = py.import("django.utils.translation")?;
+    translation
+        .getattr("pgettext")?
+        .call1((
+            "String to return when truncating text",
+            "%(truncated_text)s…",
+        ))?
+        .extract()
+}
 
-    fn mark_safe(py: Python<'_>, string: String) -> Result<Py<PyAny>, PyErr> {
-        let mark_safe = match MARK_SAFE.get(py) {
-            Some(mark_safe) => mark_safe,
-            None => {
-                let py_mark_safe = py.import("django.utils.safestring")?;
-                let py_mark_safe = py_mark_safe.getattr("mark_safe")?;
-                MARK_SAFE.set(py, py_mark_safe.into()).unwrap();
-                MARK_SAFE.get(py).unwrap()
+/// How many characters of the text fit in `length` alongside the
+/// `truncate` text.
+fn truncate_chars_length(length: usize, truncate: &str) -> usize {
+    let mut truncate_len = length;
+    for c in add_truncation_text("", truncate).chars() {
+        if !is_combining(c) {
+            truncate_len -= 1;
+            if truncate_len == 0 {
+                break;
             }
-        };
-        let safe_string = mark_safe.call1(py, (string,))?;
-        Ok(safe_string)
+        }
     }
+    truncate_len
+}
 
-    use std::collections::HashMap;
+fn truncate_chars(py: Python<'_>, text: &str, length: usize) -> PyResult<String> {
+    let text: String = text.nfc().collect();
+    if text.chars().filter(|c| !is_combining(*c)).count() <= length {
+        return Ok(text);
+    }
 
-    #[test]
-    fn test_render_filter() {
-        pyo3::prepare_freethreaded_python();
+    let truncate = truncation_text(py)?;
+    let truncate_len = truncate_chars_length(length, &truncate);
 
-        Python::with_gil(|py| {
-            let name = PyString::new(py, "Lily").into_any();
-            let context = HashMap::from([("name".to_string(), name.unbind())]);
-            let mut context = Context {
-                context,
-                request: None,
-                autoescape: false,
-            };
-            let template = TemplateString("{{ name|default:'Bryony' }}");
-            let variable = Variable::new((3, 4));
-            let filter = Filter {
-                at: (8, 7),
-                left: TagElement::Variable(variable),
-                filter: FilterType::Default(DefaultFilter::new(Argument {
-                    at: (16, 8),
-                    argument_type: ArgumentType::Text(Text::new((17, 6))),
-                })),
-            };
+    let mut s_len = 0;
+    let mut end_index = None;
+    for (index, c) in text.char_indices() {
+        if is_combining(c) {
+            continue;
+        }
+        s_len += 1;
+        if end_index.is_none() && s_len > truncate_len {
+            end_index = Some(index);
+        }
+        if s_len > length {
+            return Ok(add_truncation_text(
+                &text[..end_index.unwrap_or(0)],
+                &truncate,
+            ));
+        }
+    }
+    Ok(text)
+}
+
+/// Elements without an end tag, from Django's `VOID_ELEMENTS`.
+const VOID_ELEMENTS: [&str; 16] = [
+    "area", "base", "br", "col", "embed", "hr", "img", "input", "link", "meta", "param", "source",
+    "track", "wbr", "frame", "spacer",
+];
+
+enum HtmlToken {
+    StartTag {
+        len: usize,
+        name: String,
+        self_closing: bool,
+    },
+    EndTag {
+        len: usize,
+        name: String,
+    },
+    /// Comments and declarations, which Django drops from the output.
+    Ignored(usize),
+    /// A `<` that doesn't start a tag.
+    Text,
+    /// An unterminated tag, which Django drops with the rest of the text.
+    Incomplete,
+}
+
+/// Read the tag at the start of `html`, which starts with `<`, roughly
+/// following Python's `HTMLParser`.
+fn next_html_tag(html: &str) -> HtmlToken {
+    let inner = &html[1..];
+    match inner.chars().next() {
+        Some(c) if c.is_ascii_alphabetic() => {
+            let name_end = inner
+                .find(|c: char| c.is_whitespace() || c == '/' || c == '>')
+                .unwrap_or(inner.len());
+            let mut quote = None;
+            for (index, c) in inner[name_end..].char_indices() {
+                match quote {
+                    Some(q) if c == q => quote = None,
+                    Some(_) => {}
+                    None if c == '"' || c == '\'' => quote = Some(c),
+                    None if c == '>' => {
+                        let len = 1 + name_end + index + 1;
+                        return HtmlToken::StartTag {
+                            len,
+                            name: inner[..name_end].to_ascii_lowercase(),
+                            self_closing: html[..len - 1].ends_with('/'),
+                        };
+                    }
+                    None => {}
+                }
+            }
+            HtmlToken::Incomplete
+        }
+        Some('/') => {
+            let Some(end) = html.find('>') else {
+                return HtmlToken::Incomplete;
+            };
+            let tag = &html[2..end];
+            match tag.starts_with(|c: char| c.is_ascii_alphabetic()) {
+                true => {
+                    let name = tag
+                        .split(|c: char| c.is_whitespace() || c == '/')
+                        .next()
+                        .unwrap_or_default();
+                    HtmlToken::EndTag {
+                        len: end + 1,
+                        name: name.to_ascii_lowercase(),
+                    }
+                }
+                false => HtmlToken::Ignored(end + 1),
+            }
+        }
+        Some('!') if html.starts_with("<!--") => match html[4..].find("-->") {
+            Some(end) => HtmlToken::Ignored(4 + end + 3),
+            None => HtmlToken::Incomplete,
+        },
+        Some('!' | '?') => match html.find('>') {
+            Some(end) => HtmlToken::Ignored(end + 1),
+            None => HtmlToken::Incomplete,
+        },
+        Some(_) => HtmlToken::Text,
+        None => HtmlToken::Incomplete,
+    }
+}
+
+/// Split text like Django's `truncatewords_html`, on whitespace between
+/// words, so surrounding whitespace stays attached to the first and last
+/// words and whitespace alone counts as a word.
+fn split_html_words(data: &str) -> Vec<Cow<'_, str>> {
+    let trimmed = data.trim();
+    if trimmed.is_empty() {
+        return vec![Cow::Borrowed(data)];
+    }
+    let leading = &data[..data.len() - data.trim_start().len()];
+    let trailing = &data[data.trim_end().len()..];
+    let mut words: Vec<_> = trimmed.split_whitespace().map(Cow::Borrowed).collect();
+    if !leading.is_empty() {
+        words[0] = Cow::Owned(format!("{leading}{}", words[0]));
+    }
+    if !trailing.is_empty() {
+        let last = words.len() - 1;
+        words[last] = Cow::Owned(format!("{}{trailing}", words[last]));
+    }
+    words
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum HtmlTruncation {
+    Chars,
+    Words,
+}
+
+/// Truncate the text of `html` while keeping its tags, closing any tags
+/// left open at the cut. Like Django's `TruncateHTMLParser`, text is
+/// unescaped to be measured and escaped again in the output.
+struct HtmlTruncator<'a> {
+    truncation: HtmlTruncation,
+    length: usize,
+    remaining: usize,
+    processed_chars: usize,
+    total_chars: usize,
+    replacement: &'a str,
+    open_tags: Vec<String>,
+    output: String,
+}
+
+impl<'a> HtmlTruncator<'a> {
+    fn new(
+        truncation: HtmlTruncation,
+        length: usize,
+        remaining: usize,
+        replacement: &'a str,
+    ) -> Self {
+        Self {
+            truncation,
+            length,
+            remaining,
+            processed_chars: 0,
+            total_chars: 0,
+            replacement,
+            open_tags: Vec::new(),
+            output: String::new(),
+        }
+    }
+
+    fn truncate(mut self, html: &str) -> String {
+        self.total_chars = html.chars().count();
+        let mut rest = html;
+        while !rest.is_empty() {
+            let Some(tag_start) = rest.find('<') else {
+                self.handle_data(rest);
+                break;
+            };
+            if tag_start > 0 && self.handle_data(&rest[..tag_start]) {
+                break;
+            }
+            rest = &rest[tag_start..];
+            let len = match next_html_tag(rest) {
+                HtmlToken::StartTag {
+                    len,
+                    name,
+                    self_closing,
+                } => {
+                    self.output.push_str(&rest[..len]);
+                    if !VOID_ELEMENTS.contains(&name.as_str()) {
+                        if self_closing {
+                            self.output.push_str(&format!("</{name}>"));
+                        } else {
+                            self.open_tags.push(name);
+                        }
+                    }
+                    len
+                }
+                HtmlToken::EndTag { len, name } => {
+                    if !VOID_ELEMENTS.contains(&name.as_str()) {
+                        self.output.push_str(&format!("</{name}>"));
+                        if let Some(index) = self.open_tags.iter().rposition(|tag| *tag == name) {
+                            self.open_tags.remove(index);
+                        }
+                    }
+                    len
+                }
+                HtmlToken::Ignored(len) => len,
+                HtmlToken::Text => {
+                    if self.handle_data("<") {
+                        break;
+                    }
+                    1
+                }
+                HtmlToken::Incomplete => break,
+            };
+            rest = &rest[len..];
+        }
+        self.output
+    }
+
+    fn close_tags(&mut self) {
+        for tag in self.open_tags.drain(..).rev() {
+            self.output.push_str(&format!("</{tag}>"));
+        }
+    }
+
+    /// Add the text between tags to the output, returning whether it was
+    /// cut short. Once cut, any open tags are closed.
+    fn handle_data(&mut self, data: &str) -> bool {
+        let data = decode_html_entities(data);
+        let (data_len, output) = match self.truncation {
+            HtmlTruncation::Words => {
+                let words = split_html_words(&data);
+                let kept = &words[..words.len().min(self.remaining)];
+                let output = encode_quoted_attribute(&kept.join(" ")).into_owned();
+                (words.len(), output)
+            }
+            HtmlTruncation::Chars => {
+                let data_len = data.chars().count();
+                self.processed_chars += data_len;
+                // Like Django, text of exactly `length` characters without
+                // any markup is kept as is.
+                if self.processed_chars == self.length
+                    && self.output.is_empty()
+                    && data_len == self.total_chars
+                {
+                    self.output.push_str(&data);
+                    self.close_tags();
+                    return true;
+                }
+                let kept: String = data.chars().take(self.remaining).collect();
+                (data_len, encode_quoted_attribute(&kept).into_owned())
+            }
+        };
+        if self.remaining < data_len {
+            self.remaining = 0;
+            let output = add_truncation_text(&output, self.replacement);
+            self.output.push_str(&output);
+            self.close_tags();
+            return true;
+        }
+        self.remaining -= data_len;
+        self.output.push_str(&output);
+        false
+    }
+}
+
+impl ResolveFilter for StringformatFilter {
+    fn resolve<'t, 'py>(
+        &self,
+        variable: Option<Content<'t, 'py>>,
+        py: Python<'py>,
+        template: TemplateString<'t>,
+        context: &mut Context,
+    ) -> ResolveResult<'t, 'py> {
+        let Some(variable) = variable else {
+            return Ok("".as_content());
+        };
+        let argument = self
+            .argument
+            .resolve(py, template, context, ResolveFailures::Raise)?
+            .expect("missing argument in context should already have raised")
+            .to_py(py)?
+            .str()?;
+        let value = variable.to_py(py)?;
+        #[allow(non_snake_case)]
+        let SafeData = SAFEDATA.import(py, "django.utils.safestring", "SafeData")?;
+        let safe = value.is_instance(SafeData)?;
+        // A tuple would be used for the format arguments
+        let value = match value.is_instance_of::<PyTuple>() {
+            true => value.str()?.into_any(),
+            false => value,
+        };
+        // Use Python's `%` formatting, like Django
+        let format = PyString::new(py, &format!("%{argument}"));
+        let formatted = match format.rem(value) {
+            Ok(formatted) => Cow::Owned(formatted.str()?.to_string()),
+            Err(e) if e.is_instance_of::<PyTypeError>(py) => return Ok("".as_content()),
+            Err(e) if e.is_instance_of::<PyValueError>(py) => return Ok("".as_content()),
+            Err(e) => return Err(e.into()),
+        };
+        Ok(Some(Content::String(match (safe, context.autoescape) {
+            (true, _) => ContentString::HtmlSafe(formatted),
+            (false, true) => ContentString::HtmlUnsafe(formatted),
+            (false, false) => ContentString::String(formatted),
+        })))
+    }
+}
+
+impl ResolveFilter for TruncatecharsFilter {
+    fn resolve<'t, 'py>(
+        &self,
+        variable: Option<Content<'t, 'py>>,
+        py: Python<'py>,
+        template: TemplateString<'t>,
+        context: &mut Context,
+    ) -> ResolveResult<'t, 'py> {
+        let content = match variable {
+            Some(content) => content.resolve_string(context)?,
+            None => return Ok("".as_content()),
+        };
+        let length = match truncate_length(&self.argument, py, template, context)? {
+            Some(length) => length,
+            None => return Ok(Some(Content::String(content))),
+        };
+        if length == 0 {
+            return Ok(Some(content.map_content(|_| Cow::Borrowed(""))));
+        }
+        let truncated = truncate_chars(py, content.as_raw(), length)?;
+        Ok(Some(content.map_content(|_| Cow::Owned(truncated))))
+    }
+}
+
+impl ResolveFilter for TruncatecharsHtmlFilter {
+    fn resolve<'t, 'py>(
+        &self,
+        variable: Option<Content<'t, 'py>>,
+        py: Python<'py>,
+        template: TemplateString<'t>,
+        context: &mut Context,
+    ) -> ResolveResult<'t, 'py> {
+        let content = match variable {
+            Some(content) => content.resolve_string(context)?,
+            None => return Ok("".as_content()),
+        };
+        let length = match truncate_length(&self.argument, py, template, context)? {
+            Some(length) => length,
+            None => return Ok(Some(Content::String(content))),
+        };
+        if length == 0 {
+            return Ok(Some(content.map_content(|_| Cow::Borrowed(""))));
+        }
+        let truncate = truncation_text(py)?;
+        let remaining = truncate_chars_length(length, &truncate);
+        let text: String = content.as_raw().nfc().collect();
+        let truncator = HtmlTruncator::new(HtmlTruncation::Chars, length, remaining, &truncate);
+        let truncated = truncator.truncate(&text);
+        Ok(Some(content.map_content(|_| Cow::Owned(truncated))))
+    }
+}
+
+impl ResolveFilter for TruncatewordsFilter {
+    fn resolve<'t, 'py>(
+        &self,
+        variable: Option<Content<'t, 'py>>,
+        py: Python<'py>,
+        template: TemplateString<'t>,
+        context: &mut Context,
+    ) -> ResolveResult<'t, 'py> {
+        let content = match variable {
+            Some(content) => content.resolve_string(context)?,
+            None => return Ok("".as_content()),
+        };
+        let length = match truncate_length(&self.argument, py, template, context)? {
+            Some(length) => length,
+            None => return Ok(Some(Content::String(content))),
+        };
+        Ok(Some(content.map_content(|content| {
+            if length == 0 {
+                return Cow::Borrowed("");
+            }
+            let words: Vec<_> = content.split_whitespace().collect();
+            if words.len() > length {
+                Cow::Owned(add_truncation_text(&words[..length].join(" "), " …"))
+            } else {
+                Cow::Owned(words.join(" "))
+            }
+        })))
+    }
+}
+
+impl ResolveFilter for TruncatewordsHtmlFilter {
+    fn resolve<'t, 'py>(
+        &self,
+        variable: Option<Content<'t, 'py>>,
+        py: Python<'py>,
+        template: TemplateString<'t>,
+        context: &mut Context,
+    ) -> ResolveResult<'t, 'py> {
+        let content = match variable {
+            Some(content) => content.resolve_string(context)?,
+            None => return Ok("".as_content()),
+        };
+        let length = match truncate_length(&self.argument, py, template, context)? {
+            Some(length) => length,
+            None => return Ok(Some(Content::String(content))),
+        };
+        Ok(Some(content.map_content(|content| {
+            if length == 0 {
+                return Cow::Borrowed("");
+            }
+            let truncator = HtmlTruncator::new(HtmlTruncation::Words, length, length, " …");
+            Cow::Owned(truncator.truncate(&content))
+        })))
+    }
+}
+
+impl ResolveFilter for UpperFilter {
+    fn resolve<'t, 'py>(
+        &self,
+        variable: Option<Content<'t, 'py>>,
+        _py: Python<'py>,
+        _template: TemplateString<'t>,
+        context: &mut Context,
+    ) -> ResolveResult<'t, 'py> {
+        let content = match variable {
+            Some(content) => {
+                let content = content.resolve_string(context)?;
+                Some(content.map_content(|content| {
+                    convert_case(content, char::to_uppercase, str::to_uppercase)
+                }))
+            }
+            None => "".as_content(),
+        };
+        Ok(content)
+    }
+}
+
+impl ResolveFilter for WordcountFilter {
+    fn resolve<'t, 'py>(
+        &self,
+        variable: Option<Content<'t, 'py>>,
+        _py: Python<'py>,
+        _template: TemplateString<'t>,
+        context: &mut Context,
+    ) -> ResolveResult<'t, 'py> {
+        let count = match variable {
+            Some(content) => content
+                .resolve_string(context)?
+                .as_raw()
+                .split_whitespace()
+                .count(),
+            None => 0,
+        };
+        Ok(Some(Content::Int(count.into())))
+    }
+}
+
+impl ResolveFilter for YesnoFilter {
+    fn resolve<'t, 'py>(
+        &self,
+        variable: Option<Content<'t, 'py>>,
+        py: Python<'py>,
+        template: TemplateString<'t>,
+        context: &mut Context,
+    ) -> ResolveResult<'t, 'py> {
+        let labels = match &self.argument {
+            Some(arg) => arg
+                .resolve(py, template, context, ResolveFailures::Raise)?
+                .expect("missing argument in context should already have raised")
+                .resolve_string(context)?
+                .into_raw()
+                .into_owned(),
+            None => {
+                let django_translation = py.import("django.utils.translation")?;
+                let get_text = django_translation.getattr("gettext")?;
+                get_text.call1(("yes,no,maybe",))?.extract::<String>()?
+            }
+        };
+        let bits: Vec<_> = labels.split(',').collect();
+        let (yes, no, maybe) = match bits.as_slice() {
+            [yes, no, maybe] => (yes, no, maybe),
+            [yes, no, ..] => (yes, no, no),
+            // Invalid argument
+            _ => return Ok(variable),
+        };
+        let label = match variable {
+            Some(Content::Py(ref obj)) if obj.is_none() => maybe,
+            Some(ref content) => match content.evaluate(py, template, context) {
+                Some(true) => yes,
+                _ => no,
+            },
+            None => no,
+        };
+        let label = Cow::Owned(label.to_string());
+        Ok(Some(Content::String(match context.autoescape {
+            true => ContentString::HtmlUnsafe(label),
+            false => ContentString::String(label),
+        })))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::filters::{
+        AddSlashesFilter, DefaultFilter, ExternalFilter, LowerFilter, SafeFilter, UpperFilter,
+    };
+    use crate::parse::TagElement;
+    use crate::render::Render;
+    use crate::template::django_rusty_templates::{EngineData, Template};
+    use crate::types::{Argument, ArgumentType, Text, Variable};
+
+    use pyo3::types::{PyDict, PyString};
+    static MARK_SAFE: GILOnceCell<Py<PyAny>> = GILOnceCell::new();
+
+    fn mark_safe(py: Python<'_>, string: String) -> Result<Py<PyAny>, PyErr> {
+        let mark_safe = match MARK_SAFE.get(py) {
+            Some(mark_safe) => mark_safe,
+            None => {
+                let py_mark_safe = py.import("django.utils.safestring")?;
+                let py_mark_safe = py_mark_safe.getattr("mark_safe")?;
+                MARK_SAFE.set(py, py_mark_safe.into()).unwrap();
+                MARK_SAFE.get(py).unwrap()
+            }
+        };
+        let safe_string = mark_safe.call1(py, (string,))?;
+        Ok(safe_string)
+    }
+
+    use std::collections::HashMap;
+
+    #[test]
+    fn test_render_filter() {
+        pyo3::prepare_freethreaded_python();
+
+        Python::with_gil(|py| {
+            let name = PyString::new(py, "Lily").into_any();
+            let context = HashMap::from([("name".to_string(), name.unbind())]);
+            let mut context = Context::new(context, None, false);
+            let template = TemplateString("{{ name|default:'Bryony' }}");
+            let variable = Variable::new((3, 4), template);
+            let filter = Filter {
+                at: (8, 7),
+                left: TagElement::Variable(variable),
+                filter: FilterType::Default(DefaultFilter::new(Argument {
+                    at: (16, 8),
+                    argument_type: ArgumentType::Text(Text::new((17, 6))),
+                })),
+            };
+
+            let rendered = filter.render(py, template, &mut context).unwrap();
+            assert_eq!(rendered, "Lily");
+        })
+    }
+
+    #[test]
+    fn test_pass_through_filters_borrow() {
+        pyo3::prepare_freethreaded_python();
+
+        Python::with_gil(|py| {
+            let mut context = Context::new(HashMap::new(), None, false);
+            let template = TemplateString("{{ 'text'|default:'other' }}");
+            let text = "text";
+            let borrowed = || Some(Content::String(ContentString::String(Cow::Borrowed(text))));
+            let assert_borrowed = |resolved: ResolveResult| match resolved.unwrap() {
+                Some(Content::String(content)) => match content.into_raw() {
+                    Cow::Borrowed(content) => {
+                        assert_eq!(content.as_ptr(), text.as_ptr());
+                        assert_eq!(content.len(), text.len());
+                    }
+                    Cow::Owned(_) => panic!("Expected borrowed content"),
+                },
+                _ => panic!("Expected string content"),
+            };
+
+            let default = DefaultFilter::new(Argument {
+                at: (18, 7),
+                argument_type: ArgumentType::Text(Text::new((19, 5))),
+            });
+            assert_borrowed(default.resolve(borrowed(), py, template, &mut context));
+            assert_borrowed(SafeFilter.resolve(borrowed(), py, template, &mut context));
+            assert_borrowed(LowerFilter.resolve(borrowed(), py, template, &mut context));
+
+            let upper = UpperFilter.resolve(borrowed(), py, template, &mut context);
+            match upper.unwrap() {
+                Some(Content::String(content)) => {
+                    assert!(matches!(content.into_raw(), Cow::Owned(content) if content == "TEXT"))
+                }
+                _ => panic!("Expected string content"),
+            }
+        })
+    }
+
+    #[test]
+    fn test_convert_case() {
+        let upper = |s| convert_case(Cow::Borrowed(s), char::to_uppercase, str::to_uppercase);
+        assert!(matches!(upper("ABC 1!"), Cow::Borrowed("ABC 1!")));
+        assert!(matches!(upper("aBC"), Cow::Owned(s) if s == "ABC"));
+        assert!(matches!(upper("STRAẞE"), Cow::Borrowed(_)));
+        assert!(matches!(upper("ß"), Cow::Owned(s) if s == "SS"));
+        let lower = |s| convert_case(Cow::Borrowed(s), char::to_lowercase, str::to_lowercase);
+        assert!(matches!(lower("abc"), Cow::Borrowed("abc")));
+        assert!(matches!(lower("ΟΔΟΣ"), Cow::Owned(s) if s == "οδος"));
+    }
+
+    #[test]
+    fn test_render_external_filter_without_name_lookup() {
+        pyo3::prepare_freethreaded_python();
+
+        Python::with_gil(|py| {
+            let name = PyString::new(py, "Lily").into_any();
+            let context = HashMap::from([("name".to_string(), name.unbind())]);
+            let mut context = Context::new(context, None, false);
+            // `unregistered` isn't a known filter, so rendering must only use
+            // the function stored when parsing.
+            let template = TemplateString("{{ name|unregistered }}");
+            let shout = py.eval(c"lambda value: value + '!'", None, None).unwrap();
+            let filter = Filter {
+                at: (8, 12),
+                left: TagElement::Variable(Variable::new((3, 4), template)),
+                filter: FilterType::External(ExternalFilter::new(shout.unbind(), None)),
+            };
+
+            let rendered = filter.render(py, template, &mut context).unwrap();
+            assert_eq!(rendered, "Lily!");
+        })
+    }
+
+    #[test]
+    fn test_render_filter_slugify_happy_path() {
+        pyo3::prepare_freethreaded_python();
+
+        Python::with_gil(|py| {
+            let engine = EngineData::empty();
+            let template_string = "{{ var|slugify }}".to_string();
+            let context = PyDict::new(py);
+            context.set_item("var", "hello world").unwrap();
+            let template = Template::new_from_string(py, template_string, &engine).unwrap();
+            let result = template.render(py, Some(context), None).unwrap();
+
+            assert_eq!(result, "hello-world");
+        })
+    }
+
+    #[test]
+    fn test_render_filter_slugify_spaces_omitted() {
+        pyo3::prepare_freethreaded_python();
+
+        Python::with_gil(|py| {
+            let engine = EngineData::empty();
+            let template_string = "{{ var|slugify }}".to_string();
+            let context = PyDict::new(py);
+            context.set_item("var", " hello world").unwrap();
+            let template = Template::new_from_string(py, template_string, &engine).unwrap();
+            let result = template.render(py, Some(context), None).unwrap();
+
+            assert_eq!(result, "hello-world");
+        })
+    }
+
+    #[test]
+    fn test_render_filter_slugify_special_characters_omitted() {
+        pyo3::prepare_freethreaded_python();
+
+        Python::with_gil(|py| {
+            let engine = EngineData::empty();
+            let template_string = "{{ var|slugify }}".to_string();
+            let context = PyDict::new(py);
+            context.set_item("var", "a&€%").unwrap();
+            let template = Template::new_from_string(py, template_string, &engine).unwrap();
+            let result = template.render(py, Some(context), None).unwrap();
+
+            assert_eq!(result, "a");
+        })
+    }
+
+    #[test]
+    fn test_render_filter_slugify_multiple_spaces_inside_becomes_single() {
+        pyo3::prepare_freethreaded_python();
+
+        Python::with_gil(|py| {
+            let engine = EngineData::empty();
+            let template_string = "{{ var|slugify }}".to_string();
+            let context = PyDict::new(py);
+            context.set_item("var", "a & b").unwrap();
+            let template = Template::new_from_string(py, template_string, &engine).unwrap();
+            let result = template.render(py, Some(context), None).unwrap();
+
+            assert_eq!(result, "a-b");
+        })
+    }
+
+    #[test]
+    fn test_render_filter_slugify_integer() {
+        pyo3::prepare_freethreaded_python();
+
+        Python::with_gil(|py| {
+            let engine = EngineData::empty();
+            let template_string = "{{ var|default:1|slugify }}".to_string();
+            let context = PyDict::new(py);
+            let template = Template::new_from_string(py, template_string, &engine).unwrap();
+            let result = template.render(py, Some(context), None).unwrap();
+
+            assert_eq!(result, "1");
+        })
+    }
+
+    #[test]
+    fn test_render_filter_slugify_float() {
+        pyo3::prepare_freethreaded_python();
+
+        Python::with_gil(|py| {
+            let engine = EngineData::empty();
+            let template_string = "{{ var|default:1.3|slugify }}".to_string();
+            let context = PyDict::new(py);
+            let template = Template::new_from_string(py, template_string, &engine).unwrap();
+            let result = template.render(py, Some(context), None).unwrap();
+
+            assert_eq!(result, "1.3");
+        })
+    }
+
+    #[test]
+    fn test_render_filter_slugify_rust_string() {
+        pyo3::prepare_freethreaded_python();
+
+        Python::with_gil(|py| {
+            let engine = EngineData::empty();
+            let template_string = "{{ var|default:'hello world'|slugify }}".to_string();
+            let context = PyDict::new(py);
+            let template = Template::new_from_string(py, template_string, &engine).unwrap();
+            let result = template.render(py, Some(context), None).unwrap();
+
+            assert_eq!(result, "hello-world");
+        })
+    }
+
+    #[test]
+    fn test_render_filter_slugify_safe_string() {
+        pyo3::prepare_freethreaded_python();
+
+        Python::with_gil(|py| {
+            let engine = EngineData::empty();
+            let template_string = "{{ var|default:'hello world'|safe|slugify }}".to_string();
+            let context = PyDict::new(py);
+            let template = Template::new_from_string(py, template_string, &engine).unwrap();
+            let result = template.render(py, Some(context), None).unwrap();
+
+            assert_eq!(result, "hello-world");
+        })
+    }
+
+    #[test]
+    fn test_render_filter_slugify_safe_string_from_rust_treated_as_py() {
+        pyo3::prepare_freethreaded_python();
+
+        Python::with_gil(|py| {
+            let engine = EngineData::empty();
+            let template_string = "{{ var|slugify }}".to_string();
+            let context = PyDict::new(py);
+            let safe_string = mark_safe(py, "a &amp; b".to_string()).unwrap();
+            context.set_item("var", safe_string).unwrap();
+            let template = Template::new_from_string(py, template_string, &engine).unwrap();
+            let result = template.render(py, Some(context), None).unwrap();
+
+            assert_eq!(result, "a-amp-b");
+        })
+    }
+
+    #[test]
+    fn test_render_filter_escape_safe_string() {
+        pyo3::prepare_freethreaded_python();
+
+        Python::with_gil(|py| {
+            let engine = EngineData::empty();
+            let template_string = "{{ safe|escape }}|{{ html|escape }}".to_string();
+            let context = PyDict::new(py);
+            let safe_string = mark_safe(py, "a &amp; <b>".to_string()).unwrap();
+            context.set_item("safe", safe_string).unwrap();
+            context.set_item("html", "a & <b>").unwrap();
+            let template = Template::new_from_string(py, template_string, &engine)
+                .unwrap()
+                .with_autoescape(true);
+            let result = template.render(py, Some(context), None).unwrap();
+
+            assert_eq!(result, "a &amp; <b>|a &amp; &lt;b&gt;");
+        })
+    }
+
+    #[test]
+    fn test_render_filter_escape_autoescape_off() {
+        pyo3::prepare_freethreaded_python();
+
+        Python::with_gil(|py| {
+            let engine = EngineData::empty();
+            let template_string =
+                "{{ html }}|{{ html|escape }}|{{ html|escape|escape }}|{{ 1.0|escape }}"
+                    .to_string();
+            let context = PyDict::new(py);
+            context.set_item("html", "<b>").unwrap();
+            let template = Template::new_from_string(py, template_string, &engine)
+                .unwrap()
+                .with_autoescape(false);
+            let result = template.render(py, Some(context), None).unwrap();
+
+            assert_eq!(result, "<b>|&lt;b&gt;|&lt;b&gt;|1.0");
+        })
+    }
+
+    #[test]
+    fn test_render_filter_slugify_non_existing_variable() {
+        pyo3::prepare_freethreaded_python();
+
+        Python::with_gil(|py| {
+            let engine = EngineData::empty();
+            let template_string = "{{ not_there|slugify }}".to_string();
+            let context = PyDict::new(py);
+            let template = Template::new_from_string(py, template_string, &engine).unwrap();
+            let result = template.render(py, Some(context), None).unwrap();
+
+            assert_eq!(result, "");
+        })
+    }
+
+    #[test]
+    fn test_render_filter_slugify_invalid() {
+        pyo3::prepare_freethreaded_python();
+
+        Python::with_gil(|py| {
+            let engine = EngineData::empty();
+            let template_string = "{{ var|slugify:invalid }}".to_string();
+            let error = Template::new_from_string(py, template_string, &engine).unwrap_err();
+
+            let error_string = format!("{error}");
+            assert!(error_string.contains("slugify filter does not take an argument"));
+        })
+    }
+
+    #[test]
+    fn test_render_filter_add_dunder_add() {
+        pyo3::prepare_freethreaded_python();
+
+        Python::with_gil(|py| {
+            let locals = PyDict::new(py);
+            py.run(
+                c"
+class Money:
+    def __init__(self, amount):
+        self.amount = amount
+
+    def __add__(self, other):
+        return f'{self.amount} + {other}'
+
+class Broken:
+    def __add__(self, other):
+        raise RuntimeError
+
+money = Money(5)
+broken = Broken()
+",
+                None,
+                Some(&locals),
+            )
+            .unwrap();
+
+            let engine = EngineData::empty();
+            let template_string = "{{ money|add:3 }}|{{ broken|add:3 }}".to_string();
+            let template = Template::new_from_string(py, template_string, &engine).unwrap();
+            let result = template.render(py, Some(locals), None).unwrap();
+            assert_eq!(result, "5 + 3|");
+        })
+    }
+
+    #[test]
+    fn test_render_filter_external() {
+        pyo3::prepare_freethreaded_python();
+
+        Python::with_gil(|py| {
+            let locals = PyDict::new(py);
+            py.run(
+                c"
+from types import SimpleNamespace
+
+def reverse_str(value):
+    return value[::-1]
+
+library = SimpleNamespace(filters={'reverse_str': reverse_str}, tags={})
+",
+                None,
+                Some(&locals),
+            )
+            .unwrap();
+            let library = locals.get_item("library").unwrap().unwrap();
+            let libraries = HashMap::from([("strings".to_string(), library.unbind())]);
+            let engine = EngineData::with_libraries(libraries);
+
+            let template_string = "{% load strings %}{{ name|reverse_str }}".to_string();
+            let context = PyDict::new(py);
+            context.set_item("name", "Lily").unwrap();
+            let template = Template::new_from_string(py, template_string, &engine).unwrap();
+            let result = template.render(py, Some(context), None).unwrap();
+            assert_eq!(result, "yliL");
+        })
+    }
+
+    #[test]
+    fn test_render_filter_external_is_safe() {
+        pyo3::prepare_freethreaded_python();
+
+        Python::with_gil(|py| {
+            let locals = PyDict::new(py);
+            py.run(
+                c"
+from types import SimpleNamespace
+
+def bold(value):
+    return f'<b>{value}</b>'
+bold.is_safe = True
+
+def autoescape(value, autoescape=None):
+    return f'{value}:{autoescape}'
+autoescape.needs_autoescape = True
+
+library = SimpleNamespace(filters={'bold': bold, 'autoescape': autoescape}, tags={})
+",
+                None,
+                Some(&locals),
+            )
+            .unwrap();
+            let library = locals.get_item("library").unwrap().unwrap();
+            let libraries = HashMap::from([("html".to_string(), library.unbind())]);
+            let engine = EngineData::with_libraries(libraries);
+
+            let template_string =
+                "{% load html %}{{ text|bold }} {{ text|safe|bold }} {{ text|autoescape }}"
+                    .to_string();
+            let context = PyDict::new(py);
+            context.set_item("text", "<i>x</i>").unwrap();
+            let template = Template::new_from_string(py, template_string, &engine)
+                .unwrap()
+                .with_autoescape(true);
+            let result = template.render(py, Some(context.clone()), None).unwrap();
+            assert_eq!(
+                result,
+                "&lt;b&gt;&lt;i&gt;x&lt;/i&gt;&lt;/b&gt; <b><i>x</i></b> &lt;i&gt;x&lt;/i&gt;:True"
+            );
+
+            let template = template.with_autoescape(false);
+            let result = template.render(py, Some(context), None).unwrap();
+            assert_eq!(result, "<b><i>x</i></b> <b><i>x</i></b> <i>x</i>:False");
+        })
+    }
+
+    #[test]
+    fn test_render_filter_addslashes_single() {
+        pyo3::prepare_freethreaded_python();
+
+        Python::with_gil(|py| {
+            let name = PyString::new(py, "'hello'").into_any();
+            let context = HashMap::from([("quotes".to_string(), name.unbind())]);
+            let mut context = Context::new(context, None, false);
+            let template = TemplateString("{{ quotes|addslashes }}");
+            let variable = Variable::new((3, 6), template);
+            let filter = Filter {
+                at: (10, 10),
+                left: TagElement::Variable(variable),
+                filter: FilterType::AddSlashes(AddSlashesFilter),
+            };
+
+            let rendered = filter.render(py, template, &mut context).unwrap();
+            assert_eq!(rendered, r"\'hello\'");
+        })
+    }
+
+    #[test]
+    fn test_render_filter_capfirst() {
+        pyo3::prepare_freethreaded_python();
+
+        Python::with_gil(|py| {
+            let engine = EngineData::empty();
+            let template_string = "{{ var|capfirst }}".to_string();
+            let context = PyDict::new(py);
+            context.set_item("var", "hello world").unwrap();
+            let template = Template::new_from_string(py, template_string, &engine).unwrap();
+            let result = template.render(py, Some(context), None).unwrap();
+
+            assert_eq!(result, "Hello world");
+
+            let context = PyDict::new(py);
+            context.set_item("var", "").unwrap();
+            let template_string = "{{ var|capfirst }}".to_string();
+            let template = Template::new_from_string(py, template_string, &engine).unwrap();
+            let result = template.render(py, Some(context), None).unwrap();
+
+            assert_eq!(result, "");
+
+            let context = PyDict::new(py);
+            context.set_item("bar", "").unwrap();
+            let template_string = "{{ var|capfirst }}".to_string();
+            let template = Template::new_from_string(py, template_string, &engine).unwrap();
+            let result = template.render(py, Some(context), None).unwrap();
+
+            assert_eq!(result, "");
+
+            let template_string = "{{ var|capfirst:invalid }}".to_string();
+            let error = Template::new_from_string(py, template_string, &engine).unwrap_err();
+
+            let error_string = format!("{error}");
+            assert!(error_string.contains("capfirst filter does not take an argument"));
+        })
+    }
+
+    #[test]
+    fn test_render_filter_default() {
+        pyo3::prepare_freethreaded_python();
+
+        Python::with_gil(|py| {
+            let context = HashMap::new();
+            let mut context = Context::new(context, None, false);
+            let template = TemplateString("{{ name|default:'Bryony' }}");
+            let variable = Variable::new((3, 4), template);
+            let filter = Filter {
+                at: (8, 7),
+                left: TagElement::Variable(variable),
+                filter: FilterType::Default(DefaultFilter::new(Argument {
+                    at: (16, 8),
+                    argument_type: ArgumentType::Text(Text::new((17, 6))),
+                })),
+            };
+
+            let rendered = filter.render(py, template, &mut context).unwrap();
+            assert_eq!(rendered, "Bryony");
+        })
+    }
+
+    #[test]
+    fn test_render_filter_default_nested_argument() {
+        pyo3::prepare_freethreaded_python();
+
+        Python::with_gil(|py| {
+            let engine = EngineData::empty();
+            let template_string =
+                "{{ missing|default:user.profile.name }} {{ missing|default:user.emails.1 }}"
+                    .to_string();
+            let profile = PyDict::new(py);
+            profile.set_item("name", "Lily").unwrap();
+            let user = PyDict::new(py);
+            user.set_item("profile", profile).unwrap();
+            user.set_item("emails", vec!["a@example.com", "b@example.com"])
+                .unwrap();
+            let context = PyDict::new(py);
+            context.set_item("user", user).unwrap();
+            let template = Template::new_from_string(py, template_string, &engine).unwrap();
+            let result = template.render(py, Some(context), None).unwrap();
+
+            assert_eq!(result, "Lily b@example.com");
+        })
+    }
+
+    #[test]
+    fn test_render_filter_default_integer() {
+        pyo3::prepare_freethreaded_python();
+
+        Python::with_gil(|py| {
+            let context = HashMap::new();
+            let mut context = Context::new(context, None, false);
+            let template = TemplateString("{{ count|default:12}}");
+            let variable = Variable::new((3, 5), template);
+            let filter = Filter {
+                at: (9, 7),
+                left: TagElement::Variable(variable),
+                filter: FilterType::Default(DefaultFilter::new(Argument {
+                    at: (17, 2),
+                    argument_type: ArgumentType::Int(12.into()),
+                })),
+            };
+
+            let rendered = filter.render(py, template, &mut context).unwrap();
+            assert_eq!(rendered, "12");
+        })
+    }
+
+    #[test]
+    fn test_render_filter_default_float() {
+        pyo3::prepare_freethreaded_python();
+
+        Python::with_gil(|py| {
+            let context = HashMap::new();
+            let mut context = Context::new(context, None, false);
+            let template = TemplateString("{{ count|default:3.5}}");
+            let variable = Variable::new((3, 5), template);
+            let filter = Filter {
+                at: (9, 7),
+                left: TagElement::Variable(variable),
+                filter: FilterType::Default(DefaultFilter::new(Argument {
+                    at: (17, 3),
+                    argument_type: ArgumentType::Float(3.5),
+                })),
+            };
+
+            let rendered = filter.render(py, template, &mut context).unwrap();
+            assert_eq!(rendered, "3.5");
+        })
+    }
+
+    #[test]
+    fn test_render_filter_default_variable() {
+        pyo3::prepare_freethreaded_python();
+
+        Python::with_gil(|py| {
+            let me = PyString::new(py, "Lily").into_any();
+            let context = HashMap::from([("me".to_string(), me.unbind())]);
+            let mut context = Context::new(context, None, false);
+            let template = TemplateString("{{ name|default:me}}");
+            let variable = Variable::new((3, 4), template);
+            let filter = Filter {
+                at: (8, 7),
+                left: TagElement::Variable(variable),
+                filter: FilterType::Default(DefaultFilter::new(Argument {
+                    at: (16, 2),
+                    argument_type: ArgumentType::Variable(Variable::new((16, 2), template)),
+                })),
+            };
 
             let rendered = filter.render(py, template, &mut context).unwrap();
             assert_eq!(rendered, "Lily");
@@ -404,425 +2045,563 @@ mod tests {
     }
 
     #[test]
-    fn test_render_filter_slugify_happy_path() {
+    fn test_render_filter_default_text_argument_safe() {
+        pyo3::prepare_freethreaded_python();
+
+        Python::with_gil(|py| {
+            let engine = EngineData::empty();
+            let template_string =
+                "{% autoescape on %}{{ name|default:'<b>' }}{% endautoescape %}".to_string();
+            let template = Template::new_from_string(py, template_string, &engine).unwrap();
+            let context = PyDict::new(py);
+
+            assert_eq!(template.render(py, Some(context), None).unwrap(), "<b>");
+        })
+    }
+
+    #[test]
+    fn test_render_filter_default_variable_argument_escaped() {
+        pyo3::prepare_freethreaded_python();
+
+        Python::with_gil(|py| {
+            let engine = EngineData::empty();
+            let template_string =
+                "{% autoescape on %}{{ name|default:other }}{% endautoescape %}".to_string();
+            let template = Template::new_from_string(py, template_string, &engine).unwrap();
+            let context = PyDict::new(py);
+            context.set_item("other", "<b>").unwrap();
+
+            assert_eq!(
+                template.render(py, Some(context), None).unwrap(),
+                "&lt;b&gt;"
+            );
+        })
+    }
+
+    #[test]
+    fn test_render_filter_lower() {
+        pyo3::prepare_freethreaded_python();
+
+        Python::with_gil(|py| {
+            let name = PyString::new(py, "Lily").into_any();
+            let context = HashMap::from([("name".to_string(), name.unbind())]);
+            let mut context = Context::new(context, None, false);
+            let template = TemplateString("{{ name|lower }}");
+            let variable = Variable::new((3, 4), template);
+            let filter = Filter {
+                at: (8, 5),
+                left: TagElement::Variable(variable),
+                filter: FilterType::Lower(LowerFilter),
+            };
+
+            let rendered = filter.render(py, template, &mut context).unwrap();
+            assert_eq!(rendered, "lily");
+        })
+    }
+
+    #[test]
+    fn test_render_filter_lower_missing_left() {
+        pyo3::prepare_freethreaded_python();
+
+        Python::with_gil(|py| {
+            let context = HashMap::new();
+            let mut context = Context::new(context, None, false);
+            let template = TemplateString("{{ name|lower }}");
+            let variable = Variable::new((3, 4), template);
+            let filter = Filter {
+                at: (8, 5),
+                left: TagElement::Variable(variable),
+                filter: FilterType::Lower(LowerFilter),
+            };
+
+            let rendered = filter.render(py, template, &mut context).unwrap();
+            assert_eq!(rendered, "");
+        })
+    }
+
+    #[test]
+    fn test_render_chained_filters() {
+        pyo3::prepare_freethreaded_python();
+
+        Python::with_gil(|py| {
+            let context = HashMap::new();
+            let mut context = Context::new(context, None, false);
+            let template = TemplateString("{{ name|default:'Bryony'|lower }}");
+            let variable = Variable::new((3, 4), template);
+            let default = Filter {
+                at: (8, 7),
+                left: TagElement::Variable(variable),
+                filter: FilterType::Default(DefaultFilter::new(Argument {
+                    at: (16, 8),
+                    argument_type: ArgumentType::Text(Text::new((17, 6))),
+                })),
+            };
+            let lower = Filter {
+                at: (25, 5),
+                left: TagElement::Filter(Box::new(default)),
+                filter: FilterType::Lower(LowerFilter),
+            };
+
+            let rendered = lower.render(py, template, &mut context).unwrap();
+            assert_eq!(rendered, "bryony");
+        })
+    }
+
+    #[test]
+    fn test_render_filter_dictsort() {
+        pyo3::prepare_freethreaded_python();
+
+        Python::with_gil(|py| {
+            let engine = EngineData::empty();
+            let template_string =
+                "{% for item in items|dictsort:'x' %}{{ item.x }}{% endfor %}".to_string();
+            let template = Template::new_from_string(py, template_string, &engine).unwrap();
+            let items = py
+                .eval(c"[{'x': 3}, {'x': 1}, {'x': 2}]", None, None)
+                .unwrap();
+            let context = PyDict::new(py);
+            context.set_item("items", items).unwrap();
+
+            assert_eq!(template.render(py, Some(context), None).unwrap(), "123");
+        })
+    }
+
+    #[test]
+    fn test_render_filter_dictsortreversed() {
+        pyo3::prepare_freethreaded_python();
+
+        Python::with_gil(|py| {
+            let engine = EngineData::empty();
+            let template_string =
+                "{% for item in items|dictsortreversed:'x' %}{{ item.name }}{% endfor %}"
+                    .to_string();
+            let template = Template::new_from_string(py, template_string, &engine).unwrap();
+            let items = py
+                .eval(
+                    c"[{'name': 'a', 'x': 1}, {'name': 'b', 'x': 3}, {'name': 'c', 'x': 1}, {'name': 'd', 'x': 2}]",
+                    None,
+                    None,
+                )
+                .unwrap();
+            let context = PyDict::new(py);
+            context.set_item("items", items).unwrap();
+
+            // Items with equal keys keep their original order
+            assert_eq!(template.render(py, Some(context), None).unwrap(), "bdac");
+        })
+    }
+
+    #[test]
+    fn test_render_filter_dictsort_int() {
+        pyo3::prepare_freethreaded_python();
+
+        Python::with_gil(|py| {
+            let engine = EngineData::empty();
+            let template_string = "{{ value|dictsort:\"x\" }}".to_string();
+            let template = Template::new_from_string(py, template_string, &engine).unwrap();
+            let context = PyDict::new(py);
+            context.set_item("value", 1).unwrap();
+
+            assert_eq!(template.render(py, Some(context), None).unwrap(), "");
+        })
+    }
+
+    #[test]
+    fn test_render_filter_dictsort_string() {
+        pyo3::prepare_freethreaded_python();
+
+        Python::with_gil(|py| {
+            let engine = EngineData::empty();
+            let template_string = "{{ value|dictsort:'x' }}".to_string();
+            let template = Template::new_from_string(py, template_string, &engine).unwrap();
+            let context = PyDict::new(py);
+            context.set_item("value", "abc").unwrap();
+
+            assert_eq!(template.render(py, Some(context), None).unwrap(), "");
+        })
+    }
+
+    #[test]
+    fn test_render_filter_join_set() {
         pyo3::prepare_freethreaded_python();
 
         Python::with_gil(|py| {
             let engine = EngineData::empty();
-            let template_string = "{{ var|slugify }}".to_string();
-            let context = PyDict::new(py);
-            context.set_item("var", "hello world").unwrap();
+            let template_string = "{{ letters|join:',' }}".to_string();
             let template = Template::new_from_string(py, template_string, &engine).unwrap();
-            let result = template.render(py, Some(context), None).unwrap();
+            let letters = py.eval(c"{'a', 'b', 'c'}", None, None).unwrap();
+            let context = PyDict::new(py);
+            context.set_item("letters", letters).unwrap();
 
-            assert_eq!(result, "hello-world");
+            let rendered = template.render(py, Some(context), None).unwrap();
+            let mut parts: Vec<_> = rendered.split(',').collect();
+            parts.sort();
+            assert_eq!(parts, vec!["a", "b", "c"]);
         })
     }
 
     #[test]
-    fn test_render_filter_slugify_spaces_omitted() {
+    fn test_render_filter_safeseq_join() {
         pyo3::prepare_freethreaded_python();
 
         Python::with_gil(|py| {
             let engine = EngineData::empty();
-            let template_string = "{{ var|slugify }}".to_string();
+            let template_string = "{{ items|safeseq|join:', ' }}|{{ items|join:', ' }}".to_string();
+            let template = Template::new_from_string(py, template_string, &engine)
+                .unwrap()
+                .with_autoescape(true);
             let context = PyDict::new(py);
-            context.set_item("var", " hello world").unwrap();
-            let template = Template::new_from_string(py, template_string, &engine).unwrap();
-            let result = template.render(py, Some(context), None).unwrap();
+            context.set_item("items", vec!["<b>", "&"]).unwrap();
 
-            assert_eq!(result, "hello-world");
+            assert_eq!(
+                template.render(py, Some(context), None).unwrap(),
+                "<b>, &|&lt;b&gt;, &amp;"
+            );
         })
     }
 
     #[test]
-    fn test_render_filter_slugify_special_characters_omitted() {
+    fn test_render_filter_join_not_iterable() {
         pyo3::prepare_freethreaded_python();
 
         Python::with_gil(|py| {
             let engine = EngineData::empty();
-            let template_string = "{{ var|slugify }}".to_string();
-            let context = PyDict::new(py);
-            context.set_item("var", "a&€%").unwrap();
+            let template_string = "{{ value|join:',' }}".to_string();
             let template = Template::new_from_string(py, template_string, &engine).unwrap();
-            let result = template.render(py, Some(context), None).unwrap();
+            let context = PyDict::new(py);
+            context.set_item("value", 42).unwrap();
 
-            assert_eq!(result, "a");
+            assert_eq!(template.render(py, Some(context), None).unwrap(), "42");
         })
     }
 
     #[test]
-    fn test_render_filter_slugify_multiple_spaces_inside_becomes_single() {
+    fn test_render_filter_length_set() {
         pyo3::prepare_freethreaded_python();
 
         Python::with_gil(|py| {
             let engine = EngineData::empty();
-            let template_string = "{{ var|slugify }}".to_string();
-            let context = PyDict::new(py);
-            context.set_item("var", "a & b").unwrap();
+            let template_string = "{{ letters|length }}".to_string();
             let template = Template::new_from_string(py, template_string, &engine).unwrap();
-            let result = template.render(py, Some(context), None).unwrap();
+            let letters = py.eval(c"{'a', 'b', 'c', 'a'}", None, None).unwrap();
+            let context = PyDict::new(py);
+            context.set_item("letters", letters).unwrap();
 
-            assert_eq!(result, "a-b");
+            assert_eq!(template.render(py, Some(context), None).unwrap(), "3");
         })
     }
 
     #[test]
-    fn test_render_filter_slugify_integer() {
+    fn test_render_filter_length_invalid() {
         pyo3::prepare_freethreaded_python();
 
         Python::with_gil(|py| {
             let engine = EngineData::empty();
-            let template_string = "{{ var|default:1|slugify }}".to_string();
-            let context = PyDict::new(py);
+            let template_string = "{{ value|length }}{{ missing|length }}".to_string();
             let template = Template::new_from_string(py, template_string, &engine).unwrap();
-            let result = template.render(py, Some(context), None).unwrap();
+            let context = PyDict::new(py);
+            context.set_item("value", 42).unwrap();
 
-            assert_eq!(result, "1");
+            assert_eq!(template.render(py, Some(context), None).unwrap(), "00");
         })
     }
 
     #[test]
-    fn test_render_filter_slugify_float() {
+    fn test_render_filter_stringformat_none() {
         pyo3::prepare_freethreaded_python();
 
         Python::with_gil(|py| {
             let engine = EngineData::empty();
-            let template_string = "{{ var|default:1.3|slugify }}".to_string();
-            let context = PyDict::new(py);
+            let template_string = "{{ none_val|stringformat:\"s\" }}".to_string();
             let template = Template::new_from_string(py, template_string, &engine).unwrap();
-            let result = template.render(py, Some(context), None).unwrap();
+            let context = PyDict::new(py);
+            context.set_item("none_val", py.None()).unwrap();
 
-            assert_eq!(result, "1.3");
+            assert_eq!(template.render(py, Some(context), None).unwrap(), "None");
         })
     }
 
     #[test]
-    fn test_render_filter_slugify_rust_string() {
+    fn test_render_filter_stringformat_invalid() {
         pyo3::prepare_freethreaded_python();
 
         Python::with_gil(|py| {
             let engine = EngineData::empty();
-            let template_string = "{{ var|default:'hello world'|slugify }}".to_string();
-            let context = PyDict::new(py);
+            let template_string =
+                "{{ num|stringformat:\"03d\" }}|{{ text|stringformat:\"d\" }}".to_string();
             let template = Template::new_from_string(py, template_string, &engine).unwrap();
-            let result = template.render(py, Some(context), None).unwrap();
+            let context = PyDict::new(py);
+            context.set_item("num", 7).unwrap();
+            context.set_item("text", "seven").unwrap();
 
-            assert_eq!(result, "hello-world");
+            assert_eq!(template.render(py, Some(context), None).unwrap(), "007|");
         })
     }
 
     #[test]
-    fn test_render_filter_slugify_safe_string() {
+    fn test_render_filter_divisibleby() {
         pyo3::prepare_freethreaded_python();
 
         Python::with_gil(|py| {
             let engine = EngineData::empty();
-            let template_string = "{{ var|default:'hello world'|safe|slugify }}".to_string();
-            let context = PyDict::new(py);
+            let template_string =
+                "{{ text|divisibleby:3 }} {{ text|divisibleby:\"4\" }} {{ value|divisibleby:n }}"
+                    .to_string();
             let template = Template::new_from_string(py, template_string, &engine).unwrap();
-            let result = template.render(py, Some(context), None).unwrap();
-
-            assert_eq!(result, "hello-world");
+            let context = PyDict::new(py);
+            context.set_item("text", "21").unwrap();
+            context.set_item("value", " 12 ").unwrap();
+            context.set_item("n", "6").unwrap();
+
+            assert_eq!(
+                template.render(py, Some(context), None).unwrap(),
+                "True False True"
+            );
         })
     }
 
     #[test]
-    fn test_render_filter_slugify_safe_string_from_rust_treated_as_py() {
+    fn test_resolve_filter_divisibleby_bool() {
         pyo3::prepare_freethreaded_python();
 
         Python::with_gil(|py| {
-            let engine = EngineData::empty();
-            let template_string = "{{ var|slugify }}".to_string();
-            let context = PyDict::new(py);
-            let safe_string = mark_safe(py, "a &amp; b".to_string()).unwrap();
-            context.set_item("var", safe_string).unwrap();
-            let template = Template::new_from_string(py, template_string, &engine).unwrap();
-            let result = template.render(py, Some(context), None).unwrap();
-
-            assert_eq!(result, "a-amp-b");
+            let mut context = Context::new(HashMap::new(), None, false);
+            let template = TemplateString("{{ 4|divisibleby:2 }}");
+            let filter = DivisiblebyFilter::new(Argument {
+                at: (17, 1),
+                argument_type: ArgumentType::Int(2.into()),
+            });
+            let resolved = filter
+                .resolve(Some(Content::Int(4.into())), py, template, &mut context)
+                .unwrap();
+            assert!(matches!(resolved, Some(Content::Bool(true))));
         })
     }
 
     #[test]
-    fn test_render_filter_slugify_non_existing_variable() {
+    fn test_render_filter_divisibleby_bool() {
         pyo3::prepare_freethreaded_python();
 
         Python::with_gil(|py| {
             let engine = EngineData::empty();
-            let template_string = "{{ not_there|slugify }}".to_string();
-            let context = PyDict::new(py);
+            let template_string = "{% if four|divisibleby:2 %}yes{% endif %}\
+                {% if three|divisibleby:2 == 0 %} zero{% endif %}\
+                {% if four|divisibleby:2 is True %} is{% endif %}\
+                {% if four|divisibleby:2 in bools %} in{% endif %}"
+                .to_string();
             let template = Template::new_from_string(py, template_string, &engine).unwrap();
-            let result = template.render(py, Some(context), None).unwrap();
-
-            assert_eq!(result, "");
+            let context = PyDict::new(py);
+            context.set_item("three", 3).unwrap();
+            context.set_item("four", 4).unwrap();
+            context.set_item("bools", vec![true]).unwrap();
+
+            assert_eq!(
+                template.render(py, Some(context), None).unwrap(),
+                "yes zero is in"
+            );
         })
     }
 
     #[test]
-    fn test_render_filter_slugify_invalid() {
+    fn test_render_filter_divisibleby_invalid() {
         pyo3::prepare_freethreaded_python();
 
         Python::with_gil(|py| {
             let engine = EngineData::empty();
-            let template_string = "{{ var|slugify:invalid }}".to_string();
-            let error = Template::new_from_string(py, template_string, &engine).unwrap_err();
+            let template_string = "{{ value|divisibleby:3 }}".to_string();
+            let template = Template::new_from_string(py, template_string, &engine).unwrap();
+            let context = PyDict::new(py);
+            context.set_item("value", "abc").unwrap();
 
-            let error_string = format!("{error}");
-            assert!(error_string.contains("slugify filter does not take an argument"));
+            let error = template.render(py, Some(context), None).unwrap_err();
+            assert!(error.is_instance_of::<PyValueError>(py));
         })
     }
 
     #[test]
-    fn test_render_filter_addslashes_single() {
+    fn test_render_filter_date_none() {
         pyo3::prepare_freethreaded_python();
 
         Python::with_gil(|py| {
-            let name = PyString::new(py, "'hello'").into_any();
-            let context = HashMap::from([("quotes".to_string(), name.unbind())]);
-            let mut context = Context {
-                context,
-                request: None,
-                autoescape: false,
-            };
-            let template = TemplateString("{{ quotes|addslashes }}");
-            let variable = Variable::new((3, 6));
-            let filter = Filter {
-                at: (10, 10),
-                left: TagElement::Variable(variable),
-                filter: FilterType::AddSlashes(AddSlashesFilter),
-            };
+            let engine = EngineData::empty();
+            let template_string =
+                "{{ none_val|date:\"Y\" }}|{{ empty|date }}|{{ missing|time:\"H:i\" }}".to_string();
+            let template = Template::new_from_string(py, template_string, &engine).unwrap();
+            let context = PyDict::new(py);
+            context.set_item("none_val", py.None()).unwrap();
+            context.set_item("empty", "").unwrap();
 
-            let rendered = filter.render(py, template, &mut context).unwrap();
-            assert_eq!(rendered, r"\'hello\'");
+            assert_eq!(template.render(py, Some(context), None).unwrap(), "||");
         })
     }
 
     #[test]
-    fn test_render_filter_capfirst() {
+    fn test_render_filter_length_add() {
         pyo3::prepare_freethreaded_python();
 
         Python::with_gil(|py| {
             let engine = EngineData::empty();
-            let template_string = "{{ var|capfirst }}".to_string();
-            let context = PyDict::new(py);
-            context.set_item("var", "hello world").unwrap();
-            let template = Template::new_from_string(py, template_string, &engine).unwrap();
-            let result = template.render(py, Some(context), None).unwrap();
-
-            assert_eq!(result, "Hello world");
-
-            let context = PyDict::new(py);
-            context.set_item("var", "").unwrap();
-            let template_string = "{{ var|capfirst }}".to_string();
-            let template = Template::new_from_string(py, template_string, &engine).unwrap();
-            let result = template.render(py, Some(context), None).unwrap();
-
-            assert_eq!(result, "");
-
+            let template_string = "{{ items|length|add:1 }}".to_string();
             let context = PyDict::new(py);
-            context.set_item("bar", "").unwrap();
-            let template_string = "{{ var|capfirst }}".to_string();
+            context.set_item("items", vec![1, 2, 3]).unwrap();
             let template = Template::new_from_string(py, template_string, &engine).unwrap();
             let result = template.render(py, Some(context), None).unwrap();
-
-            assert_eq!(result, "");
-
-            let template_string = "{{ var|capfirst:invalid }}".to_string();
-            let error = Template::new_from_string(py, template_string, &engine).unwrap_err();
-
-            let error_string = format!("{error}");
-            assert!(error_string.contains("capfirst filter does not take an argument"));
+            assert_eq!(result, "4");
         })
     }
 
     #[test]
-    fn test_render_filter_default() {
+    fn test_render_filter_wordcount() {
         pyo3::prepare_freethreaded_python();
 
         Python::with_gil(|py| {
-            let context = HashMap::new();
-            let mut context = Context {
-                context,
-                request: None,
-                autoescape: false,
-            };
-            let template = TemplateString("{{ name|default:'Bryony' }}");
-            let variable = Variable::new((3, 4));
-            let filter = Filter {
-                at: (8, 7),
-                left: TagElement::Variable(variable),
-                filter: FilterType::Default(DefaultFilter::new(Argument {
-                    at: (16, 8),
-                    argument_type: ArgumentType::Text(Text::new((17, 6))),
-                })),
-            };
-
-            let rendered = filter.render(py, template, &mut context).unwrap();
-            assert_eq!(rendered, "Bryony");
+            let engine = EngineData::empty();
+            let template_string =
+                "{{ text|wordcount }} {{ text|wordcount|add:1 }} {{ missing|wordcount }}"
+                    .to_string();
+            let context = PyDict::new(py);
+            context.set_item("text", " lorem\nipsum\tdolor ").unwrap();
+            let template = Template::new_from_string(py, template_string, &engine).unwrap();
+            let result = template.render(py, Some(context), None).unwrap();
+            assert_eq!(result, "3 4 0");
         })
     }
 
     #[test]
-    fn test_render_filter_default_integer() {
+    fn test_render_filter_truncatechars() {
         pyo3::prepare_freethreaded_python();
 
         Python::with_gil(|py| {
-            let context = HashMap::new();
-            let mut context = Context {
-                context,
-                request: None,
-                autoescape: false,
-            };
-            let template = TemplateString("{{ count|default:12}}");
-            let variable = Variable::new((3, 5));
-            let filter = Filter {
-                at: (9, 7),
-                left: TagElement::Variable(variable),
-                filter: FilterType::Default(DefaultFilter::new(Argument {
-                    at: (17, 2),
-                    argument_type: ArgumentType::Int(12.into()),
-                })),
-            };
+            let engine = EngineData::empty();
+            let template_string = "{{ text|truncatechars:5 }}".to_string();
+            let template = Template::new_from_string(py, template_string, &engine).unwrap();
+            let context = PyDict::new(py);
+            context.set_item("text", "Hello world").unwrap();
 
-            let rendered = filter.render(py, template, &mut context).unwrap();
-            assert_eq!(rendered, "12");
+            assert_eq!(template.render(py, Some(context), None).unwrap(), "Hell…");
         })
     }
 
     #[test]
-    fn test_render_filter_default_float() {
+    fn test_render_filter_truncatechars_invalid_count() {
         pyo3::prepare_freethreaded_python();
 
         Python::with_gil(|py| {
-            let context = HashMap::new();
-            let mut context = Context {
-                context,
-                request: None,
-                autoescape: false,
-            };
-            let template = TemplateString("{{ count|default:3.5}}");
-            let variable = Variable::new((3, 5));
-            let filter = Filter {
-                at: (9, 7),
-                left: TagElement::Variable(variable),
-                filter: FilterType::Default(DefaultFilter::new(Argument {
-                    at: (17, 3),
-                    argument_type: ArgumentType::Float(3.5),
-                })),
-            };
+            let engine = EngineData::empty();
+            let template_string = "{{ text|truncatechars:'abc' }}".to_string();
+            let template = Template::new_from_string(py, template_string, &engine).unwrap();
+            let context = PyDict::new(py);
+            context.set_item("text", "Hello world").unwrap();
 
-            let rendered = filter.render(py, template, &mut context).unwrap();
-            assert_eq!(rendered, "3.5");
+            assert_eq!(
+                template.render(py, Some(context), None).unwrap(),
+                "Hello world"
+            );
         })
     }
 
     #[test]
-    fn test_render_filter_default_variable() {
+    fn test_render_filter_truncatewords_invalid_count() {
         pyo3::prepare_freethreaded_python();
 
         Python::with_gil(|py| {
-            let me = PyString::new(py, "Lily").into_any();
-            let context = HashMap::from([("me".to_string(), me.unbind())]);
-            let mut context = Context {
-                context,
-                request: None,
-                autoescape: false,
-            };
-            let template = TemplateString("{{ name|default:me}}");
-            let variable = Variable::new((3, 4));
-            let filter = Filter {
-                at: (8, 7),
-                left: TagElement::Variable(variable),
-                filter: FilterType::Default(DefaultFilter::new(Argument {
-                    at: (16, 2),
-                    argument_type: ArgumentType::Variable(Variable::new((16, 2))),
-                })),
-            };
+            let engine = EngineData::empty();
+            let template_string = "{{ text|truncatewords:count }}".to_string();
+            let template = Template::new_from_string(py, template_string, &engine).unwrap();
+            let context = PyDict::new(py);
+            context.set_item("text", "Hello  big world").unwrap();
+            context.set_item("count", "two").unwrap();
 
-            let rendered = filter.render(py, template, &mut context).unwrap();
-            assert_eq!(rendered, "Lily");
+            assert_eq!(
+                template.render(py, Some(context), None).unwrap(),
+                "Hello  big world"
+            );
         })
     }
 
     #[test]
-    fn test_render_filter_lower() {
+    fn test_render_filter_truncatewords() {
         pyo3::prepare_freethreaded_python();
 
         Python::with_gil(|py| {
-            let name = PyString::new(py, "Lily").into_any();
-            let context = HashMap::from([("name".to_string(), name.unbind())]);
-            let mut context = Context {
-                context,
-                request: None,
-                autoescape: false,
-            };
-            let template = TemplateString("{{ name|lower }}");
-            let variable = Variable::new((3, 4));
-            let filter = Filter {
-                at: (8, 5),
-                left: TagElement::Variable(variable),
-                filter: FilterType::Lower(LowerFilter),
-            };
+            let engine = EngineData::empty();
+            let template_string = "{{ text|truncatewords:2 }}".to_string();
+            let template = Template::new_from_string(py, template_string, &engine).unwrap();
+            let context = PyDict::new(py);
+            context.set_item("text", "Hello  big world").unwrap();
 
-            let rendered = filter.render(py, template, &mut context).unwrap();
-            assert_eq!(rendered, "lily");
+            assert_eq!(
+                template.render(py, Some(context), None).unwrap(),
+                "Hello big …"
+            );
         })
     }
 
     #[test]
-    fn test_render_filter_lower_missing_left() {
+    fn test_render_filter_truncatewords_html() {
         pyo3::prepare_freethreaded_python();
 
         Python::with_gil(|py| {
-            let context = HashMap::new();
-            let mut context = Context {
-                context,
-                request: None,
-                autoescape: false,
-            };
-            let template = TemplateString("{{ name|lower }}");
-            let variable = Variable::new((3, 4));
-            let filter = Filter {
-                at: (8, 5),
-                left: TagElement::Variable(variable),
-                filter: FilterType::Lower(LowerFilter),
-            };
-
-            let rendered = filter.render(py, template, &mut context).unwrap();
-            assert_eq!(rendered, "");
+            let engine = EngineData::empty();
+            let template_string = "{{ text|truncatewords_html:count }}".to_string();
+            let template = Template::new_from_string(py, template_string, &engine).unwrap();
+            let link = r##"<p>one <a href="#">two - three <br>four</a> five</p>"##;
+            let cases = [
+                (2, "<p>one two <b>three</b></p>", "<p>one two <b> …</b></p>"),
+                (
+                    3,
+                    "<p>one two <b>three</b></p>",
+                    "<p>one two <b>three</b></p>",
+                ),
+                (2, link, r##"<p>one <a href="#">two …</a></p>"##),
+                (
+                    4,
+                    link,
+                    r##"<p>one <a href="#">two - three <br> …</a></p>"##,
+                ),
+                (1, "<p>Tom &amp; Jerry</p>", "<p>Tom …</p>"),
+                (3, "<p>Tom &amp; Jerry</p>", "<p>Tom &amp; Jerry</p>"),
+                (5, "<p>one<!-- two --> three</p>", "<p>one three</p>"),
+                (0, "<p>one</p>", ""),
+            ];
+            for (count, text, expected) in cases {
+                let context = PyDict::new(py);
+                context.set_item("text", text).unwrap();
+                context.set_item("count", count).unwrap();
+
+                assert_eq!(template.render(py, Some(context), None).unwrap(), expected);
+            }
         })
     }
 
     #[test]
-    fn test_render_chained_filters() {
+    fn test_render_filter_truncatechars_html() {
         pyo3::prepare_freethreaded_python();
 
         Python::with_gil(|py| {
-            let context = HashMap::new();
-            let mut context = Context {
-                context,
-                request: None,
-                autoescape: false,
-            };
-            let template = TemplateString("{{ name|default:'Bryony'|lower }}");
-            let variable = Variable::new((3, 4));
-            let default = Filter {
-                at: (8, 7),
-                left: TagElement::Variable(variable),
-                filter: FilterType::Default(DefaultFilter::new(Argument {
-                    at: (16, 8),
-                    argument_type: ArgumentType::Text(Text::new((17, 6))),
-                })),
-            };
-            let lower = Filter {
-                at: (25, 5),
-                left: TagElement::Filter(Box::new(default)),
-                filter: FilterType::Lower(LowerFilter),
-            };
-
-            let rendered = lower.render(py, template, &mut context).unwrap();
-            assert_eq!(rendered, "bryony");
+            let engine = EngineData::empty();
+            let template_string = "{{ text|truncatechars_html:count }}".to_string();
+            let template = Template::new_from_string(py, template_string, &engine).unwrap();
+            let cases = [
+                (9, "<p>one two <b>three</b></p>", "<p>one two <b>…</b></p>"),
+                (7, "<p>one two <b>three</b></p>", "<p>one tw…</p>"),
+                (
+                    20,
+                    "<p>one two <b>three</b></p>",
+                    "<p>one two <b>three</b></p>",
+                ),
+                (4, "abcd", "abcd"),
+                (4, "<b>abcd", "<b>abc…</b>"),
+                (4, "abcde", "abc…"),
+                (4, "<p>a<br/>b<span/>c</p>", "<p>a<br/>b<span/></span>c</p>"),
+            ];
+            for (count, text, expected) in cases {
+                let context = PyDict::new(py);
+                context.set_item("text", text).unwrap();
+                context.set_item("count", count).unwrap();
+
+                assert_eq!(template.render(py, Some(context), None).unwrap(), expected);
+            }
         })
     }
 
@@ -833,13 +2612,9 @@ mod tests {
         Python::with_gil(|py| {
             let name = PyString::new(py, "Foo").into_any();
             let context = HashMap::from([("name".to_string(), name.unbind())]);
-            let mut context = Context {
-                context,
-                request: None,
-                autoescape: false,
-            };
+            let mut context = Context::new(context, None, false);
             let template = TemplateString("{{ name|upper }}");
-            let variable = Variable::new((3, 4));
+            let variable = Variable::new((3, 4), template);
             let filter = Filter {
                 at: (8, 5),
                 left: TagElement::Variable(variable),
@@ -857,13 +2632,9 @@ mod tests {
 
         Python::with_gil(|py| {
             let context = HashMap::new();
-            let mut context = Context {
-                context,
-                request: None,
-                autoescape: false,
-            };
+            let mut context = Context::new(context, None, false);
             let template = TemplateString("{{ name|upper }}");
-            let variable = Variable::new((3, 4));
+            let variable = Variable::new((3, 4), template);
             let filter = Filter {
                 at: (8, 5),
                 left: TagElement::Variable(variable),
@@ -874,4 +2645,20 @@ mod tests {
             assert_eq!(rendered, "");
         })
     }
+
+    #[test]
+    fn test_render_filter_yesno_none_two_labels() {
+        pyo3::prepare_freethreaded_python();
+
+        Python::with_gil(|py| {
+            let engine = EngineData::empty();
+            let template_string = "{{ var|yesno:'yes,no' }}".to_string();
+            let context = PyDict::new(py);
+            context.set_item("var", py.None()).unwrap();
+            let template = Template::new_from_string(py, template_string, &engine).unwrap();
+            let result = template.render(py, Some(context), None).unwrap();
+
+            assert_eq!(result, "no");
+        })
+    }
 }
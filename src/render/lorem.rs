@@ -0,0 +1,339 @@
+//! Placeholder text for the `{% lorem %}` tag, following Django's
+//! `django.utils.lorem_ipsum`.
+
+use super::types::RenderState;
+
+pub const COMMON_P: &str = "Lorem ipsum dolor sit amet, consectetur adipisicing elit, sed do eiusmod tempor incididunt ut labore et dolore magna aliqua. Ut enim ad minim veniam, quis nostrud exercitation ullamco laboris nisi ut aliquip ex ea commodo consequat. Duis aute irure dolor in reprehenderit in voluptate velit esse cillum dolore eu fugiat nulla pariatur. Excepteur sint occaecat cupidatat non proident, sunt in culpa qui officia deserunt mollit anim id est laborum.";
+
+const WORDS: [&str; 182] = [
+    "exercitationem",
+    "perferendis",
+    "perspiciatis",
+    "laborum",
+    "eveniet",
+    "sunt",
+    "iure",
+    "nam",
+    "nobis",
+    "eum",
+    "cum",
+    "officiis",
+    "excepturi",
+    "odio",
+    "consectetur",
+    "quasi",
+    "aut",
+    "quisquam",
+    "vel",
+    "eligendi",
+    "itaque",
+    "non",
+    "odit",
+    "tempore",
+    "quaerat",
+    "dignissimos",
+    "facilis",
+    "neque",
+    "nihil",
+    "expedita",
+    "vitae",
+    "vero",
+    "ipsum",
+    "nisi",
+    "animi",
+    "cumque",
+    "pariatur",
+    "velit",
+    "modi",
+    "natus",
+    "iusto",
+    "eaque",
+    "sequi",
+    "illo",
+    "sed",
+    "ex",
+    "et",
+    "voluptatibus",
+    "tempora",
+    "veritatis",
+    "ratione",
+    "assumenda",
+    "incidunt",
+    "nostrum",
+    "placeat",
+    "aliquid",
+    "fuga",
+    "provident",
+    "praesentium",
+    "rem",
+    "necessitatibus",
+    "suscipit",
+    "adipisci",
+    "quidem",
+    "possimus",
+    "voluptas",
+    "debitis",
+    "sint",
+    "accusantium",
+    "unde",
+    "sapiente",
+    "voluptate",
+    "qui",
+    "aspernatur",
+    "laudantium",
+    "soluta",
+    "amet",
+    "quo",
+    "aliquam",
+    "saepe",
+    "culpa",
+    "libero",
+    "ipsa",
+    "dicta",
+    "reiciendis",
+    "nesciunt",
+    "doloribus",
+    "autem",
+    "impedit",
+    "minima",
+    "maiores",
+    "repudiandae",
+    "ipsam",
+    "obcaecati",
+    "ullam",
+    "enim",
+    "totam",
+    "delectus",
+    "ducimus",
+    "quis",
+    "voluptates",
+    "dolores",
+    "molestiae",
+    "harum",
+    "dolorem",
+    "quia",
+    "voluptatem",
+    "molestias",
+    "magni",
+    "distinctio",
+    "omnis",
+    "illum",
+    "dolorum",
+    "voluptatum",
+    "ea",
+    "quas",
+    "quam",
+    "corporis",
+    "quae",
+    "blanditiis",
+    "atque",
+    "deserunt",
+    "laboriosam",
+    "earum",
+    "consequuntur",
+    "hic",
+    "cupiditate",
+    "quibusdam",
+    "accusamus",
+    "ut",
+    "rerum",
+    "error",
+    "minus",
+    "eius",
+    "ab",
+    "ad",
+    "nemo",
+    "fugit",
+    "officia",
+    "at",
+    "in",
+    "id",
+    "quos",
+    "reprehenderit",
+    "numquam",
+    "iste",
+    "fugiat",
+    "sit",
+    "inventore",
+    "beatae",
+    "repellendus",
+    "magnam",
+    "recusandae",
+    "quod",
+    "explicabo",
+    "doloremque",
+    "aperiam",
+    "consequatur",
+    "asperiores",
+    "commodi",
+    "optio",
+    "dolor",
+    "labore",
+    "temporibus",
+    "repellat",
+    "veniam",
+    "architecto",
+    "est",
+    "esse",
+    "mollitia",
+    "nulla",
+    "a",
+    "similique",
+    "eos",
+    "alias",
+    "dolore",
+    "tenetur",
+    "deleniti",
+    "porro",
+    "facere",
+    "maxime",
+    "corrupti",
+];
+
+const COMMON_WORDS: [&str; 19] = [
+    "lorem",
+    "ipsum",
+    "dolor",
+    "sit",
+    "amet",
+    "consectetur",
+    "adipisicing",
+    "elit",
+    "sed",
+    "do",
+    "eiusmod",
+    "tempor",
+    "incididunt",
+    "ut",
+    "labore",
+    "et",
+    "dolore",
+    "magna",
+    "aliqua",
+];
+
+/// A random integer between `low` and `high` inclusive, like Python's
+/// `random.randint`.
+fn randint(state: &mut RenderState, low: usize, high: usize) -> usize {
+    low + state.random(high - low + 1)
+}
+
+/// Choose `count` distinct words at random, like Python's `random.sample`.
+fn sample(state: &mut RenderState, count: usize) -> Vec<&'static str> {
+    let mut words = WORDS;
+    for i in 0..count {
+        let j = i + state.random(words.len() - i);
+        words.swap(i, j);
+    }
+    words[..count].to_vec()
+}
+
+/// A randomly generated sentence of lorem ipsum text.
+fn sentence(state: &mut RenderState) -> String {
+    let sections: Vec<String> = (0..randint(state, 1, 5))
+        .map(|_| {
+            let count = randint(state, 3, 12);
+            sample(state, count).join(" ")
+        })
+        .collect();
+    let sentence = sections.join(", ");
+    let mut chars = sentence.chars();
+    let first = chars.next().expect("Sentences are never empty");
+    let end = if state.random(2) == 0 { '?' } else { '.' };
+    format!("{}{}{end}", first.to_uppercase(), chars.as_str())
+}
+
+/// A randomly generated paragraph of lorem ipsum text.
+fn paragraph(state: &mut RenderState) -> String {
+    (0..randint(state, 1, 4))
+        .map(|_| sentence(state))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// `count` paragraphs of lorem ipsum text. When `common` is set, the first
+/// is the standard "Lorem ipsum..." paragraph.
+pub fn paragraphs(state: &mut RenderState, count: usize, common: bool) -> Vec<String> {
+    (0..count)
+        .map(|i| match common && i == 0 {
+            true => COMMON_P.to_string(),
+            false => paragraph(state),
+        })
+        .collect()
+}
+
+/// `count` words of lorem ipsum text. When `common` is set, the first words
+/// are the standard "lorem ipsum dolor...".
+///
+/// A negative `count` slices the common words like Python does.
+pub fn words(state: &mut RenderState, count: i64, common: bool) -> String {
+    let mut word_list = match common {
+        true => COMMON_WORDS.to_vec(),
+        false => Vec::new(),
+    };
+    let common_len = word_list.len() as i64;
+    if count > common_len {
+        let mut remaining = (count - common_len) as usize;
+        while remaining > 0 {
+            let count = remaining.min(WORDS.len());
+            remaining -= count;
+            word_list.extend(sample(state, count));
+        }
+    } else {
+        let end = match count < 0 {
+            true => (common_len + count).max(0),
+            false => count,
+        };
+        word_list.truncate(end as usize);
+    }
+    word_list.join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_words_common() {
+        let mut state = RenderState::with_seed(1);
+        assert_eq!(words(&mut state, 3, true), "lorem ipsum dolor");
+        assert_eq!(words(&mut state, 0, true), "");
+        assert_eq!(words(&mut state, -17, true), "lorem ipsum");
+    }
+
+    #[test]
+    fn test_words_count() {
+        let mut state = RenderState::with_seed(1);
+        let text = words(&mut state, 25, true);
+        let text_words: Vec<_> = text.split(' ').collect();
+        assert_eq!(text_words.len(), 25);
+        assert_eq!(text_words[..19], COMMON_WORDS);
+
+        let text = words(&mut state, 200, false);
+        assert_eq!(text.split(' ').count(), 200);
+    }
+
+    #[test]
+    fn test_random_with_seed() {
+        let mut state = RenderState::with_seed(42);
+        let first = paragraphs(&mut state, 2, false);
+        let mut state = RenderState::with_seed(42);
+        let second = paragraphs(&mut state, 2, false);
+        assert_eq!(first, second);
+
+        for paragraph in first {
+            assert_ne!(paragraph, COMMON_P);
+            assert!(paragraph.starts_with(|c: char| c.is_uppercase()));
+            assert!(paragraph.ends_with(['.', '?']));
+        }
+    }
+
+    #[test]
+    fn test_paragraphs_common() {
+        let mut state = RenderState::with_seed(1);
+        let text = paragraphs(&mut state, 2, true);
+        assert_eq!(text.len(), 2);
+        assert_eq!(text[0], COMMON_P);
+        assert_ne!(text[1], COMMON_P);
+    }
+}
@@ -1,16 +1,31 @@
 use std::borrow::Cow;
+use std::collections::HashMap;
+use std::rc::Rc;
+use std::sync::Arc;
 
+use html_escape::encode_quoted_attribute;
 use num_bigint::BigInt;
 use num_traits::cast::ToPrimitive;
-use pyo3::exceptions::PyAttributeError;
+use pyo3::exceptions::{PyAttributeError, PyTypeError, PyValueError};
+use pyo3::intern;
 use pyo3::prelude::*;
-use pyo3::types::{PyBool, PyDict, PyList, PyNone};
+use pyo3::sync::GILOnceCell;
+use pyo3::types::{PyBool, PyDict, PyFloat, PyInt, PyList, PyNone, PyString, PyType};
 
-use super::types::{Content, ContentString, Context};
+use super::common::{django_translation, render_invalid};
+use super::filters::ResolveFilter;
+use super::lorem::{paragraphs, words};
+use super::types::{BlockDefinition, Content, ContentString, Context};
 use super::{Evaluate, Render, RenderResult, Resolve, ResolveFailures, ResolveResult};
 use crate::error::PyRenderError;
-use crate::parse::{IfCondition, Tag, Url};
-use crate::template::django_rusty_templates::NoReverseMatch;
+use crate::parse::{
+    Block, Blocktranslate, Cycle, Extends, FilterBlock, Firstof, For, IfCondition, Include, Lorem,
+    LoremMethod, Now, Regroup, Tag, TagElement, TokenTree, Translate, Url, Widthratio,
+    collect_blocks,
+};
+use crate::template::django_rusty_templates::{
+    NoReverseMatch, Template, TemplateSyntaxError, VariableDoesNotExist,
+};
 use crate::types::TemplateString;
 use crate::utils::PyResultMethods;
 
@@ -72,14 +87,840 @@ impl Resolve for Url {
         };
         match &self.variable {
             None => Ok(Some(Content::Py(url?))),
-            Some(variable) => match url.ok_or_isinstance_of::<NoReverseMatch>(py)? {
-                Ok(url) => {
-                    context.context.insert(variable.clone(), url.unbind());
-                    Ok(None)
+            Some(variable) => {
+                // Django still assigns an empty string when reversing fails
+                let url = match url.ok_or_isinstance_of::<NoReverseMatch>(py)? {
+                    Ok(url) => url,
+                    Err(_) => PyString::new(py, "").into_any(),
+                };
+                context.insert(variable.clone(), url.unbind());
+                Ok(None)
+            }
+        }
+    }
+}
+
+static GROUPED_RESULT: GILOnceCell<Py<PyType>> = GILOnceCell::new();
+
+impl Render for Regroup {
+    /// Group consecutive items with equal keys into Django's `GroupedResult`
+    /// namedtuples. Like Django, the list isn't sorted first.
+    fn render<'t>(
+        &self,
+        py: Python<'_>,
+        template: TemplateString<'t>,
+        context: &mut Context,
+    ) -> RenderResult<'t> {
+        let failures = ResolveFailures::IgnoreVariableDoesNotExist;
+        let target = self
+            .target
+            .resolve(py, template, context, failures)?
+            .into_pyobject(py)?;
+        if target.is_none() {
+            let empty = PyList::empty(py).into_any();
+            context.insert(self.variable.clone(), empty.unbind());
+            return Ok(Cow::Borrowed(""));
+        }
+        let mut groups: Vec<(Bound<'_, PyAny>, Bound<'_, PyList>)> = Vec::new();
+        for item in target.try_iter()? {
+            let item = item?;
+            context.insert(self.variable.clone(), item.clone().unbind());
+            let key = self
+                .key
+                .resolve(py, template, context, failures)?
+                .into_pyobject(py)?;
+            match groups.last() {
+                Some((grouper, list)) if grouper.eq(&key)? => list.append(item)?,
+                _ => groups.push((key, PyList::new(py, [item])?)),
+            }
+        }
+        let grouped_result =
+            GROUPED_RESULT.import(py, "django.template.defaulttags", "GroupedResult")?;
+        let grouped = PyList::empty(py);
+        for (grouper, list) in groups {
+            grouped.append(grouped_result.call1((grouper, list))?)?;
+        }
+        context.insert(self.variable.clone(), grouped.into_any().unbind());
+        Ok(Cow::Borrowed(""))
+    }
+}
+
+impl Render for Lorem {
+    fn render<'t>(
+        &self,
+        py: Python<'_>,
+        template: TemplateString<'t>,
+        context: &mut Context,
+    ) -> RenderResult<'t> {
+        let count = self.count.resolve(
+            py,
+            template,
+            context,
+            ResolveFailures::IgnoreVariableDoesNotExist,
+        )?;
+        // Like Django, fall back to one when the count isn't an integer
+        let count = count
+            .and_then(|count| count.to_bigint())
+            .and_then(|count| count.to_i64())
+            .unwrap_or(1);
+        let state = &mut context.state;
+        let paragraph_count = count.max(0) as usize;
+        Ok(Cow::Owned(match self.method {
+            LoremMethod::Words => words(state, count, self.common),
+            LoremMethod::Paragraphs => paragraphs(state, paragraph_count, self.common)
+                .iter()
+                .map(|paragraph| format!("<p>{paragraph}</p>"))
+                .collect::<Vec<_>>()
+                .join("\n\n"),
+            LoremMethod::Blocks => paragraphs(state, paragraph_count, self.common).join("\n\n"),
+        }))
+    }
+}
+
+impl Resolve for Now {
+    fn resolve<'t, 'py>(
+        &self,
+        py: Python<'py>,
+        _template: TemplateString<'t>,
+        context: &mut Context,
+        _failures: ResolveFailures,
+    ) -> ResolveResult<'t, 'py> {
+        // `timezone.now` is aware when `USE_TZ` is set, in which case Django
+        // uses the current timezone.
+        let timezone = py.import("django.utils.timezone")?;
+        let mut now = timezone.getattr("now")?.call0()?;
+        if timezone.getattr("is_aware")?.call1((&now,))?.is_truthy()? {
+            now = timezone.getattr("localtime")?.call1((now,))?;
+        }
+        let date = py
+            .import("django.template.defaultfilters")?
+            .getattr("date")?;
+        let formatted = date.call1((now, &self.format))?;
+        match &self.variable {
+            None => Ok(Some(Content::String(ContentString::String(Cow::Owned(
+                formatted.str()?.to_string(),
+            ))))),
+            Some(variable) => {
+                context.insert(variable.clone(), formatted.unbind());
+                Ok(None)
+            }
+        }
+    }
+}
+
+/// Convert an operand with Python's `int` or `float`, returning `None` for
+/// the `ValueError` or `TypeError` Django catches. A missing variable becomes
+/// Django's empty `string_if_invalid`.
+fn convert_operand<'py>(
+    py: Python<'py>,
+    operand: Option<Content<'_, 'py>>,
+    convert: Bound<'py, PyType>,
+) -> PyResult<Option<Bound<'py, PyAny>>> {
+    let operand = match operand {
+        Some(operand) => operand.to_py(py)?,
+        None => PyString::new(py, "").into_any(),
+    };
+    match convert.call1((operand,)) {
+        Ok(converted) => Ok(Some(converted)),
+        Err(e) if e.is_instance_of::<PyValueError>(py) || e.is_instance_of::<PyTypeError>(py) => {
+            Ok(None)
+        }
+        Err(e) => Err(e),
+    }
+}
+
+impl Widthratio {
+    fn ratio<'py>(
+        py: Python<'py>,
+        value: Option<Content<'_, 'py>>,
+        max_value: Option<Content<'_, 'py>>,
+        max_width: Bound<'py, PyAny>,
+    ) -> PyResult<String> {
+        let float = py.get_type::<PyFloat>();
+        let Some(value) = convert_operand(py, value, float.clone())? else {
+            return Ok(String::new());
+        };
+        let Some(max_value) = convert_operand(py, max_value, float)? else {
+            return Ok(String::new());
+        };
+        let max_value: f64 = max_value.extract()?;
+        if max_value == 0.0 {
+            return Ok("0".to_string());
+        }
+        let Ok(max_width) = max_width.extract::<f64>() else {
+            return Ok(String::new());
+        };
+        let ratio = value.extract::<f64>()? / max_value * max_width;
+        // Python's `round` fails for infinity and NaN and rounds half to even
+        if !ratio.is_finite() {
+            return Ok(String::new());
+        }
+        // Adding zero turns `-0` into `0`
+        Ok(format!("{:.0}", ratio.round_ties_even() + 0.0))
+    }
+}
+
+impl Resolve for Widthratio {
+    fn resolve<'t, 'py>(
+        &self,
+        py: Python<'py>,
+        template: TemplateString<'t>,
+        context: &mut Context,
+        _failures: ResolveFailures,
+    ) -> ResolveResult<'t, 'py> {
+        let failures = ResolveFailures::IgnoreVariableDoesNotExist;
+        let max_value = self.max_value.resolve(py, template, context, failures)?;
+        let max_width = self.max_width.resolve(py, template, context, failures)?;
+        let Some(max_width) = convert_operand(py, max_width, py.get_type::<PyInt>())? else {
+            let error = "widthratio final argument must be a number";
+            return Err(TemplateSyntaxError::new_err(error).into());
+        };
+        let value = self.value.resolve(py, template, context, failures)?;
+        let ratio = Self::ratio(py, value, max_value, max_width)?;
+        match &self.variable {
+            None => Ok(Some(Content::String(ContentString::String(Cow::Owned(
+                ratio,
+            ))))),
+            Some(variable) => {
+                let ratio = PyString::new(py, &ratio).into_any();
+                context.insert(variable.clone(), ratio.unbind());
+                Ok(None)
+            }
+        }
+    }
+}
+
+/// Render a block defined in another template of the inheritance chain.
+fn render_block_definition(
+    py: Python<'_>,
+    definition: Rc<BlockDefinition>,
+    context: &mut Context,
+) -> Result<String, PyRenderError> {
+    let template = TemplateString(&definition.template);
+    context.push_scope();
+    context.blocks.enter(&definition.block.name);
+    let rendered = definition.block.nodes.render(py, template, context);
+    context.blocks.exit();
+    context.pop_scope();
+    let rendered = match rendered {
+        Ok(rendered) => rendered.into_owned(),
+        Err(err) => {
+            // Error locations refer to the template defining the block, not
+            // the one currently being rendered.
+            let err = err.try_into_render_error()?;
+            let source = definition.template.to_string();
+            return Err(VariableDoesNotExist::with_source_code(err.into(), source).into());
+        }
+    };
+    context.blocks.push(definition);
+    Ok(rendered)
+}
+
+/// Render the parent's version of the innermost block being rendered, for
+/// `{{ block.super }}`.
+pub fn render_block_super(py: Python<'_>, context: &mut Context) -> Result<String, PyRenderError> {
+    let name = match context.blocks.active() {
+        Some(name) => name.to_string(),
+        None => return Ok(String::new()),
+    };
+    match context.blocks.pop(&name) {
+        Some(definition) => render_block_definition(py, definition, context),
+        None => Ok(String::new()),
+    }
+}
+
+impl Render for Block {
+    fn render<'t>(
+        &self,
+        py: Python<'_>,
+        template: TemplateString<'t>,
+        context: &mut Context,
+    ) -> RenderResult<'t> {
+        if let Some(definition) = context.blocks.pop(&self.name) {
+            return Ok(Cow::Owned(render_block_definition(
+                py, definition, context,
+            )?));
+        }
+        context.push_scope();
+        context.blocks.enter(&self.name);
+        let rendered = self.nodes.render(py, template, context);
+        context.blocks.exit();
+        context.pop_scope();
+        rendered
+    }
+}
+
+impl Extends {
+    fn render_parent(
+        &self,
+        py: Python<'_>,
+        template: TemplateString<'_>,
+        parent: &Template,
+        context: &mut Context,
+    ) -> PyResult<String> {
+        let source = Rc::from(template.0);
+        context.blocks.add_blocks(&source, self.blocks.iter());
+        let parent_extends = parent
+            .nodes
+            .iter()
+            .any(|node| matches!(node, TokenTree::Tag(Tag::Extends(_))));
+        // The root template's blocks are the last fallback for `block.super`
+        if !parent_extends {
+            let mut blocks = Vec::new();
+            collect_blocks(&parent.nodes, &mut blocks);
+            let source = Rc::from(parent.template.as_str());
+            context.blocks.add_blocks(&source, blocks.into_iter());
+        }
+        parent._render(py, context)
+    }
+}
+
+impl Render for Extends {
+    fn render<'t>(
+        &self,
+        py: Python<'_>,
+        template: TemplateString<'t>,
+        context: &mut Context,
+    ) -> RenderResult<'t> {
+        let parent = match self
+            .parent
+            .resolve(py, template, context, ResolveFailures::Raise)?
+        {
+            Some(parent) => parent.to_py(py)?,
+            None => PyString::new(py, "").into_any(),
+        };
+        if !parent.is_truthy()? {
+            let mut error = format!(
+                "Invalid template name in 'extends' tag: {}.",
+                parent.repr()?
+            );
+            if matches!(self.parent, TagElement::Variable(_) | TagElement::Filter(_)) {
+                let token = template.content(self.parent_at);
+                error.push_str(&format!(" Got this from the '{token}' variable."));
+            }
+            return Err(TemplateSyntaxError::new_err(error).into());
+        }
+        // The parent can be a `Template` instead of a name
+        let rendered = match parent.downcast_into::<Template>() {
+            Ok(parent) => self.render_parent(py, template, &parent.borrow(), context)?,
+            Err(e) => {
+                let parent_name = e.into_inner().str()?;
+                let engine = context
+                    .engine
+                    .clone()
+                    .expect("Templates are rendered with their engine");
+                let parent = engine.get_template(py, parent_name.to_str()?)?;
+                self.render_parent(py, template, &parent, context)?
+            }
+        };
+        Ok(Cow::Owned(rendered))
+    }
+}
+
+impl Include {
+    fn render_template(
+        &self,
+        py: Python<'_>,
+        included: &Template,
+        values: Vec<(String, Py<PyAny>)>,
+        context: &mut Context,
+    ) -> PyResult<String> {
+        // The included template's blocks and node state are independent of
+        // the current template's
+        let blocks = std::mem::take(&mut context.blocks);
+        let nodes = std::mem::take(&mut context.state.nodes);
+        let rendered = if self.only {
+            let mut scope = Context::builtins(py);
+            scope.extend(values);
+            let request = context
+                .request
+                .as_ref()
+                .map(|request| request.clone_ref(py));
+            let mut isolated = Context::new(scope, request, context.autoescape);
+            isolated.engine = context.engine.clone();
+            std::mem::swap(&mut isolated.state, &mut context.state);
+            let rendered = included._render(py, &mut isolated);
+            std::mem::swap(&mut isolated.state, &mut context.state);
+            rendered
+        } else {
+            context.push_scope();
+            for (key, value) in values {
+                context.insert(key, value);
+            }
+            let rendered = included._render(py, context);
+            context.pop_scope();
+            rendered
+        };
+        context.state.nodes = nodes;
+        context.blocks = blocks;
+        rendered
+    }
+}
+
+impl Render for Include {
+    fn render<'t>(
+        &self,
+        py: Python<'_>,
+        template: TemplateString<'t>,
+        context: &mut Context,
+    ) -> RenderResult<'t> {
+        let mut values = Vec::with_capacity(self.kwargs.len());
+        for (key, value) in &self.kwargs {
+            let value = match value.resolve(py, template, context, ResolveFailures::Raise)? {
+                Some(value) => value.to_py(py)?.unbind(),
+                None => PyString::new(py, "").into_any().unbind(),
+            };
+            values.push((key.clone(), value));
+        }
+
+        let template_name =
+            self.template_name
+                .resolve(py, template, context, ResolveFailures::Raise)?;
+        let rendered = match template_name {
+            // A `Template` can be included directly, instead of by name
+            Some(Content::Py(included)) if included.is_instance_of::<Template>() => {
+                let included = included
+                    .downcast_into::<Template>()
+                    .expect("The type was checked above");
+                self.render_template(py, &included.borrow(), values, context)?
+            }
+            template_name => {
+                let template_name = match template_name {
+                    Some(template_name) => template_name.resolve_string(context)?.into_raw(),
+                    None => Cow::Borrowed(""),
+                };
+                let engine = context
+                    .engine
+                    .clone()
+                    .expect("Templates are rendered with their engine");
+                let included = engine.get_template(py, &template_name)?;
+                self.render_template(py, &included, values, context)?
+            }
+        };
+        Ok(Cow::Owned(rendered))
+    }
+}
+
+impl Resolve for Cycle {
+    fn resolve<'t, 'py>(
+        &self,
+        py: Python<'py>,
+        template: TemplateString<'t>,
+        context: &mut Context,
+        failures: ResolveFailures,
+    ) -> ResolveResult<'t, 'py> {
+        let key = Arc::as_ptr(&self.values) as usize;
+        let index = context.state.cycle(key, self.values.len());
+        let value = self.values[index].resolve(py, template, context, failures)?;
+        if let Some(variable) = &self.variable {
+            let py_value = match &value {
+                Some(value) => value.to_py(py)?,
+                None => PyString::new(py, "").into_any(),
+            };
+            context.set_upward(variable.clone(), py_value.unbind());
+        }
+        match self.silent {
+            true => Ok(None),
+            false => Ok(value),
+        }
+    }
+}
+
+/// Substitute `%(name)s` placeholders and `%%` escapes like Python's `%`
+/// formatting, or `None` if the message doesn't match the values.
+fn interpolate(message: &str, values: &HashMap<&str, String>) -> Option<String> {
+    let mut interpolated = String::with_capacity(message.len());
+    let mut rest = message;
+    while let Some(index) = rest.find('%') {
+        interpolated.push_str(&rest[..index]);
+        rest = &rest[index + 1..];
+        if let Some(after) = rest.strip_prefix('%') {
+            interpolated.push('%');
+            rest = after;
+            continue;
+        }
+        let name = rest.strip_prefix('(')?;
+        let end = name.find(")s")?;
+        interpolated.push_str(values.get(&name[..end])?);
+        rest = &name[end + 2..];
+    }
+    interpolated.push_str(rest);
+    Some(interpolated)
+}
+
+fn is_number(value: &Bound<'_, PyAny>) -> PyResult<bool> {
+    if value.is_instance_of::<PyInt>() || value.is_instance_of::<PyFloat>() {
+        return Ok(true);
+    }
+    let py = value.py();
+    let decimal = py.import(intern!(py, "decimal"))?;
+    value.is_instance(&decimal.getattr(intern!(py, "Decimal"))?)
+}
+
+impl Blocktranslate {
+    fn render_message(
+        &self,
+        py: Python<'_>,
+        template: TemplateString<'_>,
+        context: &mut Context,
+    ) -> Result<String, PyRenderError> {
+        let mut values = Vec::with_capacity(self.kwargs.len());
+        for (key, value) in &self.kwargs {
+            let value = match value.resolve(
+                py,
+                template,
+                context,
+                ResolveFailures::IgnoreVariableDoesNotExist,
+            )? {
+                Some(value) => value.to_py(py)?.unbind(),
+                None => PyString::new(py, "").into_any().unbind(),
+            };
+            values.push((key.clone(), value));
+        }
+        for (key, value) in values {
+            context.insert(key, value);
+        }
+
+        let django_translation = django_translation(py)?;
+        let (message, translated) = match &self.count {
+            None => {
+                let translated = match &django_translation {
+                    Some(django_translation) => django_translation
+                        .getattr(intern!(py, "gettext"))?
+                        .call1((&self.singular,))?
+                        .extract::<String>()?,
+                    None => self.singular.clone(),
+                };
+                (&self.singular, translated)
+            }
+            Some((name, counter)) => {
+                let count = match counter.resolve(
+                    py,
+                    template,
+                    context,
+                    ResolveFailures::IgnoreVariableDoesNotExist,
+                )? {
+                    Some(count) => count.to_py(py)?,
+                    None => PyString::new(py, "").into_any(),
+                };
+                if !is_number(&count)? {
+                    let error =
+                        format!("'{name}' argument to '{}' tag must be a number.", self.tag);
+                    return Err(TemplateSyntaxError::new_err(error).into());
                 }
-                Err(_) => Ok(None),
+                context.insert(name.clone(), count.clone().unbind());
+                let plural = self
+                    .plural
+                    .as_ref()
+                    .expect("A counted message always has a plural");
+                let message = match count.eq(1)? {
+                    true => &self.singular,
+                    false => plural,
+                };
+                let translated = match &django_translation {
+                    Some(django_translation) => django_translation
+                        .getattr(intern!(py, "ngettext"))?
+                        .call1((&self.singular, plural, count))?
+                        .extract::<String>()?,
+                    None => message.clone(),
+                };
+                (message, translated)
+            }
+        };
+
+        let mut values = HashMap::new();
+        for variable in &self.variables {
+            let value = match context.get(variable) {
+                Some(value) => Content::Py(value.bind(py).clone())
+                    .render(context)?
+                    .into_owned(),
+                None => render_invalid(context.string_if_invalid(variable), context).into_owned(),
+            };
+            values.insert(variable.as_str(), value);
+        }
+        // Like Django, a translation with mismatched placeholders falls back
+        // to the untranslated message.
+        if let Some(rendered) =
+            interpolate(&translated, &values).or_else(|| interpolate(message, &values))
+        {
+            return Ok(rendered);
+        }
+        // The untranslated message can still be malformed when a variable
+        // name contains a placeholder's closing `)s`.
+        let data = PyDict::new(py);
+        for variable in &self.variables {
+            data.set_item(variable, &values[variable.as_str()])?;
+        }
+        let error = format!(
+            "'{}' is unable to format string returned by gettext: {} using {}",
+            self.tag,
+            PyString::new(py, message).repr()?,
+            data.repr()?
+        );
+        Err(TemplateSyntaxError::new_err(error).into())
+    }
+}
+
+impl Render for Blocktranslate {
+    fn render<'t>(
+        &self,
+        py: Python<'_>,
+        template: TemplateString<'t>,
+        context: &mut Context,
+    ) -> RenderResult<'t> {
+        context.push_scope();
+        let rendered = self.render_message(py, template, context);
+        context.pop_scope();
+        Ok(Cow::Owned(rendered?))
+    }
+}
+
+impl Translate {
+    /// Resolve `element`, translating the message before applying any
+    /// filters like Django does.
+    fn resolve_message<'t, 'py>(
+        &self,
+        element: &TagElement,
+        message_context: Option<&str>,
+        py: Python<'py>,
+        template: TemplateString<'t>,
+        context: &mut Context,
+    ) -> ResolveResult<'t, 'py> {
+        if let TagElement::Filter(filter) = element {
+            let left =
+                self.resolve_message(&filter.left, message_context, py, template, context)?;
+            return filter.filter.resolve(left, py, template, context);
+        }
+        let message = element.resolve(
+            py,
+            template,
+            context,
+            ResolveFailures::IgnoreVariableDoesNotExist,
+        )?;
+        let message = match message {
+            Some(message) if !self.noop => message.resolve_string(context)?,
+            message => return Ok(message),
+        };
+        let Some(django_translation) = django_translation(py)? else {
+            return Ok(Some(Content::String(message)));
+        };
+        // Percent signs are doubled in messages, as for `{% blocktranslate %}`
+        let msgid = message.as_raw().replace('%', "%%");
+        let translated = match message_context {
+            Some(message_context) => django_translation
+                .getattr(intern!(py, "pgettext"))?
+                .call1((message_context, msgid))?,
+            None => django_translation
+                .getattr(intern!(py, "gettext"))?
+                .call1((msgid,))?,
+        };
+        let translated = translated.extract::<String>()?;
+        Ok(Some(message.map_content(|_| Cow::Owned(translated))))
+    }
+}
+
+impl Render for Translate {
+    fn render<'t>(
+        &self,
+        py: Python<'_>,
+        template: TemplateString<'t>,
+        context: &mut Context,
+    ) -> RenderResult<'t> {
+        let message_context = match &self.message_context {
+            Some(message_context) => match message_context.resolve(
+                py,
+                template,
+                context,
+                ResolveFailures::IgnoreVariableDoesNotExist,
+            )? {
+                Some(message_context) => Some(
+                    message_context
+                        .resolve_string(context)?
+                        .into_raw()
+                        .into_owned(),
+                ),
+                None => Some(String::new()),
             },
+            None => None,
+        };
+        let message = self.resolve_message(
+            &self.message,
+            message_context.as_deref(),
+            py,
+            template,
+            context,
+        )?;
+        let rendered = match message {
+            Some(message) => message.render(context)?.replace("%%", "%"),
+            None => String::new(),
+        };
+        match &self.variable {
+            None => Ok(Cow::Owned(rendered)),
+            Some(variable) => {
+                // The rendered message is already escaped
+                let rendered = match context.autoescape {
+                    true => ContentString::HtmlSafe(Cow::Owned(rendered)),
+                    false => ContentString::String(Cow::Owned(rendered)),
+                };
+                let value = Content::String(rendered).to_py(py)?;
+                context.insert(variable.clone(), value.unbind());
+                Ok(Cow::Borrowed(""))
+            }
+        }
+    }
+}
+
+impl Render for FilterBlock {
+    fn render<'t>(
+        &self,
+        py: Python<'_>,
+        template: TemplateString<'t>,
+        context: &mut Context,
+    ) -> RenderResult<'t> {
+        // Django marks the rendered block safe before applying the filters
+        let rendered = self.nodes.render(py, template, context)?;
+        let mut content = Some(Content::String(ContentString::HtmlSafe(rendered)));
+        for filter in &self.filters {
+            content = filter.resolve(content, py, template, context)?;
+        }
+        // Like any tag's output, the filtered block isn't autoescaped
+        Ok(match content {
+            None => Cow::Borrowed(""),
+            Some(Content::Py(content)) => Cow::Owned(content.str()?.to_string()),
+            Some(Content::String(content)) => content.into_raw(),
+            Some(content) => content.render(context)?,
+        })
+    }
+}
+
+impl Resolve for Firstof {
+    fn resolve<'t, 'py>(
+        &self,
+        py: Python<'py>,
+        template: TemplateString<'t>,
+        context: &mut Context,
+        _failures: ResolveFailures,
+    ) -> ResolveResult<'t, 'py> {
+        let mut first = None;
+        for arg in &self.args {
+            let content = arg.resolve(
+                py,
+                template,
+                context,
+                ResolveFailures::IgnoreVariableDoesNotExist,
+            )?;
+            if content.evaluate(py, template, context).unwrap_or(false) {
+                first = content;
+                break;
+            }
+        }
+        let Some(variable) = &self.variable else {
+            return Ok(first);
+        };
+        // Django stores the value as it would have been rendered
+        let rendered = match first {
+            Some(content) => content.render(context)?,
+            None => Cow::Borrowed(""),
+        };
+        let rendered = Content::String(ContentString::HtmlSafe(rendered)).to_py(py)?;
+        context.insert(variable.clone(), rendered.unbind());
+        Ok(None)
+    }
+}
+
+impl For {
+    fn render_body<'py>(
+        &self,
+        py: Python<'py>,
+        template: TemplateString<'_>,
+        context: &mut Context,
+        forloop: &Bound<'py, PyDict>,
+        values: Vec<Bound<'py, PyAny>>,
+    ) -> Result<String, PyRenderError> {
+        let len = values.len();
+        let mut rendered = String::new();
+        for (index, value) in values.into_iter().enumerate() {
+            forloop.set_item("counter0", index)?;
+            forloop.set_item("counter", index + 1)?;
+            forloop.set_item("revcounter", len - index)?;
+            forloop.set_item("revcounter0", len - index - 1)?;
+            forloop.set_item("first", index == 0)?;
+            forloop.set_item("last", index == len - 1)?;
+            self.bind_variables(py, value, context)?;
+            rendered.push_str(&self.body.render(py, template, context)?);
+        }
+        Ok(rendered)
+    }
+
+    fn bind_variables<'py>(
+        &self,
+        py: Python<'py>,
+        value: Bound<'py, PyAny>,
+        context: &mut Context,
+    ) -> PyResult<()> {
+        if let [variable] = self.variables.as_slice() {
+            context.insert(variable.clone(), value.unbind());
+            return Ok(());
+        }
+        let len = value
+            .len()
+            .ok_or_isinstance_of::<PyTypeError>(py)?
+            .unwrap_or(1);
+        if len != self.variables.len() {
+            return Err(PyValueError::new_err(format!(
+                "Need {} values to unpack in for loop; got {}. ",
+                self.variables.len(),
+                len,
+            )));
         }
+        for (variable, item) in self.variables.iter().zip(value.try_iter()?) {
+            context.insert(variable.clone(), item?.unbind());
+        }
+        Ok(())
+    }
+}
+
+impl Render for For {
+    fn render<'t>(
+        &self,
+        py: Python<'_>,
+        template: TemplateString<'t>,
+        context: &mut Context,
+    ) -> RenderResult<'t> {
+        let iterable = match self.iterable.resolve(
+            py,
+            template,
+            context,
+            ResolveFailures::IgnoreVariableDoesNotExist,
+        )? {
+            Some(iterable) => iterable.to_py(py)?,
+            None => return self.empty.render(py, template, context),
+        };
+        if iterable.is_none() {
+            return self.empty.render(py, template, context);
+        }
+        let mut values = iterable.try_iter()?.collect::<PyResult<Vec<_>>>()?;
+        if values.is_empty() {
+            return self.empty.render(py, template, context);
+        }
+        if self.reversed {
+            values.reverse();
+        }
+
+        let forloop = PyDict::new(py);
+        let parentloop = match context.get("forloop") {
+            Some(parentloop) => parentloop.clone_ref(py),
+            None => PyDict::new(py).into_any().unbind(),
+        };
+        forloop.set_item("parentloop", parentloop)?;
+
+        // `forloop` and the loop variables only exist inside the loop, even
+        // when rendering the body fails.
+        context.push_scope();
+        context.insert("forloop".to_string(), forloop.clone().into_any().unbind());
+        let rendered = self.render_body(py, template, context, &forloop, values);
+        context.pop_scope();
+        Ok(Cow::Owned(rendered?))
     }
 }
 
@@ -90,12 +931,7 @@ impl Evaluate for Content<'_, '_> {
         _template: TemplateString<'_>,
         _context: &mut Context,
     ) -> Option<bool> {
-        Some(match self {
-            Self::Py(obj) => obj.is_truthy().unwrap_or(false),
-            Self::String(s) => !s.as_raw().is_empty(),
-            Self::Float(f) => *f != 0.0,
-            Self::Int(n) => *n != BigInt::ZERO,
-        })
+        Some(self.is_truthy().unwrap_or(false))
     }
 }
 
@@ -115,6 +951,10 @@ trait PyCmp<T> {
     fn gte(&self, other: &T) -> bool;
 }
 
+fn bool_to_int(b: bool) -> Content<'static, 'static> {
+    Content::Int(BigInt::from(b as u8))
+}
+
 impl PyCmp<Content<'_, '_>> for Content<'_, '_> {
     fn eq(&self, other: &Content<'_, '_>) -> bool {
         match (self, other) {
@@ -142,6 +982,9 @@ impl PyCmp<Content<'_, '_>> for Content<'_, '_> {
                 }
             }
             (Self::String(obj), Content::String(other)) => obj.as_raw() == other.as_raw(),
+            // Python's `bool` is a subclass of `int`
+            (Self::Bool(obj), other) => bool_to_int(*obj).eq(other),
+            (obj, Content::Bool(other)) => obj.eq(&bool_to_int(*other)),
             _ => false,
         }
     }
@@ -172,6 +1015,9 @@ impl PyCmp<Content<'_, '_>> for Content<'_, '_> {
                 }
             }
             (Self::String(obj), Content::String(other)) => obj.as_raw() < other.as_raw(),
+            // Python's `bool` is a subclass of `int`
+            (Self::Bool(obj), other) => bool_to_int(*obj).lt(other),
+            (obj, Content::Bool(other)) => obj.lt(&bool_to_int(*other)),
             _ => false,
         }
     }
@@ -202,6 +1048,9 @@ impl PyCmp<Content<'_, '_>> for Content<'_, '_> {
                 }
             }
             (Self::String(obj), Content::String(other)) => obj.as_raw() > other.as_raw(),
+            // Python's `bool` is a subclass of `int`
+            (Self::Bool(obj), other) => bool_to_int(*obj).gt(other),
+            (obj, Content::Bool(other)) => obj.gt(&bool_to_int(*other)),
             _ => false,
         }
     }
@@ -232,6 +1081,9 @@ impl PyCmp<Content<'_, '_>> for Content<'_, '_> {
                 }
             }
             (Self::String(obj), Content::String(other)) => obj.as_raw() <= other.as_raw(),
+            // Python's `bool` is a subclass of `int`
+            (Self::Bool(obj), other) => bool_to_int(*obj).lte(other),
+            (obj, Content::Bool(other)) => obj.lte(&bool_to_int(*other)),
             _ => false,
         }
     }
@@ -262,6 +1114,9 @@ impl PyCmp<Content<'_, '_>> for Content<'_, '_> {
                 }
             }
             (Self::String(obj), Content::String(other)) => obj.as_raw() >= other.as_raw(),
+            // Python's `bool` is a subclass of `int`
+            (Self::Bool(obj), other) => bool_to_int(*obj).gte(other),
+            (obj, Content::Bool(other)) => obj.gte(&bool_to_int(*other)),
             _ => false,
         }
     }
@@ -324,6 +1179,7 @@ impl PyCmp<bool> for Option<Content<'_, '_>> {
                     false => 0.into(),
                 }
             }
+            Some(Content::Bool(b)) => b == other,
             _ => false,
         }
     }
@@ -343,6 +1199,7 @@ impl PyCmp<bool> for Option<Content<'_, '_>> {
                     false => 0.into(),
                 }
             }
+            Some(Content::Bool(b)) => b < other,
             _ => false,
         }
     }
@@ -362,6 +1219,7 @@ impl PyCmp<bool> for Option<Content<'_, '_>> {
                     false => 0.into(),
                 }
             }
+            Some(Content::Bool(b)) => b > other,
             _ => false,
         }
     }
@@ -381,6 +1239,7 @@ impl PyCmp<bool> for Option<Content<'_, '_>> {
                     false => 0.into(),
                 }
             }
+            Some(Content::Bool(b)) => b <= other,
             _ => false,
         }
     }
@@ -400,6 +1259,7 @@ impl PyCmp<bool> for Option<Content<'_, '_>> {
                     false => 0.into(),
                 }
             }
+            Some(Content::Bool(b)) => b >= other,
             _ => false,
         }
     }
@@ -422,7 +1282,7 @@ impl Contains<Option<Content<'_, '_>>> for Content<'_, '_> {
             }
             Some(Content::String(other)) => match self {
                 Self::String(obj) => Some(obj.as_raw().contains(other.as_raw().as_ref())),
-                Self::Int(_) | Self::Float(_) => None,
+                Self::Int(_) | Self::Float(_) | Self::Bool(_) => None,
                 Self::Py(obj) => obj.contains(other).ok(),
             },
             Some(Content::Int(n)) => match self {
@@ -433,6 +1293,10 @@ impl Contains<Option<Content<'_, '_>>> for Content<'_, '_> {
                 Self::Py(obj) => obj.contains(f).ok(),
                 _ => None,
             },
+            Some(Content::Bool(b)) => match self {
+                Self::Py(obj) => obj.contains(b).ok(),
+                _ => None,
+            },
         }
     }
 }
@@ -568,7 +1432,7 @@ impl Evaluate for IfCondition {
                     (Resolved::Content(l), Resolved::Content(r)) => l.lt(&r),
                     (Resolved::Evaluate(l), Resolved::Content(r)) => r.gt(&l),
                     (Resolved::Content(l), Resolved::Evaluate(r)) => l.lt(&r),
-                    (Resolved::Evaluate(l), Resolved::Evaluate(r)) => l < r,
+                    (Resolved::Evaluate(l), Resolved::Evaluate(r)) => !l & r,
                 }
             }
             Self::GreaterThan(inner) => {
@@ -580,7 +1444,7 @@ impl Evaluate for IfCondition {
                     (Resolved::Content(l), Resolved::Content(r)) => l.gt(&r),
                     (Resolved::Evaluate(l), Resolved::Content(r)) => r.lt(&l),
                     (Resolved::Content(l), Resolved::Evaluate(r)) => l.gt(&r),
-                    (Resolved::Evaluate(l), Resolved::Evaluate(r)) => l > r,
+                    (Resolved::Evaluate(l), Resolved::Evaluate(r)) => l & !r,
                 }
             }
             Self::LessThanEqual(inner) => {
@@ -645,6 +1509,11 @@ impl Evaluate for IfCondition {
                 match inner {
                     (Resolved::Content(l), Resolved::Content(r)) => match (l, r) {
                         (Some(Content::Py(left)), Some(Content::Py(right))) => left.is(&right),
+                        (Some(Content::Bool(left)), Some(Content::Py(right)))
+                        | (Some(Content::Py(right)), Some(Content::Bool(left))) => {
+                            right.is(PyBool::new(py, left).as_any())
+                        }
+                        (Some(Content::Bool(left)), Some(Content::Bool(right))) => left == right,
                         (Some(Content::Py(obj)), None) | (None, Some(Content::Py(obj))) => {
                             obj.is(PyNone::get(py).as_any())
                         }
@@ -654,6 +1523,7 @@ impl Evaluate for IfCondition {
                     (Resolved::Evaluate(l), Resolved::Content(r)) => match r {
                         None => false,
                         Some(Content::Py(right)) => right.is(PyBool::new(py, l).as_any()),
+                        Some(Content::Bool(right)) => l == right,
                         _ => false,
                     },
                     _ => unreachable!(),
@@ -667,6 +1537,11 @@ impl Evaluate for IfCondition {
                 match inner {
                     (Resolved::Content(l), Resolved::Content(r)) => match (l, r) {
                         (Some(Content::Py(left)), Some(Content::Py(right))) => !left.is(&right),
+                        (Some(Content::Bool(left)), Some(Content::Py(right)))
+                        | (Some(Content::Py(right)), Some(Content::Bool(left))) => {
+                            !right.is(PyBool::new(py, left).as_any())
+                        }
+                        (Some(Content::Bool(left)), Some(Content::Bool(right))) => left != right,
                         (Some(Content::Py(obj)), None) | (None, Some(Content::Py(obj))) => {
                             !obj.is(PyNone::get(py).as_any())
                         }
@@ -675,10 +1550,12 @@ impl Evaluate for IfCondition {
                     },
                     (Resolved::Evaluate(l), Resolved::Content(r)) => match r {
                         Some(Content::Py(right)) => !right.is(PyBool::new(py, l).as_any()),
+                        Some(Content::Bool(right)) => l != right,
                         _ => true,
                     },
                     (Resolved::Content(l), Resolved::Evaluate(r)) => match l {
                         Some(Content::Py(left)) => !left.is(PyBool::new(py, r).as_any()),
+                        Some(Content::Bool(left)) => left != r,
                         _ => true,
                     },
                     (Resolved::Evaluate(l), Resolved::Evaluate(r)) => l != r,
@@ -688,6 +1565,60 @@ impl Evaluate for IfCondition {
     }
 }
 
+/// Remove whitespace between HTML tags, like Django's `strip_spaces_between_tags`.
+fn strip_spaces_between_tags(content: &str) -> String {
+    let mut stripped = String::with_capacity(content.len());
+    let mut rest = content;
+    while let Some(index) = rest.find('>') {
+        stripped.push_str(&rest[..=index]);
+        rest = &rest[index + 1..];
+        let trimmed = rest.trim_start();
+        if trimmed.len() < rest.len() && trimmed.starts_with('<') {
+            rest = trimmed;
+        }
+    }
+    stripped.push_str(rest);
+    stripped
+}
+
+/// Render the hidden CSRF input for the context's `csrf_token`, or nothing
+/// if it's missing, falsey or Django's `"NOTPROVIDED"` placeholder.
+fn render_csrf_token(py: Python<'_>, context: &Context) -> PyResult<String> {
+    let Some(csrf_token) = context.get("csrf_token") else {
+        return Ok(String::new());
+    };
+    let csrf_token = csrf_token.bind(py);
+    if !csrf_token.is_truthy()? || csrf_token.eq("NOTPROVIDED")? {
+        return Ok(String::new());
+    }
+    // Like Django's `format_html`, the token is escaped even when
+    // autoescaping is off, unless it's marked safe.
+    let csrf_token = match csrf_token
+        .getattr(intern!(py, "__html__"))
+        .ok_or_isinstance_of::<PyAttributeError>(py)?
+    {
+        Ok(html) => html.call0()?.extract::<String>()?,
+        Err(_) => encode_quoted_attribute(&csrf_token.str()?.to_string()).into_owned(),
+    };
+    Ok(format!(
+        r#"<input type="hidden" name="csrfmiddlewaretoken" value="{csrf_token}">"#
+    ))
+}
+
+/// Dump each context variable's `repr` and the loaded filter names. The
+/// output is always escaped, since the `repr`s may contain HTML.
+fn render_debug(py: Python<'_>, filters: &[String], context: &Context) -> PyResult<String> {
+    let mut output = String::new();
+    for (key, value) in context.flatten() {
+        let repr = value.bind(py).repr()?;
+        output.push_str(&format!("{key}: {}\n", repr.to_str()?));
+    }
+    if !filters.is_empty() {
+        output.push_str(&format!("\nLoaded filters: {}\n", filters.join(", ")));
+    }
+    Ok(encode_quoted_attribute(&output).into_owned())
+}
+
 impl Render for Tag {
     fn render<'t>(
         &self,
@@ -708,6 +1639,15 @@ impl Render for Tag {
                 context.autoescape = autoescape;
                 Cow::Owned(rendered.join(""))
             }
+            Self::Block(block) => block.render(py, template, context)?,
+            Self::Blocktranslate(blocktranslate) => blocktranslate.render(py, template, context)?,
+            Self::CsrfToken => Cow::Owned(render_csrf_token(py, context)?),
+            Self::Cycle(cycle) => cycle.render(py, template, context)?,
+            Self::Debug { filters } => Cow::Owned(render_debug(py, filters, context)?),
+            Self::Extends(extends) => extends.render(py, template, context)?,
+            Self::FilterBlock(filter_block) => filter_block.render(py, template, context)?,
+            Self::Firstof(firstof) => firstof.render(py, template, context)?,
+            Self::For(for_tag) => for_tag.render(py, template, context)?,
             Self::If {
                 condition,
                 truthy,
@@ -719,8 +1659,19 @@ impl Render for Tag {
                     falsey.render(py, template, context)?
                 }
             }
+            Self::Include(include) => include.render(py, template, context)?,
             Self::Load => Cow::Borrowed(""),
+            Self::Lorem(lorem) => lorem.render(py, template, context)?,
+            Self::Now(now) => now.render(py, template, context)?,
+            Self::Regroup(regroup) => regroup.render(py, template, context)?,
+            Self::Spaceless { nodes } => {
+                let rendered = nodes.render(py, template, context)?;
+                Cow::Owned(strip_spaces_between_tags(rendered.trim()))
+            }
+            Self::Templatetag(literal) => Cow::Borrowed(literal),
+            Self::Translate(translate) => translate.render(py, template, context)?,
             Self::Url(url) => url.render(py, template, context)?,
+            Self::Widthratio(widthratio) => widthratio.render(py, template, context)?,
         })
     }
 }
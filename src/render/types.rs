@@ -1,19 +1,263 @@
 use std::borrow::Cow;
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
+use std::hash::{BuildHasher, Hasher, RandomState};
+use std::rc::Rc;
+use std::sync::Arc;
 
 use html_escape::encode_quoted_attribute;
 use num_bigint::{BigInt, ToBigInt};
-use pyo3::exceptions::PyAttributeError;
+use pyo3::exceptions::{PyAttributeError, PyRecursionError};
 use pyo3::intern;
 use pyo3::prelude::*;
-use pyo3::types::{PyInt, PyString, PyType};
+use pyo3::types::{PyBool, PyInt, PyString, PyType};
 
+use crate::parse::Block;
+use crate::template::django_rusty_templates::EngineData;
 use crate::utils::PyResultMethods;
 
+/// A `{% block %}` along with the source of the template defining it.
+pub struct BlockDefinition {
+    pub template: Rc<str>,
+    pub block: Block,
+}
+
+/// The blocks available while rendering a chain of templates using
+/// `{% extends %}`. Each block name maps to its definitions, from the least
+/// derived template to the most derived.
+#[derive(Default)]
+pub struct BlockContext {
+    blocks: HashMap<String, Vec<Rc<BlockDefinition>>>,
+    active: Vec<String>,
+}
+
+impl BlockContext {
+    /// Add blocks from a parent template, which are overridden by any blocks
+    /// already added.
+    pub fn add_blocks<'a>(&mut self, template: &Rc<str>, blocks: impl Iterator<Item = &'a Block>) {
+        for block in blocks {
+            let definition = BlockDefinition {
+                template: template.clone(),
+                block: block.clone(),
+            };
+            self.blocks
+                .entry(block.name.clone())
+                .or_default()
+                .insert(0, Rc::new(definition));
+        }
+    }
+
+    pub fn pop(&mut self, name: &str) -> Option<Rc<BlockDefinition>> {
+        self.blocks.get_mut(name)?.pop()
+    }
+
+    pub fn push(&mut self, definition: Rc<BlockDefinition>) {
+        self.blocks
+            .entry(definition.block.name.clone())
+            .or_default()
+            .push(definition);
+    }
+
+    /// The name of the innermost block currently being rendered.
+    pub fn active(&self) -> Option<&str> {
+        self.active.last().map(String::as_str)
+    }
+
+    pub fn enter(&mut self, name: &str) {
+        self.active.push(name.to_string());
+    }
+
+    pub fn exit(&mut self) {
+        self.active.pop();
+    }
+}
+
+/// How deeply templates may nest through `{% include %}` and `{% extends %}`
+/// before rendering is abandoned, since a template including itself would
+/// otherwise never finish.
+const MAX_TEMPLATE_DEPTH: usize = 100;
+
+/// State kept by individual nodes between renders of the same node, keyed
+/// by the node's address. An included template starts with its own.
+#[derive(Default)]
+pub struct NodeState {
+    cycles: HashMap<usize, usize>,
+    ifchanged: HashMap<usize, String>,
+}
+
+/// Mutable state shared across a single render, separate from the variables
+/// in the context.
+pub struct RenderState {
+    depth: usize,
+    pub nodes: NodeState,
+    rng: u64,
+}
+
+impl Default for RenderState {
+    fn default() -> Self {
+        let seed = RandomState::new().build_hasher().finish();
+        Self {
+            depth: 0,
+            nodes: NodeState::default(),
+            // xorshift gets stuck at zero
+            rng: seed | 1,
+        }
+    }
+}
+
+#[allow(dead_code)] // Not all stateful tags are implemented yet
+impl RenderState {
+    /// A render state with a fixed random seed, for reproducible output.
+    #[cfg(test)]
+    pub fn with_seed(seed: u64) -> Self {
+        Self {
+            rng: seed | 1,
+            ..Self::default()
+        }
+    }
+
+    /// Start rendering a nested template.
+    pub fn enter_template(&mut self) -> PyResult<()> {
+        if self.depth >= MAX_TEMPLATE_DEPTH {
+            return Err(PyRecursionError::new_err(
+                "maximum template nesting depth exceeded",
+            ));
+        }
+        self.depth += 1;
+        Ok(())
+    }
+
+    pub fn exit_template(&mut self) {
+        self.depth -= 1;
+    }
+
+    /// The position of a `{% cycle %}` node, advancing it for next time.
+    pub fn cycle(&mut self, node: usize, len: usize) -> usize {
+        let position = self.nodes.cycles.entry(node).or_default();
+        let current = *position % len;
+        *position = current + 1;
+        current
+    }
+
+    pub fn reset_cycle(&mut self, node: usize) {
+        self.nodes.cycles.remove(&node);
+    }
+
+    /// Remember the content rendered by an `{% ifchanged %}` node, returning
+    /// whether it differs from last time.
+    pub fn changed(&mut self, node: usize, content: String) -> bool {
+        match self.nodes.ifchanged.insert(node, content.clone()) {
+            Some(previous) => previous != content,
+            None => true,
+        }
+    }
+
+    /// A pseudo-random number below `bound`.
+    pub fn random(&mut self, bound: usize) -> usize {
+        self.rng ^= self.rng << 13;
+        self.rng ^= self.rng >> 7;
+        self.rng ^= self.rng << 17;
+        (self.rng % bound as u64) as usize
+    }
+}
+
 pub struct Context {
     pub request: Option<Py<PyAny>>,
-    pub context: HashMap<String, Py<PyAny>>,
+    scopes: Vec<HashMap<String, Py<PyAny>>>,
     pub autoescape: bool,
+    /// The engine of the template being rendered, used to load other templates.
+    pub engine: Option<Arc<EngineData>>,
+    pub blocks: BlockContext,
+    pub state: RenderState,
+}
+
+impl Context {
+    pub fn new(
+        context: HashMap<String, Py<PyAny>>,
+        request: Option<Py<PyAny>>,
+        autoescape: bool,
+    ) -> Self {
+        Self {
+            request,
+            scopes: vec![context],
+            autoescape,
+            engine: None,
+            blocks: BlockContext::default(),
+            state: RenderState::default(),
+        }
+    }
+
+    /// The variables available in every template.
+    pub fn builtins(py: Python<'_>) -> HashMap<String, Py<PyAny>> {
+        HashMap::from([
+            ("None".to_string(), py.None()),
+            ("True".to_string(), PyBool::new(py, true).to_owned().into()),
+            (
+                "False".to_string(),
+                PyBool::new(py, false).to_owned().into(),
+            ),
+        ])
+    }
+
+    /// Look up a variable, starting from the innermost scope.
+    ///
+    /// Like Django, templates split variables on dots before looking them up,
+    /// so a flat key containing a dot (`{"a.b": ...}`) is never found here.
+    pub fn get(&self, key: &str) -> Option<&Py<PyAny>> {
+        self.scopes.iter().rev().find_map(|scope| scope.get(key))
+    }
+
+    /// Set a variable in the innermost scope.
+    pub fn insert(&mut self, key: String, value: Py<PyAny>) {
+        self.scopes
+            .last_mut()
+            .expect("The base scope is never popped")
+            .insert(key, value);
+    }
+
+    /// Set a variable in the innermost scope already defining it, or else in
+    /// the innermost scope.
+    pub fn set_upward(&mut self, key: String, value: Py<PyAny>) {
+        match self
+            .scopes
+            .iter_mut()
+            .rev()
+            .find(|scope| scope.contains_key(&key))
+        {
+            Some(scope) => {
+                scope.insert(key, value);
+            }
+            None => self.insert(key, value),
+        }
+    }
+
+    /// The engine's `string_if_invalid` for the missing `variable`, or `None`
+    /// if it isn't set.
+    pub fn string_if_invalid(&self, variable: &str) -> Option<String> {
+        let string_if_invalid = &self.engine.as_ref()?.string_if_invalid;
+        match string_if_invalid.is_empty() {
+            true => None,
+            false => Some(string_if_invalid.replace("%s", variable)),
+        }
+    }
+
+    /// Flatten all scopes into a single mapping for display, with inner
+    /// scopes shadowing outer ones.
+    pub fn flatten(&self) -> BTreeMap<&str, &Py<PyAny>> {
+        self.scopes
+            .iter()
+            .flatten()
+            .map(|(key, value)| (key.as_str(), value))
+            .collect()
+    }
+
+    pub fn push_scope(&mut self) {
+        self.scopes.push(HashMap::new());
+    }
+
+    pub fn pop_scope(&mut self) {
+        assert!(self.scopes.len() > 1, "Cannot pop the base scope");
+        self.scopes.pop();
+    }
 }
 
 #[derive(Debug, IntoPyObject)]
@@ -87,6 +331,43 @@ pub enum Content<'t, 'py> {
     String(ContentString<'t>),
     Float(f64),
     Int(BigInt),
+    Bool(bool),
+}
+
+/// Format a float like Python's `repr`, so `1.0` keeps its decimal point
+/// and very large or small numbers use scientific notation.
+pub fn float_repr(float: f64) -> String {
+    if float.is_nan() {
+        return "nan".to_string();
+    }
+    if float.is_infinite() {
+        return match float.is_sign_positive() {
+            true => "inf".to_string(),
+            false => "-inf".to_string(),
+        };
+    }
+    let scientific = format!("{float:e}");
+    let (mantissa, exponent) = scientific
+        .split_once('e')
+        .expect("Scientific notation always has an exponent");
+    let exponent: i32 = exponent.parse().expect("The exponent is an integer");
+    if float != 0.0 && !(-4..16).contains(&exponent) {
+        let sign = if exponent < 0 { '-' } else { '+' };
+        return format!("{mantissa}e{sign}{:02}", exponent.abs());
+    }
+    let fixed = float.to_string();
+    match fixed.contains('.') {
+        true => fixed,
+        false => fixed + ".0",
+    }
+}
+
+/// Format a bool like Python's `str`.
+pub fn bool_repr(content: bool) -> &'static str {
+    match content {
+        true => "True",
+        false => "False",
+    }
 }
 
 impl<'t, 'py> Content<'t, 'py> {
@@ -94,28 +375,41 @@ impl<'t, 'py> Content<'t, 'py> {
         Ok(match self {
             Self::Py(content) => resolve_python(content, context)?.content(),
             Self::String(content) => content.content(),
-            Self::Float(content) => content.to_string().into(),
+            Self::Float(content) => float_repr(content).into(),
             Self::Int(content) => content.to_string().into(),
+            Self::Bool(content) => bool_repr(content).into(),
         })
     }
 
     pub fn resolve_string(self, context: &Context) -> PyResult<ContentString<'t>> {
         Ok(match self {
             Self::String(content) => content,
-            Self::Float(content) => ContentString::String(content.to_string().into()),
+            Self::Float(content) => ContentString::String(float_repr(content).into()),
             Self::Int(content) => ContentString::String(content.to_string().into()),
+            Self::Bool(content) => ContentString::String(bool_repr(content).into()),
             Self::Py(content) => return resolve_python(content, context),
         })
     }
 
+    /// Whether the content is truthy in Python, so empty strings, zero and
+    /// empty collections are falsy.
+    pub fn is_truthy(&self) -> PyResult<bool> {
+        Ok(match self {
+            Self::Py(obj) => obj.is_truthy()?,
+            Self::String(s) => !s.as_raw().is_empty(),
+            Self::Float(f) => *f != 0.0,
+            Self::Int(n) => *n != BigInt::ZERO,
+            Self::Bool(b) => *b,
+        })
+    }
+
     pub fn to_bigint(&self) -> Option<BigInt> {
         match self {
             Self::Int(left) => Some(left.clone()),
-            Self::String(left) => match left.as_raw().parse::<BigInt>() {
-                Ok(left) => Some(left),
-                Err(_) => None,
-            },
+            // Python's `int` ignores surrounding whitespace
+            Self::String(left) => left.as_raw().trim().parse::<BigInt>().ok(),
             Self::Float(left) => left.trunc().to_bigint(),
+            Self::Bool(left) => Some(BigInt::from(*left as u8)),
             Self::Py(left) => match left.extract::<BigInt>() {
                 Ok(left) => Some(left),
                 Err(_) => {
@@ -143,6 +437,7 @@ impl<'t, 'py> Content<'t, 'py> {
                 .into_pyobject(py)
                 .expect("An f64 can always be converted to a Python float.")
                 .into_any(),
+            Self::Bool(b) => PyBool::new(py, *b).to_owned().into_any(),
             Self::String(s) => match s {
                 ContentString::String(s) => s
                     .into_pyobject(py)
@@ -164,3 +459,152 @@ impl<'t, 'py> Content<'t, 'py> {
         })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use pyo3::types::{PyList, PyNone, PyString};
+
+    #[test]
+    fn test_float_repr() {
+        assert_eq!(float_repr(1.0), "1.0");
+        assert_eq!(float_repr(-0.0), "-0.0");
+        assert_eq!(float_repr(2.5), "2.5");
+        assert_eq!(float_repr(-5.5), "-5.5");
+        assert_eq!(float_repr(0.0001), "0.0001");
+        assert_eq!(float_repr(0.00001), "1e-05");
+        assert_eq!(float_repr(1e15), "1000000000000000.0");
+        assert_eq!(float_repr(1e16), "1e+16");
+        assert_eq!(float_repr(1.5e300), "1.5e+300");
+        assert_eq!(float_repr(f64::INFINITY), "inf");
+        assert_eq!(float_repr(f64::NAN), "nan");
+    }
+
+    #[test]
+    fn test_render_bool() {
+        pyo3::prepare_freethreaded_python();
+
+        Python::with_gil(|py| {
+            let context = Context::new(HashMap::new(), None, false);
+            assert_eq!(Content::Bool(true).render(&context).unwrap(), "True");
+            assert_eq!(Content::Bool(false).render(&context).unwrap(), "False");
+            assert_eq!(Content::Bool(true).to_bigint(), Some(BigInt::from(1)));
+            let py_bool = Content::Bool(false).to_py(py).unwrap();
+            assert!(py_bool.is(PyBool::new(py, false).as_any()));
+        })
+    }
+
+    #[test]
+    fn test_content_is_truthy() {
+        pyo3::prepare_freethreaded_python();
+
+        Python::with_gil(|py| {
+            let string = |s: &'static str| Content::String(ContentString::String(Cow::Borrowed(s)));
+            assert!(string("a").is_truthy().unwrap());
+            assert!(!string("").is_truthy().unwrap());
+            assert!(Content::Int(1.into()).is_truthy().unwrap());
+            assert!(!Content::Int(0.into()).is_truthy().unwrap());
+            assert!(Content::Float(0.5).is_truthy().unwrap());
+            assert!(!Content::Float(0.0).is_truthy().unwrap());
+            assert!(Content::Bool(true).is_truthy().unwrap());
+            assert!(!Content::Bool(false).is_truthy().unwrap());
+
+            let list = PyList::new(py, [1]).unwrap().into_any();
+            assert!(Content::Py(list).is_truthy().unwrap());
+            let empty = PyList::empty(py).into_any();
+            assert!(!Content::Py(empty).is_truthy().unwrap());
+            let none = PyNone::get(py).to_owned().into_any();
+            assert!(!Content::Py(none).is_truthy().unwrap());
+        })
+    }
+
+    #[test]
+    fn test_render_state_nodes() {
+        let mut state = RenderState::default();
+
+        assert_eq!(state.cycle(1, 2), 0);
+        assert_eq!(state.cycle(2, 3), 0);
+        assert_eq!(state.cycle(1, 2), 1);
+        assert_eq!(state.cycle(2, 3), 1);
+        assert_eq!(state.cycle(1, 2), 0);
+        state.reset_cycle(2);
+        assert_eq!(state.cycle(2, 3), 0);
+
+        assert!(state.changed(1, "a".to_string()));
+        assert!(!state.changed(1, "a".to_string()));
+        assert!(state.changed(2, "a".to_string()));
+        assert!(state.changed(1, "b".to_string()));
+
+        assert!((0..100).all(|_| state.random(3) < 3));
+    }
+
+    #[test]
+    fn test_render_state_depth() {
+        let mut state = RenderState::default();
+        for _ in 0..MAX_TEMPLATE_DEPTH {
+            state.enter_template().unwrap();
+        }
+        assert!(state.enter_template().is_err());
+        state.exit_template();
+        state.enter_template().unwrap();
+    }
+
+    #[test]
+    fn test_context_scopes_shadowing() {
+        pyo3::prepare_freethreaded_python();
+
+        Python::with_gil(|py| {
+            let outer = PyString::new(py, "outer").into_any().unbind();
+            let context = HashMap::from([("name".to_string(), outer)]);
+            let mut context = Context::new(context, None, false);
+
+            context.push_scope();
+            let inner = PyString::new(py, "inner").into_any().unbind();
+            context.insert("name".to_string(), inner);
+            let only_inner = PyString::new(py, "only inner").into_any().unbind();
+            context.insert("other".to_string(), only_inner);
+
+            let name = context.get("name").unwrap().bind(py);
+            assert_eq!(name.extract::<String>().unwrap(), "inner");
+            assert!(context.get("other").is_some());
+
+            context.pop_scope();
+
+            let name = context.get("name").unwrap().bind(py);
+            assert_eq!(name.extract::<String>().unwrap(), "outer");
+            assert!(context.get("other").is_none());
+        })
+    }
+
+    #[test]
+    fn test_context_flatten() {
+        pyo3::prepare_freethreaded_python();
+
+        Python::with_gil(|py| {
+            let outer = PyString::new(py, "outer").into_any().unbind();
+            let only_outer = PyString::new(py, "only outer").into_any().unbind();
+            let context = HashMap::from([
+                ("name".to_string(), outer),
+                ("outer".to_string(), only_outer),
+            ]);
+            let mut context = Context::new(context, None, false);
+            context.push_scope();
+            let inner = PyString::new(py, "inner").into_any().unbind();
+            context.insert("name".to_string(), inner);
+
+            let flattened: Vec<(&str, String)> = context
+                .flatten()
+                .into_iter()
+                .map(|(key, value)| (key, value.extract::<String>(py).unwrap()))
+                .collect();
+            assert_eq!(
+                flattened,
+                vec![
+                    ("name", "inner".to_string()),
+                    ("outer", "only outer".to_string()),
+                ]
+            );
+        })
+    }
+}
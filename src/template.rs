@@ -4,20 +4,22 @@ use pyo3::prelude::*;
 pub mod django_rusty_templates {
     use std::collections::HashMap;
     use std::path::PathBuf;
+    use std::sync::Arc;
 
     use encoding_rs::Encoding;
-    use pyo3::exceptions::{PyAttributeError, PyImportError};
+    use miette::Diagnostic;
+    use pyo3::exceptions::{PyAttributeError, PyImportError, PyTypeError};
     use pyo3::import_exception_bound;
     use pyo3::intern;
     use pyo3::prelude::*;
-    use pyo3::types::{PyBool, PyDict, PyString};
+    use pyo3::types::{PyDict, PyList, PyString, PyTuple};
 
-    use crate::loaders::{AppDirsLoader, CachedLoader, FileSystemLoader, Loader};
-    use crate::parse::{Parser, TokenTree};
+    use crate::loaders::{AppDirsLoader, CachedLoader, FileSystemLoader, Loader, LocMemLoader};
+    use crate::parse::{ParseError, Parser, TokenTree};
     use crate::render::Render;
     use crate::render::types::Context;
     use crate::types::TemplateString;
-    use crate::utils::PyResultMethods;
+    use crate::utils::{PyResultMethods, line_col};
 
     import_exception_bound!(django.core.exceptions, ImproperlyConfigured);
     import_exception_bound!(django.template.base, VariableDoesNotExist);
@@ -34,10 +36,118 @@ pub mod django_rusty_templates {
             let miette_err = err.with_source_code(source);
             Self::new_err(format!("{miette_err:?}"))
         }
+
+        /// Report a `ParseError` with the offending snippet of the template.
+        ///
+        /// Like Django, the exception also gets a `template_debug` attribute
+        /// describing where in the template the error is.
+        fn from_parse_error(
+            py: Python<'_>,
+            err: ParseError,
+            name: Option<String>,
+            template: &str,
+        ) -> PyErr {
+            let span = err
+                .labels()
+                .and_then(|mut labels| labels.next())
+                .map(|label| *label.inner());
+            let message = err.to_string();
+            let error = match &name {
+                Some(name) => {
+                    let source = miette::NamedSource::new(name, template.to_string());
+                    Self::with_source_code(err.into(), source)
+                }
+                None => Self::with_source_code(err.into(), template.to_string()),
+            };
+            if let Some(span) = span {
+                let set_debug = template_debug(py, message, name, template, span)
+                    .and_then(|debug| error.value(py).setattr("template_debug", debug))
+                    .and_then(|_| add_location_note(py, &error, template, span));
+                if let Err(err) = set_debug {
+                    return err;
+                }
+            }
+            error
+        }
+    }
+
+    /// Add a note like "at line 3, column 12" to an error, which Python shows
+    /// in the traceback. Notes need Python 3.11 or newer.
+    fn add_location_note(
+        py: Python<'_>,
+        error: &PyErr,
+        template: &str,
+        span: miette::SourceSpan,
+    ) -> PyResult<()> {
+        let value = error.value(py);
+        if value.hasattr(intern!(py, "add_note"))? {
+            let (line, column) = line_col(template, span.offset());
+            let note = format!("at line {line}, column {column}");
+            value.call_method1(intern!(py, "add_note"), (note,))?;
+        }
+        Ok(())
+    }
+
+    /// Build Django's `template_debug` dictionary for an error at `span`.
+    ///
+    /// Django works with character offsets, so `start` and `end` are
+    /// converted from bytes.
+    fn template_debug<'py>(
+        py: Python<'py>,
+        message: String,
+        name: Option<String>,
+        template: &str,
+        span: miette::SourceSpan,
+    ) -> PyResult<Bound<'py, PyDict>> {
+        const CONTEXT_LINES: usize = 10;
+
+        let start = span.offset();
+        let end = start + span.len();
+        let mut line = 0;
+        let mut before = "";
+        let mut during = "";
+        let mut after = "";
+        // Like Django, the first entry is an empty line zero
+        let mut source_lines = vec![(0, "")];
+        let mut upto = 0;
+        for (num, next) in template
+            .match_indices('\n')
+            .map(|(index, _)| index + 1)
+            .chain([template.len()])
+            .enumerate()
+        {
+            if start >= upto && end <= next {
+                line = num + 1;
+                before = &template[upto..start];
+                during = &template[start..end];
+                after = &template[end..next];
+            }
+            source_lines.push((num + 1, &template[upto..next]));
+            upto = next;
+        }
+        let total = source_lines.len();
+        let top = line.saturating_sub(CONTEXT_LINES).max(1);
+        let bottom = total.min(line + 1 + CONTEXT_LINES);
+
+        let char_offset = |byte: usize| template[..byte].chars().count();
+        let debug = PyDict::new(py);
+        debug.set_item("message", message)?;
+        debug.set_item("source_lines", &source_lines[top..bottom])?;
+        debug.set_item("before", before)?;
+        debug.set_item("during", during)?;
+        debug.set_item("after", after)?;
+        debug.set_item("top", top)?;
+        debug.set_item("bottom", bottom)?;
+        debug.set_item("total", total)?;
+        debug.set_item("line", line)?;
+        debug.set_item("name", name)?;
+        debug.set_item("start", char_offset(start))?;
+        debug.set_item("end", char_offset(end))?;
+        Ok(debug)
     }
 
     impl VariableDoesNotExist {
-        fn with_source_code(
+        pub fn with_source_code(
             err: miette::Report,
             source: impl miette::SourceCode + 'static,
         ) -> PyErr {
@@ -51,17 +161,127 @@ pub mod django_rusty_templates {
 
     pub struct EngineData {
         autoescape: bool,
+        /// Callables run with the request, whose returned dicts are added
+        /// to the context of every render that has a request.
+        context_processors: Vec<Py<PyAny>>,
         libraries: HashMap<String, Py<PyAny>>,
+        template_loaders: Vec<Loader>,
+        /// Whether `|slugify` keeps unicode letters, like Django's
+        /// `slugify(value, allow_unicode=True)`.
+        pub slugify_allow_unicode: bool,
+        /// Rendered in place of a missing variable, with any `%s` replaced
+        /// by the variable's name.
+        pub string_if_invalid: String,
+    }
+
+    impl Default for EngineData {
+        /// Django's `Engine` defaults: autoescaping on, with no libraries
+        /// or loaders.
+        fn default() -> Self {
+            Self {
+                autoescape: true,
+                context_processors: Vec::new(),
+                libraries: HashMap::new(),
+                template_loaders: Vec::new(),
+                slugify_allow_unicode: false,
+                string_if_invalid: String::new(),
+            }
+        }
     }
 
     impl EngineData {
         #[cfg(test)]
-        pub fn empty() -> Self {
-            Self {
+        pub fn empty() -> Arc<Self> {
+            Self::with_loaders(Vec::new())
+        }
+
+        #[cfg(test)]
+        pub fn with_libraries(libraries: HashMap<String, Py<PyAny>>) -> Arc<Self> {
+            Arc::new(Self {
+                autoescape: false,
+                context_processors: Vec::new(),
+                libraries,
+                template_loaders: Vec::new(),
+                slugify_allow_unicode: false,
+                string_if_invalid: String::new(),
+            })
+        }
+
+        #[cfg(test)]
+        pub fn with_string_if_invalid(string_if_invalid: &str) -> Arc<Self> {
+            Arc::new(Self {
+                autoescape: true,
+                context_processors: Vec::new(),
+                libraries: HashMap::new(),
+                template_loaders: Vec::new(),
+                slugify_allow_unicode: false,
+                string_if_invalid: string_if_invalid.to_string(),
+            })
+        }
+
+        #[cfg(test)]
+        pub fn with_loaders(template_loaders: Vec<Loader>) -> Arc<Self> {
+            Arc::new(Self {
                 autoescape: false,
+                context_processors: Vec::new(),
                 libraries: HashMap::new(),
+                template_loaders,
+                slugify_allow_unicode: false,
+                string_if_invalid: String::new(),
+            })
+        }
+
+        pub fn get_template(
+            self: &Arc<Self>,
+            py: Python<'_>,
+            template_name: &str,
+        ) -> PyResult<Template> {
+            let mut tried = Vec::new();
+            for loader in &self.template_loaders {
+                match loader.get_template(py, template_name, self) {
+                    Ok(template) => return template,
+                    Err(e) => tried.push(e.tried),
+                }
             }
+            Err(TemplateDoesNotExist::new_err((
+                template_name.to_string(),
+                tried,
+            )))
+        }
+    }
+
+    impl std::fmt::Debug for EngineData {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            f.debug_struct("EngineData")
+                .field("autoescape", &self.autoescape)
+                .finish_non_exhaustive()
+        }
+    }
+
+    /// Engines are compared by identity, since loaders can't be compared.
+    impl PartialEq for EngineData {
+        fn eq(&self, other: &Self) -> bool {
+            std::ptr::eq(self, other)
+        }
+    }
+
+    /// Resolve each context processor, importing those given as dotted paths.
+    fn import_context_processors(context_processors: Bound<'_, PyAny>) -> PyResult<Vec<Py<PyAny>>> {
+        let py = context_processors.py();
+        let import_string = py
+            .import(intern!(py, "django.utils.module_loading"))?
+            .getattr(intern!(py, "import_string"))?;
+        let mut processors = Vec::new();
+        for processor in context_processors.try_iter()? {
+            let processor = processor?;
+            let processor = if processor.is_instance_of::<PyString>() {
+                import_string.call1((processor,))?
+            } else {
+                processor
+            };
+            processors.push(processor.unbind());
         }
+        Ok(processors)
     }
 
     fn import_libraries(libraries: Bound<'_, PyAny>) -> PyResult<HashMap<String, Py<PyAny>>> {
@@ -100,31 +320,89 @@ pub mod django_rusty_templates {
 
     #[pyclass]
     pub struct Engine {
-        dirs: Vec<PathBuf>,
-        app_dirs: bool,
-        context_processors: Vec<String>,
-        debug: bool,
-        string_if_invalid: String,
-        encoding: &'static Encoding,
-        builtins: Vec<String>,
-        template_loaders: Vec<Loader>,
-        data: EngineData,
+        data: Arc<EngineData>,
     }
 
     impl Engine {
-        fn find_template_loader<'py>(
-            _py: Python<'py>,
-            _loader: &str,
-            _args: Option<Bound<'py, PyAny>>,
-        ) -> PyResult<Bound<'py, PyAny>> {
-            todo!()
+        fn get_template_loaders(
+            loaders: &Bound<'_, PyAny>,
+            dirs: &[PathBuf],
+            encoding: &'static Encoding,
+        ) -> PyResult<Vec<Loader>> {
+            let mut template_loaders = Vec::new();
+            for loader in loaders.try_iter()? {
+                template_loaders.push(Self::find_template_loader(&loader?, dirs, encoding)?);
+            }
+            Ok(template_loaders)
         }
+
+        fn find_template_loader(
+            loader: &Bound<'_, PyAny>,
+            dirs: &[PathBuf],
+            encoding: &'static Encoding,
+        ) -> PyResult<Loader> {
+            let (path, args) =
+                if loader.is_instance_of::<PyList>() || loader.is_instance_of::<PyTuple>() {
+                    let mut items = loader.try_iter()?;
+                    match items.next() {
+                        Some(path) => (path?, items.collect::<PyResult<Vec<_>>>()?),
+                        None => return Err(invalid_loader(loader)),
+                    }
+                } else {
+                    (loader.clone(), Vec::new())
+                };
+            let Ok(path) = path.downcast::<PyString>() else {
+                return Err(invalid_loader(&path));
+            };
+            let mut args = args.into_iter();
+            Ok(match path.to_str()? {
+                "django.template.loaders.app_directories.Loader" => {
+                    Loader::AppDirs(AppDirsLoader::new(encoding))
+                }
+                "django.template.loaders.cached.Loader" => {
+                    let loaders = match args.next() {
+                        Some(loaders) => Self::get_template_loaders(&loaders, dirs, encoding)?,
+                        None => Vec::new(),
+                    };
+                    Loader::Cached(CachedLoader::new(loaders))
+                }
+                "django.template.loaders.filesystem.Loader" => {
+                    let dirs = match args.next() {
+                        Some(dirs) if dirs.is_truthy()? => dirs.extract()?,
+                        _ => dirs.to_vec(),
+                    };
+                    Loader::FileSystem(FileSystemLoader::new(dirs, encoding))
+                }
+                "django.template.loaders.locmem.Loader" => {
+                    let templates = match args.next() {
+                        Some(templates) => templates.extract()?,
+                        None => HashMap::new(),
+                    };
+                    Loader::LocMem(LocMemLoader::new(templates))
+                }
+                path => {
+                    return Err(ImproperlyConfigured::new_err(format!(
+                        "Template loader '{path}' is not supported."
+                    )));
+                }
+            })
+        }
+    }
+
+    fn invalid_loader(loader: &Bound<'_, PyAny>) -> PyErr {
+        let loader = match loader.repr() {
+            Ok(repr) => repr.to_string(),
+            Err(e) => return e,
+        };
+        ImproperlyConfigured::new_err(format!(
+            "Invalid value in template loaders configuration: {loader}"
+        ))
     }
 
     #[pymethods]
     impl Engine {
         #[new]
-        #[pyo3(signature = (dirs=None, app_dirs=false, context_processors=None, debug=false, loaders=None, string_if_invalid="".to_string(), file_charset="utf-8".to_string(), libraries=None, builtins=None, autoescape=true))]
+        #[pyo3(signature = (dirs=None, app_dirs=false, context_processors=None, debug=false, loaders=None, string_if_invalid="".to_string(), file_charset="utf-8".to_string(), libraries=None, builtins=None, autoescape=true, slugify_allow_unicode=false))]
         #[allow(clippy::too_many_arguments)] // We're matching Django's Engine __init__ signature
         pub fn new(
             _py: Python<'_>,
@@ -138,13 +416,14 @@ pub mod django_rusty_templates {
             libraries: Option<Bound<'_, PyAny>>,
             builtins: Option<Bound<'_, PyAny>>,
             autoescape: bool,
+            slugify_allow_unicode: bool,
         ) -> PyResult<Self> {
             let dirs = match dirs {
                 Some(dirs) => dirs.extract()?,
                 None => Vec::new(),
             };
             let context_processors = match context_processors {
-                Some(context_processors) => context_processors.extract()?,
+                Some(context_processors) => import_context_processors(context_processors)?,
                 None => Vec::new(),
             };
             let encoding = match Encoding::for_label(file_charset.as_bytes()) {
@@ -158,7 +437,7 @@ pub mod django_rusty_templates {
                     );
                     return Err(err);
                 }
-                Some(_loaders) => todo!(),
+                Some(loaders) => Self::get_template_loaders(&loaders, &dirs, encoding)?,
                 None => {
                     let filesystem_loader =
                         Loader::FileSystem(FileSystemLoader::new(dirs.clone(), encoding));
@@ -176,42 +455,56 @@ pub mod django_rusty_templates {
                 None => HashMap::new(),
                 Some(libraries) => import_libraries(libraries)?,
             };
-            let builtins = vec![];
-            let data = EngineData {
+            // Debug information and extra builtin libraries aren't supported yet
+            let _ = (debug, builtins);
+            let data = Arc::new(EngineData {
                 autoescape,
-                libraries,
-            };
-            Ok(Self {
-                dirs,
-                app_dirs,
                 context_processors,
-                debug,
+                libraries,
                 template_loaders,
+                slugify_allow_unicode,
                 string_if_invalid,
-                encoding,
-                builtins,
-                data,
-            })
+            });
+            Ok(Self { data })
         }
 
-        pub fn get_template(
-            &mut self,
-            py: Python<'_>,
-            template_name: String,
-        ) -> PyResult<Template> {
-            let mut tried = Vec::new();
-            for loader in &mut self.template_loaders {
-                match loader.get_template(py, &template_name, &self.data) {
-                    Ok(template) => return template,
-                    Err(e) => tried.push(e.tried),
-                }
+        #[getter]
+        pub fn autoescape(&self) -> bool {
+            self.data.autoescape
+        }
+
+        #[getter]
+        pub fn string_if_invalid(&self) -> &str {
+            &self.data.string_if_invalid
+        }
+
+        pub fn get_template(&self, py: Python<'_>, template_name: String) -> PyResult<Template> {
+            self.data.get_template(py, &template_name)
+        }
+
+        /// Clear the caches of any cached template loaders.
+        pub fn reset_loaders(&self) {
+            for loader in &self.data.template_loaders {
+                loader.reset();
             }
-            Err(TemplateDoesNotExist::new_err((template_name, tried)))
         }
 
         #[allow(clippy::wrong_self_convention)] // We're implementing a Django interface
-        pub fn from_string(&self, template_code: Bound<'_, PyString>) -> PyResult<Template> {
-            Template::new_from_string(template_code.py(), template_code.extract()?, &self.data)
+        #[pyo3(signature = (template_code, autoescape=None))]
+        pub fn from_string(
+            &self,
+            template_code: Bound<'_, PyString>,
+            autoescape: Option<bool>,
+        ) -> PyResult<Template> {
+            let template = Template::new_from_string(
+                template_code.py(),
+                template_code.extract()?,
+                &self.data,
+            )?;
+            Ok(match autoescape {
+                Some(autoescape) => template.with_autoescape(autoescape),
+                None => template,
+            })
         }
 
         // TODO render_to_string needs implementation.
@@ -224,6 +517,9 @@ pub mod django_rusty_templates {
         pub template: String,
         pub nodes: Vec<TokenTree>,
         pub autoescape: bool,
+        /// `None` only for copies held in a cached loader, since the engine
+        /// owns that cache and would otherwise never be freed.
+        engine: Option<Arc<EngineData>>,
     }
 
     impl Template {
@@ -231,16 +527,20 @@ pub mod django_rusty_templates {
             py: Python<'_>,
             template: &str,
             filename: PathBuf,
-            engine_data: &EngineData,
+            engine_data: &Arc<EngineData>,
         ) -> PyResult<Self> {
             let mut parser = Parser::new(py, TemplateString(template), &engine_data.libraries);
             let nodes = match parser.parse() {
                 Ok(nodes) => nodes,
                 Err(err) => {
                     let err = err.try_into_parse_error()?;
-                    let source =
-                        miette::NamedSource::new(filename.to_string_lossy(), template.to_string());
-                    return Err(TemplateSyntaxError::with_source_code(err.into(), source));
+                    let name = filename.to_string_lossy().into_owned();
+                    return Err(TemplateSyntaxError::from_parse_error(
+                        py,
+                        err,
+                        Some(name),
+                        template,
+                    ));
                 }
             };
             Ok(Self {
@@ -248,20 +548,23 @@ pub mod django_rusty_templates {
                 filename: Some(filename),
                 nodes,
                 autoescape: engine_data.autoescape,
+                engine: Some(engine_data.clone()),
             })
         }
 
         pub fn new_from_string(
             py: Python<'_>,
             template: String,
-            engine_data: &EngineData,
+            engine_data: &Arc<EngineData>,
         ) -> PyResult<Self> {
             let mut parser = Parser::new(py, TemplateString(&template), &engine_data.libraries);
             let nodes = match parser.parse() {
                 Ok(nodes) => nodes,
                 Err(err) => {
                     let err = err.try_into_parse_error()?;
-                    return Err(TemplateSyntaxError::with_source_code(err.into(), template));
+                    return Err(TemplateSyntaxError::from_parse_error(
+                        py, err, None, &template,
+                    ));
                 }
             };
             Ok(Self {
@@ -269,60 +572,208 @@ pub mod django_rusty_templates {
                 filename: None,
                 nodes,
                 autoescape: engine_data.autoescape,
+                engine: Some(engine_data.clone()),
             })
         }
 
-        fn _render(&self, py: Python<'_>, context: &mut Context) -> PyResult<String> {
-            let mut rendered = String::with_capacity(self.template.len());
+        /// A copy without a reference to the engine, for storing in one of
+        /// the engine's own caches.
+        pub fn without_engine(&self) -> Self {
+            Self {
+                engine: None,
+                ..self.clone()
+            }
+        }
+
+        /// Attach `engine` to a template taken from one of its caches.
+        pub fn with_engine(self, engine: &Arc<EngineData>) -> Self {
+            Self {
+                engine: Some(engine.clone()),
+                ..self
+            }
+        }
+
+        /// Override the engine's `autoescape` setting for this template.
+        pub fn with_autoescape(self, autoescape: bool) -> Self {
+            Self { autoescape, ..self }
+        }
+
+        /// Render with `context` layered over the builtins and the results
+        /// of any context processors, which only run when there's a request.
+        pub fn render(
+            &self,
+            py: Python<'_>,
+            context: Option<Bound<'_, PyDict>>,
+            request: Option<Bound<'_, PyAny>>,
+        ) -> PyResult<String> {
+            let engine = self.engine.clone().unwrap_or_default();
+            let mut base_context = HashMap::new();
+            if let Some(request) = &request {
+                for processor in &engine.context_processors {
+                    let processed: HashMap<_, _> =
+                        processor.bind(py).call1((request,))?.extract()?;
+                    base_context.extend(processed);
+                }
+            }
+            if let Some(context) = context {
+                let new_context: HashMap<_, _> = context.extract()?;
+                base_context.extend(new_context);
+            }
+            self.render_with(py, base_context, request, self.autoescape)
+        }
+
+        /// Render an already flattened `context` layered over the builtins,
+        /// with an `autoescape` setting that may come from a Django `Context`.
+        fn render_with(
+            &self,
+            py: Python<'_>,
+            context: HashMap<String, Py<PyAny>>,
+            request: Option<Bound<'_, PyAny>>,
+            autoescape: bool,
+        ) -> PyResult<String> {
+            let mut base_context = Context::builtins(py);
+            base_context.extend(context);
+            let request = request.map(|request| request.unbind());
+            let mut context = Context::new(base_context, request, autoescape);
+            context.engine = Some(self.engine.clone().unwrap_or_default());
+            self._render(py, &mut context)
+        }
+
+        pub fn _render(&self, py: Python<'_>, context: &mut Context) -> PyResult<String> {
+            context.state.enter_template()?;
             let template = TemplateString(&self.template);
-            for node in &self.nodes {
-                match node.render(py, template, context) {
-                    Ok(content) => rendered.push_str(&content),
-                    Err(err) => {
-                        let err = err.try_into_render_error()?;
-                        return Err(VariableDoesNotExist::with_source_code(
-                            err.into(),
-                            self.template.clone(),
-                        ));
+            let rendered = render_template(py, &self.nodes, template, context);
+            context.state.exit_template();
+            rendered
+        }
+    }
+
+    /// Render a parsed node list into the complete output. `Text` nodes are
+    /// output as is, while variables and filters are autoescaped according
+    /// to the `context`.
+    pub fn render_template(
+        py: Python<'_>,
+        nodes: &[TokenTree],
+        template: TemplateString<'_>,
+        context: &mut Context,
+    ) -> PyResult<String> {
+        let mut rendered = String::with_capacity(template.0.len());
+        for node in nodes {
+            match node.render(py, template, context) {
+                Ok(content) => rendered.push_str(&content),
+                Err(err) => {
+                    let err = err.try_into_render_error()?;
+                    let span = err
+                        .labels()
+                        .and_then(|mut labels| labels.next())
+                        .map(|label| *label.inner());
+                    let error =
+                        VariableDoesNotExist::with_source_code(err.into(), template.0.to_string());
+                    if let Some(span) = span {
+                        add_location_note(py, &error, template.0, span)?;
                     }
+                    return Err(error);
                 }
             }
-            Ok(rendered)
         }
+        Ok(rendered)
     }
 
     #[pymethods]
     impl Template {
-        #[pyo3(signature = (context=None, request=None))]
-        pub fn render(
+        /// Parse a standalone template using the given `engine`'s settings,
+        /// or the default engine settings if there isn't one. Like
+        /// `Engine.from_string`, `autoescape` overrides the engine's setting.
+        #[new]
+        #[pyo3(signature = (template_string, engine=None, autoescape=None))]
+        pub fn py_new(
+            py: Python<'_>,
+            template_string: String,
+            engine: Option<PyRef<'_, Engine>>,
+            autoescape: Option<bool>,
+        ) -> PyResult<Self> {
+            let template = match engine {
+                Some(engine) => Self::new_from_string(py, template_string, &engine.data),
+                None => {
+                    Self::new_from_string(py, template_string, &Arc::new(EngineData::default()))
+                }
+            }?;
+            Ok(match autoescape {
+                Some(autoescape) => template.with_autoescape(autoescape),
+                None => template,
+            })
+        }
+
+        /// Render with a plain dict, or an object with a `dicts` stack like
+        /// Django's `Context`, whose `autoescape` setting is used. A
+        /// `RequestContext` also provides the request and its own context
+        /// processors.
+        #[pyo3(name = "render", signature = (context=None, request=None))]
+        pub fn py_render(
             &self,
             py: Python<'_>,
-            context: Option<Bound<'_, PyDict>>,
+            context: Option<Bound<'_, PyAny>>,
             request: Option<Bound<'_, PyAny>>,
         ) -> PyResult<String> {
-            let mut base_context = HashMap::from([
-                ("None".to_string(), py.None()),
-                ("True".to_string(), PyBool::new(py, true).to_owned().into()),
-                (
-                    "False".to_string(),
-                    PyBool::new(py, false).to_owned().into(),
-                ),
-            ]);
-            let context = match context {
-                Some(context) => {
-                    let new_context: HashMap<_, _> = context.extract()?;
-                    base_context.extend(new_context.into_iter());
-                    base_context
+            let Some(context) = context else {
+                return self.render(py, None, request);
+            };
+            if let Ok(context) = context.downcast::<PyDict>() {
+                return self.render(py, Some(context.clone()), request);
+            }
+            if !context.hasattr(intern!(py, "dicts"))? {
+                return Err(PyTypeError::new_err(format!(
+                    "context must be a dict or Context rather than {}.",
+                    context.get_type().name()?
+                )));
+            }
+            let request = match request {
+                Some(request) => Some(request),
+                None if context.hasattr(intern!(py, "request"))? => {
+                    Some(context.getattr(intern!(py, "request"))?)
                 }
-                None => base_context,
+                None => None,
             };
-            let request = request.map(|request| request.unbind());
-            let mut context = Context {
-                request,
-                context,
-                autoescape: self.autoescape,
+            let autoescape = match context.hasattr(intern!(py, "autoescape"))? {
+                true => context.getattr(intern!(py, "autoescape"))?.is_truthy()?,
+                false => self.autoescape,
             };
-            self._render(py, &mut context)
+            // Like `RequestContext.bind_template`, the context processors'
+            // results replace the placeholder at `_processors_index`, above
+            // the constructor's dict but below anything pushed since.
+            let processors_index = match context.hasattr(intern!(py, "_processors_index"))? {
+                true => Some(
+                    context
+                        .getattr(intern!(py, "_processors_index"))?
+                        .extract::<usize>()?,
+                ),
+                false => None,
+            };
+            let mut flattened = HashMap::new();
+            let dicts = context.getattr(intern!(py, "dicts"))?;
+            for (index, dict) in dicts.try_iter()?.enumerate() {
+                match (&request, processors_index) {
+                    (Some(request), Some(processors_index)) if index == processors_index => {
+                        let engine = self.engine.clone().unwrap_or_default();
+                        let engine_processors =
+                            engine.context_processors.iter().map(|p| p.bind(py).clone());
+                        let processors = context
+                            .getattr(intern!(py, "_processors"))?
+                            .try_iter()?
+                            .collect::<PyResult<Vec<_>>>()?;
+                        for processor in engine_processors.chain(processors) {
+                            let processed: HashMap<_, _> =
+                                processor.call1((request,))?.extract()?;
+                            flattened.extend(processed);
+                        }
+                    }
+                    _ => {
+                        let dict: HashMap<_, _> = dict?.extract()?;
+                        flattened.extend(dict);
+                    }
+                }
+            }
+            self.render_with(py, flattened, request, autoescape)
         }
     }
 }
@@ -331,8 +782,17 @@ pub mod django_rusty_templates {
 mod tests {
     use super::django_rusty_templates::*;
 
-    use pyo3::Python;
-    use pyo3::types::{PyDict, PyDictMethods, PyString};
+    use std::collections::HashMap;
+    use std::sync::Arc;
+
+    use pyo3::prelude::*;
+    use pyo3::types::{PyDict, PyDictMethods, PyList, PyString};
+
+    use crate::loaders::{Loader, LocMemLoader};
+    use crate::parse::Parser;
+    use crate::render::types::{Context, RenderState};
+    use crate::types::TemplateString;
+    use crate::utils::with_mock_translation;
 
     #[test]
     fn test_syntax_error() {
@@ -390,6 +850,85 @@ mod tests {
         })
     }
 
+    #[test]
+    fn test_syntax_error_template_debug() {
+        pyo3::prepare_freethreaded_python();
+
+        Python::with_gil(|py| {
+            let engine = EngineData::empty();
+            let template_string = "{{ }}".to_string();
+            let error = Template::new_from_string(py, template_string, &engine).unwrap_err();
+
+            let debug = error.value(py).getattr("template_debug").unwrap();
+            let item = |key: &str| debug.get_item(key).unwrap();
+            assert_eq!(item("line").extract::<usize>().unwrap(), 1);
+            assert_eq!(item("start").extract::<usize>().unwrap(), 0);
+            assert_eq!(item("end").extract::<usize>().unwrap(), 5);
+            assert_eq!(item("during").extract::<String>().unwrap(), "{{ }}");
+            assert!(item("name").is_none());
+        })
+    }
+
+    #[test]
+    fn test_syntax_error_template_debug_multiline() {
+        pyo3::prepare_freethreaded_python();
+
+        Python::with_gil(|py| {
+            let engine = EngineData::empty();
+            let template_string = "<p>\n  é {{ }} ü\n</p>".to_string();
+            let error = Template::new_from_string(py, template_string, &engine).unwrap_err();
+
+            let debug = error.value(py).getattr("template_debug").unwrap();
+            let item = |key: &str| debug.get_item(key).unwrap();
+            assert_eq!(item("line").extract::<usize>().unwrap(), 2);
+            assert_eq!(item("start").extract::<usize>().unwrap(), 8);
+            assert_eq!(item("end").extract::<usize>().unwrap(), 13);
+            assert_eq!(item("before").extract::<String>().unwrap(), "  é ");
+            assert_eq!(item("during").extract::<String>().unwrap(), "{{ }}");
+            assert_eq!(item("after").extract::<String>().unwrap(), " ü\n");
+            assert_eq!(item("total").extract::<usize>().unwrap(), 4);
+            let notes: Vec<String> = error
+                .value(py)
+                .getattr("__notes__")
+                .unwrap()
+                .extract()
+                .unwrap();
+            assert_eq!(notes, vec!["at line 2, column 5"]);
+            let source_lines: Vec<(usize, String)> = item("source_lines").extract().unwrap();
+            assert_eq!(
+                source_lines,
+                vec![
+                    (1, "<p>\n".to_string()),
+                    (2, "  é {{ }} ü\n".to_string()),
+                    (3, "</p>".to_string()),
+                ]
+            );
+        })
+    }
+
+    #[test]
+    fn test_render_error_location_note() {
+        pyo3::prepare_freethreaded_python();
+
+        Python::with_gil(|py| {
+            let engine = EngineData::empty();
+            let template_string = "<p>\n{{ n|add:foo.bar }}".to_string();
+            let template = Template::new_from_string(py, template_string, &engine).unwrap();
+            let context = PyDict::new(py);
+            context.set_item("n", 1).unwrap();
+            context.set_item("foo", PyDict::new(py)).unwrap();
+            let error = template.render(py, Some(context), None).unwrap_err();
+
+            let notes: Vec<String> = error
+                .value(py)
+                .getattr("__notes__")
+                .unwrap()
+                .extract()
+                .unwrap();
+            assert_eq!(notes, vec!["at line 2, column 14"]);
+        })
+    }
+
     #[test]
     fn test_render_empty_template() {
         pyo3::prepare_freethreaded_python();
@@ -469,62 +1008,1111 @@ user = User(["Lily"])
     }
 
     #[test]
-    fn test_engine_from_string() {
+    fn test_render_template_autoescape_blocks() {
         pyo3::prepare_freethreaded_python();
 
         Python::with_gil(|py| {
-            let engine = Engine::new(
-                py,
-                None,
-                false,
-                None,
-                false,
-                None,
-                "".to_string(),
-                "utf-8".to_string(),
-                None,
-                None,
-                false,
-            )
-            .unwrap();
-            let template_string = PyString::new(py, "Hello {{ user }}!");
-            let template = engine.from_string(template_string).unwrap();
+            let engine = EngineData::empty();
+            let template_string = "{% autoescape on %}{{ html }}{% autoescape off %}{{ html }}{% endautoescape %}{{ html }}{% endautoescape %}{{ html }}".to_string();
+            let template = Template::new_from_string(py, template_string, &engine).unwrap();
             let context = PyDict::new(py);
+            context.set_item("html", "<b>").unwrap();
 
-            assert_eq!(template.render(py, Some(context), None).unwrap(), "Hello !");
+            assert_eq!(
+                template.render(py, Some(context), None).unwrap(),
+                "&lt;b&gt;<b>&lt;b&gt;<b>"
+            );
         })
     }
 
     #[test]
-    fn test_clone_template() {
-        use std::collections::HashMap;
+    fn test_render_template_for_unpack() {
+        pyo3::prepare_freethreaded_python();
 
-        use pyo3::IntoPyObject;
-        use pyo3::types::{PyAnyMethods, PyListMethods};
+        Python::with_gil(|py| {
+            let engine = EngineData::empty();
+            let template_string =
+                "{% for k, v in items %}{{ k }}={{ v }};{% empty %}none{% endfor %}{{ k }}"
+                    .to_string();
+            let template = Template::new_from_string(py, template_string, &engine).unwrap();
+            let data = PyDict::new(py);
+            data.set_item("a", 1).unwrap();
+            data.set_item("b", 2).unwrap();
+            let context = PyDict::new(py);
+            context
+                .set_item("items", data.call_method0("items").unwrap())
+                .unwrap();
+            context.set_item("k", "outer").unwrap();
+
+            assert_eq!(
+                template.render(py, Some(context), None).unwrap(),
+                "a=1;b=2;outer"
+            );
+
+            let context = PyDict::new(py);
+            context.set_item("items", PyList::empty(py)).unwrap();
+            assert_eq!(template.render(py, Some(context), None).unwrap(), "none");
+        })
+    }
 
+    #[test]
+    fn test_render_template_for_dict_keys() {
         pyo3::prepare_freethreaded_python();
 
         Python::with_gil(|py| {
-            let cwd = std::env::current_dir().unwrap();
-            let sys_path = py.import("sys").unwrap().getattr("path").unwrap();
-            let sys_path = sys_path.downcast().unwrap();
-            sys_path.append(cwd).unwrap();
-            let mut engine = Engine::new(
-                py,
-                Some(vec!["tests/templates"].into_pyobject(py).unwrap()),
-                false,
-                None,
-                false,
-                None,
-                "".to_string(),
-                "utf-8".to_string(),
-                Some(
-                    HashMap::from([("custom_filters", "tests.templatetags.custom_filters")])
-                        .into_pyobject(py)
-                        .unwrap()
-                        .into_any(),
-                ),
+            let engine = EngineData::empty();
+            let template_string = "{% for key in data %}{{ key }},{% endfor %}".to_string();
+            let template = Template::new_from_string(py, template_string, &engine).unwrap();
+            let data = PyDict::new(py);
+            data.set_item("a", 1).unwrap();
+            data.set_item("b", 2).unwrap();
+            let context = PyDict::new(py);
+            context.set_item("data", data).unwrap();
+
+            assert_eq!(template.render(py, Some(context), None).unwrap(), "a,b,");
+        })
+    }
+
+    #[test]
+    fn test_render_template_forloop_parentloop() {
+        pyo3::prepare_freethreaded_python();
+
+        Python::with_gil(|py| {
+            let engine = EngineData::empty();
+            let template_string = "{% for row in rows %}{% for cell in row %}{{ forloop.parentloop.counter }}{{ forloop.counter }}{{ forloop.last }} {% endfor %}{% endfor %}".to_string();
+            let template = Template::new_from_string(py, template_string, &engine).unwrap();
+            let rows = PyList::new(py, [vec![1, 2], vec![3]]).unwrap();
+            let context = PyDict::new(py);
+            context.set_item("rows", rows).unwrap();
+
+            assert_eq!(
+                template.render(py, Some(context), None).unwrap(),
+                "11False 12True 21True "
+            );
+        })
+    }
+
+    #[test]
+    fn test_render_template_if_escaped_string_literal() {
+        pyo3::prepare_freethreaded_python();
+
+        Python::with_gil(|py| {
+            let engine = EngineData::empty();
+            let template_string =
+                r"{% if status == 'it\'s ok' %}yes{% endif %}{{ status|default:'\\n' }}"
+                    .to_string();
+            let template = Template::new_from_string(py, template_string, &engine).unwrap();
+            let context = PyDict::new(py);
+            context.set_item("status", "it's ok").unwrap();
+
+            assert_eq!(
+                template.render(py, Some(context), None).unwrap(),
+                "yesit's ok"
+            );
+
+            let context = PyDict::new(py);
+            assert_eq!(template.render(py, Some(context), None).unwrap(), r"\n");
+        })
+    }
+
+    fn locmem_engine(templates: &[(&str, &str)]) -> Arc<EngineData> {
+        let templates = templates
+            .iter()
+            .map(|(name, template)| (name.to_string(), template.to_string()))
+            .collect();
+        EngineData::with_loaders(vec![Loader::LocMem(LocMemLoader::new(templates))])
+    }
+
+    #[test]
+    fn test_render_template_extends() {
+        pyo3::prepare_freethreaded_python();
+
+        Python::with_gil(|py| {
+            let engine = locmem_engine(&[
+                (
+                    "base.html",
+                    "<title>{% block title %}Site{% endblock %}</title>{% block content %}Base{% endblock %}",
+                ),
+                (
+                    "child.html",
+                    "{% extends 'base.html' %}ignored{% block title %}{{ page }} | {{ block.super }}{% endblock %}",
+                ),
+            ]);
+            let template = engine.get_template(py, "child.html").unwrap();
+            let context = PyDict::new(py);
+            context.set_item("page", "Home").unwrap();
+
+            assert_eq!(
+                template.render(py, Some(context), None).unwrap(),
+                "<title>Home | Site</title>Base"
+            );
+        })
+    }
+
+    #[test]
+    fn test_render_template_block_variable() {
+        pyo3::prepare_freethreaded_python();
+
+        Python::with_gil(|py| {
+            let engine = locmem_engine(&[
+                ("base.html", "{% block title %}Site{% endblock %}"),
+                (
+                    "child.html",
+                    "{% extends 'base.html' %}{% block title %}{{ block.name }}: Welcome to {{ block.super }}{{ block.missing }}{% endblock %}",
+                ),
+            ]);
+            let template = engine.get_template(py, "child.html").unwrap();
+            let context = PyDict::new(py);
+            let block = PyDict::new(py);
+            block.set_item("super", "shadowed").unwrap();
+            context.set_item("block", block).unwrap();
+
+            assert_eq!(
+                template.render(py, Some(context.clone()), None).unwrap(),
+                "title: Welcome to Site"
+            );
+
+            let engine = EngineData::empty();
+            let template_string = "{{ block.super }}".to_string();
+            let template = Template::new_from_string(py, template_string, &engine).unwrap();
+            assert_eq!(
+                template.render(py, Some(context), None).unwrap(),
+                "shadowed"
+            );
+        })
+    }
+
+    #[test]
+    fn test_render_template_extends_chain() {
+        pyo3::prepare_freethreaded_python();
+
+        Python::with_gil(|py| {
+            let engine = locmem_engine(&[
+                ("base.html", "[{% block content %}base{% endblock %}]"),
+                (
+                    "middle.html",
+                    "{% extends 'base.html' %}{% block content %}middle {{ block.super }}{% endblock %}",
+                ),
+                (
+                    "child.html",
+                    "{% extends 'middle.html' %}{% block content %}child {{ block.super }}{% endblock %}",
+                ),
+            ]);
+            let template = engine.get_template(py, "child.html").unwrap();
+
+            assert_eq!(
+                template.render(py, None, None).unwrap(),
+                "[child middle base]"
+            );
+        })
+    }
+
+    #[test]
+    fn test_render_template_extends_nested_block() {
+        pyo3::prepare_freethreaded_python();
+
+        Python::with_gil(|py| {
+            let engine = locmem_engine(&[
+                (
+                    "base.html",
+                    "{% block outer %}<{% block inner %}base{% endblock %}>{% endblock %}",
+                ),
+                (
+                    "child.html",
+                    "{% extends 'base.html' %}{% if True %}{% block inner %}child{% endblock %}{% endif %}",
+                ),
+            ]);
+            let template = engine.get_template(py, "child.html").unwrap();
+
+            assert_eq!(template.render(py, None, None).unwrap(), "<child>");
+        })
+    }
+
+    #[test]
+    fn test_render_template_extends_block_in_parent_loop() {
+        pyo3::prepare_freethreaded_python();
+
+        Python::with_gil(|py| {
+            let engine = locmem_engine(&[
+                (
+                    "base.html",
+                    "{% for x in items %}{% if x %}[{% block item %}base{% endblock %}]{% endif %}{% endfor %}",
+                ),
+                (
+                    "child.html",
+                    "{% extends 'base.html' %}{% block item %}{{ x }} {{ block.super }}{% endblock %}",
+                ),
+            ]);
+            let template = engine.get_template(py, "child.html").unwrap();
+            let context = PyDict::new(py);
+            context.set_item("items", vec![1, 0, 2]).unwrap();
+
+            assert_eq!(
+                template.render(py, Some(context), None).unwrap(),
+                "[1 base][2 base]"
+            );
+        })
+    }
+
+    #[test]
+    fn test_render_template_extends_missing_parent() {
+        pyo3::prepare_freethreaded_python();
+
+        Python::with_gil(|py| {
+            let engine = locmem_engine(&[("child.html", "{% extends 'missing.html' %}")]);
+            let template = engine.get_template(py, "child.html").unwrap();
+
+            let error = template.render(py, None, None).unwrap_err();
+            assert!(error.is_instance_of::<TemplateDoesNotExist>(py));
+        })
+    }
+
+    #[test]
+    fn test_render_template_block_error_restores_scope() {
+        pyo3::prepare_freethreaded_python();
+
+        Python::with_gil(|py| {
+            let engine = EngineData::empty();
+            let failing = "{% block a %}{% cycle 'x' 'y' as letter %}{{ error }}{% endblock %}";
+            let failing = Template::new_from_string(py, failing.to_string(), &engine).unwrap();
+            let after = "[{{ letter }}]".to_string();
+            let after = Template::new_from_string(py, after, &engine).unwrap();
+
+            let error = py.eval(c"lambda: 1 / 0", None, None).unwrap().unbind();
+            let mut context = Context::new(HashMap::new(), None, false);
+            context.insert("error".to_string(), error);
+            assert!(failing._render(py, &mut context).is_err());
+
+            // The block's scope is gone, so its variables don't leak
+            assert_eq!(after._render(py, &mut context).unwrap(), "[]");
+        })
+    }
+
+    #[test]
+    fn test_render_template_block_without_extends() {
+        pyo3::prepare_freethreaded_python();
+
+        Python::with_gil(|py| {
+            let engine = EngineData::empty();
+            let template_string = "{% block a %}a {{ block.super }}{% endblock %}".to_string();
+            let template = Template::new_from_string(py, template_string, &engine).unwrap();
+
+            assert_eq!(template.render(py, None, None).unwrap(), "a ");
+        })
+    }
+
+    #[test]
+    fn test_render_template_include() {
+        pyo3::prepare_freethreaded_python();
+
+        Python::with_gil(|py| {
+            let engine = locmem_engine(&[
+                ("snippet.html", "{{ greeting|default:'Hello' }} {{ name }}!"),
+                (
+                    "page.html",
+                    "{% include 'snippet.html' %}|{% include 'snippet.html' with greeting='Hi' %}|{% include 'snippet.html' with greeting='Hi' only %}|{{ greeting }}",
+                ),
+            ]);
+            let template = engine.get_template(py, "page.html").unwrap();
+            let context = PyDict::new(py);
+            context.set_item("name", "Lily").unwrap();
+
+            assert_eq!(
+                template.render(py, Some(context), None).unwrap(),
+                "Hello Lily!|Hi Lily!|Hi !|"
+            );
+        })
+    }
+
+    #[test]
+    fn test_render_template_include_missing() {
+        pyo3::prepare_freethreaded_python();
+
+        Python::with_gil(|py| {
+            let engine = locmem_engine(&[("page.html", "{% include 'missing.html' %}")]);
+            let template = engine.get_template(py, "page.html").unwrap();
+
+            let error = template.render(py, None, None).unwrap_err();
+            assert!(error.is_instance_of::<TemplateDoesNotExist>(py));
+        })
+    }
+
+    #[test]
+    fn test_render_template_include_recursive() {
+        pyo3::prepare_freethreaded_python();
+
+        Python::with_gil(|py| {
+            let engine = locmem_engine(&[
+                ("base.html", "{% block content %}{% endblock %}"),
+                (
+                    "child.html",
+                    "{% extends 'base.html' %}{% block content %}{% include 'loop.html' %}{% endblock %}",
+                ),
+                ("loop.html", "{% include 'loop.html' %}"),
+            ]);
+            let template = engine.get_template(py, "child.html").unwrap();
+
+            let error = template.render(py, None, None).unwrap_err();
+            assert!(error.is_instance_of::<pyo3::exceptions::PyRecursionError>(py));
+        })
+    }
+
+    #[test]
+    fn test_render_template_include_extends_depth_restored() {
+        pyo3::prepare_freethreaded_python();
+
+        Python::with_gil(|py| {
+            let engine = locmem_engine(&[
+                ("base.html", "[{% block content %}{% endblock %}]"),
+                (
+                    "child.html",
+                    "{% extends 'base.html' %}{% block content %}{% include 'a.html' %}{% endblock %}",
+                ),
+                ("a.html", "a"),
+            ]);
+            let template = engine.get_template(py, "child.html").unwrap();
+            let repeated = Template::new_from_string(
+                py,
+                "{% for i in items %}{% include 'child.html' %}{% endfor %}".to_string(),
+                &engine,
+            )
+            .unwrap();
+            let context = PyDict::new(py);
+            let items = PyList::new(py, 0..150).unwrap();
+            context.set_item("items", items).unwrap();
+
+            assert_eq!(template.render(py, None, None).unwrap(), "[a]");
+            assert_eq!(
+                repeated.render(py, Some(context), None).unwrap(),
+                "[a]".repeat(150)
+            );
+        })
+    }
+
+    #[test]
+    fn test_render_template_cycle_in_for_loop() {
+        pyo3::prepare_freethreaded_python();
+
+        Python::with_gil(|py| {
+            let engine = EngineData::empty();
+            let template_string = "{% for i in items %}{% cycle 'odd' 'even' %} {% endfor %}";
+            let template =
+                Template::new_from_string(py, template_string.to_string(), &engine).unwrap();
+            let context = PyDict::new(py);
+            context
+                .set_item("items", PyList::new(py, 0..5).unwrap())
+                .unwrap();
+
+            assert_eq!(
+                template.render(py, Some(context), None).unwrap(),
+                "odd even odd even odd "
+            );
+        })
+    }
+
+    #[test]
+    fn test_render_template_cycles_keep_separate_state() {
+        pyo3::prepare_freethreaded_python();
+
+        Python::with_gil(|py| {
+            let engine = EngineData::empty();
+            let template_string =
+                "{% for i in items %}{% cycle 'a' 'b' %}{% cycle 'x' 'y' 'z' %} {% endfor %}";
+            let template =
+                Template::new_from_string(py, template_string.to_string(), &engine).unwrap();
+            let context = PyDict::new(py);
+            context
+                .set_item("items", PyList::new(py, 0..4).unwrap())
+                .unwrap();
+
+            assert_eq!(
+                template.render(py, Some(context), None).unwrap(),
+                "ax by az bx "
+            );
+        })
+    }
+
+    #[test]
+    fn test_render_template_cycle_named() {
+        pyo3::prepare_freethreaded_python();
+
+        Python::with_gil(|py| {
+            let engine = EngineData::empty();
+            let template_string = "{% for i in items %}{% cycle 'a' 'b' 'c' as letter silent %}{{ letter }}{% cycle letter %}{% endfor %}";
+            let template =
+                Template::new_from_string(py, template_string.to_string(), &engine).unwrap();
+            let context = PyDict::new(py);
+            context
+                .set_item("items", PyList::new(py, 0..3).unwrap())
+                .unwrap();
+
+            // The named reference advances the same silent cycle
+            assert_eq!(template.render(py, Some(context), None).unwrap(), "acb");
+        })
+    }
+
+    #[test]
+    fn test_render_template_firstof() {
+        pyo3::prepare_freethreaded_python();
+
+        Python::with_gil(|py| {
+            let engine = EngineData::empty();
+            let template_string =
+                "{% firstof a b 'fallback' %}|{% firstof a b 'fallback' as c %}{{ c }}";
+            let template =
+                Template::new_from_string(py, template_string.to_string(), &engine).unwrap();
+
+            let context = PyDict::new(py);
+            context.set_item("a", 0).unwrap();
+            context.set_item("b", "second").unwrap();
+            assert_eq!(
+                template.render(py, Some(context), None).unwrap(),
+                "second|second"
+            );
+
+            let context = PyDict::new(py);
+            context.set_item("a", "").unwrap();
+            context.set_item("b", false).unwrap();
+            assert_eq!(
+                template.render(py, Some(context), None).unwrap(),
+                "fallback|fallback"
+            );
+        })
+    }
+
+    #[test]
+    fn test_render_template_extends_variable() {
+        pyo3::prepare_freethreaded_python();
+
+        Python::with_gil(|py| {
+            let engine = locmem_engine(&[
+                ("base.html", "<{% block content %}base{% endblock %}>"),
+                (
+                    "child.html",
+                    "{% extends parent %}{% block content %}child{% endblock %}",
+                ),
+            ]);
+            let template = engine.get_template(py, "child.html").unwrap();
+
+            let context = PyDict::new(py);
+            context.set_item("parent", "base.html").unwrap();
+            assert_eq!(template.render(py, Some(context), None).unwrap(), "<child>");
+
+            // A `Template` object can be used directly
+            let parent = engine.get_template(py, "base.html").unwrap();
+            let context = PyDict::new(py);
+            context.set_item("parent", parent).unwrap();
+            assert_eq!(template.render(py, Some(context), None).unwrap(), "<child>");
+        })
+    }
+
+    #[test]
+    fn test_render_template_extends_variable_missing() {
+        pyo3::prepare_freethreaded_python();
+
+        Python::with_gil(|py| {
+            let engine = locmem_engine(&[(
+                "child.html",
+                "{% extends parent %}{% block content %}child{% endblock %}",
+            )]);
+            let template = engine.get_template(py, "child.html").unwrap();
+
+            let error = template.render(py, None, None).unwrap_err();
+            assert!(error.is_instance_of::<TemplateSyntaxError>(py));
+            assert_eq!(
+                error.value(py).to_string(),
+                "Invalid template name in 'extends' tag: ''. Got this from the 'parent' variable."
+            );
+        })
+    }
+
+    #[test]
+    fn test_render_spaceless() {
+        pyo3::prepare_freethreaded_python();
+
+        Python::with_gil(|py| {
+            let engine = EngineData::empty();
+            let template_string =
+                "{% spaceless %}\n<ul>\n  <li> {{ name }} </li>\n</ul>\n{% endspaceless %}"
+                    .to_string();
+            let template = Template::new_from_string(py, template_string, &engine).unwrap();
+            let context = PyDict::new(py);
+            context.set_item("name", "Lily").unwrap();
+
+            assert_eq!(
+                template.render(py, Some(context), None).unwrap(),
+                "<ul><li> Lily </li></ul>"
+            );
+        })
+    }
+
+    #[test]
+    fn test_render_widthratio() {
+        pyo3::prepare_freethreaded_python();
+
+        Python::with_gil(|py| {
+            let engine = EngineData::empty();
+            let template_string =
+                "{% widthratio a b 100 %}|{% widthratio a 0 100 %}|{% widthratio a b 10 as w %}<{{ w }}>"
+                    .to_string();
+            let template = Template::new_from_string(py, template_string, &engine).unwrap();
+            let context = PyDict::new(py);
+            context.set_item("a", 175).unwrap();
+            context.set_item("b", 200).unwrap();
+
+            assert_eq!(
+                template.render(py, Some(context), None).unwrap(),
+                "88|0|<9>"
+            );
+        })
+    }
+
+    #[test]
+    fn test_render_widthratio_invalid_width() {
+        pyo3::prepare_freethreaded_python();
+
+        Python::with_gil(|py| {
+            let engine = EngineData::empty();
+            let template_string = "{% widthratio 50 100 width %}".to_string();
+            let template = Template::new_from_string(py, template_string, &engine).unwrap();
+            let context = PyDict::new(py);
+            context.set_item("width", "wide").unwrap();
+
+            let error = template.render(py, Some(context), None).unwrap_err();
+            assert!(error.is_instance_of::<TemplateSyntaxError>(py));
+            assert_eq!(
+                error.value(py).to_string(),
+                "widthratio final argument must be a number"
+            );
+        })
+    }
+
+    #[test]
+    fn test_render_filter_tag() {
+        pyo3::prepare_freethreaded_python();
+
+        Python::with_gil(|py| {
+            let engine = EngineData::empty();
+            let template_string =
+                "{% filter upper %}Hello {{ name }}{% endfilter %}|{% filter lower|capfirst %}HELLO {{ name }}{% endfilter %}"
+                    .to_string();
+            let template = Template::new_from_string(py, template_string, &engine).unwrap();
+            let context = PyDict::new(py);
+            context.set_item("name", "Lily").unwrap();
+
+            assert_eq!(
+                template.render(py, Some(context), None).unwrap(),
+                "HELLO LILY|Hello lily"
+            );
+        })
+    }
+
+    #[test]
+    fn test_render_template_forloop_after_loop() {
+        pyo3::prepare_freethreaded_python();
+
+        Python::with_gil(|py| {
+            let engine = EngineData::empty();
+            let template_string = "{% for row in rows %}{% for cell in row %}{% endfor %}{{ forloop.counter }}{% endfor %}|{{ forloop.counter }}|{{ row }}"
+                .to_string();
+            let template = Template::new_from_string(py, template_string, &engine).unwrap();
+            let context = PyDict::new(py);
+            let rows = vec![vec![1, 2], vec![3]];
+            context.set_item("rows", rows).unwrap();
+
+            assert_eq!(template.render(py, Some(context), None).unwrap(), "12||");
+        })
+    }
+
+    #[test]
+    fn test_render_lorem() {
+        pyo3::prepare_freethreaded_python();
+
+        Python::with_gil(|py| {
+            let engine = EngineData::empty();
+            let template_string = "{% lorem %}|{% lorem 3 w %}|{% lorem 1 p %}".to_string();
+            let template = Template::new_from_string(py, template_string, &engine).unwrap();
+
+            let lorem = crate::render::lorem::COMMON_P;
+            assert_eq!(
+                template.render(py, None, None).unwrap(),
+                format!("{lorem}|lorem ipsum dolor|<p>{lorem}</p>")
+            );
+        })
+    }
+
+    #[test]
+    fn test_render_csrf_token() {
+        pyo3::prepare_freethreaded_python();
+
+        Python::with_gil(|py| {
+            let engine = EngineData::empty();
+            let template_string = "{% csrf_token %}".to_string();
+            let template = Template::new_from_string(py, template_string, &engine).unwrap();
+
+            let context = PyDict::new(py);
+            context.set_item("csrf_token", "a\"b").unwrap();
+            assert_eq!(
+                template.render(py, Some(context), None).unwrap(),
+                r#"<input type="hidden" name="csrfmiddlewaretoken" value="a&quot;b">"#
+            );
+
+            let context = PyDict::new(py);
+            context.set_item("csrf_token", "").unwrap();
+            assert_eq!(template.render(py, Some(context), None).unwrap(), "");
+
+            assert_eq!(template.render(py, None, None).unwrap(), "");
+        })
+    }
+
+    #[test]
+    fn test_render_regroup() {
+        pyo3::prepare_freethreaded_python();
+
+        Python::with_gil(|py| {
+            let engine = EngineData::empty();
+            let template_string = "{% regroup people by gender as grouped %}\
+                {% for group in grouped %}{{ group.grouper }}:\
+                {% for person in group.list %}{{ person.name }},{% endfor %};\
+                {% endfor %}"
+                .to_string();
+            let template = Template::new_from_string(py, template_string, &engine).unwrap();
+            let people = PyList::empty(py);
+            for (name, gender) in [("Lily", "F"), ("Jo", "F"), ("Sam", "M"), ("Ann", "F")] {
+                let person = PyDict::new(py);
+                person.set_item("name", name).unwrap();
+                person.set_item("gender", gender).unwrap();
+                people.append(person).unwrap();
+            }
+            let context = PyDict::new(py);
+            context.set_item("people", people).unwrap();
+
+            // Like Django, only consecutive items are grouped together
+            assert_eq!(
+                template.render(py, Some(context), None).unwrap(),
+                "F:Lily,Jo,;M:Sam,;F:Ann,;"
+            );
+
+            assert_eq!(template.render(py, None, None).unwrap(), "");
+        })
+    }
+
+    #[test]
+    fn test_render_debug() {
+        pyo3::prepare_freethreaded_python();
+
+        Python::with_gil(|py| {
+            let engine = EngineData::empty();
+            let template_string = "{% debug %}".to_string();
+            let template = Template::new_from_string(py, template_string, &engine).unwrap();
+            let context = PyDict::new(py);
+            context.set_item("name", "<b>Lily</b>").unwrap();
+
+            assert_eq!(
+                template.render(py, Some(context), None).unwrap(),
+                "False: False\nNone: None\nTrue: True\nname: &#x27;&lt;b&gt;Lily&lt;/b&gt;&#x27;\n"
+            );
+        })
+    }
+
+    #[test]
+    fn test_render_lorem_random_seeded() {
+        pyo3::prepare_freethreaded_python();
+
+        Python::with_gil(|py| {
+            let engine = EngineData::empty();
+            let template_string = "{% lorem 5 w random %}".to_string();
+            let template = Template::new_from_string(py, template_string, &engine).unwrap();
+
+            let render = |seed| {
+                let mut context = Context::new(HashMap::new(), None, false);
+                context.state = RenderState::with_seed(seed);
+                template._render(py, &mut context).unwrap()
+            };
+            let rendered = render(7);
+            assert_eq!(rendered, render(7));
+            assert_eq!(rendered.split(' ').count(), 5);
+            assert!(!rendered.starts_with("lorem ipsum"));
+        })
+    }
+
+    #[test]
+    fn test_template_standalone() {
+        pyo3::prepare_freethreaded_python();
+
+        Python::with_gil(|py| {
+            let template =
+                Template::py_new(py, "Hello {{ name }}!".to_string(), None, None).unwrap();
+            let context = PyDict::new(py);
+            context.set_item("name", "<Lily>").unwrap();
+
+            assert_eq!(
+                template.render(py, Some(context), None).unwrap(),
+                "Hello &lt;Lily&gt;!"
+            );
+        })
+    }
+
+    #[test]
+    fn test_template_standalone_autoescape_off() {
+        pyo3::prepare_freethreaded_python();
+
+        Python::with_gil(|py| {
+            let template =
+                Template::py_new(py, "Hello {{ name }}!".to_string(), None, Some(false)).unwrap();
+            let context = PyDict::new(py);
+            context.set_item("name", "<Lily>").unwrap();
+
+            assert_eq!(
+                template.render(py, Some(context), None).unwrap(),
+                "Hello <Lily>!"
+            );
+        })
+    }
+
+    #[test]
+    fn test_render_template_nodes() {
+        pyo3::prepare_freethreaded_python();
+
+        Python::with_gil(|py| {
+            let template = TemplateString("<p>Hi {{ name }}!</p>");
+            let libraries = HashMap::new();
+            let nodes = Parser::new(py, template, &libraries).parse().unwrap();
+            let name = PyString::new(py, "<Lily>").into_any().unbind();
+            let context = HashMap::from([("name".to_string(), name)]);
+            let mut context = Context::new(context, None, true);
+
+            let rendered = render_template(py, &nodes, template, &mut context).unwrap();
+            assert_eq!(rendered, "<p>Hi &lt;Lily&gt;!</p>");
+        })
+    }
+
+    #[test]
+    fn test_render_if_is_none() {
+        pyo3::prepare_freethreaded_python();
+
+        Python::with_gil(|py| {
+            let engine = EngineData::empty();
+            let template_string =
+                "{% if x is None %}none{% endif %}|{% if x is not None %}set{% endif %}"
+                    .to_string();
+            let template = Template::new_from_string(py, template_string, &engine).unwrap();
+
+            let context = PyDict::new(py);
+            context.set_item("x", py.None()).unwrap();
+            assert_eq!(template.render(py, Some(context), None).unwrap(), "none|");
+
+            let context = PyDict::new(py);
+            context.set_item("x", 0).unwrap();
+            assert_eq!(template.render(py, Some(context), None).unwrap(), "|set");
+
+            let context = PyDict::new(py);
+            assert_eq!(template.render(py, Some(context), None).unwrap(), "none|");
+        })
+    }
+
+    #[test]
+    fn test_render_numeric_literals() {
+        pyo3::prepare_freethreaded_python();
+
+        Python::with_gil(|py| {
+            let engine = EngineData::empty();
+            let template_string =
+                "{{ 42 }} {{ 3.5 }} {{ -5 }} {{ 1.0 }} {{ -5|add:2 }}".to_string();
+            let template = Template::new_from_string(py, template_string, &engine).unwrap();
+
+            assert_eq!(template.render(py, None, None).unwrap(), "42 3.5 -5 1.0 -3");
+        })
+    }
+
+    #[test]
+    fn test_render_string_literals() {
+        pyo3::prepare_freethreaded_python();
+
+        Python::with_gil(|py| {
+            let engine = Arc::new(EngineData::default());
+            let template_string = "{{ 'hello' }} {{ \"<b>\" }} {{ 'foo'|upper }}".to_string();
+            let template = Template::new_from_string(py, template_string, &engine).unwrap();
+
+            assert_eq!(template.render(py, None, None).unwrap(), "hello <b> FOO");
+        })
+    }
+
+    #[test]
+    fn test_render_string_if_invalid() {
+        pyo3::prepare_freethreaded_python();
+
+        Python::with_gil(|py| {
+            let render = |string_if_invalid: &str, template_string: &str| {
+                let engine = EngineData::with_string_if_invalid(string_if_invalid);
+                let template =
+                    Template::new_from_string(py, template_string.to_string(), &engine).unwrap();
+                let context = PyDict::new(py);
+                context.set_item("name", "Lily").unwrap();
+                template.render(py, Some(context), None).unwrap()
+            };
+
+            assert_eq!(
+                render(
+                    "INVALID",
+                    "{{ missing }} {{ missing|upper }} {{ name|upper }}"
+                ),
+                "INVALID INVALID LILY"
+            );
+            assert_eq!(
+                render("INVALID(%s)", "{{ missing }} {{ other|default:'x' }}"),
+                "INVALID(missing) INVALID(other)"
+            );
+            assert_eq!(render("<%s>", "{{ missing }}"), "&lt;missing&gt;");
+            assert_eq!(render("", "{{ missing }}{{ other|default:'x' }}"), "x");
+        })
+    }
+
+    #[test]
+    fn test_render_blocktranslate() {
+        pyo3::prepare_freethreaded_python();
+
+        Python::with_gil(|py| {
+            let engine = Arc::new(EngineData::default());
+            let template_string =
+                "{% blocktranslate %}Hello {{ name }}{% endblocktranslate %}".to_string();
+            let template = Template::new_from_string(py, template_string, &engine).unwrap();
+            let context = PyDict::new(py);
+            context.set_item("name", "<Lily>").unwrap();
+
+            let mut rendered = None;
+            with_mock_translation(py, || {
+                rendered = Some(template.render(py, Some(context), None));
+            });
+            assert_eq!(rendered.unwrap().unwrap(), "Bonjour &lt;Lily&gt;");
+        })
+    }
+
+    #[test]
+    fn test_render_blocktranslate_count() {
+        pyo3::prepare_freethreaded_python();
+
+        Python::with_gil(|py| {
+            let engine = Arc::new(EngineData::default());
+            let template_string = "{% blocktranslate count counter=items|length with unit='%' %}{{ counter }}{{ unit }} item{% plural %}{{ counter }}{{ unit }} items{% endblocktranslate %}".to_string();
+            let template = Template::new_from_string(py, template_string, &engine).unwrap();
+
+            let mut rendered = Vec::new();
+            with_mock_translation(py, || {
+                for items in [vec![1], vec![1, 2, 3]] {
+                    let context = PyDict::new(py);
+                    context.set_item("items", items).unwrap();
+                    rendered.push(template.render(py, Some(context), None).unwrap());
+                }
+            });
+            assert_eq!(rendered, vec!["1% item", "3% items"]);
+        })
+    }
+
+    #[test]
+    fn test_render_translate() {
+        pyo3::prepare_freethreaded_python();
+
+        Python::with_gil(|py| {
+            let engine = Arc::new(EngineData::default());
+            let mut rendered = Vec::new();
+            with_mock_translation(py, || {
+                for template_string in [
+                    "{% translate 'Hello' %}",
+                    "{% trans greeting|upper %}",
+                    "{% translate 'Hello' noop %}",
+                    "{% translate 'Hello' as greeting %}[{{ greeting }}]",
+                    "{% translate 'File' context 'menu' %} {% translate 'File' %}",
+                    "{% translate '100%' %}",
+                ] {
+                    let template =
+                        Template::new_from_string(py, template_string.to_string(), &engine)
+                            .unwrap();
+                    let context = PyDict::new(py);
+                    context.set_item("greeting", "Hello").unwrap();
+                    rendered.push(template.render(py, Some(context), None).unwrap());
+                }
+            });
+            assert_eq!(
+                rendered,
+                vec![
+                    "Bonjour",
+                    "BONJOUR",
+                    "Hello",
+                    "[Bonjour]",
+                    "Fichier File",
+                    "100%"
+                ]
+            );
+        })
+    }
+
+    #[test]
+    fn test_engine_from_string() {
+        pyo3::prepare_freethreaded_python();
+
+        Python::with_gil(|py| {
+            let engine = Engine::new(
+                py,
+                None,
+                false,
+                None,
+                false,
                 None,
+                "".to_string(),
+                "utf-8".to_string(),
+                None,
+                None,
+                false,
+                false,
+            )
+            .unwrap();
+            let template_string = PyString::new(py, "Hello {{ user }}!");
+            let template = engine.from_string(template_string, None).unwrap();
+            let context = PyDict::new(py);
+
+            assert_eq!(template.render(py, Some(context), None).unwrap(), "Hello !");
+        })
+    }
+
+    #[test]
+    fn test_engine_from_string_autoescape_override() {
+        pyo3::prepare_freethreaded_python();
+
+        Python::with_gil(|py| {
+            let engine = Engine::new(
+                py,
+                None,
+                false,
+                None,
+                false,
+                None,
+                "".to_string(),
+                "utf-8".to_string(),
+                None,
+                None,
+                true,
+                false,
+            )
+            .unwrap();
+            let context = PyDict::new(py);
+            context.set_item("html", "<b>").unwrap();
+
+            let template_string = PyString::new(py, "{{ html }}");
+            let template = engine.from_string(template_string.clone(), None).unwrap();
+            assert_eq!(
+                template.render(py, Some(context.clone()), None).unwrap(),
+                "&lt;b&gt;"
+            );
+
+            let template = engine.from_string(template_string, Some(false)).unwrap();
+            assert_eq!(template.render(py, Some(context), None).unwrap(), "<b>");
+        })
+    }
+
+    #[test]
+    fn test_engine_slugify_allow_unicode() {
+        pyo3::prepare_freethreaded_python();
+
+        Python::with_gil(|py| {
+            let engine = Engine::new(
+                py,
+                None,
+                false,
+                None,
+                false,
+                None,
+                "".to_string(),
+                "utf-8".to_string(),
+                None,
+                None,
+                false,
+                true,
+            )
+            .unwrap();
+            let template_string = PyString::new(py, "{{ title|slugify }}");
+            let template = engine.from_string(template_string, None).unwrap();
+            let context = PyDict::new(py);
+            context.set_item("title", "你好 世界").unwrap();
+
+            assert_eq!(
+                template.render(py, Some(context), None).unwrap(),
+                "你好-世界"
+            );
+        })
+    }
+
+    #[test]
+    fn test_engine_locmem_loader() {
+        use pyo3::types::PyList;
+
+        pyo3::prepare_freethreaded_python();
+
+        Python::with_gil(|py| {
+            let templates = PyDict::new(py);
+            templates
+                .set_item("hello.html", "Hello {{ user }}!")
+                .unwrap();
+            let locmem = ("django.template.loaders.locmem.Loader", templates);
+            let cached = ("django.template.loaders.cached.Loader", vec![locmem]);
+            let loaders = PyList::new(py, [cached]).unwrap();
+            let engine = Engine::new(
+                py,
+                None,
+                false,
+                None,
+                false,
+                Some(loaders.into_any()),
+                "".to_string(),
+                "utf-8".to_string(),
+                None,
+                None,
+                false,
+                false,
+            )
+            .unwrap();
+            let template = engine.get_template(py, "hello.html".to_string()).unwrap();
+            let context = PyDict::new(py);
+            context.set_item("user", "Lily").unwrap();
+
+            assert_eq!(
+                template.render(py, Some(context), None).unwrap(),
+                "Hello Lily!"
+            );
+        })
+    }
+
+    #[test]
+    fn test_clone_template() {
+        use std::collections::HashMap;
+
+        use pyo3::IntoPyObject;
+        use pyo3::types::{PyAnyMethods, PyListMethods};
+
+        pyo3::prepare_freethreaded_python();
+
+        Python::with_gil(|py| {
+            let cwd = std::env::current_dir().unwrap();
+            let sys_path = py.import("sys").unwrap().getattr("path").unwrap();
+            let sys_path = sys_path.downcast().unwrap();
+            sys_path.append(cwd).unwrap();
+            let engine = Engine::new(
+                py,
+                Some(vec!["tests/templates"].into_pyobject(py).unwrap()),
+                false,
+                None,
+                false,
+                None,
+                "".to_string(),
+                "utf-8".to_string(),
+                Some(
+                    HashMap::from([("custom_filters", "tests.templatetags.custom_filters")])
+                        .into_pyobject(py)
+                        .unwrap()
+                        .into_any(),
+                ),
+                None,
+                false,
                 false,
             )
             .unwrap();
@@ -68,23 +68,38 @@ impl TranslatedText {
     }
 }
 
-#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub struct Variable {
     pub at: (usize, usize),
+    /// The spans of the `.` separated parts, split once when parsing so
+    /// rendering a variable in a loop doesn't split it again.
+    parts: Vec<(usize, usize)>,
 }
 
 impl<'t> Variable {
-    pub fn new(at: (usize, usize)) -> Self {
-        Self { at }
+    pub fn new(at: (usize, usize), template: impl Into<TemplateString<'t>>) -> Self {
+        let variable = template.into().content(at);
+        let parts = PartsIterator {
+            variable,
+            start: at.0,
+        }
+        .map(|(_, at)| at)
+        .collect();
+        Self { at, parts }
+    }
+
+    /// Look this variable up on `parent` instead of the context, like
+    /// Django's `regroup` resolving `parent.key`.
+    pub fn with_parent(mut self, parent: (usize, usize)) -> Self {
+        self.parts.insert(0, parent);
+        self
     }
 
     pub fn parts(
         &self,
         template: TemplateString<'t>,
     ) -> impl Iterator<Item = (&'t str, (usize, usize))> {
-        let start = self.at.0;
-        let variable = template.content(self.at);
-        PartsIterator { variable, start }
+        self.parts.iter().map(move |&at| (template.content(at), at))
     }
 }
 
@@ -93,7 +108,9 @@ pub enum ArgumentType {
     Variable(Variable),
     Text(Text),
     TranslatedText(TranslatedText),
-    Int(BigInt),
+    /// An integer small enough to avoid allocating a `BigInt`.
+    Int(i64),
+    BigInt(BigInt),
     Float(f64),
 }
 
@@ -1,5 +1,7 @@
+use pyo3::exceptions::PyAttributeError;
 use pyo3::prelude::*;
 use pyo3::type_object::PyTypeInfo;
+use pyo3::types::PyString;
 
 pub trait PyResultMethods<T> {
     fn ok_or_isinstance_of<E>(self, py: Python<'_>) -> PyResult<PyResult<T>>
@@ -19,3 +21,132 @@ impl<T> PyResultMethods<T> for PyResult<T> {
         }
     }
 }
+
+/// Read a flag such as `is_safe` or `alters_data` from a Python object,
+/// defaulting to `false` like Django's `getattr(obj, name, False)`.
+pub fn getattr_flag(object: &Bound<'_, PyAny>, name: &Bound<'_, PyString>) -> PyResult<bool> {
+    match object
+        .getattr(name)
+        .ok_or_isinstance_of::<PyAttributeError>(object.py())?
+    {
+        Ok(flag) => flag.is_truthy(),
+        Err(_) => Ok(false),
+    }
+}
+
+/// Find the 1-based line and column of a byte offset into a template.
+///
+/// The column counts characters rather than bytes, so multibyte characters
+/// earlier on the line only count once.
+pub fn line_col(template: &str, byte: usize) -> (usize, usize) {
+    let before = &template[..byte];
+    let (line, line_start) = match before.rfind('\n') {
+        Some(index) => (before.matches('\n').count() + 1, index + 1),
+        None => (1, 0),
+    };
+    let column = before[line_start..].chars().count() + 1;
+    (line, column)
+}
+
+/// Run `f` with `gettext`, `pgettext` and `ngettext` in Django's
+/// `django.utils.translation` replaced by fakes translating "Hello %(name)s",
+/// "Hello" and "File" in the "menu" context to French, and leaving other
+/// messages alone.
+///
+/// Only those functions are patched, so other code importing the module
+/// meanwhile still gets Django's. Tests run in parallel threads sharing one
+/// interpreter, so only one of them patches at a time.
+#[cfg(test)]
+pub fn with_mock_translation(py: Python<'_>, f: impl FnOnce()) {
+    use pyo3::sync::GILOnceCell;
+    use pyo3::types::PyDict;
+
+    static MOCK_TRANSLATION: GILOnceCell<Py<PyAny>> = GILOnceCell::new();
+
+    struct Patched<'py>(Bound<'py, PyAny>);
+
+    impl Drop for Patched<'_> {
+        fn drop(&mut self) {
+            let py = self.0.py();
+            let exited = self
+                .0
+                .call_method1("__exit__", (py.None(), py.None(), py.None()));
+            // Don't panic again while unwinding from a failed test
+            if !std::thread::panicking() {
+                exited.unwrap();
+            }
+        }
+    }
+
+    let mock_translation = MOCK_TRANSLATION
+        .get_or_try_init(py, || {
+            let globals = PyDict::new(py);
+            py.run(
+                c"
+import contextlib
+import threading
+from unittest import mock
+
+from django.utils import translation
+
+lock = threading.Lock()
+messages = {'Hello %(name)s': 'Bonjour %(name)s', 'Hello': 'Bonjour'}
+
+def gettext(message):
+    return messages.get(message, message)
+
+def pgettext(context, message):
+    return {('menu', 'File'): 'Fichier'}.get((context, message), message)
+
+def ngettext(singular, plural, count):
+    return singular if count == 1 else plural
+
+@contextlib.contextmanager
+def mock_translation():
+    with lock, mock.patch.multiple(
+        translation, gettext=gettext, pgettext=pgettext, ngettext=ngettext
+    ):
+        yield
+",
+                Some(&globals),
+                None,
+            )?;
+            PyResult::Ok(globals.get_item("mock_translation")?.unwrap().unbind())
+        })
+        .unwrap();
+    let patched = mock_translation.bind(py).call0().unwrap();
+    patched.call_method0("__enter__").unwrap();
+    let _patched = Patched(patched);
+    f();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_line_col_first_line() {
+        assert_eq!(line_col("{{ foo }}", 0), (1, 1));
+        assert_eq!(line_col("{{ foo }}", 3), (1, 4));
+    }
+
+    #[test]
+    fn test_line_col_after_newline() {
+        let template = "<p>\n  {{ foo }}\n</p>";
+        assert_eq!(line_col(template, 4), (2, 1));
+        assert_eq!(line_col(template, 9), (2, 6));
+        assert_eq!(line_col(template, 16), (3, 1));
+    }
+
+    #[test]
+    fn test_line_col_multibyte() {
+        let template = "é {{ foo }}\nü ü {{ bar }}";
+        assert_eq!(line_col(template, 6), (1, 6));
+        assert_eq!(line_col(template, 22), (2, 8));
+    }
+
+    #[test]
+    fn test_line_col_end() {
+        assert_eq!(line_col("a\n", 2), (2, 1));
+    }
+}
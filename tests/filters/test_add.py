@@ -120,6 +120,26 @@ def test_add_incompatible_float():
     assert rust_template.render({"foo": [1]}) == ""
 
 
+def test_add_string_and_integer():
+    template = "{{ label|add:count }}"
+    django_template = engines["django"].from_string(template)
+    rust_template = engines["rusty"].from_string(template)
+
+    context = {"label": "Total: ", "count": 5}
+    assert django_template.render(context) == ""
+    assert rust_template.render(context) == ""
+
+
+def test_add_integer_and_string():
+    template = "{{ count|add:label }}"
+    django_template = engines["django"].from_string(template)
+    rust_template = engines["rusty"].from_string(template)
+
+    context = {"label": "Total: ", "count": 5}
+    assert django_template.render(context) == ""
+    assert rust_template.render(context) == ""
+
+
 def test_add_missing_argument():
     template = "{{ foo|add }}"
 
@@ -169,3 +189,24 @@ def test_add_strings_autoescape_off():
 
     assert django_template.render({"foo": "abc"}) == "abcdef"
     assert rust_template.render({"foo": "abc"}) == "abcdef"
+
+
+class Money:
+    def __init__(self, amount):
+        self.amount = amount
+
+    def __add__(self, other):
+        return f"{self.amount} + {other}"
+
+
+class Broken:
+    def __add__(self, other):
+        raise RuntimeError
+
+
+def test_add_dunder_add(assert_render):
+    assert_render("{{ money|add:3 }}", {"money": Money(5)}, "5 + 3")
+
+
+def test_add_dunder_add_error(assert_render):
+    assert_render("{{ broken|add:3 }}", {"broken": Broken()}, "")
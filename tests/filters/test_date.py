@@ -0,0 +1,53 @@
+from datetime import date, datetime, time
+
+import pytest
+
+
+@pytest.mark.parametrize("value", [None, ""])
+def test_date_empty(assert_render, value):
+    template = '{{ value|date:"Y" }}'
+    assert_render(template, {"value": value}, "")
+
+
+def test_date_missing(assert_render):
+    template = '{{ missing|date:"Y" }}'
+    assert_render(template, {}, "")
+
+
+@pytest.mark.parametrize(
+    "value,expected",
+    [
+        (date(2024, 3, 7), "2024-03-07"),
+        (datetime(2024, 3, 7, 14, 5), "2024-03-07"),
+    ],
+)
+def test_date(assert_render, value, expected):
+    template = '{{ value|date:"Y-m-d" }}'
+    assert_render(template, {"value": value}, expected)
+
+
+def test_date_default_format(rusty, django_template):
+    template = "{{ value|date }}"
+    context = {"value": date(2024, 3, 7)}
+    assert rusty(template).render(context) == django_template(template).render(context)
+
+
+def test_date_invalid(assert_render):
+    template = '{{ value|date:"Y" }}'
+    assert_render(template, {"value": "not a date"}, "")
+
+
+@pytest.mark.parametrize("value", [None, ""])
+def test_time_empty(assert_render, value):
+    template = '{{ value|time:"H:i" }}'
+    assert_render(template, {"value": value}, "")
+
+
+def test_time(assert_render):
+    template = '{{ value|time:"H:i" }}'
+    assert_render(template, {"value": time(14, 5)}, "14:05")
+
+
+def test_date_format_with_colons(assert_render):
+    template = '{{ value|date:"H:i:s" }}'
+    assert_render(template, {"value": datetime(2024, 3, 7, 14, 5, 9)}, "14:05:09")
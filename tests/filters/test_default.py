@@ -0,0 +1,62 @@
+from types import SimpleNamespace
+
+import pytest
+from django.utils.safestring import mark_safe
+from django.utils.translation import override
+
+
+def test_default_missing(assert_render):
+    template = "{{ name|default:'Lily' }}"
+    assert_render(template, {}, "Lily")
+
+
+def test_default_present(assert_render):
+    template = "{{ name|default:'Lily' }}"
+    assert_render(template, {"name": "Rose"}, "Rose")
+
+
+def test_default_text_argument_not_escaped(assert_render):
+    template = "{{ name|default:'<b>' }}"
+    assert_render(template, {}, "<b>")
+
+
+def test_default_translated_argument_not_escaped(assert_render):
+    template = '{{ name|default:_("<b>") }}'
+    with override("en"):
+        assert_render(template, {}, "<b>")
+
+
+def test_default_variable_argument_escaped(assert_render):
+    template = "{{ name|default:other }}"
+    assert_render(template, {"other": "<b>"}, "&lt;b&gt;")
+
+
+def test_default_nested_variable_argument(assert_render):
+    template = "{{ missing|default:user.profile.name }}"
+    context = {"user": {"profile": {"name": "Lily"}}}
+    assert_render(template, context, "Lily")
+
+
+def test_default_mixed_lookup_argument(assert_render):
+    template = "{{ missing|default:users.1.profile.name }}"
+    users = [
+        SimpleNamespace(profile={"name": "Rose"}),
+        SimpleNamespace(profile={"name": "Lily"}),
+    ]
+    assert_render(template, {"users": users}, "Lily")
+
+
+def test_default_safe_variable_argument(assert_render):
+    template = "{{ name|default:other }}"
+    assert_render(template, {"other": mark_safe("<b>")}, "<b>")
+
+
+@pytest.mark.parametrize("argument", ["'<b>'", "other"])
+def test_default_argument_autoescape_off(assert_render, argument):
+    template = f"{{% autoescape off %}}{{{{ name|default:{argument} }}}}{{% endautoescape %}}"
+    assert_render(template, {"other": "<b>"}, "<b>")
+
+
+def test_default_leading_zeros(assert_render):
+    template = "{{ name|default:007 }}"
+    assert_render(template, {}, "7")
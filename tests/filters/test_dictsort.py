@@ -0,0 +1,68 @@
+import pytest
+
+
+def test_dictsort(assert_render):
+    template = "{% for item in items|dictsort:'age' %}{{ item.name }} {% endfor %}"
+    items = [
+        {"name": "Lily", "age": 31},
+        {"name": "Bryony", "age": 12},
+        {"name": "Ada", "age": 27},
+    ]
+    assert_render(template, {"items": items}, "Bryony Ada Lily ")
+
+
+def test_dictsort_nested_key(assert_render):
+    template = (
+        "{% for item in items|dictsort:'owner.name' %}{{ item.pet }} {% endfor %}"
+    )
+    items = [
+        {"pet": "cat", "owner": {"name": "Lily"}},
+        {"pet": "dog", "owner": {"name": "Bryony"}},
+    ]
+    assert_render(template, {"items": items}, "dog cat ")
+
+
+def test_dictsort_index(assert_render):
+    template = "{% for item in items|dictsort:1 %}{{ item.0 }}{% endfor %}"
+    items = [("a", 3), ("b", 1), ("c", 2)]
+    assert_render(template, {"items": items}, "bca")
+
+
+@pytest.mark.parametrize("value", [1, "abc", None])
+def test_dictsort_not_a_list(assert_render, value):
+    template = '{{ value|dictsort:"x" }}'
+    assert_render(template, {"value": value}, "")
+
+
+def test_dictsort_missing_key(assert_render):
+    template = "{{ items|dictsort:'x' }}"
+    assert_render(template, {"items": [{"y": 1}]}, "")
+
+
+def test_dictsort_private_key(assert_render):
+    template = "{{ items|dictsort:'_x' }}"
+    assert_render(template, {"items": [{"_x": 1}]}, "")
+
+
+def test_dictsortreversed(assert_render):
+    template = (
+        "{% for item in items|dictsortreversed:'age' %}{{ item.name }} {% endfor %}"
+    )
+    items = [
+        {"name": "Lily", "age": 31},
+        {"name": "Bryony", "age": 12},
+        {"name": "Ada", "age": 27},
+    ]
+    assert_render(template, {"items": items}, "Lily Ada Bryony ")
+
+
+def test_dictsortreversed_stable(assert_render):
+    template = "{% for item in items|dictsortreversed:1 %}{{ item.0 }}{% endfor %}"
+    items = [("a", 1), ("b", 3), ("c", 1), ("d", 3), ("e", 2)]
+    assert_render(template, {"items": items}, "bdeac")
+
+
+@pytest.mark.parametrize("value", [1, "abc", None])
+def test_dictsortreversed_not_a_list(assert_render, value):
+    template = '{{ value|dictsortreversed:"x" }}'
+    assert_render(template, {"value": value}, "")
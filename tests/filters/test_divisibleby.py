@@ -0,0 +1,62 @@
+import pytest
+from django.template import engines
+
+
+@pytest.mark.parametrize(
+    "value,argument,expected",
+    [
+        (21, 3, "True"),
+        (21, 4, "False"),
+        ("21", 3, "True"),
+        (21, "3", "True"),
+        ("21", "4", "False"),
+        (" 21 ", 7, "True"),
+        (21.9, 7, "True"),
+        (True, 1, "True"),
+    ],
+)
+def test_divisibleby(assert_render, value, argument, expected):
+    template = "{{ value|divisibleby:argument }}"
+    context = {"value": value, "argument": argument}
+    assert_render(template, context, expected)
+
+
+def test_divisibleby_numeric_string_argument(assert_render):
+    template = '{{ value|divisibleby:"3" }}'
+    assert_render(template, {"value": "21"}, "True")
+
+
+@pytest.mark.parametrize(
+    "template,expected",
+    [
+        ("{% if value|divisibleby:3 is True %}yes{% endif %}", "yes"),
+        ("{% if value|divisibleby:3 == 1 %}yes{% endif %}", "yes"),
+        ("{{ value|divisibleby:3|yesno:'a,b' }}", "a"),
+        ("{{ value|divisibleby:4|add:1 }}", "1"),
+    ],
+)
+def test_divisibleby_bool(assert_render, template, expected):
+    assert_render(template, {"value": 21}, expected)
+
+
+@pytest.mark.parametrize(
+    "context,exception",
+    [
+        ({"value": "abc", "argument": 3}, ValueError),
+        ({"argument": 3}, ValueError),
+        ({"value": None, "argument": 3}, TypeError),
+        ({"value": 3, "argument": 0}, ZeroDivisionError),
+    ],
+)
+def test_divisibleby_invalid(context, exception):
+    template = "{{ value|divisibleby:argument }}"
+    django_template = engines["django"].from_string(template)
+    rust_template = engines["rusty"].from_string(template)
+
+    with pytest.raises(exception) as django_error:
+        django_template.render(context)
+
+    with pytest.raises(exception) as rust_error:
+        rust_template.render(context)
+
+    assert str(rust_error.value) == str(django_error.value)
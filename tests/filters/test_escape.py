@@ -1,6 +1,7 @@
 import pytest
 from django.template import engines
 from django.template.exceptions import TemplateSyntaxError
+from django.utils.safestring import mark_safe
 
 
 def test_escape():
@@ -94,3 +95,36 @@ def test_escape_autoescape_off_lower():
     escaped = "&lt;p&gt;hello world!&lt;/p&gt;"
     assert django_template.render({"html": html}) == escaped
     assert rust_template.render({"html": html}) == escaped
+
+
+def test_escape_safe_string(assert_render):
+    template = "{{ html|escape }}"
+    html = mark_safe("<p>Tom &amp; Jerry</p>")
+    assert_render(template, {"html": html}, "<p>Tom &amp; Jerry</p>")
+
+
+def test_escape_after_safe(assert_render):
+    template = "{{ html|safe|escape }}"
+    assert_render(template, {"html": "<p>"}, "<p>")
+
+
+def test_escape_float_repr(assert_render):
+    template = "{{ num|default:1.0|escape }}"
+    assert_render(template, {}, "1.0")
+
+
+@pytest.mark.parametrize(
+    "template",
+    [
+        "{{ html|escape }}",
+        "{% autoescape off %}{{ html|escape }}{% endautoescape %}",
+        "{{ html }}",
+    ],
+)
+def test_escape_versus_autoescape(assert_render, template):
+    assert_render(template, {"html": "<b>&</b>"}, "&lt;b&gt;&amp;&lt;/b&gt;")
+
+
+def test_no_escape_autoescape_off(assert_render):
+    template = "{% autoescape off %}{{ html }}{% endautoescape %}"
+    assert_render(template, {"html": "<b>&</b>"}, "<b>&</b>")
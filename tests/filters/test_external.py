@@ -103,3 +103,28 @@ def test_filter_error_with_argument():
 
     with pytest.raises(ZeroDivisionError):
         rust_template.render({"num": 1})
+
+
+def test_load_and_render_reverse_str(assert_render):
+    template = "{% load reverse_str from custom_filters %}{{ name|reverse_str }}"
+    assert_render(template, {"name": "Lily"}, "yliL")
+
+
+def test_is_safe_filter(assert_render):
+    template = "{% load bold from custom_filters %}{{ text|bold }}"
+    assert_render(template, {"text": "<i>x</i>"}, "&lt;b&gt;&lt;i&gt;x&lt;/i&gt;&lt;/b&gt;")
+
+
+def test_is_safe_filter_safe_input(assert_render):
+    template = "{% load bold from custom_filters %}{{ text|safe|bold }}"
+    assert_render(template, {"text": "<i>x</i>"}, "<b><i>x</i></b>")
+
+
+def test_needs_autoescape_filter(assert_render):
+    template = "{% load initial_letter from custom_filters %}{{ text|initial_letter }}"
+    assert_render(template, {"text": "<i>"}, "<strong>&lt;</strong>i&gt;")
+
+
+def test_needs_autoescape_filter_autoescape_off(assert_render):
+    template = "{% load initial_letter from custom_filters %}{% autoescape off %}{{ text|initial_letter }}{% endautoescape %}"
+    assert_render(template, {"text": "<i>"}, "<strong><</strong>i>")
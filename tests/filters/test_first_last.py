@@ -0,0 +1,51 @@
+import pytest
+from django.template import engines
+from django.template.exceptions import TemplateSyntaxError
+
+
+@pytest.mark.parametrize(
+    "value,expected",
+    [(["a", "b", "c"], "a"), ("xyz", "x"), ([], ""), ("", "")],
+)
+def test_first(assert_render, value, expected):
+    template = "{{ value|first }}"
+    assert_render(template, {"value": value}, expected)
+
+
+@pytest.mark.parametrize(
+    "value,expected",
+    [(["a", "b", "c"], "c"), ("xyz", "z"), ([], ""), ("", "")],
+)
+def test_last(assert_render, value, expected):
+    template = "{{ value|last }}"
+    assert_render(template, {"value": value}, expected)
+
+
+def test_first_missing(assert_render):
+    assert_render("{{ value|first }}", {}, "")
+
+
+def test_first_escaped(assert_render):
+    assert_render("{{ value|first }}", {"value": ["<b>", "c"]}, "&lt;b&gt;")
+
+
+def test_last_unexpected_argument():
+    template = "{{ value|last:'x' }}"
+
+    with pytest.raises(TemplateSyntaxError) as exc_info:
+        engines["django"].from_string(template)
+
+    assert str(exc_info.value) == "last requires 1 arguments, 2 provided"
+
+    with pytest.raises(TemplateSyntaxError) as exc_info:
+        engines["rusty"].from_string(template)
+
+    expected = """\
+  × last filter does not take an argument
+   ╭────
+ 1 │ {{ value|last:'x' }}
+   ·               ─┬─
+   ·                ╰── unexpected argument
+   ╰────
+"""
+    assert str(exc_info.value) == expected
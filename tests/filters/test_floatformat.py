@@ -0,0 +1,42 @@
+from decimal import Decimal
+
+import pytest
+
+
+@pytest.mark.parametrize(
+    "value,argument,expected",
+    [
+        (2.5, "0", "3"),
+        (1.5, "0", "2"),
+        (0.125, "2", "0.13"),
+        (-2.5, "0", "-3"),
+        (34.23234, "", "34.2"),
+        (34.0, "", "34"),
+        (34.26, "", "34.3"),
+        (34.23234, "3", "34.232"),
+        (34.0, "-3", "34"),
+        (34.26, "-3", "34.260"),
+        (Decimal("1.005"), "2", "1.01"),
+        ("abc", "", ""),
+        (None, "", ""),
+    ],
+)
+def test_floatformat(assert_render, value, argument, expected):
+    argument = f":{argument}" if argument else ""
+    template = f"{{{{ value|floatformat{argument} }}}}"
+    assert_render(template, {"value": value}, expected)
+
+
+def test_floatformat_variable_argument(assert_render):
+    template = "{{ value|floatformat:places }}"
+    assert_render(template, {"value": 0.125, "places": 2}, "0.13")
+
+
+def test_floatformat_missing(assert_render):
+    template = "{{ missing|floatformat }}"
+    assert_render(template, {}, "")
+
+
+def test_floatformat_invalid_argument(assert_render):
+    template = "{{ value|floatformat:'abc' }}"
+    assert_render(template, {"value": 1.5}, "1.5")
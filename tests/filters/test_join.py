@@ -0,0 +1,42 @@
+def test_join(assert_render):
+    template = "{{ items|join:', ' }}"
+    assert_render(template, {"items": ["a", "b", "c"]}, "a, b, c")
+
+
+def test_join_set(assert_render):
+    template = "{{ items|join:',' }}"
+    items = {"a", "b", "c"}
+    expected = ",".join(items)
+    assert_render(template, {"items": items}, expected)
+
+
+def test_join_autoescape(assert_render):
+    template = "{{ items|join:sep }}"
+    context = {"items": ["<a>", "<b>"], "sep": "&"}
+    assert_render(template, context, "&lt;a&gt;&amp;&lt;b&gt;")
+
+
+def test_join_autoescape_off(assert_render):
+    template = "{% autoescape off %}{{ items|join:sep }}{% endautoescape %}"
+    context = {"items": ["<a>", "<b>"], "sep": "&"}
+    assert_render(template, context, "<a>&<b>")
+
+
+def test_join_autoescape_off_non_strings(assert_render):
+    template = "{% autoescape off %}{{ items|join:',' }}{% endautoescape %}"
+    assert_render(template, {"items": [1, 2]}, "[1, 2]")
+
+
+def test_join_integers(assert_render):
+    template = "{{ items|join:',' }}"
+    assert_render(template, {"items": [1, 2]}, "1,2")
+
+
+def test_join_not_iterable(assert_render):
+    template = "{{ value|join:',' }}"
+    assert_render(template, {"value": 42}, "42")
+
+
+def test_join_missing(assert_render):
+    template = "{{ missing|join:',' }}"
+    assert_render(template, {}, "")
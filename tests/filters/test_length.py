@@ -0,0 +1,33 @@
+import pytest
+
+
+@pytest.mark.parametrize(
+    "value,expected",
+    [
+        ([1, 2, 3], "3"),
+        ({"a", "b", "c", "a"}, "3"),
+        ({"a": 1}, "1"),
+        ("hello", "5"),
+        ("", "0"),
+        (42, "0"),
+        (None, "0"),
+    ],
+)
+def test_length(assert_render, value, expected):
+    template = "{{ value|length }}"
+    assert_render(template, {"value": value}, expected)
+
+
+def test_length_missing(assert_render):
+    template = "{{ missing|length }}"
+    assert_render(template, {}, "0")
+
+
+def test_length_add(assert_render):
+    template = "{{ items|length|add:1 }}"
+    assert_render(template, {"items": [1, 2, 3]}, "4")
+
+
+def test_length_if_comparison(assert_render):
+    template = "{% if items|length > 2 %}many{% else %}few{% endif %}"
+    assert_render(template, {"items": [1, 2, 3]}, "many")
@@ -0,0 +1,44 @@
+import pytest
+from django.template import engines
+from django.template.exceptions import TemplateSyntaxError
+
+
+def test_safeseq_join(assert_render):
+    template = "{{ items|safeseq|join:', ' }}"
+    assert_render(template, {"items": ["<b>", "&"]}, "<b>, &")
+
+
+def test_join_without_safeseq(assert_render):
+    template = "{{ items|join:', ' }}"
+    assert_render(template, {"items": ["<b>", "&"]}, "&lt;b&gt;, &amp;")
+
+
+def test_safeseq_first(assert_render):
+    template = "{{ items|safeseq|first }}"
+    assert_render(template, {"items": ["<b>"]}, "<b>")
+
+
+def test_safeseq_missing(assert_render):
+    template = "{{ items|safeseq|join:', ' }}"
+    assert_render(template, {}, "")
+
+
+def test_safeseq_argument():
+    template = "{{ items|safeseq:1 }}"
+    with pytest.raises(TemplateSyntaxError) as exc_info:
+        engines["django"].from_string(template)
+
+    assert str(exc_info.value) == "safeseq requires 1 arguments, 2 provided"
+
+    with pytest.raises(TemplateSyntaxError) as exc_info:
+        engines["rusty"].from_string(template)
+
+    expected = """\
+  × safeseq filter does not take an argument
+   ╭────
+ 1 │ {{ items|safeseq:1 }}
+   ·                  ┬
+   ·                  ╰── unexpected argument
+   ╰────
+"""
+    assert str(exc_info.value) == expected
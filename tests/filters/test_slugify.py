@@ -6,6 +6,8 @@ https://github.com/django/django/blob/5.1/tests/template_tests/filter_tests/test
 from django.utils.functional import lazy
 from django.utils.safestring import mark_safe
 
+from django_rusty_templates import RustyTemplates
+
 
 def test_slugify01(assert_render):
     """
@@ -81,3 +83,37 @@ def test_dictionary(assert_render):
     expected = "key-value"
 
     assert_render(template, context, expected)
+
+
+def test_slugify_ascii_mode(assert_render):
+    template = "{{ test|slugify }}"
+    context = {"test": "你好 世界 caf\xe9"}
+    assert_render(template, context, "cafe")
+
+
+def test_slugify_allow_unicode():
+    engine = RustyTemplates(
+        {
+            "NAME": "rust",
+            "OPTIONS": {"slugify_allow_unicode": True},
+            "DIRS": [],
+            "APP_DIRS": False,
+        }
+    )
+    template = engine.from_string("{{ a|slugify }} {{ b|slugify }}")
+    context = {"a": "你好 世界", "b": "Caf\xe9 & Cr\xe8me"}
+    assert template.render(context) == "你好-世界 caf\xe9-cr\xe8me"
+
+
+def test_slugify_allow_unicode_safe():
+    engine = RustyTemplates(
+        {
+            "NAME": "rust",
+            "OPTIONS": {"slugify_allow_unicode": True},
+            "DIRS": [],
+            "APP_DIRS": False,
+        }
+    )
+    template = engine.from_string("{{ a|slugify }} {{ b|slugify }}")
+    context = {"a": "a & b", "b": mark_safe("a &amp; b")}
+    assert template.render(context) == "a-b a-amp-b"
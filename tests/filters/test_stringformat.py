@@ -0,0 +1,28 @@
+import pytest
+
+
+@pytest.mark.parametrize(
+    "value,format,expected",
+    [
+        (None, "s", "None"),
+        (7, "03d", "007"),
+        (3.14159, ".2f", "3.14"),
+        ("hello", "10s", "     hello"),
+        ((1, 2), "s", "(1, 2)"),
+        ("seven", "d", ""),
+        (7, "z", ""),
+    ],
+)
+def test_stringformat(assert_render, value, format, expected):
+    template = f'{{{{ value|stringformat:"{format}" }}}}'
+    assert_render(template, {"value": value}, expected)
+
+
+def test_stringformat_missing(assert_render):
+    template = '{{ missing|stringformat:"s" }}'
+    assert_render(template, {}, "")
+
+
+def test_stringformat_autoescape(assert_render):
+    template = '{{ value|stringformat:"s" }}'
+    assert_render(template, {"value": "<b>"}, "&lt;b&gt;")
@@ -0,0 +1,73 @@
+import pytest
+from django.template import engines
+from django.template.exceptions import TemplateSyntaxError
+from django.utils.safestring import mark_safe
+
+
+@pytest.mark.parametrize(
+    "count,expected",
+    [
+        (5, "Hell…"),
+        (11, "Hello world"),
+        (20, "Hello world"),
+        (1, "…"),
+        (0, ""),
+        (-1, ""),
+    ],
+)
+def test_truncatechars(assert_render, count, expected):
+    template = "{{ text|truncatechars:count }}"
+    assert_render(template, {"text": "Hello world", "count": count}, expected)
+
+
+@pytest.mark.parametrize("count", ["'abc'", "'5.5'", "count"])
+def test_truncatechars_invalid_count(assert_render, count):
+    template = f"{{{{ text|truncatechars:{count} }}}}"
+    assert_render(template, {"text": "Hello world", "count": "abc"}, "Hello world")
+
+
+def test_truncatechars_integer(assert_render):
+    template = "{{ number|truncatechars:3 }}"
+    assert_render(template, {"number": 123456}, "12…")
+
+
+def test_truncatechars_missing(assert_render):
+    template = "{{ text|truncatechars:3 }}"
+    assert_render(template, {}, "")
+
+
+def test_truncatechars_combining(assert_render):
+    template = "{{ text|truncatechars:3 }}"
+    assert_render(template, {"text": "áb́ćd"}, "áb́…")
+
+
+def test_truncatechars_escaped(assert_render):
+    template = "{{ text|truncatechars:4 }}"
+    assert_render(template, {"text": "<p>Hello</p>"}, "&lt;p&gt;…")
+
+
+def test_truncatechars_safe(assert_render):
+    template = "{{ text|truncatechars:4 }}"
+    assert_render(template, {"text": mark_safe("<p>Hello</p>")}, "<p>…")
+
+
+def test_truncatechars_missing_argument():
+    template = "{{ text|truncatechars }}"
+
+    with pytest.raises(TemplateSyntaxError) as exc_info:
+        engines["django"].from_string(template)
+
+    assert str(exc_info.value) == "truncatechars requires 2 arguments, 1 provided"
+
+    with pytest.raises(TemplateSyntaxError) as exc_info:
+        engines["rusty"].from_string(template)
+
+    expected = """\
+  × Expected an argument
+   ╭────
+ 1 │ {{ text|truncatechars }}
+   ·         ──────┬──────
+   ·               ╰── here
+   ╰────
+"""
+    assert str(exc_info.value) == expected
@@ -0,0 +1,30 @@
+import pytest
+from django.utils.safestring import mark_safe
+
+
+@pytest.mark.parametrize(
+    "count,text,expected",
+    [
+        (9, "<p>one two <b>three</b></p>", "<p>one two <b>…</b></p>"),
+        (7, "<p>one two <b>three</b></p>", "<p>one tw…</p>"),
+        (20, "<p>one two <b>three</b></p>", "<p>one two <b>three</b></p>"),
+        (4, "abcd", "abcd"),
+        (4, "abcde", "abc…"),
+        (0, "<p>one</p>", ""),
+    ],
+)
+def test_truncatechars_html(assert_render, count, text, expected):
+    template = "{{ text|truncatechars_html:count }}"
+    context = {"text": mark_safe(text), "count": count}
+    assert_render(template, context, expected)
+
+
+def test_truncatechars_html_invalid_count(assert_render):
+    template = "{{ text|truncatechars_html:'abc' }}"
+    text = mark_safe("<p>one two</p>")
+    assert_render(template, {"text": text}, text)
+
+
+def test_truncatechars_html_missing(assert_render):
+    template = "{{ text|truncatechars_html:3 }}"
+    assert_render(template, {}, "")
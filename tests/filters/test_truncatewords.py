@@ -0,0 +1,40 @@
+import pytest
+from django.utils.safestring import mark_safe
+
+
+@pytest.mark.parametrize(
+    "count,expected",
+    [
+        (1, "Hello …"),
+        (2, "Hello big …"),
+        (3, "Hello big world"),
+        (10, "Hello big world"),
+        (0, ""),
+        (-1, ""),
+    ],
+)
+def test_truncatewords(assert_render, count, expected):
+    template = "{{ text|truncatewords:count }}"
+    assert_render(template, {"text": "Hello  big\nworld", "count": count}, expected)
+
+
+@pytest.mark.parametrize("count", ["'abc'", "'1.5'", "count"])
+def test_truncatewords_invalid_count(assert_render, count):
+    template = f"{{{{ text|truncatewords:{count} }}}}"
+    text = "Hello  big world"
+    assert_render(template, {"text": text, "count": "abc"}, text)
+
+
+def test_truncatewords_missing(assert_render):
+    template = "{{ text|truncatewords:3 }}"
+    assert_render(template, {}, "")
+
+
+def test_truncatewords_escaped(assert_render):
+    template = "{{ text|truncatewords:1 }}"
+    assert_render(template, {"text": "<p>Hello world</p>"}, "&lt;p&gt;Hello …")
+
+
+def test_truncatewords_safe(assert_render):
+    template = "{{ text|truncatewords:1 }}"
+    assert_render(template, {"text": mark_safe("<p>Hello world</p>")}, "<p>Hello …")
@@ -0,0 +1,37 @@
+import pytest
+from django.utils.safestring import mark_safe
+
+LINK = '<p>one <a href="#">two - three <br>four</a> five</p>'
+
+
+@pytest.mark.parametrize(
+    "count,text,expected",
+    [
+        (2, "<p>one two <b>three</b></p>", "<p>one two <b> …</b></p>"),
+        (3, "<p>one two <b>three</b></p>", "<p>one two <b>three</b></p>"),
+        (2, LINK, '<p>one <a href="#">two …</a></p>'),
+        (4, LINK, '<p>one <a href="#">two - three <br> …</a></p>'),
+        (1, "<p>Tom &amp; Jerry</p>", "<p>Tom …</p>"),
+        (0, "<p>one</p>", ""),
+    ],
+)
+def test_truncatewords_html(assert_render, count, text, expected):
+    template = "{{ text|truncatewords_html:count }}"
+    context = {"text": mark_safe(text), "count": count}
+    assert_render(template, context, expected)
+
+
+def test_truncatewords_html_escaped(assert_render):
+    template = "{{ text|truncatewords_html:1 }}"
+    assert_render(template, {"text": "<p>one two</p>"}, "&lt;p&gt;one …&lt;/p&gt;")
+
+
+def test_truncatewords_html_invalid_count(assert_render):
+    template = "{{ text|truncatewords_html:'abc' }}"
+    text = mark_safe("<p>one two</p>")
+    assert_render(template, {"text": text}, text)
+
+
+def test_truncatewords_html_missing(assert_render):
+    template = "{{ text|truncatewords_html:3 }}"
+    assert_render(template, {}, "")
@@ -0,0 +1,31 @@
+import pytest
+
+
+@pytest.mark.parametrize(
+    "value,expected",
+    [
+        ("lorem ipsum", "2"),
+        ("  lorem\n ipsum\tdolor  ", "3"),
+        ("", "0"),
+        (None, "1"),
+        (42, "1"),
+    ],
+)
+def test_wordcount(assert_render, value, expected):
+    template = "{{ value|wordcount }}"
+    assert_render(template, {"value": value}, expected)
+
+
+def test_wordcount_missing(assert_render):
+    template = "{{ missing|wordcount }}"
+    assert_render(template, {}, "0")
+
+
+def test_wordcount_add(assert_render):
+    template = "{{ text|wordcount|add:1 }}"
+    assert_render(template, {"text": "lorem ipsum"}, "3")
+
+
+def test_wordcount_divisibleby(assert_render):
+    template = "{{ text|wordcount|divisibleby:2 }}"
+    assert_render(template, {"text": "lorem ipsum"}, "True")
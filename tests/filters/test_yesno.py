@@ -0,0 +1,57 @@
+import pytest
+
+
+@pytest.mark.parametrize(
+    "value,expected",
+    [(True, "yes"), (False, "no"), (None, "maybe"), (1, "yes"), ("", "no")],
+)
+def test_yesno_default_labels(assert_render, value, expected):
+    template = "{{ value|yesno }}"
+    assert_render(template, {"value": value}, expected)
+
+
+@pytest.mark.parametrize(
+    "value,expected",
+    [(True, "certainly"), (False, "get out of town"), (None, "perhaps")],
+)
+def test_yesno_three_labels(assert_render, value, expected):
+    template = '{{ value|yesno:"certainly,get out of town,perhaps" }}'
+    assert_render(template, {"value": value}, expected)
+
+
+@pytest.mark.parametrize(
+    "value,expected", [(True, "certainly"), (False, "get out of town")]
+)
+def test_yesno_two_labels(assert_render, value, expected):
+    template = '{{ value|yesno:"certainly,get out of town" }}'
+    assert_render(template, {"value": value}, expected)
+
+
+def test_yesno_two_labels_none(assert_render):
+    template = '{{ value|yesno:"yes,no" }}'
+    assert_render(template, {"value": None}, "no")
+
+
+def test_yesno_too_many_labels(assert_render):
+    template = '{{ value|yesno:"a,b,c,d" }}'
+    assert_render(template, {"value": None}, "b")
+
+
+def test_yesno_invalid_labels(assert_render):
+    template = '{{ value|yesno:"yes" }}'
+    assert_render(template, {"value": True}, "True")
+
+
+def test_yesno_missing_variable(assert_render):
+    template = '{{ value|yesno:"yes,no,maybe" }}'
+    assert_render(template, {}, "no")
+
+
+def test_yesno_variable_labels(assert_render):
+    template = "{{ value|yesno:labels }}"
+    assert_render(template, {"value": False, "labels": "on,off"}, "off")
+
+
+def test_yesno_escaped(assert_render):
+    template = '{{ value|yesno:"<b>yes</b>,no" }}'
+    assert_render(template, {"value": True}, "&lt;b&gt;yes&lt;/b&gt;")
@@ -198,3 +198,17 @@ def test_unexpected_end_tag():
    ╰────
 """
     assert str(exc_info.value) == expected
+
+
+@pytest.mark.parametrize(
+    "enabled,expected",
+    [("on", "&lt;b&gt;&lt;b&gt;"), ("off", "<b><b>")],
+)
+def test_autoescape_for_loop(assert_render, enabled, expected):
+    template = f"{{% autoescape {enabled} %}}{{% for x in items %}}{{{{ x }}}}{{% endfor %}}{{% endautoescape %}}"
+    assert_render(template, {"items": ["<b>", "<b>"]}, expected)
+
+
+def test_autoescape_restored_after_block(assert_render):
+    template = "{% autoescape off %}{{ html }}{% endautoescape %}{{ html }}"
+    assert_render(template, {"html": "<b>"}, "<b>&lt;b&gt;")
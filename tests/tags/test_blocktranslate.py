@@ -0,0 +1,114 @@
+import pytest
+from django.template import engines
+from django.template.backends.django import DjangoTemplates
+from django.template.exceptions import TemplateSyntaxError
+from django.utils import translation
+
+from django_rusty_templates import RustyTemplates
+
+
+@pytest.fixture
+def mock_translation(monkeypatch):
+    messages = {
+        "Hello %(name)s": "Bonjour %(name)s",
+        "%(counter)s apple": "%(counter)s pomme",
+        "%(counter)s apples": "%(counter)s pommes",
+    }
+
+    def gettext(message):
+        return messages.get(message, message)
+
+    def ngettext(singular, plural, count):
+        return gettext(singular if count == 1 else plural)
+
+    # Patch the backend so both engines see the mock through ``gettext``.
+    monkeypatch.setattr(translation._trans, "gettext", gettext)
+    monkeypatch.setattr(translation._trans, "ngettext", ngettext)
+
+
+def test_blocktranslate(mock_translation, assert_render):
+    template = "{% load i18n %}{% blocktranslate %}Hello {{ name }}{% endblocktranslate %}"
+    assert_render(template, {"name": "<Lily>"}, "Bonjour &lt;Lily&gt;")
+
+
+def test_blocktrans_untranslated(mock_translation, assert_render):
+    template = "{% load i18n %}{% blocktrans %}Goodbye {{ name }}, 100%{% endblocktrans %}"
+    assert_render(template, {"name": "Lily"}, "Goodbye Lily, 100%")
+
+
+def test_blocktranslate_with(mock_translation, assert_render):
+    template = "{% load i18n %}{% blocktranslate with name=user|upper %}Hello {{ name }}{% endblocktranslate %}{{ name }}"
+    assert_render(template, {"user": "lily"}, "Bonjour LILY")
+
+
+@pytest.mark.parametrize(
+    "apples,expected",
+    [([1], "1 pomme"), ([1, 2, 3], "3 pommes"), ([], "0 pommes")],
+)
+def test_blocktranslate_count(mock_translation, assert_render, apples, expected):
+    template = "{% load i18n %}{% blocktranslate count counter=apples|length %}{{ counter }} apple{% plural %}{{ counter }} apples{% endblocktranslate %}"
+    assert_render(template, {"apples": apples}, expected)
+
+
+def test_blocktranslate_trimmed(mock_translation, assert_render):
+    template = "{% load i18n %}{% blocktranslate trimmed %}\n  Hello\n  {{ name }}\n{% endblocktranslate %}"
+    assert_render(template, {"name": "Lily"}, "Bonjour Lily")
+
+
+@pytest.mark.parametrize("tag", ["blocktranslate", "blocktrans"])
+def test_blocktranslate_count_not_number(tag):
+    template = f"{{% load i18n %}}{{% {tag} count counter=apples %}}apple{{% plural %}}apples{{% end{tag} %}}"
+
+    with pytest.raises(TemplateSyntaxError) as django_error:
+        engines["django"].from_string(template).render({"apples": "many"})
+
+    with pytest.raises(TemplateSyntaxError) as rusty_error:
+        engines["rusty"].from_string(template).render({"apples": "many"})
+
+    expected = f"'counter' argument to '{tag}' tag must be a number."
+    assert str(django_error.value) == expected
+    assert str(rusty_error.value) == expected
+
+
+def test_blocktranslate_nested_tag():
+    template = "{% load i18n %}{% blocktranslate %}{% if x %}{% endif %}{% endblocktranslate %}"
+
+    with pytest.raises(TemplateSyntaxError):
+        engines["django"].from_string(template)
+
+    with pytest.raises(TemplateSyntaxError) as exc_info:
+        engines["rusty"].from_string(template)
+
+    assert str(exc_info.value) == """\
+  × 'blocktranslate' doesn't allow other block tags (seen 'if x') inside it
+   ╭────
+ 1 │ {% load i18n %}{% blocktranslate %}{% if x %}{% endif %}{% endblocktranslate %}
+   ·                                    ─────┬────
+   ·                                         ╰── here
+   ╰────
+"""
+
+
+def test_blocktranslate_string_if_invalid():
+    template = "{% load i18n %}{% blocktranslate %}Hello {{ missing }}{% endblocktranslate %}"
+    params = {"NAME": "test", "DIRS": [], "APP_DIRS": False}
+    params["OPTIONS"] = {"string_if_invalid": "<%s>"}
+
+    expected = "Hello &lt;missing&gt;"
+    assert DjangoTemplates(params).from_string(template).render({}) == expected
+    assert RustyTemplates(params).from_string(template).render({}) == expected
+
+
+def test_blocktranslate_malformed_placeholder():
+    template = "{% load i18n %}{% blocktranslate %}{{ a)s }}{% endblocktranslate %}"
+
+    with pytest.raises(TemplateSyntaxError):
+        engines["django"].from_string(template).render({})
+
+    with pytest.raises(TemplateSyntaxError) as exc_info:
+        engines["rusty"].from_string(template).render({})
+
+    assert (
+        str(exc_info.value)
+        == "'blocktranslate' is unable to format string returned by gettext: '%(a)s)s' using {'a)s': ''}"
+    )
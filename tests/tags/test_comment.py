@@ -0,0 +1,13 @@
+def test_comment(assert_render):
+    template = "a{% comment %}{% for x in y %}{{ x|invalid }}{% endcomment %}b"
+    assert_render(template, {}, "ab")
+
+
+def test_comment_with_note(assert_render):
+    template = 'a{% comment "Not used" %}\n<p>{{ name }}</p>\n{% endcomment %}b'
+    assert_render(template, {"name": "Lily"}, "ab")
+
+
+def test_comment_inside_block(assert_render):
+    template = "{% if True %}a{% comment %}{% endif %}{% endcomment %}b{% endif %}"
+    assert_render(template, {}, "ab")
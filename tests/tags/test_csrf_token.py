@@ -0,0 +1,13 @@
+import pytest
+
+
+def test_csrf_token(assert_render):
+    template = "{% csrf_token %}"
+    expected = '<input type="hidden" name="csrfmiddlewaretoken" value="abc&lt;">'
+    assert_render(template, {"csrf_token": "abc<"}, expected)
+
+
+@pytest.mark.parametrize("context", [{}, {"csrf_token": ""}, {"csrf_token": "NOTPROVIDED"}])
+def test_csrf_token_missing(assert_render, context):
+    assert_render("{% csrf_token %}", context, "")
+
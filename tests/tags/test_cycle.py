@@ -0,0 +1,63 @@
+import pytest
+from django.template import engines
+from django.template.exceptions import TemplateSyntaxError
+
+
+def test_cycle(assert_render):
+    template = "{% for i in items %}{% cycle 'row1' 'row2' %} {% endfor %}"
+    expected = "row1 row2 row1 row2 row1 "
+    assert_render(template, {"items": range(5)}, expected)
+
+
+def test_cycle_variables(assert_render):
+    template = "{% for i in items %}{% cycle first second %}{% endfor %}"
+    context = {"items": range(3), "first": "<a>", "second": "b"}
+    assert_render(template, context, "&lt;a&gt;b&lt;a&gt;")
+
+
+def test_cycle_outside_loop(assert_render):
+    template = "{% cycle 'a' 'b' %}{% cycle 'a' 'b' %}"
+    assert_render(template, {}, "aa")
+
+
+def test_cycle_as(assert_render):
+    template = "{% for i in items %}{% cycle 'a' 'b' as letter %}-{{ letter }} {% endfor %}"
+    assert_render(template, {"items": range(3)}, "a-a b-b a-a ")
+
+
+def test_cycle_named_reference(assert_render):
+    template = (
+        "{% cycle 'a' 'b' 'c' as letter %}{% cycle letter %}{% cycle letter %}"
+        "{% cycle letter %}"
+    )
+    assert_render(template, {}, "abca")
+
+
+def test_cycle_silent(assert_render):
+    template = (
+        "{% for i in items %}{% cycle 'odd' 'even' as parity silent %}"
+        "{{ parity }} {% endfor %}"
+    )
+    assert_render(template, {"items": range(3)}, "odd even odd ")
+
+
+def test_cycle_missing_variable(assert_render):
+    template = "{% for i in items %}{% cycle missing 'b' %}{% endfor %}"
+    assert_render(template, {"items": range(3)}, "b")
+
+
+@pytest.mark.parametrize(
+    "template",
+    [
+        "{% cycle %}",
+        "{% cycle row %}",
+        "{% cycle 'a' 'b' as row %}{% cycle col %}",
+        "{% cycle 'a' 'b' as row loud %}",
+    ],
+)
+def test_cycle_errors(template):
+    with pytest.raises(TemplateSyntaxError):
+        engines["django"].from_string(template)
+
+    with pytest.raises(TemplateSyntaxError):
+        engines["rusty"].from_string(template)
@@ -0,0 +1,13 @@
+def test_debug(rusty):
+    template = rusty("{% debug %}")
+    rendered = template.render({"name": "<b>Lily</b>"})
+
+    assert "name: &#x27;&lt;b&gt;Lily&lt;/b&gt;&#x27;\n" in rendered
+    assert "True: True\n" in rendered
+
+
+def test_debug_loaded_filters(rusty):
+    template = rusty("{% load custom_filters %}{% debug %}")
+    rendered = template.render({})
+
+    assert "Loaded filters: " in rendered
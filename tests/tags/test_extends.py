@@ -0,0 +1,108 @@
+import pytest
+from django.template import engines
+from django.template.exceptions import TemplateSyntaxError
+from django.template.loader import get_template
+
+
+def render(template, context, *, using):
+    return get_template(template, using=using).render(context)
+
+
+def test_render_extends():
+    context = {"page": "Home"}
+    expected = "<title>Home | Site</title>\nBase content\n"
+    assert render("inheritance/child.html", context, using="django") == expected
+    assert render("inheritance/child.html", context, using="rusty") == expected
+
+
+def test_render_extends_chain():
+    context = {"page": "Home"}
+    expected = "<title>Home | Site</title>\nBase content and more\n"
+    assert render("inheritance/grandchild.html", context, using="django") == expected
+    assert render("inheritance/grandchild.html", context, using="rusty") == expected
+
+
+def test_render_block_super_prepend():
+    expected = "<title>Site</title>\nBefore Base content in content\n"
+    assert render("inheritance/prepend.html", {}, using="django") == expected
+    assert render("inheritance/prepend.html", {}, using="rusty") == expected
+
+
+def test_render_block_without_extends(assert_render):
+    template = "{% block content %}Hello {{ user }}{% endblock %}"
+    assert_render(template, {"user": "Lily"}, "Hello Lily")
+
+
+def test_extends_not_first():
+    template = "{{ foo }}{% extends 'inheritance/base.html' %}"
+
+    with pytest.raises(TemplateSyntaxError):
+        engines["django"].from_string(template)
+
+    with pytest.raises(TemplateSyntaxError) as exc_info:
+        engines["rusty"].from_string(template)
+
+    assert str(exc_info.value) == """\
+  × 'extends' must be the first tag in the template
+   ╭────
+ 1 │ {{ foo }}{% extends 'inheritance/base.html' %}
+   ·          ──────────────────┬──────────────────
+   ·                            ╰── here
+   ╰────
+"""
+
+
+def test_duplicate_block():
+    template = "{% block a %}{% endblock %}{% block a %}{% endblock %}"
+
+    with pytest.raises(TemplateSyntaxError) as exc_info:
+        engines["django"].from_string(template)
+
+    assert str(exc_info.value) == "'block' tag with name 'a' appears more than once"
+
+    with pytest.raises(TemplateSyntaxError) as exc_info:
+        engines["rusty"].from_string(template)
+
+    assert str(exc_info.value) == """\
+  × 'block' tag with name 'a' appears more than once
+   ╭────
+ 1 │ {% block a %}{% endblock %}{% block a %}{% endblock %}
+   ·                            ──────┬──────
+   ·                                  ╰── here
+   ╰────
+"""
+
+
+def test_render_extends_variable(assert_render):
+    template = "{% extends parent %}{% block content %}Child{% endblock %}"
+    context = {"parent": "inheritance/base.html"}
+    expected = "<title>Site</title>\nChild\n"
+    assert_render(template, context, expected)
+
+
+def test_render_extends_template_object():
+    template = "{% extends parent %}{% block title %}Child{% endblock %}"
+    for using in ["django", "rusty"]:
+        parent = get_template("inheritance/base.html", using=using)
+        rendered = engines[using].from_string(template).render({"parent": parent})
+        assert rendered == "<title>Child</title>\nBase content\n"
+
+
+@pytest.mark.parametrize("context", [{}, {"parent": None}])
+def test_render_extends_variable_missing(context):
+    template = "{% extends parent %}"
+    parent = repr(context.get("parent", ""))
+    expected = (
+        f"Invalid template name in 'extends' tag: {parent}. "
+        "Got this from the 'parent' variable."
+    )
+
+    with pytest.raises(TemplateSyntaxError) as exc_info:
+        engines["django"].from_string(template).render(context)
+
+    assert str(exc_info.value) == expected
+
+    with pytest.raises(TemplateSyntaxError) as exc_info:
+        engines["rusty"].from_string(template).render(context)
+
+    assert str(exc_info.value) == expected
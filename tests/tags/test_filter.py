@@ -0,0 +1,46 @@
+import pytest
+from django.template import engines
+from django.template.exceptions import TemplateSyntaxError
+
+
+def test_filter_upper(assert_render):
+    template = "{% filter upper %}Hello {{ name }}!{% endfilter %}"
+    assert_render(template, {"name": "Lily"}, "HELLO LILY!")
+
+
+def test_filter_chain(assert_render):
+    template = "{% filter lower|capfirst %}HELLO {{ name }}{% endfilter %}"
+    assert_render(template, {"name": "LILY"}, "Hello lily")
+
+
+def test_filter_empty(assert_render):
+    template = "{% filter upper %}{% endfilter %}"
+    assert_render(template, {}, "")
+
+
+def test_filter_argument(assert_render):
+    template = "{% filter truncatechars:5 %}Hello world{% endfilter %}"
+    assert_render(template, {}, "Hell…")
+
+
+def test_filter_html_not_escaped(assert_render):
+    template = "{% filter upper %}<b>{{ name }}</b>{% endfilter %}"
+    assert_render(template, {"name": "<i>"}, "<B>&LT;I&GT;</B>")
+
+
+@pytest.mark.parametrize("filter_name", ["escape", "safe"])
+def test_filter_not_permitted(filter_name):
+    template = f"{{% filter lower|{filter_name} %}}Hi{{% endfilter %}}"
+    expected = (
+        f'"filter {filter_name}" is not permitted.  Use the "autoescape" tag instead.'
+    )
+
+    with pytest.raises(TemplateSyntaxError) as exc_info:
+        engines["django"].from_string(template)
+
+    assert str(exc_info.value) == expected
+
+    with pytest.raises(TemplateSyntaxError) as exc_info:
+        engines["rusty"].from_string(template)
+
+    assert expected in str(exc_info.value)
@@ -0,0 +1,75 @@
+import pytest
+from django.template import engines
+from django.template.exceptions import TemplateSyntaxError
+
+
+def test_firstof_first_truthy(assert_render):
+    template = "{% firstof a b c %}"
+    assert_render(template, {"a": 0, "b": "foo", "c": "bar"}, "foo")
+
+
+def test_firstof_all_falsy(assert_render):
+    template = "{% firstof a b c %}"
+    assert_render(template, {"a": 0, "b": "", "c": None}, "")
+
+
+def test_firstof_all_missing(assert_render):
+    template = "{% firstof a b c %}"
+    assert_render(template, {}, "")
+
+
+def test_firstof_literal(assert_render):
+    template = "{% firstof a b 'fallback' %}"
+    assert_render(template, {"a": False, "b": []}, "fallback")
+
+
+def test_firstof_escaped(assert_render):
+    template = "{% firstof a b %}"
+    assert_render(template, {"b": "<p>"}, "&lt;p&gt;")
+
+
+def test_firstof_literal_not_escaped(assert_render):
+    template = "{% firstof a '<p>' %}"
+    assert_render(template, {}, "<p>")
+
+
+def test_firstof_filter(assert_render):
+    template = "{% firstof a|default:b %}"
+    assert_render(template, {"b": "foo"}, "foo")
+
+
+def test_firstof_as(assert_render):
+    template = "{% firstof a b as result %}[{{ result }}]"
+    assert_render(template, {"a": "", "b": "foo"}, "[foo]")
+
+
+def test_firstof_as_all_falsy(assert_render):
+    template = "{% firstof a 0 as result %}[{{ result }}]"
+    assert_render(template, {"result": "old"}, "[]")
+
+
+def test_firstof_as_escaped_once(assert_render):
+    template = "{% firstof a as result %}{{ result }}"
+    assert_render(template, {"a": "<p>"}, "&lt;p&gt;")
+
+
+def test_firstof_no_arguments():
+    template = "{% firstof %}"
+
+    with pytest.raises(TemplateSyntaxError) as exc_info:
+        engines["django"].from_string(template)
+
+    assert str(exc_info.value) == "'firstof' statement requires at least one argument"
+
+    with pytest.raises(TemplateSyntaxError) as exc_info:
+        engines["rusty"].from_string(template)
+
+    expected = """\
+  × 'firstof' statement requires at least one argument
+   ╭────
+ 1 │ {% firstof %}
+   · ──────┬──────
+   ·       ╰── here
+   ╰────
+"""
+    assert str(exc_info.value) == expected
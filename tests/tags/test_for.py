@@ -0,0 +1,178 @@
+import pytest
+from django.template import engines
+from django.template.exceptions import TemplateSyntaxError
+
+
+def test_render_for(assert_render):
+    template = "{% for item in items %}{{ item }},{% endfor %}"
+    assert_render(template, {"items": [1, 2, 3]}, "1,2,3,")
+
+
+def test_render_for_empty(assert_render):
+    template = "{% for item in items %}{{ item }}{% empty %}none{% endfor %}"
+    assert_render(template, {"items": []}, "none")
+
+
+def test_render_for_missing(assert_render):
+    template = "{% for item in items %}{{ item }}{% empty %}none{% endfor %}"
+    assert_render(template, {}, "none")
+
+
+def test_render_for_none(assert_render):
+    template = "{% for item in items %}{{ item }}{% empty %}none{% endfor %}"
+    assert_render(template, {"items": None}, "none")
+
+
+def test_render_for_reversed(assert_render):
+    template = "{% for item in items reversed %}{{ item }}{% endfor %}"
+    assert_render(template, {"items": [1, 2, 3]}, "321")
+
+
+def test_render_for_string(assert_render):
+    template = "{% for char in word %}{{ char }}-{% endfor %}"
+    assert_render(template, {"word": "abc"}, "a-b-c-")
+
+
+def test_render_for_generator(assert_render):
+    template = "{% for item in items %}{{ item }}{% endfor %}"
+    assert_render(template, {"items": (i for i in range(3))}, "012")
+
+
+def test_render_for_dict_items(assert_render):
+    template = "{% for key, value in items %}{{ key }}={{ value }};{% endfor %}"
+    assert_render(template, {"items": {"a": 1, "b": 2}.items()}, "a=1;b=2;")
+
+
+def test_render_for_unpack_spaces(assert_render):
+    template = "{% for x , y in pairs %}{{ x }}{{ y }}{% endfor %}"
+    assert_render(template, {"pairs": [(1, 2), (3, 4)]}, "1234")
+
+
+def test_render_for_nested(assert_render):
+    template = "{% for row in rows %}{% for cell in row %}{{ cell }}{% endfor %};{% endfor %}"
+    assert_render(template, {"rows": [[1, 2], [3]]}, "12;3;")
+
+
+def test_render_for_escaped(assert_render):
+    template = "{% for item in items %}{{ item }}{% endfor %}"
+    assert_render(template, {"items": ["<p>"]}, "&lt;p&gt;")
+
+
+def test_render_for_restores_variable(assert_render):
+    template = "{% for x in items %}{{ x }}{% endfor %}{{ x }}"
+    assert_render(template, {"x": "outer", "items": [1, 2]}, "12outer")
+
+
+def test_render_for_unpack_mismatch():
+    template = "{% for x, y in items %}{{ x }}{% endfor %}"
+    django_template = engines["django"].from_string(template)
+    rust_template = engines["rusty"].from_string(template)
+
+    expected = "Need 2 values to unpack in for loop; got 3. "
+    with pytest.raises(ValueError) as exc_info:
+        django_template.render({"items": [(1, 2, 3)]})
+
+    assert str(exc_info.value) == expected
+
+    with pytest.raises(ValueError) as exc_info:
+        rust_template.render({"items": [(1, 2, 3)]})
+
+    assert str(exc_info.value) == expected
+
+
+def test_for_missing_in():
+    template = "{% for x from y %}{% endfor %}"
+
+    with pytest.raises(TemplateSyntaxError) as exc_info:
+        engines["django"].from_string(template)
+
+    expected = "'for' statements should use the format 'for x in y': for x from y"
+    assert str(exc_info.value) == expected
+
+    with pytest.raises(TemplateSyntaxError) as exc_info:
+        engines["rusty"].from_string(template)
+
+    expected = """\
+  × 'for' statements should use the format 'for x in y'
+   ╭────
+ 1 │ {% for x from y %}{% endfor %}
+   ·          ──┬─
+   ·            ╰── expected 'in'
+   ╰────
+"""
+    assert str(exc_info.value) == expected
+
+
+def test_for_missing_endfor():
+    template = "{% for x in y %}{{ x }}"
+
+    with pytest.raises(TemplateSyntaxError) as exc_info:
+        engines["django"].from_string(template)
+
+    expected = "Unclosed tag on line 1: 'for'. Looking for one of: empty, endfor."
+    assert str(exc_info.value) == expected
+
+    with pytest.raises(TemplateSyntaxError) as exc_info:
+        engines["rusty"].from_string(template)
+
+    expected = """\
+  × Unclosed 'for' tag. Looking for one of: empty, endfor
+   ╭────
+ 1 │ {% for x in y %}{{ x }}
+   · ────────┬───────
+   ·         ╰── started here
+   ╰────
+"""
+    assert str(exc_info.value) == expected
+
+
+def test_render_forloop_counters(assert_render):
+    template = (
+        "{% for item in items %}"
+        "{{ forloop.counter }}{{ forloop.counter0 }}"
+        "{{ forloop.revcounter }}{{ forloop.revcounter0 }},"
+        "{% endfor %}"
+    )
+    assert_render(template, {"items": "abc"}, "1032,2121,3210,")
+
+
+def test_render_forloop_first_last(assert_render):
+    template = "{% for item in items %}{% if forloop.first %}[{% endif %}{{ item }}{% if forloop.last %}]{% else %},{% endif %}{% endfor %}"
+    assert_render(template, {"items": [1, 2, 3]}, "[1,2,3]")
+
+
+def test_render_forloop_parentloop(assert_render):
+    template = (
+        "{% for row in rows %}{% for cell in row %}"
+        "{{ forloop.parentloop.counter }}.{{ forloop.counter }} "
+        "{% endfor %}{% endfor %}"
+    )
+    assert_render(template, {"rows": [[1, 2], [3]]}, "1.1 1.2 2.1 ")
+
+
+def test_render_forloop_outside_loop(assert_render):
+    template = "{% for item in items %}{% endfor %}{{ forloop.counter }}"
+    assert_render(template, {"items": [1]}, "")
+
+
+def test_render_for_dict_keys(assert_render):
+    template = "{% for key in data %}{{ key }},{% endfor %}"
+    assert_render(template, {"data": {"a": 1, "b": 2}}, "a,b,")
+
+
+def test_render_for_dict_keys_reversed(assert_render):
+    template = "{% for key in data reversed %}{{ key }},{% endfor %}"
+    assert_render(template, {"data": {"a": 1, "b": 2}}, "b,a,")
+
+
+def test_render_for_dict_items_method(assert_render):
+    template = "{% for key, value in data.items %}{{ key }}={{ value }};{% endfor %}"
+    assert_render(template, {"data": {"a": 1, "b": 2}}, "a=1;b=2;")
+
+
+def test_render_forloop_after_nested_loop(assert_render):
+    template = (
+        "{% for row in rows %}{% for cell in row %}{% endfor %}"
+        "{{ forloop.counter }}{% endfor %}|{{ forloop.counter }}|{{ row }}"
+    )
+    assert_render(template, {"rows": [[1, 2], [3]]}, "12||")
@@ -45,6 +45,48 @@ def test_render_elif():
     assert rust_template.render() == "bar"
 
 
+@pytest.mark.parametrize(
+    "a,b,expected",
+    [
+        (True, True, "ab"),
+        (True, False, "a"),
+        (False, True, "c"),
+        (False, False, "c"),
+    ],
+)
+def test_render_nested_if(a, b, expected):
+    template = "{% if a %}a{% if b %}b{% endif %}{% else %}c{% endif %}"
+    django_template = engines["django"].from_string(template)
+    rust_template = engines["rusty"].from_string(template)
+
+    context = {"a": a, "b": b}
+    assert django_template.render(context) == expected
+    assert rust_template.render(context) == expected
+
+
+def test_missing_endif():
+    template = "{% if foo %}bar"
+
+    with pytest.raises(TemplateSyntaxError) as exc_info:
+        engines["django"].from_string(template)
+
+    expected = "Unclosed tag on line 1: 'if'. Looking for one of: elif, else, endif."
+    assert str(exc_info.value) == expected
+
+    with pytest.raises(TemplateSyntaxError) as exc_info:
+        engines["rusty"].from_string(template)
+
+    expected = """\
+  × Unclosed 'if' tag. Looking for one of: elif, else, endif
+   ╭────
+ 1 │ {% if foo %}bar
+   · ──────┬─────
+   ·       ╰── started here
+   ╰────
+"""
+    assert str(exc_info.value) == expected
+
+
 def test_render_if_true_literal():
     template = "{% if True %}foo{% endif %}"
     django_template = engines["django"].from_string(template)
@@ -108,6 +150,45 @@ def test_render_or(a, b):
     assert rust_template.render({"a": a, "b": b}) == expected
 
 
+@pytest.mark.parametrize(
+    "a,b,c",
+    [
+        (True, False, False),
+        (False, True, False),
+        (False, True, True),
+        (False, False, True),
+        (False, False, False),
+    ],
+)
+def test_render_or_and_not_precedence(a, b, c):
+    template = "{% if a or b and not c %}truthy{% else %}falsey{% endif %}"
+    django_template = engines["django"].from_string(template)
+    rust_template = engines["rusty"].from_string(template)
+
+    context = {"a": a, "b": b, "c": c}
+    expected = "truthy" if a or b and not c else "falsey"
+    assert django_template.render(context) == expected
+    assert rust_template.render(context) == expected
+
+
+@pytest.mark.parametrize(
+    "x,item,expected",
+    [
+        (1, "a", "falsey"),
+        (2, "a", "truthy"),
+        (2, "z", "falsey"),
+    ],
+)
+def test_render_not_comparison_precedence(x, item, expected):
+    template = "{% if not x == 1 and item in items %}truthy{% else %}falsey{% endif %}"
+    django_template = engines["django"].from_string(template)
+    rust_template = engines["rusty"].from_string(template)
+
+    context = {"x": x, "item": item, "items": ["a", "b"]}
+    assert django_template.render(context) == expected
+    assert rust_template.render(context) == expected
+
+
 @pytest.mark.parametrize("a", [True, False, "foo", 1, "", 0])
 def test_render_not(a):
     template = "{% if not a %}foo{% else %}bar{% endif %}"
@@ -325,6 +406,18 @@ def test_render_is_not(a, b):
     assert rust_template.render({"a": a, "b": b}) == expected
 
 
+@pytest.mark.parametrize("x", [None, 0, "", False])
+def test_render_is_none_literal(assert_render, x):
+    template = "{% if x is None %}none{% endif %}|{% if x is not None %}set{% endif %}"
+    expected = "none|" if x is None else "|set"
+    assert_render(template, {"x": x}, expected)
+
+
+def test_render_is_none_missing(assert_render):
+    template = "{% if x is None %}none{% endif %}|{% if x is not None %}set{% endif %}"
+    assert_render(template, {}, "none|")
+
+
 def test_invalid_and_position():
     template = "{% if and %}{{ foo }}{% endif %}"
 
@@ -1289,3 +1382,13 @@ def test_not_inf_or_not_none():
 
     assert django_template.render({}) == "truthy"
     assert rust_template.render({}) == "truthy"
+
+
+def test_render_equal_escaped_string_literal(assert_render):
+    template = r"{% if status == 'it\'s ok' %}yes{% else %}no{% endif %}"
+    assert_render(template, {"status": "it's ok"}, "yes")
+
+
+def test_render_equal_string_literal_other_quote(assert_render):
+    template = """{% if status == "it's ok" %}yes{% else %}no{% endif %}"""
+    assert_render(template, {"status": "it's ok"}, "yes")
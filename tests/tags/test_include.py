@@ -0,0 +1,96 @@
+import pytest
+from django.template import engines
+from django.template.exceptions import TemplateDoesNotExist, TemplateSyntaxError
+
+
+def test_render_include(assert_render):
+    template = "{% include 'include/snippet.html' %}"
+    assert_render(template, {"name": "Lily"}, "Hello Lily!")
+
+
+def test_render_include_variable_name(assert_render):
+    template = "{% include snippet %}"
+    context = {"snippet": "include/snippet.html", "name": "Lily"}
+    assert_render(template, context, "Hello Lily!")
+
+
+def test_render_include_with(assert_render):
+    template = "{% include 'include/snippet.html' with greeting='Hi' %}"
+    assert_render(template, {"name": "Lily"}, "Hi Lily!")
+
+
+def test_render_include_with_only(assert_render):
+    template = "{% include 'include/snippet.html' with greeting='Hi' only %}"
+    assert_render(template, {"name": "Lily"}, "Hi !")
+
+
+def test_render_include_with_does_not_leak(assert_render):
+    template = "{% include 'include/snippet.html' with name='Lily' %}{{ name }}"
+    assert_render(template, {}, "Hello Lily!")
+
+
+def test_render_include_missing():
+    template = "{% include 'include/missing.html' %}"
+    django_template = engines["django"].from_string(template)
+    rust_template = engines["rusty"].from_string(template)
+
+    with pytest.raises(TemplateDoesNotExist):
+        django_template.render({})
+
+    with pytest.raises(TemplateDoesNotExist):
+        rust_template.render({})
+
+
+def test_include_no_arguments():
+    template = "{% include %}"
+
+    with pytest.raises(TemplateSyntaxError):
+        engines["django"].from_string(template)
+
+    with pytest.raises(TemplateSyntaxError) as exc_info:
+        engines["rusty"].from_string(template)
+
+    assert str(exc_info.value) == """\
+  × 'include' tag takes at least one argument: the name of the template to be
+  │ included
+   ╭────
+ 1 │ {% include %}
+   · ──────┬──────
+   ·       ╰── here
+   ╰────
+"""
+
+
+def test_include_with_without_keywords():
+    template = "{% include 'include/snippet.html' with only %}"
+
+    with pytest.raises(TemplateSyntaxError):
+        engines["django"].from_string(template)
+
+    with pytest.raises(TemplateSyntaxError) as exc_info:
+        engines["rusty"].from_string(template)
+
+    assert str(exc_info.value) == """\
+  × 'with' in 'include' tag needs at least one keyword argument
+   ╭────
+ 1 │ {% include 'include/snippet.html' with only %}
+   ·                                   ──┬─
+   ·                                     ╰── here
+   ╰────
+"""
+
+
+def test_include_recursive():
+    template = "{% include 'include/recursive.html' %}"
+
+    with pytest.raises(RecursionError):
+        engines["django"].from_string(template).render({})
+
+    with pytest.raises(RecursionError):
+        engines["rusty"].from_string(template).render({})
+
+
+def test_include_repeated(assert_render):
+    template = "{% for name in names %}{% include 'include/snippet.html' %} {% endfor %}"
+    context = {"names": ["Lily", "Bryony"]}
+    assert_render(template, context, "Hello Lily! Hello Bryony! ")
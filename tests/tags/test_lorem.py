@@ -0,0 +1,46 @@
+from django.utils.lorem_ipsum import COMMON_P, WORDS
+
+
+def test_lorem_default(assert_render):
+    assert_render("{% lorem %}", {}, COMMON_P)
+
+
+def test_lorem_words(assert_render):
+    assert_render("{% lorem 3 w %}", {}, "lorem ipsum dolor")
+
+
+def test_lorem_paragraphs(assert_render):
+    assert_render("{% lorem 1 p %}", {}, f"<p>{COMMON_P}</p>")
+
+
+def test_lorem_count_variable(assert_render):
+    assert_render("{% lorem count w %}", {"count": 2}, "lorem ipsum")
+
+
+def test_lorem_invalid_count(assert_render):
+    assert_render("{% lorem count w %}", {"count": "x"}, "lorem")
+
+
+def test_lorem_random_words(rusty):
+    rendered = rusty("{% lorem 7 w random %}").render({})
+    words = rendered.split(" ")
+    assert len(words) == 7
+    assert set(words) <= set(WORDS)
+
+
+def test_lorem_random_paragraphs(rusty):
+    rendered = rusty("{% lorem 2 p random %}").render({})
+    paragraphs = rendered.split("\n\n")
+    assert len(paragraphs) == 2
+    for paragraph in paragraphs:
+        assert paragraph.startswith("<p>")
+        assert paragraph.endswith(".</p>") or paragraph.endswith("?</p>")
+        assert COMMON_P not in paragraph
+
+
+def test_lorem_blocks(rusty):
+    rendered = rusty("{% lorem 2 b %}").render({})
+    paragraphs = rendered.split("\n\n")
+    assert len(paragraphs) == 2
+    assert paragraphs[0] == COMMON_P
+    assert not paragraphs[1].startswith("<p>")
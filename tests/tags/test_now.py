@@ -0,0 +1,55 @@
+from datetime import datetime
+from unittest.mock import patch
+
+import pytest
+from django.template import engines
+from django.template.exceptions import TemplateSyntaxError
+
+FIXED_NOW = datetime(2024, 3, 7, 14, 5)
+
+
+def render_rusty(template, context=None):
+    rust_template = engines["rusty"].from_string(template)
+    with patch("django.utils.timezone.now", return_value=FIXED_NOW):
+        return rust_template.render(context or {})
+
+
+def test_now():
+    assert render_rusty("{% now 'Y-m-d H:i' %}") == "2024-03-07 14:05"
+
+
+def test_now_double_quotes():
+    assert render_rusty('{% now "jS F Y" %}') == "7th March 2024"
+
+
+def test_now_as():
+    template = "{% now 'Y' as year %}Copyright {{ year }}"
+    assert render_rusty(template) == "Copyright 2024"
+
+
+def test_now_as_shadows_variable():
+    template = "{% now 'Y' as year %}{{ year }}"
+    assert render_rusty(template, {"year": 1999}) == "2024"
+
+
+def test_now_not_escaped():
+    template = "{% now 'Y & m' %}"
+    assert render_rusty(template) == "2024 & 03"
+
+
+def test_now_current_time(assert_render):
+    template = "{% now 'Y' %}"
+    assert_render(template, {}, str(datetime.now().year))
+
+
+@pytest.mark.parametrize(
+    "template", ["{% now %}", "{% now 'Y' 'm' %}", "{% now 'Y' to year %}"]
+)
+def test_now_arguments(template):
+    with pytest.raises(TemplateSyntaxError):
+        engines["django"].from_string(template)
+
+    with pytest.raises(TemplateSyntaxError) as exc_info:
+        engines["rusty"].from_string(template)
+
+    assert str(exc_info.value).startswith("  × 'now' statement takes one argument")
@@ -0,0 +1,69 @@
+import pytest
+from django.template import engines
+from django.template.exceptions import TemplateSyntaxError
+
+PEOPLE = [
+    {"name": "Lily", "gender": "Female", "age": 31},
+    {"name": "Jo", "gender": "Female", "age": 24},
+    {"name": "Sam", "gender": "Male", "age": 45},
+    {"name": "Ann", "gender": "Female", "age": 38},
+]
+
+TEMPLATE = """{% regroup people by gender as grouped %}\
+{% for group in grouped %}{{ group.grouper }}:\
+{% for person in group.list %}{{ person.name }},{% endfor %};\
+{% endfor %}"""
+
+
+def test_regroup_sorted(assert_render):
+    people = sorted(PEOPLE, key=lambda person: person["gender"])
+    expected = "Female:Lily,Jo,Ann,;Male:Sam,;"
+    assert_render(TEMPLATE, {"people": people}, expected)
+
+
+def test_regroup_consecutive(assert_render):
+    expected = "Female:Lily,Jo,;Male:Sam,;Female:Ann,;"
+    assert_render(TEMPLATE, {"people": PEOPLE}, expected)
+
+
+def test_regroup_missing(assert_render):
+    assert_render(TEMPLATE, {}, "")
+
+
+def test_regroup_unpack(assert_render):
+    template = """{% regroup people by gender as grouped %}\
+{% for gender, people in grouped %}{{ gender }}={{ people|length }};{% endfor %}"""
+    assert_render(template, {"people": PEOPLE}, "Female=2;Male=1;Female=1;")
+
+
+def test_regroup_filtered_key(assert_render):
+    template = """{% regroup people by name|first as grouped %}\
+{% for group in grouped %}{{ group.grouper }}{{ group.list|length }}{% endfor %}"""
+    people = [{"name": "Lily"}, {"name": "Lou"}, {"name": "Jo"}]
+    assert_render(template, {"people": people}, "L2J1")
+
+
+@pytest.mark.parametrize(
+    "template,error",
+    [
+        ("{% regroup people by gender %}", "'regroup' tag takes five arguments"),
+        (
+            "{% regroup people with gender as grouped %}",
+            "second argument to 'regroup' tag must be 'by'",
+        ),
+        (
+            "{% regroup people by gender to grouped %}",
+            "next-to-last argument to 'regroup' tag must be 'as'",
+        ),
+    ],
+)
+def test_regroup_errors(template, error):
+    with pytest.raises(TemplateSyntaxError) as exc_info:
+        engines["django"].from_string(template)
+
+    assert str(exc_info.value) == error
+
+    with pytest.raises(TemplateSyntaxError) as exc_info:
+        engines["rusty"].from_string(template)
+
+    assert error in str(exc_info.value)
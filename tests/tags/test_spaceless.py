@@ -0,0 +1,28 @@
+def test_spaceless_nested_tags(assert_render):
+    template = """{% spaceless %}
+<div>
+    <p>
+        <a href="/">Home</a>
+    </p>
+</div>
+{% endspaceless %}"""
+    expected = '<div><p><a href="/">Home</a></p></div>'
+    assert_render(template, {}, expected)
+
+
+def test_spaceless_preserves_text_whitespace(assert_render):
+    template = "{% spaceless %}<p>  Hello   {{ name }}  </p> <b>x y</b>{% endspaceless %}"
+    expected = "<p>  Hello   Lily  </p><b>x y</b>"
+    assert_render(template, {"name": "Lily"}, expected)
+
+
+def test_spaceless_preserves_whitespace_inside_tags(assert_render):
+    template = '{% spaceless %}<a  href="/"   title="x">  </a>{% endspaceless %}'
+    expected = '<a  href="/"   title="x"></a>'
+    assert_render(template, {}, expected)
+
+
+def test_spaceless_variable_content(assert_render):
+    template = "{% spaceless %}<p>{{ html }}</p>{% endspaceless %}"
+    expected = "<p>&lt;b&gt; &lt;/b&gt;</p>"
+    assert_render(template, {"html": "<b> </b>"}, expected)
@@ -0,0 +1,75 @@
+import pytest
+from django.template import engines
+from django.template.exceptions import TemplateSyntaxError
+
+
+@pytest.mark.parametrize(
+    "argument,expected",
+    [
+        ("openblock", "{%"),
+        ("closeblock", "%}"),
+        ("openvariable", "{{"),
+        ("closevariable", "}}"),
+        ("openbrace", "{"),
+        ("closebrace", "}"),
+        ("opencomment", "{#"),
+        ("closecomment", "#}"),
+    ],
+)
+def test_templatetag(assert_render, argument, expected):
+    template = f"{{% templatetag {argument} %}}"
+    assert_render(template, {}, expected)
+
+
+def test_templatetag_not_escaped(assert_render):
+    template = "{% templatetag openvariable %} name {% templatetag closevariable %}"
+    assert_render(template, {}, "{{ name }}")
+
+
+@pytest.mark.parametrize(
+    "template,expected",
+    [
+        ("{% templatetag %}", "'templatetag' statement takes one argument"),
+        (
+            "{% templatetag openblock closeblock %}",
+            "'templatetag' statement takes one argument",
+        ),
+    ],
+)
+def test_templatetag_errors(template, expected):
+    with pytest.raises(TemplateSyntaxError) as exc_info:
+        engines["django"].from_string(template)
+
+    assert str(exc_info.value) == expected
+
+    with pytest.raises(TemplateSyntaxError) as exc_info:
+        engines["rusty"].from_string(template)
+
+    assert expected in str(exc_info.value)
+
+
+def test_templatetag_invalid_argument():
+    template = "{% templatetag openbracket %}"
+    with pytest.raises(TemplateSyntaxError) as exc_info:
+        engines["django"].from_string(template)
+
+    assert str(exc_info.value) == (
+        "Invalid templatetag argument: 'openbracket'. Must be one of: "
+        "['openblock', 'closeblock', 'openvariable', 'closevariable', "
+        "'openbrace', 'closebrace', 'opencomment', 'closecomment']"
+    )
+
+    with pytest.raises(TemplateSyntaxError) as exc_info:
+        engines["rusty"].from_string(template)
+
+    expected = """\
+  × Invalid templatetag argument: 'openbracket'. Must be one of: ['openblock',
+  │ 'closeblock', 'openvariable', 'closevariable', 'openbrace', 'closebrace',
+  │ 'opencomment', 'closecomment']
+   ╭────
+ 1 │ {% templatetag openbracket %}
+   ·                ─────┬─────
+   ·                     ╰── here
+   ╰────
+"""
+    assert str(exc_info.value) == expected
@@ -0,0 +1,62 @@
+import pytest
+from django.template import engines
+from django.template.exceptions import TemplateSyntaxError
+from django.utils import translation
+
+
+@pytest.fixture
+def mock_translation(monkeypatch):
+    def gettext(message):
+        return {"Hello": "Bonjour"}.get(message, message)
+
+    def pgettext(context, message):
+        return {("menu", "File"): "Fichier"}.get((context, message), message)
+
+    # Patch the backend so both engines see the mock through ``gettext``.
+    monkeypatch.setattr(translation._trans, "gettext", gettext)
+    monkeypatch.setattr(translation._trans, "pgettext", pgettext)
+
+
+@pytest.mark.parametrize(
+    "template,expected",
+    [
+        ("{% translate 'Hello' %}", "Bonjour"),
+        ("{% trans 'Goodbye' %}", "Goodbye"),
+        ("{% translate greeting %}", "Bonjour"),
+        ("{% translate greeting|upper %}", "BONJOUR"),
+        ("{% translate 'Hello' noop %}", "Hello"),
+        ("{% translate '100%' %}", "100%"),
+    ],
+)
+def test_translate(mock_translation, assert_render, template, expected):
+    template = "{% load i18n %}" + template
+    assert_render(template, {"greeting": "Hello"}, expected)
+
+
+def test_translate_as(mock_translation, assert_render):
+    template = "{% load i18n %}{% translate 'Hello' as greeting %}[{{ greeting }}]"
+    assert_render(template, {}, "[Bonjour]")
+
+
+def test_translate_context(mock_translation, assert_render):
+    template = "{% load i18n %}{% translate 'File' context 'menu' %} {% translate 'File' %}"
+    assert_render(template, {}, "Fichier File")
+
+
+def test_translate_escapes_variable(mock_translation, assert_render):
+    template = "{% load i18n %}{% translate greeting %}"
+    assert_render(template, {"greeting": "<b>"}, "&lt;b&gt;")
+
+
+def test_translate_unknown_argument():
+    template = "{% load i18n %}{% translate 'Hello' foo %}"
+
+    with pytest.raises(TemplateSyntaxError):
+        engines["django"].from_string(template)
+
+    with pytest.raises(TemplateSyntaxError) as exc_info:
+        engines["rusty"].from_string(template)
+
+    assert str(exc_info.value).startswith(
+        "  × Unknown argument for 'translate' tag: 'foo'. The only options"
+    )
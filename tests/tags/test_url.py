@@ -1,3 +1,5 @@
+from unittest.mock import patch
+
 import pytest
 from django.template import engines
 from django.template.base import VariableDoesNotExist
@@ -255,3 +257,30 @@ def test_render_url_dotted_lookup_filter_with_equal_char():
         rust_template.render({})
 
     assert rust_error.value.args[0] == msg
+
+
+def test_render_url_view_missing_as_shadows_variable(assert_render):
+    template = "{% url 'missing' as missing %}{{ missing }}"
+    assert_render(template, {"missing": "present"}, "")
+
+
+def test_render_url_mocked_reverse_args():
+    template = "{% url 'bio' 'lily' age as bio %}{{ bio }}"
+    rust_template = engines["rusty"].from_string(template)
+
+    with patch("django.urls.reverse", return_value="/mocked/") as reverse:
+        assert rust_template.render({"age": 31}) == "/mocked/"
+
+    reverse.assert_called_once_with("bio", None, ("lily", 31), None, None)
+
+
+def test_render_url_mocked_reverse_kwargs():
+    template = "{% url 'bio' username='lily' age=age %}"
+    rust_template = engines["rusty"].from_string(template)
+
+    with patch("django.urls.reverse", return_value="/mocked/") as reverse:
+        assert rust_template.render({"age": 31}) == "/mocked/"
+
+    reverse.assert_called_once_with(
+        "bio", None, None, {"username": "lily", "age": 31}, None
+    )
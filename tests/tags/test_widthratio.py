@@ -0,0 +1,68 @@
+import pytest
+from django.template import engines
+from django.template.exceptions import TemplateSyntaxError
+
+
+@pytest.mark.parametrize(
+    "context,expected",
+    [
+        ({"a": 50, "b": 100}, "50"),
+        ({"a": 175, "b": 200}, "88"),
+        ({"a": 1, "b": 3}, "33"),
+        ({"a": 0, "b": 100}, "0"),
+        ({"a": "50", "b": "100"}, "50"),
+        ({"a": 50, "b": 0}, "0"),
+        ({"a": None, "b": 100}, ""),
+        ({"a": "a", "b": 100}, ""),
+        ({"b": 100}, ""),
+        ({"a": float("inf"), "b": 100}, ""),
+    ],
+)
+def test_widthratio(assert_render, context, expected):
+    template = "{% widthratio a b 100 %}"
+    assert_render(template, context, expected)
+
+
+def test_widthratio_as(assert_render):
+    template = "{% widthratio a b 10 as width %}<{{ width }}>"
+    assert_render(template, {"a": 3, "b": 4}, "<8>")
+
+
+def test_widthratio_invalid_width():
+    template = "{% widthratio 50 100 width %}"
+    django_template = engines["django"].from_string(template)
+    rust_template = engines["rusty"].from_string(template)
+
+    expected = "widthratio final argument must be a number"
+    for width in ["wide", None]:
+        with pytest.raises(TemplateSyntaxError) as exc_info:
+            django_template.render({"width": width})
+
+        assert str(exc_info.value) == expected
+
+        with pytest.raises(TemplateSyntaxError) as exc_info:
+            rust_template.render({"width": width})
+
+        assert str(exc_info.value) == expected
+
+
+@pytest.mark.parametrize(
+    "template,expected",
+    [
+        ("{% widthratio a b %}", "widthratio takes at least three arguments"),
+        (
+            "{% widthratio a b c to width %}",
+            "Invalid syntax in widthratio tag. Expecting 'as' keyword",
+        ),
+    ],
+)
+def test_widthratio_syntax_error(template, expected):
+    with pytest.raises(TemplateSyntaxError) as exc_info:
+        engines["django"].from_string(template)
+
+    assert str(exc_info.value) == expected
+
+    with pytest.raises(TemplateSyntaxError) as exc_info:
+        engines["rusty"].from_string(template)
+
+    assert expected in str(exc_info.value)
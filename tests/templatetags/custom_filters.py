@@ -1,4 +1,6 @@
 from django import template
+from django.utils.html import conditional_escape
+from django.utils.safestring import mark_safe
 
 register = template.Library()
 
@@ -22,3 +24,20 @@ def multiply(value, by=3):
 @register.filter
 def divide_by_zero(value, zero=0):
     return value / zero
+
+
+@register.filter
+def reverse_str(value):
+    return value[::-1]
+
+
+@register.filter(is_safe=True)
+def bold(value):
+    return f"<b>{value}</b>"
+
+
+@register.filter(needs_autoescape=True)
+def initial_letter(value, autoescape=True):
+    esc = conditional_escape if autoescape else lambda x: x
+    first, other = value[0], value[1:]
+    return mark_safe(f"<strong>{esc(first)}</strong>{esc(other)}")
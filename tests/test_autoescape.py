@@ -106,3 +106,14 @@ def test_safe_lower():
     expected = "<p>hello world!</p>"
     assert django_template.render({"html": html}) == expected
     assert rust_template.render({"html": html}) == expected
+
+
+def test_autoescape_literal_text():
+    html = "<b>Lily</b>"
+    template = "<div class=\"name\">{{ html }} & {{ html|lower }}</div>"
+    django_template = engines["django"].from_string(template)
+    rust_template = engines["rusty"].from_string(template)
+
+    expected = '<div class="name">&lt;b&gt;Lily&lt;/b&gt; & &lt;b&gt;lily&lt;/b&gt;</div>'
+    assert django_template.render({"html": html}) == expected
+    assert rust_template.render({"html": html}) == expected
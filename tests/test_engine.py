@@ -2,10 +2,13 @@ from pathlib import Path
 
 import pytest
 from django.conf import settings
+from django.core.exceptions import ImproperlyConfigured
+from django.template import Context
 from django.template.engine import Engine
 from django.template.library import InvalidTemplateLibrary
 
-from django_rusty_templates import RustyTemplates
+from django_rusty_templates import Engine as RustyEngine
+from django_rusty_templates import RustyTemplates, Template
 
 
 def test_import_libraries_import_error():
@@ -55,3 +58,151 @@ def test_pathlib_dirs():
 
     template = engine.get_template("basic.txt")
     assert template.render({"user": "Lily"}) == "Hello Lily!\n"
+
+
+def test_locmem_loader():
+    templates = {"hello.html": "Hi {{ user }}"}
+    loaders = [("django.template.loaders.locmem.Loader", templates)]
+    engine = RustyTemplates(
+        {
+            "NAME": "rust",
+            "OPTIONS": {"loaders": loaders},
+            "DIRS": [],
+            "APP_DIRS": False,
+        }
+    )
+
+    template = engine.get_template("hello.html")
+    assert template.render({"user": "Lily"}) == "Hi Lily"
+
+
+def test_cached_filesystem_loader():
+    loaders = [
+        (
+            "django.template.loaders.cached.Loader",
+            ["django.template.loaders.filesystem.Loader"],
+        )
+    ]
+    engine = RustyTemplates(
+        {
+            "NAME": "rust",
+            "OPTIONS": {"loaders": loaders},
+            "DIRS": [Path(settings.BASE_DIR) / "templates"],
+            "APP_DIRS": False,
+        }
+    )
+
+    template = engine.get_template("basic.txt")
+    assert template.render({"user": "Lily"}) == "Hello Lily!\n"
+
+
+@pytest.mark.parametrize("loader", [1, (), [1]])
+def test_invalid_loader(loader):
+    with pytest.raises(ImproperlyConfigured) as exc_info:
+        RustyTemplates(
+            {
+                "NAME": "rust",
+                "OPTIONS": {"loaders": [loader]},
+                "DIRS": [],
+                "APP_DIRS": False,
+            }
+        )
+
+    assert str(exc_info.value).startswith(
+        "Invalid value in template loaders configuration: "
+    )
+
+
+def test_cached_loader_reset(tmp_path):
+    template_path = tmp_path / "cached.txt"
+    template_path.write_text("first")
+    engine = RustyTemplates(
+        {"NAME": "rust", "OPTIONS": {}, "DIRS": [tmp_path], "APP_DIRS": False}
+    )
+
+    assert engine.get_template("cached.txt").render({}) == "first"
+
+    template_path.write_text("second")
+    assert engine.get_template("cached.txt").render({}) == "first"
+
+    engine.engine.reset_loaders()
+    assert engine.get_template("cached.txt").render({}) == "second"
+
+
+def test_from_string_autoescape_override():
+    engine = RustyTemplates(
+        {
+            "NAME": "rust",
+            "OPTIONS": {},
+            "DIRS": [],
+            "APP_DIRS": False,
+        }
+    ).engine
+
+    template = engine.from_string("{{ html }}")
+    assert template.render({"html": "<b>"}) == "&lt;b&gt;"
+
+    template = engine.from_string("{{ html }}", autoescape=False)
+    assert template.render({"html": "<b>"}) == "<b>"
+
+
+@pytest.mark.parametrize(
+    "string_if_invalid,expected",
+    [
+        ("INVALID", "INVALID INVALID LILY"),
+        ("INVALID(%s)", "INVALID(missing) INVALID(other) LILY"),
+    ],
+)
+def test_string_if_invalid(string_if_invalid, expected):
+    template = "{{ missing }} {{ other|default:'x' }} {{ name|upper }}"
+    context = {"name": "Lily"}
+    params = {"string_if_invalid": string_if_invalid}
+
+    django_template = Engine(**params).from_string(template)
+    assert django_template.render(Context(context)) == expected
+
+    rusty_engine = RustyTemplates(
+        {"OPTIONS": params, "NAME": "rust", "DIRS": [], "APP_DIRS": False}
+    )
+    assert rusty_engine.from_string(template).render(context) == expected
+
+
+def test_engine_get_template():
+    templates = {"hello.html": "Hi {{ user }}"}
+    loaders = [("django.template.loaders.locmem.Loader", templates)]
+    engine = RustyEngine(loaders=loaders, string_if_invalid="?")
+
+    assert engine.autoescape is True
+    assert engine.string_if_invalid == "?"
+
+    template = engine.get_template("hello.html")
+    assert template.render({"user": "Lily"}) == "Hi Lily"
+    assert template.render({}) == "Hi ?"
+
+
+def test_template_with_engine():
+    engine = RustyEngine(autoescape=False, string_if_invalid="?")
+
+    template = Template("{{ html }} {{ missing }}", engine=engine)
+    assert template.render({"html": "<b>"}) == "<b> ?"
+
+
+def site_name(request):
+    return {"site_name": "Example", "path": request.path}
+
+
+def test_context_processors(rf):
+    request = rf.get("/hello/")
+    engine = RustyEngine(context_processors=[site_name])
+
+    template = engine.from_string("{{ site_name }} {{ path }}")
+    assert template.render(request=request) == "Example /hello/"
+    assert template.render({"site_name": "Other"}, request) == "Other /hello/"
+    assert template.render() == " "
+
+
+def test_context_processors_dotted_path(rf):
+    engine = RustyEngine(context_processors=["tests.test_engine.site_name"])
+
+    template = engine.from_string("{{ site_name }}")
+    assert template.render(request=rf.get("/")) == "Example"
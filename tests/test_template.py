@@ -0,0 +1,121 @@
+import sys
+
+import pytest
+from django.template import Context, RequestContext
+from django.template.exceptions import TemplateSyntaxError
+
+from django_rusty_templates import Engine, Template
+
+
+def test_render():
+    template = Template("{{ name }}")
+    assert template.render({"name": "Lily"}) == "Lily"
+
+
+def test_render_dict():
+    template = Template("{{ name }} {{ age }}")
+    assert template.render({"name": "Lily", "age": 3}) == "Lily 3"
+
+
+def test_render_flatten():
+    class Flattenable:
+        def flatten(self):
+            return {"name": "Lily"}
+
+    template = Template("{{ name }}")
+    assert template.render(Flattenable()) == "Lily"
+
+
+def test_render_django_context():
+    context = Context({"name": "Lily"})
+    context.push({"name": "Jo", "age": 3})
+
+    template = Template("{{ name }} {{ age }}")
+    assert template.render(context) == "Jo 3"
+
+
+def test_render_request_context(rf):
+    def processor(request):
+        return {"path": request.path}
+
+    engine = Engine(context_processors=[processor])
+    context = RequestContext(rf.get("/hello/"), {"name": "Lily"})
+
+    template = engine.from_string("{{ name }} {{ path }}")
+    assert template.render(context) == "Jo /hello/"
+
+    with context.push(name="Ann"):
+        assert template.render(context) == "Ann /hello/"
+
+
+def test_render_django_context_autoescape():
+    context = Context({"name": "<b>Lily</b>"}, autoescape=False)
+
+    template = Template("{{ name }}")
+    assert template.render(context) == "<b>Lily</b>"
+
+
+def test_render_request_context_processors(rf):
+    def engine_processor(request):
+        return {"path": "engine", "name": "Jo"}
+
+    def processor(request):
+        return {"path": request.path}
+
+    engine = Engine(context_processors=[engine_processor])
+    context = RequestContext(rf.get("/hello/"), {"name": "Lily"}, processors=[processor])
+
+    template = engine.from_string("{{ name }} {{ path }}")
+    assert template.render(context) == "Jo /hello/"
+
+    with context.push(name="Ann"):
+        assert template.render(context) == "Ann /hello/"
+
+
+def test_render_invalid_context():
+    template = Template("{{ name }}")
+    with pytest.raises(TypeError) as exc_info:
+        template.render(["Lily"])
+
+    assert str(exc_info.value) == "context must be a dict or Context rather than list."
+
+
+def test_render_no_context():
+    template = Template("Hello {{ name }}!")
+    assert template.render() == "Hello !"
+
+
+def test_render_autoescape():
+    template = Template("{{ name }}")
+    assert template.render({"name": "<b>Lily</b>"}) == "&lt;b&gt;Lily&lt;/b&gt;"
+
+
+def test_render_autoescape_off():
+    template = Template("{{ name }}", autoescape=False)
+    assert template.render({"name": "<b>Lily</b>"}) == "<b>Lily</b>"
+
+
+def test_syntax_error():
+    with pytest.raises(TemplateSyntaxError):
+        Template("{% if %}")
+
+
+def test_syntax_error_template_debug():
+    with pytest.raises(TemplateSyntaxError) as exc_info:
+        Template("<p>\n  {{ }}\n</p>")
+
+    debug = exc_info.value.template_debug
+    assert debug["line"] == 2
+    assert debug["before"] == "  "
+    assert debug["during"] == "{{ }}"
+    assert debug["after"] == "\n"
+    assert debug["source_lines"] == [(1, "<p>\n"), (2, "  {{ }}\n"), (3, "</p>")]
+    assert debug["name"] is None
+
+
+@pytest.mark.skipif(sys.version_info < (3, 11), reason="Exception notes need Python 3.11")
+def test_syntax_error_location_note():
+    with pytest.raises(TemplateSyntaxError) as exc_info:
+        Template("<p>\n  é {{ }}\n</p>")
+
+    assert exc_info.value.__notes__ == ["at line 2, column 5"]
@@ -1,3 +1,5 @@
+import pytest
+from django.utils import translation
 from django.utils.translation import override
 
 from .utils import render
@@ -23,3 +25,24 @@ def test_translate_valid():
     with override("de"):
         assert render("translation.txt", {}, using="django") == expected
         assert render("translation.txt", {}, using="rusty") == expected
+
+
+@pytest.fixture
+def mock_gettext(monkeypatch):
+    def gettext(message):
+        return {"Hello": "Bonjour"}.get(message, message)
+
+    # Patch the backend so both engines see the mock through ``gettext``.
+    monkeypatch.setattr(translation._trans, "gettext", gettext)
+
+
+@pytest.mark.parametrize(
+    "template,expected",
+    [
+        ('{{ _("Hello") }}', "Bonjour"),
+        ('{{ _("Goodbye") }}', "Goodbye"),
+        ('{{ missing|default:_("Hello") }}', "Bonjour"),
+    ],
+)
+def test_translate_mocked(mock_gettext, assert_render, template, expected):
+    assert_render(template, {}, expected)
@@ -0,0 +1,174 @@
+import pytest
+from django.template import engines
+from django.template.exceptions import TemplateSyntaxError
+
+
+def get_greeting():
+    return "Hello"
+
+
+def greet(name):
+    return f"Hello {name}"
+
+
+def broken():
+    raise TypeError("broken")
+
+
+def test_top_level_callable(assert_render):
+    template = "{{ get_greeting }}"
+    assert_render(template, {"get_greeting": get_greeting}, "Hello")
+
+
+def test_top_level_callable_lookup(assert_render):
+    template = "{{ get_greeting.upper }}"
+    assert_render(template, {"get_greeting": get_greeting}, "HELLO")
+
+
+def test_top_level_callable_requires_arguments(assert_render):
+    template = "{{ greet }}"
+    assert_render(template, {"greet": greet}, "")
+
+
+def test_top_level_callable_in_filter(assert_render):
+    template = "{{ get_greeting|lower }}"
+    assert_render(template, {"get_greeting": get_greeting}, "hello")
+
+
+def test_top_level_callable_raises_type_error():
+    template = "{{ broken }}"
+    django_template = engines["django"].from_string(template)
+    rust_template = engines["rusty"].from_string(template)
+
+    with pytest.raises(TypeError) as exc_info:
+        django_template.render({"broken": broken})
+
+    assert str(exc_info.value) == "broken"
+
+    with pytest.raises(TypeError) as exc_info:
+        rust_template.render({"broken": broken})
+
+    assert str(exc_info.value) == "broken"
+
+
+def test_flat_dotted_key_is_not_found(assert_render):
+    template = "{{ a.b }}"
+    assert_render(template, {"a.b": "flat"}, "")
+
+
+def test_dict_key_shadows_method(assert_render):
+    template = "{{ data.items }}"
+    assert_render(template, {"data": {"items": "shadowed"}}, "shadowed")
+
+
+def test_dict_method_without_key(assert_render):
+    template = "{% for key, value in data.items %}{{ key }}={{ value }};{% endfor %}"
+    assert_render(template, {"data": {"a": 1, "b": 2}}, "a=1;b=2;")
+
+
+@pytest.mark.parametrize(
+    "template,expected",
+    [
+        ("{{ 42 }}", "42"),
+        ("{{ 3.14 }}", "3.14"),
+        ("{{ -5 }}", "-5"),
+        ("{{ 1.0 }}", "1.0"),
+        ("{{ 1e3 }}", "1000.0"),
+        ("{{ -5|add:2 }}", "-3"),
+    ],
+)
+def test_numeric_literal(assert_render, template, expected):
+    assert_render(template, {}, expected)
+
+
+def test_numeric_index_lookup(assert_render):
+    template = "{{ a.0 }} {{ a.0.b }} {{ 0.5 }}"
+    assert_render(template, {"a": [{"b": "nested"}]}, "{'b': 'nested'} nested 0.5")
+
+
+@pytest.mark.parametrize(
+    "template,expected",
+    [
+        ('{{ "hello" }}', "hello"),
+        ("{{ 'hello'|upper }}", "HELLO"),
+        ("{{ '<b>' }}", "<b>"),
+        ('{{ _("translate me") }}', "translate me"),
+        ("{{ _('it\\'s') }}", "it's"),
+    ],
+)
+def test_string_literal(assert_render, template, expected):
+    assert_render(template, {}, expected)
+
+
+def test_list_index_out_of_range(assert_render):
+    template = "{{ items.1 }}{{ items.5 }}"
+    assert_render(template, {"items": ["a", "b"]}, "b")
+
+
+class Lookup:
+    name = "attribute"
+    _private = "private"
+
+    def __getitem__(self, key):
+        if key == "name":
+            return "key"
+        raise KeyError(key)
+
+
+def test_key_lookup_before_attribute(assert_render):
+    assert_render("{{ obj.name }}", {"obj": Lookup()}, "key")
+
+
+def test_private_attribute():
+    template = "{{ obj._private }}"
+
+    with pytest.raises(TemplateSyntaxError):
+        engines["django"].from_string(template)
+
+    with pytest.raises(TemplateSyntaxError) as exc_info:
+        engines["rusty"].from_string(template)
+
+    assert str(exc_info.value) == """\
+  × Variables and attributes may not begin with underscores
+   ╭────
+ 1 │ {{ obj._private }}
+   ·        ────┬───
+   ·            ╰── here
+   ╰────
+"""
+
+
+class User:
+    def __init__(self):
+        self.deleted = False
+
+    def get_full_name(self):
+        return "Lily Potter"
+
+    def delete(self):
+        self.deleted = True
+        return "Deleted"
+
+    delete.alters_data = True
+
+
+class Choices:
+    do_not_call_in_templates = True
+    label = "Choice"
+
+    def __init__(self, value):
+        pass
+
+
+def test_callable_called(assert_render):
+    assert_render("{{ user.get_full_name }}", {"user": User()}, "Lily Potter")
+
+
+def test_callable_alters_data(assert_render):
+    user = User()
+    assert_render("{{ user.delete }}", {"user": user}, "")
+    assert not user.deleted
+
+
+def test_callable_do_not_call_in_templates(assert_render):
+    assert_render("{{ choices.label }}", {"choices": Choices}, "Choice")